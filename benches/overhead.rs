@@ -0,0 +1,208 @@
+//! Benchmarks of the work rorm does on top of the database
+//!
+//! Run them using `cargo bench --features bench-utils`.
+//! The database is an in-memory SQLite database, so the results mostly reflect rorm's own overhead.
+
+use std::time::{Duration, Instant};
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use rorm::bench_utils::{build_select, create_table};
+use rorm::crud::decoder::Decoder;
+use rorm::crud::selector::Selector;
+use rorm::database::DatabaseExt;
+use rorm::internal::query_context::QueryContext;
+use rorm::prelude::*;
+use rorm::{and, delete, insert, query, Database};
+use tokio::runtime::Runtime;
+
+#[derive(Model)]
+struct Group {
+    #[rorm(id)]
+    id: i64,
+
+    #[rorm(max_length = 255)]
+    name: String,
+}
+
+#[derive(Model)]
+struct User {
+    #[rorm(id)]
+    id: i64,
+
+    #[rorm(max_length = 255)]
+    name: String,
+
+    age: i16,
+
+    group: ForeignModel<Group>,
+}
+
+#[derive(Model)]
+struct Comment {
+    #[rorm(id)]
+    id: i64,
+
+    #[rorm(max_length = 255)]
+    text: String,
+
+    user: ForeignModel<User>,
+}
+
+const ROWS: i64 = 1000;
+
+fn users() -> Vec<User> {
+    (0..ROWS)
+        .map(|id| User {
+            id,
+            name: format!("user {id}"),
+            age: (id % 100) as i16,
+            group: ForeignModelByField(0),
+        })
+        .collect()
+}
+
+async fn setup() -> Database {
+    let db = Database::sqlite_in_memory().await.unwrap();
+    create_table::<Group>(&db).await.unwrap();
+    create_table::<User>(&db).await.unwrap();
+    create_table::<Comment>(&db).await.unwrap();
+    insert(&db, Group)
+        .return_nothing()
+        .single(&Group {
+            id: 0,
+            name: "group".to_string(),
+        })
+        .await
+        .unwrap();
+    db
+}
+
+fn query_building(c: &mut Criterion) {
+    let mut group = c.benchmark_group("query building");
+    group.bench_function("model", |b| b.iter(|| build_select(User, None)));
+    group.bench_function("condition", |b| {
+        b.iter(|| {
+            let condition = and![
+                User.name.equals(black_box("alice")),
+                User.age.greater_equals(black_box(18i16)),
+                User.group.name.equals(black_box("admins")),
+            ];
+            build_select(User, Some(&condition))
+        })
+    });
+    group.bench_function("joined tuple", |b| {
+        b.iter(|| {
+            build_select(
+                (Comment.text, Comment.user.name, Comment.user.group.name),
+                None,
+            )
+        })
+    });
+    group.finish();
+}
+
+fn path_resolution(c: &mut Criterion) {
+    let mut group = c.benchmark_group("path resolution");
+    group.bench_function("one step", |b| {
+        b.iter(|| {
+            let mut ctx = QueryContext::new();
+            Comment.user.name.select(&mut ctx);
+            ctx
+        })
+    });
+    group.bench_function("two steps", |b| {
+        b.iter(|| {
+            let mut ctx = QueryContext::new();
+            Comment.user.group.name.select(&mut ctx);
+            ctx
+        })
+    });
+    group.finish();
+}
+
+fn row_decoding(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let rows = runtime.block_on(async {
+        let db = setup().await;
+        insert(&db, User)
+            .return_nothing()
+            .bulk(&users())
+            .await
+            .unwrap();
+        query(&db, User).all_as_rows().await.unwrap()
+    });
+    let decoder = build_select(User, None);
+
+    let mut group = c.benchmark_group("row decoding");
+    group.bench_function("by index", |b| {
+        b.iter(|| {
+            rows.iter()
+                .map(|row| decoder.by_index(row).unwrap())
+                .collect::<Vec<_>>()
+        })
+    });
+    group.bench_function("by name", |b| {
+        b.iter(|| {
+            rows.iter()
+                .map(|row| decoder.by_name(row).unwrap())
+                .collect::<Vec<_>>()
+        })
+    });
+    group.finish();
+}
+
+fn bulk_insert(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let db = runtime.block_on(setup());
+
+    let mut group = c.benchmark_group("bulk insert");
+    group.bench_function("values", |b| {
+        b.iter_batched(
+            users,
+            |users| users.into_iter().map(Patch::values).collect::<Vec<_>>(),
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("references", |b| {
+        let users = users();
+        let mut values = Vec::new();
+        b.iter(|| {
+            values.clear();
+            for user in &users {
+                user.push_references(&mut values);
+            }
+            values.len()
+        })
+    });
+    // The table has to be emptied between the iterations, which shouldn't be measured
+    group.bench_function("query", |b| {
+        b.to_async(&runtime).iter_custom(|iters| {
+            let db = db.clone();
+            async move {
+                let users = users();
+                let mut elapsed = Duration::ZERO;
+                for _ in 0..iters {
+                    delete(&db, User).all().await.unwrap();
+                    let start = Instant::now();
+                    insert(&db, User)
+                        .return_nothing()
+                        .bulk(&users)
+                        .await
+                        .unwrap();
+                    elapsed += start.elapsed();
+                }
+                elapsed
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    query_building,
+    path_resolution,
+    row_decoding,
+    bulk_insert
+);
+criterion_main!(benches);
@@ -2,6 +2,7 @@ use quote::ToTokens;
 use syn::{VisRestricted, Visibility};
 
 pub mod model;
+pub mod model_enum;
 
 pub fn vis_to_display(vis: &Visibility) -> impl std::fmt::Display + '_ {
     DisplayableVisibility(vis)
@@ -1,10 +1,10 @@
 use proc_macro2::Ident;
 use quote::format_ident;
 use syn::visit_mut::VisitMut;
-use syn::{Generics, LitInt, LitStr, Type, Visibility};
+use syn::{Expr, ExprField, ExprPath, Generics, LitInt, LitStr, Member, Path, Type, Visibility};
 
 use crate::analyze::vis_to_display;
-use crate::parse::annotations::{Default, Index, OnAction};
+use crate::parse::annotations::{Default, GeneratedPatch, Index, OnAction, Validate};
 use crate::parse::model::{ModelAnnotations, ModelFieldAnnotations, ParsedField, ParsedModel};
 use crate::utils::to_db_name;
 
@@ -16,6 +16,17 @@ pub fn analyze_model(parsed: ParsedModel) -> darling::Result<AnalyzedModel> {
         annos:
             ModelAnnotations {
                 rename,
+                hooks,
+                default_scope,
+                audit,
+                track_changes,
+                database,
+                shard_by,
+                shards,
+                time_partition_by,
+                period,
+                patch,
+                insert_patch,
                 experimental_unregistered,
                 experimental_generics,
             },
@@ -32,6 +43,82 @@ pub fn analyze_model(parsed: ParsedModel) -> darling::Result<AnalyzedModel> {
         errors.push(darling::Error::custom("Generic models are not supported yet. You can try the `experimental_generics` attribute"));
     }
 
+    // Handle #[rorm(shard_by = "..", shards = ..)] annotations
+    let shard_by = match (shard_by, shards) {
+        (Some(shard_by), Some(shards)) => {
+            let field = errors.handle(shard_by.parse::<Ident>().map_err(|_| {
+                darling::Error::custom("`shard_by` has to be the name of a field")
+                    .with_span(&shard_by)
+            }));
+            let count = errors.handle(
+                shards
+                    .base10_parse::<u16>()
+                    .ok()
+                    .filter(|shards| *shards > 0)
+                    .ok_or_else(|| {
+                        darling::Error::custom("`shards` has to be between 1 and 65535")
+                            .with_span(&shards)
+                    }),
+            );
+            field.zip(count).map(|(field, _)| (field, shards))
+        }
+        (Some(shard_by), None) => {
+            errors
+                .push(darling::Error::custom("`shard_by` requires `shards`").with_span(&shard_by));
+            None
+        }
+        (None, Some(shards)) => {
+            errors.push(darling::Error::custom("`shards` requires `shard_by`").with_span(&shards));
+            None
+        }
+        (None, None) => None,
+    };
+
+    // Handle #[rorm(time_partition_by = "..", period = "..")] annotations
+    let time_partition = match (time_partition_by, period) {
+        (Some(time_partition_by), Some(period)) => {
+            let field = errors.handle(time_partition_by.parse::<Ident>().map_err(|_| {
+                darling::Error::custom("`time_partition_by` has to be the name of a field")
+                    .with_span(&time_partition_by)
+            }));
+            let variant = match period.value().as_str() {
+                "hour" => Some(format_ident!("Hour", span = period.span())),
+                "day" => Some(format_ident!("Day", span = period.span())),
+                "month" => Some(format_ident!("Month", span = period.span())),
+                _ => {
+                    errors.push(
+                        darling::Error::custom(
+                            "`period` has to be `\"hour\"`, `\"day\"` or `\"month\"`",
+                        )
+                        .with_span(&period),
+                    );
+                    None
+                }
+            };
+            if shard_by.is_some() {
+                errors.push(
+                    darling::Error::custom("`time_partition_by` can't be combined with `shard_by`")
+                        .with_span(&time_partition_by),
+                );
+            }
+            field.zip(variant)
+        }
+        (Some(time_partition_by), None) => {
+            errors.push(
+                darling::Error::custom("`time_partition_by` requires `period`")
+                    .with_span(&time_partition_by),
+            );
+            None
+        }
+        (None, Some(period)) => {
+            errors.push(
+                darling::Error::custom("`period` requires `time_partition_by`").with_span(&period),
+            );
+            None
+        }
+        (None, None) => None,
+    };
+
     // Get table name
     let table = rename.unwrap_or_else(|| LitStr::new(&to_db_name(ident.to_string()), ident.span()));
     if table.value().contains("__") {
@@ -43,8 +130,21 @@ pub fn analyze_model(parsed: ParsedModel) -> darling::Result<AnalyzedModel> {
         /* assuming most fields won't be ignored */
         fields.len(),
     );
+    let mut skipped_fields = Vec::new();
     let model_ident = &ident; // alias to avoid shadowing in following loop
     for field in fields {
+        // Handle #[rorm(skip)] annotation
+        if field.annos.skip {
+            if field.annos.id || field.annos.primary_key {
+                errors.push(
+                    darling::Error::custom("The primary key can't be skipped.")
+                        .with_span(&field.ident),
+                );
+            }
+            skipped_fields.push(field.ident);
+            continue;
+        }
+
         let ParsedField {
             vis,
             ident,
@@ -59,11 +159,17 @@ pub fn analyze_model(parsed: ParsedModel) -> darling::Result<AnalyzedModel> {
                     id,
                     on_delete,
                     on_update,
+                    readonly,
+                    defer,
+                    tenant_key,
+                    counter_cache,
+                    hash_of,
                     rename,
-                    //ignore,
+                    skip: _, // Handled above
                     default,
                     max_length,
                     index,
+                    validate,
                 },
         } = field;
         // Get column name
@@ -95,6 +201,37 @@ pub fn analyze_model(parsed: ParsedModel) -> darling::Result<AnalyzedModel> {
             auto_increment = true;
         }
 
+        // Handle #[rorm(defer)] annotation
+        if defer && primary_key {
+            errors.push(
+                darling::Error::custom("The primary key can't be deferred.").with_span(&ident),
+            );
+        }
+
+        // Handle #[rorm(validate(regex = ".."))] annotation
+        if let Some(regex) = validate
+            .as_ref()
+            .and_then(|validate| validate.regex.as_ref())
+        {
+            if let Err(error) = regex_syntax::Parser::new().parse(&regex.value()) {
+                errors.push(
+                    darling::Error::custom(format!("Invalid regex: {error}")).with_span(regex),
+                );
+            }
+        }
+
+        // Handle #[rorm(counter_cache = "..")] annotation
+        let counter_cache = counter_cache
+            .and_then(|counter_cache| errors.handle(analyze_counter_cache(&counter_cache)));
+
+        // Handle #[rorm(hash_of = "..")] annotation
+        let hash_of = hash_of.and_then(|hash_of| {
+            errors.handle(hash_of.parse::<Ident>().map_err(|_| {
+                darling::Error::custom("Expected the name of the field to hash.")
+                    .with_span(&hash_of)
+            }))
+        });
+
         // Replace `Self` in the field's type to the model's identifier
         struct ReplaceSelf<'a>(&'a Ident);
         impl<'a> VisitMut for ReplaceSelf<'a> {
@@ -120,9 +257,15 @@ pub fn analyze_model(parsed: ParsedModel) -> darling::Result<AnalyzedModel> {
                 unique,
                 on_delete,
                 on_update,
+                readonly,
+                defer,
+                tenant_key,
+                counter_cache,
+                hash_of,
                 default,
                 max_length,
                 index,
+                validate,
             },
         });
     }
@@ -154,24 +297,170 @@ pub fn analyze_model(parsed: ParsedModel) -> darling::Result<AnalyzedModel> {
         )),
     }
 
+    // Check there is at most one tenant key
+    let tenant_keys: Vec<_> = analyzed_fields
+        .iter()
+        .filter(|field| field.annos.tenant_key)
+        .collect();
+    if tenant_keys.len() > 1 {
+        errors.push(darling::Error::multiple(
+            tenant_keys
+                .into_iter()
+                .map(|field| {
+                    darling::Error::custom(
+                        "Model has more than one tenant key. Please remove all but one of them.",
+                    )
+                    .with_span(&field.ident)
+                })
+                .collect(),
+        ));
+    }
+
+    // Check the shard key and the partitioning timestamp refer to fields
+    let sharding_field = shard_by.iter().map(|(field, _)| field);
+    let partition_field = time_partition.iter().map(|(field, _)| field);
+    for field in sharding_field.chain(partition_field) {
+        if !analyzed_fields
+            .iter()
+            .any(|analyzed| analyzed.ident == *field)
+        {
+            errors.push(
+                darling::Error::custom(format!("`{field}` is not a field of the model"))
+                    .with_span(field),
+            );
+        }
+    }
+
+    // Resolve the fields of the patches generated by #[rorm(patch(..))] and #[rorm(insert_patch = "..")]
+    if (!patch.is_empty() || insert_patch.is_some()) && generics.lt_token.is_some() {
+        errors.push(darling::Error::custom(
+            "Generating patches is not supported on generic models",
+        ));
+    }
+    let mut patches = Vec::with_capacity(patch.len() + 1);
+    for GeneratedPatch { name, omit } in patch {
+        for omitted in &omit {
+            if !analyzed_fields
+                .iter()
+                .any(|field| field.ident == omitted.value())
+            {
+                errors.push(
+                    darling::Error::custom(format!(
+                        "`{}` is not a field of the model",
+                        omitted.value()
+                    ))
+                    .with_span(omitted),
+                );
+            }
+        }
+        let Some(ident) = errors.handle(name.parse::<Ident>().map_err(darling::Error::from)) else {
+            continue;
+        };
+        patches.push(AnalyzedPatch {
+            doc: LitStr::new(
+                &format!("[`{model_ident}`]'s fields generated by `#[rorm(patch(..))]`"),
+                name.span(),
+            ),
+            ident,
+            fields: analyzed_fields
+                .iter()
+                .enumerate()
+                .filter(|(_, field)| !omit.iter().any(|omitted| field.ident == omitted.value()))
+                .map(|(index, _)| index)
+                .collect(),
+        });
+    }
+    if let Some(name) = insert_patch {
+        if let Some(ident) = errors.handle(name.parse::<Ident>().map_err(darling::Error::from)) {
+            patches.push(AnalyzedPatch {
+                doc: LitStr::new(
+                    &format!("[`{model_ident}`]'s fields which are not set by the database"),
+                    name.span(),
+                ),
+                ident,
+                fields: analyzed_fields
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, field)| {
+                        !(field.annos.auto_increment
+                            || field.annos.auto_create_time
+                            || field.annos.auto_update_time)
+                    })
+                    .map(|(index, _)| index)
+                    .collect(),
+            });
+        }
+    }
+
     errors.finish_with(AnalyzedModel {
         vis: vis.clone(),
         ident,
         table,
         fields: analyzed_fields,
+        skipped_fields,
         primary_key,
+        patches,
+        hooks,
+        default_scope,
+        audit,
+        track_changes,
+        database,
+        shard_by,
+        time_partition,
         experimental_unregistered,
         experimental_generics: generics,
     })
 }
 
+/// Parse the counter named by `#[rorm(counter_cache = "Model::F.field")]`
+///
+/// `"Model.field"` is accepted as well.
+fn analyze_counter_cache(counter_cache: &LitStr) -> darling::Result<CounterCache> {
+    let error = || {
+        darling::Error::custom("Expected the counter field as `Model::F.field`.")
+            .with_span(counter_cache)
+    };
+    let ExprField { base, member, .. } = counter_cache.parse().map_err(|_| error())?;
+    let (Expr::Path(ExprPath { mut path, .. }), Member::Named(field)) = (*base, member) else {
+        return Err(error());
+    };
+    if path.segments.len() > 1
+        && path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "F")
+    {
+        path.segments.pop();
+        path.segments.pop_punct();
+    }
+    Ok(CounterCache { model: path, field })
+}
+
 pub struct AnalyzedModel {
     pub vis: Visibility,
     pub ident: Ident,
     pub table: LitStr,
     pub fields: Vec<AnalyzedField>,
+    /// The fields annotated with `#[rorm(skip)]`
+    pub skipped_fields: Vec<Ident>,
     /// the primary key's index
     pub primary_key: usize,
+    /// The patches generated by `#[rorm(patch(..))]` and `#[rorm(insert_patch = "..")]`
+    pub patches: Vec<AnalyzedPatch>,
+    /// Whether the model implements its own `ModelHooks`
+    pub hooks: bool,
+    /// Whether the model implements its own `DefaultScope`
+    pub default_scope: bool,
+    /// Whether changes to the model are written to an audit table
+    pub audit: bool,
+    /// Whether the model implements `TrackChanges`
+    pub track_changes: bool,
+    /// The name of the database the model is stored in
+    pub database: Option<LitStr>,
+    /// The field and number of shards the model is sharded by
+    pub shard_by: Option<(Ident, LitInt)>,
+    /// The timestamp field and period (as `Period`'s variant) the model is partitioned by
+    pub time_partition: Option<(Ident, Ident)>,
 
     pub experimental_unregistered: bool,
     pub experimental_generics: Generics,
@@ -194,7 +483,34 @@ pub struct AnalyzedModelFieldAnnotations {
     pub unique: bool,
     pub on_delete: Option<OnAction>,
     pub on_update: Option<OnAction>,
+    pub readonly: bool,
+    pub defer: bool,
+    pub tenant_key: bool,
+    pub counter_cache: Option<CounterCache>,
+    pub hash_of: Option<Ident>,
     pub default: Option<Default>,
     pub max_length: Option<LitInt>,
     pub index: Option<Index>,
+    pub validate: Option<Validate>,
+}
+
+/// The counter named by `#[rorm(counter_cache = "..")]`
+pub struct CounterCache {
+    /// The model containing the counter
+    pub model: Path,
+
+    /// The counter's field
+    pub field: Ident,
+}
+
+/// A patch generated by `#[rorm(patch(..))]` or `#[rorm(insert_patch = "..")]`
+pub struct AnalyzedPatch {
+    /// The patch's name
+    pub ident: Ident,
+
+    /// The patch's doc comment
+    pub doc: LitStr,
+
+    /// The indices of the model's fields contained in the patch
+    pub fields: Vec<usize>,
 }
@@ -0,0 +1,233 @@
+use proc_macro2::Ident;
+use quote::{format_ident, ToTokens};
+use syn::{parse_quote, GenericArgument, Generics, PathArguments, Type, TypePath};
+
+use crate::analyze::model::{analyze_model, AnalyzedModel};
+use crate::parse::model::{ModelAnnotations, ModelFieldAnnotations, ParsedField, ParsedModel};
+use crate::parse::model_enum::{ModelEnumAnnotations, ParsedModelEnum, ParsedVariant};
+
+pub fn analyze_model_enum(parsed: ParsedModelEnum) -> darling::Result<AnalyzedModelEnum> {
+    let ParsedModelEnum {
+        vis,
+        ident,
+        annos:
+            ModelEnumAnnotations {
+                rename,
+                discriminator,
+                hooks,
+                default_scope,
+                audit,
+                database,
+                experimental_unregistered,
+            },
+        variants,
+    } = parsed;
+    let mut errors = darling::Error::accumulator();
+
+    // The discriminator is the first field
+    let discriminator_ident = format_ident!("kind", span = ident.span());
+    let mut fields = vec![ParsedField {
+        vis: vis.clone(),
+        ident: discriminator_ident.clone(),
+        ty: parse_quote!(::rorm::model_enum::Discriminator<#ident>),
+        annos: ModelFieldAnnotations {
+            rename: discriminator,
+            ..Default::default()
+        },
+    }];
+
+    // Collect the union of the variants' fields
+    let num_variants = variants.len();
+    let mut declarations = vec![0];
+    let mut analyzed_variants = Vec::with_capacity(num_variants);
+    for ParsedVariant {
+        ident: variant,
+        fields: variant_fields,
+    } in variants
+    {
+        let mut indices = Vec::with_capacity(variant_fields.len());
+        for field in variant_fields {
+            if field.ident == discriminator_ident {
+                errors.push(
+                    darling::Error::custom(
+                        "`kind` is reserved for the discriminator. Please rename the field.",
+                    )
+                    .with_span(&field.ident),
+                );
+                continue;
+            }
+            if field.annos.skip {
+                errors.push(
+                    darling::Error::custom("A ModelEnum's fields can't be skipped.")
+                        .with_span(&field.ident),
+                );
+                continue;
+            }
+            match fields.iter().position(|other| other.ident == field.ident) {
+                Some(index) => {
+                    if fields[index].ty.to_token_stream().to_string()
+                        != field.ty.to_token_stream().to_string()
+                    {
+                        errors.push(
+                            darling::Error::custom(format!(
+                                "`{}` is declared with another type by a previous variant",
+                                field.ident
+                            ))
+                            .with_span(&field.ty),
+                        );
+                    }
+                    declarations[index] += 1;
+                    indices.push(index);
+                }
+                None => {
+                    indices.push(fields.len());
+                    declarations.push(1);
+                    fields.push(field);
+                }
+            }
+        }
+        analyzed_variants.push(AnalyzedVariant {
+            ident: variant,
+            fields: indices,
+        });
+    }
+
+    // Store the fields not declared by every variant as nullable columns
+    let mut wrapped = vec![false];
+    for (field, declarations) in fields.iter_mut().zip(declarations).skip(1) {
+        let annos = &field.annos;
+        if annos.defer {
+            errors.push(
+                darling::Error::custom("A ModelEnum's fields can't be deferred.")
+                    .with_span(&field.ident),
+            );
+        }
+        if declarations == num_variants || option_argument(&field.ty).is_some() {
+            wrapped.push(false);
+            continue;
+        }
+        if annos.id || annos.primary_key {
+            errors.push(
+                darling::Error::custom("The primary key has to be declared by every variant.")
+                    .with_span(&field.ident),
+            );
+        }
+        if annos.validate.is_some() {
+            errors.push(
+                darling::Error::custom(
+                    "`#[rorm(validate(..))]` is only supported on fields declared by every variant.",
+                )
+                .with_span(&field.ident),
+            );
+        }
+        let ty = &field.ty;
+        field.ty = parse_quote!(Option<#ty>);
+        wrapped.push(true);
+    }
+
+    let model = errors.handle(analyze_model(ParsedModel {
+        vis,
+        ident,
+        generics: Generics::default(),
+        annos: ModelAnnotations {
+            rename,
+            hooks,
+            default_scope,
+            audit,
+            track_changes: false,
+            database,
+            shard_by: None,
+            shards: None,
+            time_partition_by: None,
+            period: None,
+            patch: Vec::new(),
+            insert_patch: None,
+            experimental_unregistered,
+            experimental_generics: false,
+        },
+        fields,
+    }));
+    errors.finish()?;
+    let model = model.expect("analyze_model's errors should have been returned");
+
+    // Take the declared types from the analyzed fields whose `Self` has been replaced
+    let wrapped = model
+        .fields
+        .iter()
+        .zip(wrapped)
+        .map(|(field, wrapped)| {
+            if wrapped {
+                option_argument(&field.ty).cloned()
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    Ok(AnalyzedModelEnum {
+        module: format_ident!(
+            "{}",
+            to_snake_case(&model.ident.to_string()),
+            span = model.ident.span()
+        ),
+        model,
+        variants: analyzed_variants,
+        wrapped,
+    })
+}
+
+/// Get `T` from a type written as `Option<T>`
+fn option_argument(ty: &Type) -> Option<&Type> {
+    let Type::Path(TypePath { path, .. }) = ty else {
+        return None;
+    };
+    let segment = path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    let PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+        return None;
+    };
+    match arguments.args.first()? {
+        GenericArgument::Type(argument) => Some(argument),
+        _ => None,
+    }
+}
+
+/// Convert an enum's identifier into its module's name
+fn to_snake_case(ident: &str) -> String {
+    let mut name = String::with_capacity(ident.len());
+    for (index, char) in ident.chars().enumerate() {
+        if char.is_ascii_uppercase() {
+            if index != 0 {
+                name.push('_');
+            }
+            name.push(char.to_ascii_lowercase());
+        } else {
+            name.push(char);
+        }
+    }
+    name
+}
+
+pub struct AnalyzedModelEnum {
+    /// The model storing the variants whose first field is the discriminator
+    pub model: AnalyzedModel,
+
+    /// The module containing the variants' marker types
+    pub module: Ident,
+
+    pub variants: Vec<AnalyzedVariant>,
+
+    /// The declared types of the model's fields which are wrapped in an `Option`
+    ///
+    /// These are the fields not declared by every variant.
+    pub wrapped: Vec<Option<Type>>,
+}
+
+pub struct AnalyzedVariant {
+    pub ident: Ident,
+
+    /// The indices of the variant's fields in the model
+    pub fields: Vec<usize>,
+}
@@ -1,4 +1,4 @@
-use proc_macro2::TokenStream;
+use proc_macro2::{Literal, TokenStream};
 use quote::{format_ident, quote};
 
 use crate::parse::db_enum::ParsedDbEnum;
@@ -8,41 +8,121 @@ pub fn generate_db_enum(parsed: &ParsedDbEnum) -> TokenStream {
         vis,
         ident,
         variants,
+        repr,
+        discriminants,
     } = parsed;
     let decoder = format_ident!("__{ident}_Decoder");
 
-    quote! {
-        const _: () = {
+    // Select how the variants are represented in the db
+    let (null_type, primitive, values) = match repr {
+        None => (
+            quote! { String },
+            quote! { ::rorm::db::choice::Choice },
+            variants
+                .iter()
+                .map(|variant| quote! { stringify!(#variant) })
+                .collect::<Vec<_>>(),
+        ),
+        Some(repr) => (
+            match repr.to_string().as_str() {
+                "i16" => quote! { I16 },
+                "i32" => quote! { I32 },
+                _ => quote! { I64 },
+            },
+            quote! { #repr },
+            discriminants
+                .iter()
+                .map(|discriminant| {
+                    let literal = Literal::i64_unsuffixed(*discriminant);
+                    quote! { #literal }
+                })
+                .collect(),
+        ),
+    };
+    // Only string enums are stored as choices,
+    // integer enums are stored as plain integers without a constraint on their values.
+    let choices = match repr {
+        None => quote! {
             const CHOICES: &'static [&'static str] = &[
-                #(stringify!(#variants)),*
+                #(#values),*
             ];
+        },
+        Some(_) => quote! {},
+    };
+    let annotations = match repr {
+        None => quote! {
+            ::rorm::const_fn! {
+                pub fn get_db_enum_annotations(
+                    field: ::rorm::internal::hmr::annotations::Annotations
+                ) -> [::rorm::internal::hmr::annotations::Annotations; 1] {
+                    let mut field = field;
+                    field.choices = Some(::rorm::internal::hmr::annotations::Choices(CHOICES));
+                    [field]
+                }
+            }
+        },
+        Some(_) => quote! {},
+    };
+    let get_annotations = match repr {
+        None => quote! { get_db_enum_annotations },
+        Some(_) => quote! { ::rorm::fields::utils::get_annotations::forward_annotations<1> },
+    };
+    let into_value = match repr {
+        None => quote! { ::rorm::conditions::Value::Choice(::std::borrow::Cow::Borrowed(value)) },
+        Some(_) => quote! { ::rorm::conditions::Value::#null_type(value) },
+    };
+    let decode = match repr {
+        None => quote! {
+            let value: String = value.0;
+            match value.as_str() {
+                #(
+                    #values => Ok(#ident::#variants),
+                )*
+                _ => Err(format!("Invalid value '{}' for enum '{}'", value, stringify!(#ident))),
+            }
+        },
+        Some(_) => quote! {
+            match value {
+                #(
+                    #values => Ok(#ident::#variants),
+                )*
+                _ => Err(format!("Invalid value '{}' for enum '{}'", value, stringify!(#ident))),
+            }
+        },
+    };
+
+    quote! {
+        const _: () = {
+            #choices
 
             impl ::rorm::fields::traits::FieldType for #ident {
                 type Columns = ::rorm::fields::traits::Array<1>;
 
                 const NULL: ::rorm::fields::traits::FieldColumns<Self, ::rorm::db::sql::value::NullType> = [
-                    ::rorm::db::sql::value::NullType::String
+                    ::rorm::db::sql::value::NullType::#null_type
                 ];
 
                 fn into_values<'a>(self) -> ::rorm::fields::traits::FieldColumns<Self, ::rorm::conditions::Value<'a>> {
-                    [::rorm::conditions::Value::Choice(::std::borrow::Cow::Borrowed(match self {
+                    let value = match self {
                         #(
-                            Self::#variants => stringify!(#variants),
+                            Self::#variants => #values,
                         )*
-                    }))]
+                    };
+                    [#into_value]
                 }
 
                 fn as_values(&self) -> ::rorm::fields::traits::FieldColumns<Self, ::rorm::conditions::Value<'_>> {
-                    [::rorm::conditions::Value::Choice(::std::borrow::Cow::Borrowed(match self {
+                    let value = match self {
                         #(
-                            Self::#variants => stringify!(#variants),
+                            Self::#variants => #values,
                         )*
-                    }))]
+                    };
+                    [#into_value]
                 }
 
                 type Decoder = #decoder;
 
-                type GetAnnotations = get_db_enum_annotations;
+                type GetAnnotations = #get_annotations;
 
                 type Check = ::rorm::fields::utils::check::shared_linter_check<1>;
 
@@ -51,29 +131,15 @@ pub fn generate_db_enum(parsed: &ParsedDbEnum) -> TokenStream {
             ::rorm::new_converting_decoder!(
                 #[doc(hidden)]
                 #vis #decoder,
-                |value: ::rorm::db::choice::Choice| -> #ident {
-                    let value: String = value.0;
-                    match value.as_str() {
-                        #(
-                            stringify!(#variants) => Ok(#ident::#variants),
-                        )*
-                        _ => Err(format!("Invalid value '{}' for enum '{}'", value, stringify!(#ident))),
-                    }
+                |value: #primitive| -> #ident {
+                    #decode
                 }
             );
             ::rorm::impl_FieldEq!(impl<'rhs> FieldEq<'rhs, #ident> for #ident {
                 |value: #ident| { let [value] = <#ident as ::rorm::fields::traits::FieldType>::into_values(value); value }
             });
 
-            ::rorm::const_fn! {
-                pub fn get_db_enum_annotations(
-                    field: ::rorm::internal::hmr::annotations::Annotations
-                ) -> [::rorm::internal::hmr::annotations::Annotations; 1] {
-                    let mut field = field;
-                    field.choices = Some(::rorm::internal::hmr::annotations::Choices(CHOICES));
-                    [field]
-                }
-            }
+            #annotations
         };
     }
 }
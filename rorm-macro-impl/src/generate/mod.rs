@@ -1,4 +1,6 @@
 pub mod db_enum;
 pub mod model;
+pub mod model_enum;
+pub mod new_type;
 pub mod patch;
 mod utils;
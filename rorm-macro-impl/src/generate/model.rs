@@ -2,42 +2,276 @@ use proc_macro2::{Ident, Span, TokenStream};
 use quote::{format_ident, quote, quote_spanned, ToTokens};
 use syn::{GenericParam, LitStr};
 
-use crate::analyze::model::{AnalyzedField, AnalyzedModel, AnalyzedModelFieldAnnotations};
-use crate::generate::patch::partially_generate_patch;
+use crate::analyze::model::{
+    AnalyzedField, AnalyzedModel, AnalyzedModelFieldAnnotations, AnalyzedPatch, CounterCache,
+};
+use crate::generate::patch::{generate_patch, partially_generate_patch, PatchField};
 use crate::generate::utils::get_source;
 use crate::generate::utils::phantom_data;
-use crate::parse::annotations::{Index, NamedIndex, OnAction};
+use crate::parse::annotations::{Index, NamedIndex, OnAction, Validate, ValidateRange};
+use crate::parse::patch::{ParsedPatch, ParsedPatchField};
 
 pub fn generate_model(model: &AnalyzedModel) -> TokenStream {
-    let (fields_struct_ident, fields_struct) = generate_fields_struct(model);
-    let value_space_impl = format_ident!("__{}_ValueSpaceImpl", model.ident);
-    let field_declarations = generate_fields(model);
     let AnalyzedModel {
         vis,
         ident,
-        table,
         fields,
+        skipped_fields,
         primary_key,
-        experimental_unregistered,
+        patches,
+        track_changes,
         experimental_generics,
+        ..
     } = model;
-    let primary_struct = &fields[*primary_key].unit;
     let primary_ident = &fields[*primary_key].ident;
     let primary_type = &fields[*primary_key].ty;
+    let patch_fields = fields
+        .iter()
+        .map(|field| PatchField {
+            ident: &field.ident,
+            ty: &field.ty,
+            deferred: field.annos.defer,
+        })
+        .collect::<Vec<_>>();
+    let skipped_fields = skipped_fields.iter().collect::<Vec<_>>();
     let impl_patch = partially_generate_patch(
         ident,
         ident,
         vis,
         experimental_generics,
-        fields.iter().map(|field| &field.ident),
-        fields.iter().map(|field| &field.ty),
+        &patch_fields,
+        &skipped_fields,
     );
+    let (impl_generics, type_generics, where_clause) = experimental_generics.split_for_impl();
+    let track_changes = track_changes.then(|| {
+        let field_idents = fields.iter().map(|field| &field.ident);
+        quote! {
+            impl #impl_generics ::rorm::crud::tracked::TrackChanges for #ident #type_generics #where_clause {
+                fn push_changed_columns(&self, original: &Self, columns: &mut Vec<&'static str>) {#(
+                    if self.#field_idents != original.#field_idents {
+                        columns.extend(
+                            ::rorm::internal::field::FieldProxy::columns(<Self as ::rorm::model::Model>::FIELDS.#field_idents)
+                        );
+                    }
+                )*}
+            }
+        }
+    });
+
+    let patches = patches.iter().map(
+        |AnalyzedPatch {
+             ident: patch,
+             doc,
+             fields: indices,
+         }| {
+            let patch_fields = indices.iter().map(|index| &fields[*index]);
+            let field_vis = patch_fields.clone().map(|field| &field.vis);
+            let field_idents = patch_fields.clone().map(|field| &field.ident);
+            let field_types = patch_fields.clone().map(|field| &field.ty);
+            let impl_patch = generate_patch(&ParsedPatch {
+                vis: vis.clone(),
+                ident: patch.clone(),
+                model: ident.clone().into(),
+                fields: patch_fields
+                    .map(|field| ParsedPatchField {
+                        ident: field.ident.clone(),
+                        ty: field.ty.clone(),
+                    })
+                    .collect(),
+            });
+            quote! {
+                #[doc = #doc]
+                #vis struct #patch {#(
+                    #field_vis #field_idents: #field_types,
+                )*}
+                #impl_patch
+            }
+        },
+    );
+
+    let mut tokens = partially_generate_model(model);
+    tokens.extend(quote! {
+        #impl_patch
+        #track_changes
+        #(#patches)*
+    });
+    for field in fields {
+        let field_struct = &field.unit;
+        let field_ident = &field.ident;
+        let field_type = &field.ty;
+        tokens.extend(quote! {
+            impl #impl_generics ::rorm::model::GetField<#field_struct #type_generics> for #ident #type_generics #where_clause {
+                fn get_field(self) -> #field_type {
+                    self.#field_ident
+                }
+                fn borrow_field(&self) -> &#field_type {
+                    &self.#field_ident
+                }
+                fn borrow_field_mut(&mut self) -> &mut #field_type {
+                    &mut self.#field_ident
+                }
+            }
+        });
+        if !field.annos.primary_key {
+            tokens.extend(quote! {
+                impl #impl_generics ::rorm::model::UpdateField<#field_struct #type_generics> for #ident #type_generics #where_clause {
+                    fn update_field<'m, T>(
+                        &'m mut self,
+                        update: impl FnOnce(&'m #primary_type, &'m mut #field_type) -> T,
+                    ) -> T {
+                        update(&self.#primary_ident, &mut self.#field_ident)
+                    }
+                }
+            });
+        }
+    }
+    tokens
+}
+
+/// Generate a model's fields and its `Model` implementation
+///
+/// The `Patch` implementation, including the value space enum, is left to the caller.
+pub fn partially_generate_model(model: &AnalyzedModel) -> TokenStream {
+    let (fields_struct_ident, fields_struct) = generate_fields_struct(model);
+    let value_space_impl = format_ident!("__{}_ValueSpaceImpl", model.ident);
+    let field_declarations = generate_fields(model);
+    let AnalyzedModel {
+        vis: _,
+        ident,
+        table,
+        fields,
+        skipped_fields: _, // Generated by generate_model
+        primary_key,
+        patches: _, // Generated by generate_model
+        hooks,
+        default_scope,
+        audit,
+        track_changes: _, // Generated by generate_model
+        database,
+        shard_by,
+        time_partition,
+        experimental_unregistered,
+        experimental_generics,
+    } = model;
+    let primary_struct = &fields[*primary_key].unit;
+    let hooks_type = if *hooks {
+        quote! { Self }
+    } else {
+        quote! { () }
+    };
+    let default_scope_type = if *default_scope {
+        quote! { Self }
+    } else {
+        quote! { () }
+    };
+    let audit = audit.then(|| quote! { const AUDIT: bool = true; });
+    let database = database
+        .as_ref()
+        .map(|database| quote! { const DATABASE: Option<&'static str> = Some(#database); });
+    let tenant_key = fields
+        .iter()
+        .find(|field| field.annos.tenant_key)
+        .map(|field| {
+            let column = &field.column;
+            quote! { const TENANT_KEY: Option<&'static str> = Some(#column); }
+        });
+    let counter_caches = fields
+        .iter()
+        .filter_map(|field| {
+            let CounterCache {
+                model,
+                field: counter,
+            } = field.annos.counter_cache.as_ref()?;
+            let field = &field.ident;
+            Some(quote! {
+                ::rorm::counter_cache::CounterCache::new(
+                    <Self as ::rorm::model::Model>::FIELDS.#field,
+                    <#model as ::rorm::model::Model>::FIELDS.#counter,
+                )
+            })
+        })
+        .collect::<Vec<_>>();
+    let counter_caches = (!counter_caches.is_empty()).then(|| {
+        quote! {
+            const COUNTER_CACHES: &'static [::rorm::counter_cache::CounterCache] = &[#(#counter_caches),*];
+        }
+    });
+    let content_hashes = fields
+        .iter()
+        .filter_map(|field| {
+            let source = field.annos.hash_of.as_ref()?;
+            let field = &field.ident;
+            Some(quote! {
+                ::rorm::content_hash::ContentHash::new(
+                    <Self as ::rorm::model::Model>::FIELDS.#field,
+                    <Self as ::rorm::model::Model>::FIELDS.#source,
+                )
+            })
+        })
+        .collect::<Vec<_>>();
+    let content_hashes = (!content_hashes.is_empty()).then(|| {
+        quote! {
+            const CONTENT_HASHES: &'static [::rorm::content_hash::ContentHash] = &[#(#content_hashes),*];
+        }
+    });
+    let readonly_fields = fields
+        .iter()
+        .filter(|field| field.annos.readonly)
+        .map(|field| &field.ident)
+        .collect::<Vec<_>>();
+    let push_readonly_columns = (!readonly_fields.is_empty()).then(|| {
+        quote! {
+            fn push_readonly_columns(columns: &mut Vec<&'static str>) {#(
+                columns.extend(
+                    ::rorm::internal::field::FieldProxy::columns(<Self as ::rorm::model::Model>::FIELDS.#readonly_fields)
+                );
+            )*}
+        }
+    });
+    let deferred_fields = fields
+        .iter()
+        .filter(|field| field.annos.defer)
+        .map(|field| &field.ident)
+        .collect::<Vec<_>>();
+    let push_deferred_columns = (!deferred_fields.is_empty()).then(|| {
+        quote! {
+            fn push_deferred_columns(columns: &mut Vec<&'static str>) {#(
+                columns.extend(
+                    ::rorm::internal::field::FieldProxy::columns(<Self as ::rorm::model::Model>::FIELDS.#deferred_fields)
+                );
+            )*}
+        }
+    });
     let field_structs_1 = fields.iter().map(|field| &field.unit);
     let field_structs_2 = field_structs_1.clone();
 
     let source = get_source(ident.span());
 
     let (impl_generics, type_generics, where_clause) = experimental_generics.split_for_impl();
+    let sharding = shard_by.as_ref().and_then(|(field, shards)| {
+        let shard_field = &fields.iter().find(|analyzed| analyzed.ident == *field)?.unit;
+        Some(quote! {
+            impl #impl_generics ::rorm::sharding::ShardedModel for #ident #type_generics #where_clause {
+                type ShardField = #shard_field #type_generics;
+                const SHARDING: ::rorm::sharding::Sharding = ::rorm::sharding::Sharding::new(
+                    <Self as ::rorm::model::Model>::FIELDS.#field,
+                    #shards,
+                );
+            }
+        })
+    });
+    let time_partition = time_partition.as_ref().and_then(|(field, period)| {
+        let partition_field = &fields.iter().find(|analyzed| analyzed.ident == *field)?.unit;
+        Some(quote! {
+            impl #impl_generics ::rorm::time_partition::TimePartitionedModel for #ident #type_generics #where_clause {
+                type PartitionField = #partition_field #type_generics;
+                const TIME_PARTITION: ::rorm::time_partition::TimePartition = ::rorm::time_partition::TimePartition::new(
+                    <Self as ::rorm::model::Model>::FIELDS.#field,
+                    ::rorm::time_partition::Period::#period,
+                );
+            }
+        })
+    });
     let mut generics_with_path = model.experimental_generics.clone();
     generics_with_path
         .params
@@ -68,27 +302,53 @@ pub fn generate_model(model: &AnalyzedModel) -> TokenStream {
         }
         impl #impl_generics ::rorm::model::Model for #ident #type_generics #where_clause {
             type Primary = #primary_struct #type_generics;
+            type Hooks = #hooks_type;
+            type DefaultScope = #default_scope_type;
 
             type Fields<P: ::rorm::internal::relation_path::Path> = #fields_struct_ident #type_generics_with_path;
             const F: #fields_struct_ident #type_generics_with_self = ::rorm::model::ConstNew::NEW;
             const FIELDS: #fields_struct_ident #type_generics_with_self = ::rorm::model::ConstNew::NEW;
 
             const TABLE: &'static str = #table;
+            #audit
+            #database
+            #tenant_key
+            #counter_caches
+            #content_hashes
             const SOURCE: ::rorm::internal::hmr::Source = #source;
 
             fn push_fields_imr(fields: &mut Vec<::rorm::imr::Field>) {#(
                 ::rorm::internal::field::push_imr::<#field_structs_1 #type_generics>(&mut *fields);
             )*}
+            #push_readonly_columns
+            #push_deferred_columns
         }
-
-        #impl_patch
+        #sharding
+        #time_partition
     };
+    if let Some(database) = &model.database {
+        tokens.extend(quote! {
+            const _: () = {
+                #[::rorm::linkme::distributed_slice(::rorm::MODEL_DATABASES)]
+                #[linkme(crate = ::rorm::linkme)]
+                static __database: (&'static str, &'static str) = (<#ident as ::rorm::model::Model>::TABLE, #database);
+            };
+        });
+    }
     if !*experimental_unregistered {
+        let audit_registration = model.audit.then(|| {
+            quote! {
+                #[::rorm::linkme::distributed_slice(::rorm::MODELS)]
+                #[linkme(crate = ::rorm::linkme)]
+                static __get_audit_imr: fn() -> ::rorm::imr::Model = ::rorm::audit::get_audit_imr::<#ident>;
+            }
+        });
         tokens.extend(quote! {
             const _: () = {
                 #[::rorm::linkme::distributed_slice(::rorm::MODELS)]
                 #[linkme(crate = ::rorm::linkme)]
                 static __get_imr: fn() -> ::rorm::imr::Model = <#ident as ::rorm::model::Model>::get_imr;
+                #audit_registration
 
                 // Cross field checks
                 let mut count_auto_increment = 0;
@@ -107,37 +367,11 @@ pub fn generate_model(model: &AnalyzedModel) -> TokenStream {
     }
     for (index, field) in fields.iter().enumerate() {
         let field_struct = &field.unit;
-        let field_ident = &field.ident;
-        let field_type = &field.ty;
         tokens.extend(quote! {
             impl #impl_generics ::rorm::model::FieldByIndex<{ #index }> for #ident #type_generics #where_clause {
                 type Field = #field_struct #type_generics;
             }
-
-            impl #impl_generics ::rorm::model::GetField<#field_struct #type_generics> for #ident #type_generics #where_clause {
-                fn get_field(self) -> #field_type {
-                    self.#field_ident
-                }
-                fn borrow_field(&self) -> &#field_type {
-                    &self.#field_ident
-                }
-                fn borrow_field_mut(&mut self) -> &mut #field_type {
-                    &mut self.#field_ident
-                }
-            }
         });
-        if !field.annos.primary_key {
-            tokens.extend(quote! {
-                impl #impl_generics ::rorm::model::UpdateField<#field_struct #type_generics> for #ident #type_generics #where_clause {
-                    fn update_field<'m, T>(
-                        &'m mut self,
-                        update: impl FnOnce(&'m #primary_type, &'m mut #field_type) -> T,
-                    ) -> T {
-                        update(&self.#primary_ident, &mut self.#field_ident)
-                    }
-                }
-            });
-        }
     }
     tokens
 }
@@ -161,6 +395,11 @@ fn generate_fields(model: &AnalyzedModel) -> TokenStream {
             &format!("rorm's representation of [`{model_ident}`]'s `{ident}` field",),
             ident.span(),
         );
+        let validate = annos.validate.as_ref().map(generate_field_validate);
+        let readonly = annos
+            .readonly
+            .then(|| quote! { const READONLY: bool = true; });
+        let deferred = annos.defer.then(|| quote! { const DEFERRED: bool = true; });
         let annos = generate_field_annotations(annos);
         let (impl_generics, type_generics, where_clause) =
             model.experimental_generics.split_for_impl();
@@ -183,9 +422,12 @@ fn generate_fields(model: &AnalyzedModel) -> TokenStream {
                 const NAME: &'static str = #column;
                 const EXPLICIT_ANNOTATIONS: ::rorm::internal::hmr::annotations::Annotations = #annos;
                 const SOURCE: ::rorm::internal::hmr::Source = #source;
+                #readonly
+                #deferred
                 fn new() -> Self {
                     Self(::std::marker::PhantomData)
                 }
+                #validate
             }
         });
         if !model.experimental_unregistered {
@@ -210,9 +452,15 @@ fn generate_field_annotations(annos: &AnalyzedModelFieldAnnotations) -> TokenStr
         unique,
         on_delete,
         on_update,
+        readonly: _,      // Generated as constant by generate_fields
+        defer: _,         // Generated as constant by generate_fields
+        tenant_key: _,    // Generated as constant by generate_model
+        counter_cache: _, // Generated as constant by generate_model
+        hash_of: _,       // Generated as constant by generate_model
         default,
         max_length,
         index,
+        validate: _, // Generated as method by generate_field_validate
     } = annos;
 
     // Convert every field into its "creation" expression
@@ -223,7 +471,7 @@ fn generate_field_annotations(annos: &AnalyzedModelFieldAnnotations) -> TokenStr
     let unique = unique.then(|| quote! {Unique});
     let max_length = max_length.as_ref().map(|len| quote! {MaxLength(#len)});
     let default = default.as_ref().map(|default| {
-        let variant = Ident::new(&default.variant, default.literal.span());
+        let variant = Ident::new(default.variant, default.literal.span());
         let literal = &default.literal;
         quote! {DefaultValue(::rorm::internal::hmr::annotations::DefaultValueData::#variant(#literal))}
     });
@@ -294,6 +542,49 @@ fn generate_field_annotations(annos: &AnalyzedModelFieldAnnotations) -> TokenStr
     }
 }
 
+fn generate_field_validate(validate: &Validate) -> TokenStream {
+    let Validate {
+        range,
+        regex,
+        email,
+    } = validate;
+
+    let range = range.as_ref().map(|ValidateRange { min, max }| {
+        let min = match min {
+            Some(min) => quote! { Some((#min) as f64) },
+            None => quote! { None },
+        };
+        let max = match max {
+            Some(max) => quote! { Some((#max) as f64) },
+            None => quote! { None },
+        };
+        quote! {
+            ::rorm::validate::range(value, #min, #max)?;
+        }
+    });
+    let regex = regex.as_ref().map(|regex| {
+        quote! {{
+            static REGEX: ::rorm::validate::LazyRegex = ::rorm::validate::LazyRegex::new(#regex);
+            REGEX.check(value)?;
+        }}
+    });
+    let email = email.then(|| {
+        quote! {
+            ::rorm::validate::email(value)?;
+        }
+    });
+
+    quote! {
+        fn validate(value: &Self::Type) -> Result<(), ::rorm::validate::ValidationErrorKind> {
+            <Self::Type as ::rorm::fields::traits::FieldType>::check_value(value)?;
+            #range
+            #regex
+            #email
+            Ok(())
+        }
+    }
+}
+
 fn generate_fields_struct(model: &AnalyzedModel) -> (Ident, TokenStream) {
     let vis = &model.vis;
     let ident = format_ident!("__{}_Fields_Struct", model.ident);
@@ -0,0 +1,294 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::LitStr;
+
+use crate::analyze::model::AnalyzedModel;
+use crate::analyze::model_enum::AnalyzedModelEnum;
+use crate::generate::model::partially_generate_model;
+
+pub fn generate_model_enum(model_enum: &AnalyzedModelEnum) -> TokenStream {
+    let AnalyzedModelEnum {
+        model,
+        module,
+        variants,
+        wrapped,
+    } = model_enum;
+    let AnalyzedModel {
+        vis, ident, fields, ..
+    } = model;
+    let model_tokens = partially_generate_model(model);
+
+    let fields_struct = format_ident!("__{ident}_Fields_Struct");
+    let value_space_impl = format_ident!("__{ident}_ValueSpaceImpl");
+    let value_space_marker_impl = format_ident!("__{ident}_ValueSpaceImplMarker");
+    let decoder = format_ident!("__{ident}_Decoder");
+
+    let discriminator = &fields[0].ident;
+    let discriminator_unit = &fields[0].unit;
+    let field_idents = fields.iter().map(|field| &field.ident).collect::<Vec<_>>();
+    let field_decoders = fields.iter().zip(wrapped).map(|(field, wrapped)| {
+        let ty = &field.ty;
+        match wrapped {
+            Some(wrapped) => quote! { ::rorm::model_enum::VariantFieldDecoder<#wrapped> },
+            None => quote! { <#ty as ::rorm::fields::traits::FieldType>::Decoder },
+        }
+    });
+    let new_field_decoders = fields.iter().zip(wrapped).map(|(field, wrapped)| {
+        let field_ident = &field.ident;
+        let proxy = quote! { <#ident as ::rorm::model::Model>::FIELDS.#field_ident.through::<P>() };
+        match wrapped {
+            Some(_) => quote! { ::rorm::model_enum::VariantFieldDecoder::new(ctx, #proxy) },
+            None => quote! { ::rorm::internal::field::decoder::FieldDecoder::new(ctx, #proxy) },
+        }
+    });
+
+    let variant_idents = variants
+        .iter()
+        .map(|variant| &variant.ident)
+        .collect::<Vec<_>>();
+    let variant_names = variants
+        .iter()
+        .map(|variant| LitStr::new(&variant.ident.to_string(), variant.ident.span()))
+        .collect::<Vec<_>>();
+    let module_doc = LitStr::new(
+        &format!("Markers for [`{ident}`]'s variants"),
+        module.span(),
+    );
+    let variant_docs = variants.iter().map(|variant| {
+        LitStr::new(
+            &format!(
+                "Marker for [`{ident}::{variant}`](super::{ident}::{variant})",
+                variant = variant.ident
+            ),
+            variant.ident.span(),
+        )
+    });
+
+    // Per variant: the fields it declares and their bindings in a match arm
+    let variant_patterns = variants
+        .iter()
+        .map(|variant| {
+            let declared = variant.fields.iter().map(|index| &fields[*index].ident);
+            let bindings = variant
+                .fields
+                .iter()
+                .map(|index| format_ident!("field_{index}"));
+            let variant = &variant.ident;
+            quote! { Self::#variant { #(#declared: #bindings),* } }
+        })
+        .collect::<Vec<_>>();
+    let decode_variants = |by: TokenStream| {
+        variants.iter().map(move |variant| {
+            let declared = variant.fields.iter().map(|index| &fields[*index].ident);
+            let variant = &variant.ident;
+            quote! { #ident::#variant { #(#declared: self.#declared.#by(row)?),* } }
+        })
+    };
+    let decode_by_name = decode_variants(quote! { by_name });
+    let decode_by_index = decode_variants(quote! { by_index });
+    let push_variant_values = |into_values: TokenStream| {
+        variants.iter().map(move |variant| {
+            let values = fields.iter().enumerate().skip(1).map(|(index, field)| {
+                if variant.fields.contains(&index) {
+                    let binding = format_ident!("field_{index}");
+                    quote! { values.extend(::rorm::fields::traits::FieldType::#into_values(#binding)); }
+                } else {
+                    let ty = &field.ty;
+                    quote! { values.extend(::rorm::fields::traits::FieldType::into_values(<#ty>::None)); }
+                }
+            });
+            let variant = &variant.ident;
+            quote! {
+                values.extend(::rorm::fields::traits::FieldType::into_values(
+                    ::rorm::model_enum::Discriminator::<Self>::of::<#module::#variant>(),
+                ));
+                #(#values)*
+            }
+        })
+    };
+    let push_references = push_variant_values(quote! { as_values });
+    let push_values = push_variant_values(quote! { into_values });
+    let validate_variants = variants.iter().map(|variant| {
+        let checks = variant.fields.iter().map(|index| {
+            let field = &fields[*index];
+            let binding = format_ident!("field_{index}");
+            let field_ident = &field.ident;
+            let unit = &field.unit;
+            match &wrapped[*index] {
+                None => quote! {
+                    if let Err(error) = ::rorm::internal::field::FieldProxy::validate(
+                        <Self as ::rorm::model::Model>::FIELDS.#field_ident,
+                        #binding,
+                    ) {
+                        errors.push(error);
+                    }
+                },
+                Some(_) => quote! {
+                    if let Err(kind) = ::rorm::fields::traits::FieldType::check_value(#binding) {
+                        errors.push(::rorm::validate::ValidationError {
+                            field: <#unit as ::rorm::internal::field::Field>::NAME,
+                            kind,
+                        });
+                    }
+                },
+            }
+        });
+        quote! { #(#checks)* }
+    });
+
+    quote! {
+        #model_tokens
+
+        impl<Path: ::rorm::internal::relation_path::Path> #fields_struct<Path> {
+            /// Check the row to be of the variant `V`
+            pub fn type_is<V: ::rorm::model_enum::ModelEnumVariant<Enum = #ident>>(
+                &self,
+            ) -> ::rorm::conditions::Binary<
+                ::rorm::conditions::Column<::rorm::internal::field::FieldProxy<#discriminator_unit, Path>>,
+                ::rorm::conditions::Value<'static>,
+            > {
+                self.#discriminator.is_variant::<V>()
+            }
+        }
+
+        impl ::rorm::model_enum::ModelEnum for #ident {
+            const VARIANTS: &'static [&'static str] = &[#(#variant_names),*];
+        }
+
+        #[doc = #module_doc]
+        #vis mod #module {#(
+            #[doc = #variant_docs]
+            pub struct #variant_idents;
+            impl ::rorm::model_enum::ModelEnumVariant for #variant_idents {
+                type Enum = super::#ident;
+                const NAME: &'static str = #variant_names;
+            }
+        )*}
+
+        // Credit and explanation: https://github.com/dtolnay/case-studies/tree/master/unit-type-parameters
+        #[doc(hidden)]
+        #[allow(non_camel_case_types)]
+        #vis enum #value_space_impl {
+            #ident,
+
+            #[allow(dead_code)]
+            #[doc(hidden)]
+            #value_space_marker_impl(::std::marker::PhantomData<#ident>),
+        }
+        #vis use #value_space_impl::*;
+        impl ::std::clone::Clone for #value_space_impl {
+            fn clone(&self) -> Self {
+                *self
+            }
+        }
+        impl ::std::marker::Copy for #value_space_impl {}
+
+        #[doc(hidden)]
+        #vis struct #decoder {
+            #(
+                #field_idents: #field_decoders,
+            )*
+        }
+
+        impl ::rorm::crud::selector::Selector for #value_space_impl {
+            type Result = #ident;
+            type Model = #ident;
+            type Decoder = #decoder;
+            const INSERT_COMPATIBLE: bool = true;
+            fn select(self, ctx: &mut ::rorm::internal::query_context::QueryContext) -> Self::Decoder {
+                <#ident as ::rorm::model::Patch>::select::<#ident>(ctx)
+            }
+        }
+
+        impl ::std::default::Default for #value_space_impl {
+            fn default() -> Self {
+                Self::#ident
+            }
+        }
+
+        impl ::rorm::crud::decoder::Decoder for #decoder {
+            type Result = #ident;
+
+            fn by_name<'index>(&'index self, row: &'_ ::rorm::db::Row) -> Result<Self::Result, ::rorm::db::row::RowError<'index>> {
+                Ok(match self.#discriminator.by_name(row)?.name() {
+                    #(
+                        #variant_names => #decode_by_name,
+                    )*
+                    _ => unreachable!("The discriminator's decoder only accepts the enum's variants"),
+                })
+            }
+
+            fn by_index<'index>(&'index self, row: &'_ ::rorm::db::Row) -> Result<Self::Result, ::rorm::db::row::RowError<'index>> {
+                Ok(match self.#discriminator.by_index(row)?.name() {
+                    #(
+                        #variant_names => #decode_by_index,
+                    )*
+                    _ => unreachable!("The discriminator's decoder only accepts the enum's variants"),
+                })
+            }
+        }
+
+        impl ::rorm::model::Patch for #ident {
+            type Model = #ident;
+
+            type ValueSpaceImpl = #value_space_impl;
+
+            type Decoder = #decoder;
+
+            fn select<P: ::rorm::internal::relation_path::Path>(ctx: &mut ::rorm::internal::query_context::QueryContext) -> Self::Decoder {
+                #decoder {#(
+                    #field_idents: #new_field_decoders,
+                )*}
+            }
+
+            fn push_columns(columns: &mut impl Extend<&'static str>) {#(
+                columns.extend(
+                    ::rorm::internal::field::FieldProxy::columns(<Self as ::rorm::model::Model>::FIELDS.#field_idents)
+                );
+            )*}
+
+            fn push_references<'a>(&'a self, values: &mut impl Extend<::rorm::conditions::Value<'a>>) {
+                match self {#(
+                    #variant_patterns => {
+                        #push_references
+                    }
+                )*}
+            }
+
+            fn push_values<'a>(self, values: &mut impl Extend<::rorm::conditions::Value<'a>>) {
+                match self {#(
+                    #variant_patterns => {
+                        #push_values
+                    }
+                )*}
+            }
+        }
+
+        impl ::rorm::validate::Validate for #ident {
+            fn validate(&self) -> Result<(), ::rorm::validate::ValidationErrors> {
+                let mut errors = Vec::new();
+                match self {#(
+                    #variant_patterns => {
+                        #validate_variants
+                    }
+                )*}
+                ::rorm::validate::ValidationErrors::from_vec(errors)
+            }
+        }
+
+        impl<'a> ::rorm::internal::patch::IntoPatchCow<'a> for #ident {
+            type Patch = #ident;
+
+            fn into_patch_cow(self) -> ::rorm::internal::patch::PatchCow<'a, #ident> {
+                ::rorm::internal::patch::PatchCow::Owned(self)
+            }
+        }
+        impl<'a> ::rorm::internal::patch::IntoPatchCow<'a> for &'a #ident {
+            type Patch = #ident;
+
+            fn into_patch_cow(self) -> ::rorm::internal::patch::PatchCow<'a, #ident> {
+                ::rorm::internal::patch::PatchCow::Borrowed(self)
+            }
+        }
+    }
+}
@@ -0,0 +1,37 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+
+use crate::parse::new_type::{NewTypeAnnotations, ParsedNewType};
+
+pub fn generate_new_type(parsed: &ParsedNewType) -> TokenStream {
+    let ParsedNewType {
+        ident,
+        inner,
+        annos: NewTypeAnnotations { constructor, ord },
+    } = parsed;
+
+    let from_inner = match constructor {
+        None => quote! { Ok(Self(inner)) },
+        Some(constructor) => quote! {
+            #constructor(inner).map_err(|error| ::std::string::ToString::to_string(&error))
+        },
+    };
+    let ord = ord.then(|| quote! { , ord });
+
+    quote! {
+        const _: () = {
+            impl ::rorm::fields::custom::CustomFieldType for #ident {
+                type Inner = #inner;
+
+                fn to_inner(&self) -> Self::Inner {
+                    ::std::clone::Clone::clone(&self.0)
+                }
+
+                fn from_inner(inner: Self::Inner) -> Result<Self, String> {
+                    #from_inner
+                }
+            }
+            ::rorm::impl_CustomFieldType!(#ident #ord);
+        };
+    }
+}
@@ -14,52 +14,73 @@ pub fn generate_patch(patch: &ParsedPatch) -> TokenStream {
         fields,
     } = patch;
 
-    let field_idents_1 = fields.iter().map(|field| &field.ident);
-    let field_idents_2 = field_idents_1.clone();
+    let field_idents = fields.iter().map(|field| &field.ident);
     let field_types = fields.iter().map(|field| &field.ty);
 
-    let partial = partially_generate_patch(
-        ident,
-        model,
-        vis,
-        &Default::default(),
-        field_idents_1.clone(),
-        fields.iter().map(|field| &field.ty),
-    );
+    let patch_fields = fields
+        .iter()
+        .map(|field| PatchField {
+            ident: &field.ident,
+            ty: &field.ty,
+            deferred: false,
+        })
+        .collect::<Vec<_>>();
+    let partial =
+        partially_generate_patch(ident, model, vis, &Default::default(), &patch_fields, &[]);
 
     quote! {
         #partial
 
         #(
-            impl ::rorm::model::GetField<::rorm::get_field!(#ident, #field_idents_2)> for #ident {
+            impl ::rorm::model::GetField<::rorm::get_field!(#ident, #field_idents)> for #ident {
                 fn get_field(self) -> #field_types {
-                    self.#field_idents_2
+                    self.#field_idents
                 }
                 fn borrow_field(&self) -> &#field_types {
-                    &self.#field_idents_2
+                    &self.#field_idents
                 }
                 fn borrow_field_mut(&mut self) -> &mut #field_types {
-                    &mut self.#field_idents_2
+                    &mut self.#field_idents
                 }
             }
         )*
     }
 }
 
-pub fn partially_generate_patch<'a>(
+/// A field of a patch passed to [`partially_generate_patch`]
+pub struct PatchField<'a> {
+    pub ident: &'a Ident,
+    pub ty: &'a Type,
+
+    /// Whether the field is only loaded on demand i.e. `#[rorm(defer)]`
+    pub deferred: bool,
+}
+
+pub fn partially_generate_patch(
     patch: &Ident,
     model: &impl ToTokens, // Ident or Path
     vis: &Visibility,
     generics: &Generics,
-    fields: impl Iterator<Item = &'a Ident> + Clone,
-    types: impl Iterator<Item = &'a Type> + Clone,
+    fields: &[PatchField<'_>],
+    skipped: &[&Ident],
 ) -> TokenStream {
+    let skipped = skipped.iter();
+    let fields_iter = fields.iter().map(|field| field.ident);
     let value_space_impl = format_ident!("__{patch}_ValueSpaceImpl");
     let value_space_marker_impl = format_ident!("__{patch}_ValueSpaceImplMarker");
 
     let decoder = format_ident!("__{patch}_Decoder");
-    let [fields_1, fields_2, fields_3, fields_4, fields_5, fields_6, fields_7] =
-        array::from_fn(|_| fields.clone());
+    let [fields_1, fields_2, fields_3, fields_4, fields_5, fields_6, fields_7, fields_8] =
+        array::from_fn(|_| fields_iter.clone());
+    let [skipped_1, skipped_2] = array::from_fn(|_| skipped.clone());
+    let decoders = fields.iter().map(|&PatchField { ty, deferred, .. }| {
+        let decoder = quote! { <#ty as ::rorm::fields::traits::FieldType>::Decoder };
+        if deferred {
+            quote! { ::rorm::internal::field::decoder::DeferredDecoder<#decoder> }
+        } else {
+            decoder
+        }
+    });
     let (impl_generics, type_generics, where_clause) = generics.split_for_impl();
     let lifetime_generics = {
         let mut tokens = impl_generics
@@ -86,10 +107,16 @@ pub fn partially_generate_patch<'a>(
             #value_space_marker_impl(::std::marker::PhantomData<#patch #type_generics>),
         }
         #vis use #value_space_impl::*;
+        impl #impl_generics ::std::clone::Clone for #value_space_impl #type_generics #where_clause {
+            fn clone(&self) -> Self {
+                *self
+            }
+        }
+        impl #impl_generics ::std::marker::Copy for #value_space_impl #type_generics #where_clause {}
 
         #vis struct #decoder #impl_generics #where_clause {
             #(
-                #fields_1: <#types as ::rorm::fields::traits::FieldType>::Decoder,
+                #fields_1: #decoders,
             )*
         }
 
@@ -115,12 +142,16 @@ pub fn partially_generate_patch<'a>(
             fn by_name<'index>(&'index self, row: &'_ ::rorm::db::Row) -> Result<Self::Result, ::rorm::db::row::RowError<'index>> {
                 Ok(#patch {#(
                     #fields_2: self.#fields_2.by_name(row)?,
+                )* #(
+                    #skipped_1: ::std::default::Default::default(),
                 )*})
             }
 
             fn by_index<'index>(&'index self, row: &'_ ::rorm::db::Row) -> Result<Self::Result, ::rorm::db::row::RowError<'index>> {
                 Ok(#patch {#(
                     #fields_3: self.#fields_3.by_index(row)?,
+                )* #(
+                    #skipped_2: ::std::default::Default::default(),
                 )*})
             }
         }
@@ -141,25 +172,40 @@ pub fn partially_generate_patch<'a>(
                 )*}
             }
 
-            fn push_columns(columns: &mut Vec<&'static str>) {#(
+            fn push_columns(columns: &mut impl Extend<&'static str>) {#(
                 columns.extend(
                     ::rorm::internal::field::FieldProxy::columns(<<Self as ::rorm::model::Patch>::Model as ::rorm::model::Model>::FIELDS.#fields_5)
                 );
             )*}
 
-            fn push_references<'a>(&'a self, values: &mut Vec<::rorm::conditions::Value<'a>>) {
+            fn push_references<'a>(&'a self, values: &mut impl Extend<::rorm::conditions::Value<'a>>) {
                 #(
                     values.extend(::rorm::fields::traits::FieldType::as_values(&self.#fields_6));
                 )*
             }
 
-            fn push_values(self, values: &mut Vec<::rorm::conditions::Value>) {
+            fn push_values<'a>(self, values: &mut impl Extend<::rorm::conditions::Value<'a>>) {
                 #(
                     values.extend(::rorm::fields::traits::FieldType::into_values(self.#fields_7));
                 )*
             }
         }
 
+        impl #impl_generics ::rorm::validate::Validate for #patch #type_generics #where_clause {
+            fn validate(&self) -> Result<(), ::rorm::validate::ValidationErrors> {
+                let mut errors = Vec::new();
+                #(
+                    if let Err(error) = ::rorm::internal::field::FieldProxy::validate(
+                        <<Self as ::rorm::model::Patch>::Model as ::rorm::model::Model>::FIELDS.#fields_8,
+                        &self.#fields_8,
+                    ) {
+                        errors.push(error);
+                    }
+                )*
+                ::rorm::validate::ValidationErrors::from_vec(errors)
+            }
+        }
+
         impl #lifetime_generics ::rorm::internal::patch::IntoPatchCow<'a> for #patch #type_generics #where_clause {
             type Patch = #patch #type_generics;
 
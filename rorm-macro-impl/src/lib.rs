@@ -3,11 +3,16 @@
 use proc_macro2::TokenStream;
 
 use crate::analyze::model::analyze_model;
+use crate::analyze::model_enum::analyze_model_enum;
 use crate::generate::db_enum::generate_db_enum;
 use crate::generate::model::generate_model;
+use crate::generate::model_enum::generate_model_enum;
+use crate::generate::new_type::generate_new_type;
 use crate::generate::patch::generate_patch;
 use crate::parse::db_enum::parse_db_enum;
 use crate::parse::model::parse_model;
+use crate::parse::model_enum::parse_model_enum;
+use crate::parse::new_type::parse_new_type;
 use crate::parse::patch::parse_patch;
 
 mod analyze;
@@ -29,9 +34,23 @@ pub fn derive_model(input: TokenStream) -> TokenStream {
     }
 }
 
+pub fn derive_model_enum(input: TokenStream) -> TokenStream {
+    match parse_model_enum(input).and_then(analyze_model_enum) {
+        Ok(model_enum) => generate_model_enum(&model_enum),
+        Err(error) => error.write_errors(),
+    }
+}
+
 pub fn derive_patch(input: TokenStream) -> TokenStream {
     match parse_patch(input) {
         Ok(patch) => generate_patch(&patch),
         Err(error) => error.write_errors(),
     }
 }
+
+pub fn derive_new_type(input: TokenStream) -> TokenStream {
+    match parse_new_type(input) {
+        Ok(new_type) => generate_new_type(&new_type),
+        Err(error) => error.write_errors(),
+    }
+}
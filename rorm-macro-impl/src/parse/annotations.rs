@@ -1,7 +1,7 @@
 use darling::ast::NestedMeta;
 use darling::{Error, FromAttributes, FromMeta};
 use proc_macro2::Ident;
-use syn::{Lit, LitInt, LitStr};
+use syn::{Expr, Lit, LitInt, LitStr};
 
 #[derive(FromAttributes, Debug)]
 #[darling(attributes(rorm))]
@@ -69,3 +69,36 @@ pub struct NamedIndex {
     pub name: LitStr,
     pub priority: Option<LitInt>,
 }
+
+/// Parse the `#[rorm(validate(..))]` annotation.
+#[derive(FromMeta, Debug, Default)]
+#[darling(default)]
+pub struct Validate {
+    /// `range(min = .., max = ..)`
+    pub range: Option<ValidateRange>,
+
+    /// `regex = ".."`
+    pub regex: Option<LitStr>,
+
+    /// `email`
+    pub email: bool,
+}
+
+/// Parse the `range(..)` inside `#[rorm(validate(..))]`.
+#[derive(FromMeta, Debug, Default)]
+#[darling(default)]
+pub struct ValidateRange {
+    pub min: Option<Expr>,
+    pub max: Option<Expr>,
+}
+
+/// Parse the `#[rorm(patch(..))]` annotation.
+#[derive(FromMeta, Debug)]
+pub struct GeneratedPatch {
+    /// `name = ".."`
+    pub name: LitStr,
+
+    /// `omit("field", ..)`
+    #[darling(default)]
+    pub omit: Vec<LitStr>,
+}
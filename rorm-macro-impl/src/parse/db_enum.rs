@@ -1,6 +1,6 @@
 use darling::FromAttributes;
 use proc_macro2::{Ident, TokenStream};
-use syn::{ItemEnum, Variant, Visibility};
+use syn::{Expr, ExprLit, ExprUnary, ItemEnum, Lit, UnOp, Variant, Visibility};
 
 use crate::parse::annotations::NoAnnotations;
 
@@ -16,8 +16,17 @@ pub fn parse_db_enum(tokens: TokenStream) -> darling::Result<ParsedDbEnum> {
     } = syn::parse2(tokens)?;
     let mut errors = darling::Error::accumulator();
 
-    // check absence of #[rorm(..)] attributes
-    let _ = errors.handle(NoAnnotations::from_attributes(&attrs));
+    // parse #[rorm(..)] attributes
+    let annos = errors
+        .handle(DbEnumAnnotations::from_attributes(&attrs))
+        .unwrap_or_default();
+    if let Some(repr) = &annos.repr {
+        if !["i16", "i32", "i64"].iter().any(|valid| repr == valid) {
+            errors.push(
+                darling::Error::unknown_value("Expected one of i16, i32 or i64").with_span(repr),
+            );
+        }
+    }
 
     // check absence of generics
     if generics.lt_token.is_some() {
@@ -29,14 +38,30 @@ pub fn parse_db_enum(tokens: TokenStream) -> darling::Result<ParsedDbEnum> {
 
     // parse variants
     let mut parsed_variants = Vec::with_capacity(variants.len());
+    let mut discriminants = Vec::with_capacity(variants.len());
+    let mut next_discriminant = 0;
     for variant in variants {
         let Variant {
             attrs,
             ident,
             fields,
-            discriminant: _, // TODO maybe warn, that they aren't used?
+            discriminant, // TODO maybe warn, that they aren't used without repr?
         } = variant;
 
+        // compute the discriminant which is stored when using repr
+        if let Some((_, expr)) = discriminant {
+            match parse_discriminant(&expr) {
+                Some(value) => next_discriminant = value,
+                None if annos.repr.is_some() => errors.push(
+                    darling::Error::custom("Only integer literals are supported as discriminants")
+                        .with_span(&expr),
+                ),
+                None => {}
+            }
+        }
+        discriminants.push(next_discriminant);
+        next_discriminant = next_discriminant.wrapping_add(1);
+
         // check absence of #[rorm(..)] attributes
         let _ = errors.handle(NoAnnotations::from_attributes(&attrs));
 
@@ -55,11 +80,41 @@ pub fn parse_db_enum(tokens: TokenStream) -> darling::Result<ParsedDbEnum> {
         vis,
         ident,
         variants: parsed_variants,
+        repr: annos.repr,
+        discriminants,
     })
 }
 
+/// Parse a discriminant which is an optionally negated integer literal
+fn parse_discriminant(expr: &Expr) -> Option<i64> {
+    match expr {
+        Expr::Lit(ExprLit {
+            lit: Lit::Int(int), ..
+        }) => int.base10_parse().ok(),
+        Expr::Unary(ExprUnary {
+            op: UnOp::Neg(_),
+            expr,
+            ..
+        }) => parse_discriminant(expr).map(|value| -value),
+        _ => None,
+    }
+}
+
 pub struct ParsedDbEnum {
     pub vis: Visibility,
     pub ident: Ident,
     pub variants: Vec<Ident>,
+
+    /// The integer type to store the discriminants as instead of the variants' names
+    pub repr: Option<Ident>,
+
+    /// The variants' discriminants (only meaningful if `repr` is set)
+    pub discriminants: Vec<i64>,
+}
+
+#[derive(FromAttributes, Debug, Default)]
+#[darling(attributes(rorm), default)]
+pub struct DbEnumAnnotations {
+    /// `#[rorm(repr = i16)]`
+    pub repr: Option<Ident>,
 }
@@ -3,6 +3,8 @@ use syn::{Fields, FieldsNamed, Generics};
 pub mod annotations;
 pub mod db_enum;
 pub mod model;
+pub mod model_enum;
+pub mod new_type;
 pub mod patch;
 
 /// Get the [`Fields::Named(..)`](Fields::Named) variant's data or produce an error
@@ -1,8 +1,8 @@
 use darling::FromAttributes;
 use proc_macro2::{Ident, TokenStream};
-use syn::{parse2, Field, Generics, ItemStruct, LitInt, LitStr, Type, Visibility};
+use syn::{parse2, Field, Fields, Generics, ItemStruct, LitInt, LitStr, Type, Visibility};
 
-use crate::parse::annotations::{Default, Index, OnAction};
+use crate::parse::annotations::{Default, GeneratedPatch, Index, OnAction, Validate};
 use crate::parse::get_fields_named;
 
 pub fn parse_model(tokens: TokenStream) -> darling::Result<ParsedModel> {
@@ -24,7 +24,16 @@ pub fn parse_model(tokens: TokenStream) -> darling::Result<ParsedModel> {
 
     // parse field annotations
     let mut parsed_fields = Vec::new();
-    if let Some(raw_fields) = errors.handle(get_fields_named(fields)) {
+    if let Fields::Unnamed(_) = &fields {
+        errors.push(
+            darling::Error::custom(
+                "Models require named fields which are mapped to columns. \
+                To store additional state which is not part of the database, \
+                add a named field annotated with `#[rorm(skip)]`.",
+            )
+            .with_span(&fields),
+        );
+    } else if let Some(raw_fields) = errors.handle(get_fields_named(fields)) {
         parsed_fields.reserve_exact(raw_fields.named.len());
         for field in raw_fields.named {
             let Field {
@@ -70,6 +79,46 @@ pub struct ParsedModel {
 pub struct ModelAnnotations {
     pub rename: Option<LitStr>,
 
+    /// `#[rorm(hooks)]`
+    pub hooks: bool,
+
+    /// `#[rorm(default_scope)]`
+    pub default_scope: bool,
+
+    /// `#[rorm(audit)]`
+    pub audit: bool,
+
+    /// `#[rorm(track_changes)]`
+    pub track_changes: bool,
+
+    /// `#[rorm(database = "..")]`
+    pub database: Option<LitStr>,
+
+    /// `#[rorm(shard_by = "field")]`
+    pub shard_by: Option<LitStr>,
+
+    /// `#[rorm(shards = 16)]`
+    pub shards: Option<LitInt>,
+
+    /// `#[rorm(time_partition_by = "field")]`
+    pub time_partition_by: Option<LitStr>,
+
+    /// `#[rorm(period = "hour" | "day" | "month")]`
+    pub period: Option<LitStr>,
+
+    /// `#[rorm(patch(name = "..", omit(".." , ..)))]`
+    ///
+    /// Generates a patch containing all fields except the omitted ones.
+    /// It can be used multiple times.
+    #[darling(multiple)]
+    pub patch: Vec<GeneratedPatch>,
+
+    /// `#[rorm(insert_patch = "..")]`
+    ///
+    /// Generates a patch containing all fields which are not set by the database
+    /// i.e. without `auto_increment`, `auto_create_time` and `auto_update_time`.
+    pub insert_patch: Option<LitStr>,
+
     pub experimental_unregistered: bool,
     pub experimental_generics: bool,
 }
@@ -108,11 +157,41 @@ pub struct ModelFieldAnnotations {
     /// `#[rorm(on_update = "..")]`
     pub on_update: Option<OnAction>,
 
+    /// `#[rorm(readonly)]`
+    ///
+    /// The field is never written by updates.
+    pub readonly: bool,
+
+    /// `#[rorm(defer)]`
+    ///
+    /// The field is not selected when querying the whole model.
+    pub defer: bool,
+
+    /// `#[rorm(tenant_key)]`
+    ///
+    /// The field stores the tenant a row belongs to.
+    /// Queries are restricted to and inserts are filled with the current tenant.
+    pub tenant_key: bool,
+
+    /// `#[rorm(counter_cache = "Model::F.field")]`
+    ///
+    /// The foreign key's referenced model stores the number of rows referencing it in `field`.
+    /// Inserts increment and deletes decrement it.
+    pub counter_cache: Option<LitStr>,
+
+    /// `#[rorm(hash_of = "field")]`
+    ///
+    /// The field stores a hash of another field's value.
+    /// Inserts and updates fill it whenever they set the other field.
+    pub hash_of: Option<LitStr>,
+
     /// `#[rorm(rename = "..")]`
     pub rename: Option<LitStr>,
 
-    /// `#[rorm(ignore)]`
-    //pub ignore: bool,
+    /// `#[rorm(skip)]`
+    ///
+    /// The field is not stored in the database and populated with `Default::default()` when decoding the model.
+    pub skip: bool,
 
     /// Parse the `#[rorm(default = ..)]` annotation.
     ///
@@ -136,9 +215,18 @@ pub struct ModelFieldAnnotations {
     /// It accepts four different syntax's:
     /// - `#[rorm(index)]`
     /// - `#[rorm(index())]`
-    ///    *(semantically identical to first one)*
+    ///   *(semantically identical to first one)*
     /// - `#[rorm(index(name = <string literal>))]`
     /// - `#[rorm(index(name = <string literal>, priority = <integer literal>))]`
-    ///    *(insensitive to argument order)*
+    ///   *(insensitive to argument order)*
     pub index: Option<Index>,
+
+    /// Parse the `#[rorm(validate(..))]` annotation.
+    ///
+    /// It accepts a list of checks to run before inserting or updating a value:
+    /// - `range(min = <number>, max = <number>)`
+    ///   *(both bounds are optional)*
+    /// - `regex = <string literal>`
+    /// - `email`
+    pub validate: Option<Validate>,
 }
@@ -0,0 +1,127 @@
+use darling::FromAttributes;
+use proc_macro2::{Ident, TokenStream};
+use syn::{parse2, Field, ItemEnum, LitStr, Variant, Visibility};
+
+use crate::parse::annotations::NoAnnotations;
+use crate::parse::model::{ModelFieldAnnotations, ParsedField};
+use crate::parse::{check_non_generic, get_fields_named};
+
+pub fn parse_model_enum(tokens: TokenStream) -> darling::Result<ParsedModelEnum> {
+    let ItemEnum {
+        attrs,
+        vis,
+        enum_token: _,
+        ident,
+        generics,
+        brace_token: _,
+        variants,
+    } = parse2(tokens)?;
+    let mut errors = darling::Error::accumulator();
+
+    // parse enum annotations
+    let annos = errors
+        .handle(ModelEnumAnnotations::from_attributes(&attrs))
+        .unwrap_or_default();
+
+    // check absence of generics
+    errors.handle(check_non_generic(generics));
+
+    // parse variants
+    let mut parsed_variants = Vec::with_capacity(variants.len());
+    for variant in variants {
+        let Variant {
+            attrs,
+            ident,
+            fields,
+            discriminant,
+        } = variant;
+
+        // check absence of #[rorm(..)] attributes
+        let _ = errors.handle(NoAnnotations::from_attributes(&attrs));
+
+        if let Some((_, expr)) = discriminant {
+            errors.push(
+                darling::Error::custom("A ModelEnum's variants can't have discriminants")
+                    .with_span(&expr),
+            );
+        }
+
+        // parse field annotations
+        let mut parsed_fields = Vec::new();
+        if let Some(raw_fields) = errors.handle(get_fields_named(fields)) {
+            parsed_fields.reserve_exact(raw_fields.named.len());
+            for field in raw_fields.named {
+                let Field {
+                    attrs,
+                    vis: _,
+                    mutability: _,
+                    ident,
+                    colon_token: _,
+                    ty,
+                } = field;
+                let Some(annos) = errors.handle(ModelFieldAnnotations::from_attributes(&attrs))
+                else {
+                    continue;
+                };
+                let ident = ident.expect("Fields::Named should contain named fields");
+                parsed_fields.push(ParsedField {
+                    vis: vis.clone(),
+                    ident,
+                    ty,
+                    annos,
+                });
+            }
+        }
+
+        parsed_variants.push(ParsedVariant {
+            ident,
+            fields: parsed_fields,
+        });
+    }
+
+    errors.finish_with(ParsedModelEnum {
+        vis,
+        ident,
+        annos,
+        variants: parsed_variants,
+    })
+}
+
+pub struct ParsedModelEnum {
+    pub vis: Visibility,
+    pub ident: Ident,
+    pub annos: ModelEnumAnnotations,
+    pub variants: Vec<ParsedVariant>,
+}
+
+pub struct ParsedVariant {
+    pub ident: Ident,
+
+    /// The variant's fields whose visibility is the enum's one
+    pub fields: Vec<ParsedField>,
+}
+
+#[derive(FromAttributes, Debug, Default)]
+#[darling(attributes(rorm), default)]
+pub struct ModelEnumAnnotations {
+    pub rename: Option<LitStr>,
+
+    /// `#[rorm(discriminator = "..")]`
+    ///
+    /// The name of the column storing the variant's name, defaults to `kind`.
+    pub discriminator: Option<LitStr>,
+
+    /// `#[rorm(hooks)]`
+    pub hooks: bool,
+
+    /// `#[rorm(default_scope)]`
+    pub default_scope: bool,
+
+    /// `#[rorm(audit)]`
+    pub audit: bool,
+
+    /// `#[rorm(database = "..")]`
+    pub database: Option<LitStr>,
+
+    pub experimental_unregistered: bool,
+}
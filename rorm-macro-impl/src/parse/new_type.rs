@@ -0,0 +1,76 @@
+use darling::FromAttributes;
+use proc_macro2::{Ident, TokenStream};
+use syn::{parse2, Field, Fields, ItemStruct, Path, Type};
+
+use crate::parse::annotations::NoAnnotations;
+use crate::parse::check_non_generic;
+
+pub fn parse_new_type(tokens: TokenStream) -> darling::Result<ParsedNewType> {
+    let ItemStruct {
+        attrs,
+        vis: _,
+        struct_token: _,
+        ident,
+        generics,
+        fields,
+        semi_token: _,
+    } = parse2(tokens)?;
+    let mut errors = darling::Error::accumulator();
+
+    // Parse annotations
+    let annos = errors
+        .handle(NewTypeAnnotations::from_attributes(&attrs))
+        .unwrap_or_default();
+
+    // Check absence of generics
+    errors.handle(check_non_generic(generics));
+
+    // Get the single wrapped field
+    let inner = match fields {
+        Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+            let Field { attrs, ty, .. } = fields.unnamed.into_iter().next().unwrap();
+
+            // The wrapped field doesn't accept annotations
+            errors.handle(NoAnnotations::from_attributes(&attrs));
+
+            ty
+        }
+        _ => {
+            errors.push(
+                darling::Error::unsupported_shape_with_expected(
+                    "struct",
+                    &"tuple struct with a single field",
+                )
+                .with_span(&fields),
+            );
+            Type::Verbatim(TokenStream::new())
+        }
+    };
+
+    errors.finish_with(ParsedNewType {
+        ident,
+        inner,
+        annos,
+    })
+}
+
+pub struct ParsedNewType {
+    pub ident: Ident,
+    pub inner: Type,
+    pub annos: NewTypeAnnotations,
+}
+
+#[derive(FromAttributes, Debug, Default)]
+#[darling(attributes(rorm), default)]
+pub struct NewTypeAnnotations {
+    /// `#[rorm(constructor = "path::to::function")]`
+    ///
+    /// A function `fn(Inner) -> Result<Self, E>` where `E: Display`
+    /// which is used to check the invariants of values read from the database.
+    pub constructor: Option<Path>,
+
+    /// `#[rorm(ord)]`
+    ///
+    /// Forward the inner type's `FieldOrd` implementation.
+    pub ord: bool,
+}
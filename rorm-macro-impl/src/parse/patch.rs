@@ -1,7 +1,7 @@
 use darling::FromAttributes;
 use proc_macro2::{Ident, TokenStream};
 use quote::format_ident;
-use syn::{parse2, Field, ItemStruct, Path, PathSegment, Type, Visibility};
+use syn::{parse2, Field, ItemStruct, Meta, Path, PathSegment, Type, Visibility};
 
 use crate::parse::annotations::NoAnnotations;
 use crate::parse::{check_non_generic, get_fields_named};
@@ -20,6 +20,16 @@ pub fn parse_patch(tokens: TokenStream) -> darling::Result<ParsedPatch> {
 
     // Parse annotations
     let annos = errors.handle(PatchAnnotations::from_attributes(&attrs));
+    if let Some(omit) = annos.as_ref().and_then(|annos| annos.omit.as_ref()) {
+        errors.push(
+            darling::Error::custom(
+                "A derived patch has to declare its fields. \
+                To generate a patch containing all fields except some, \
+                annotate the model with `#[rorm(patch(name = \"..\", omit(\"..\")))]` instead.",
+            )
+            .with_span(omit),
+        );
+    }
     let model = annos.map(|annos| annos.model).unwrap_or_else(|| {
         PathSegment {
             ident: format_ident!(""),
@@ -77,4 +87,8 @@ pub struct ParsedPatchField {
 #[darling(attributes(rorm))]
 pub struct PatchAnnotations {
     pub model: Path,
+
+    /// `#[rorm(omit(..))]` is only parsed to point at `#[rorm(patch(..))]`
+    #[darling(default)]
+    pub omit: Option<Meta>,
 }
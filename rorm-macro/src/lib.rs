@@ -4,7 +4,7 @@ use proc_macro::TokenStream;
 use proc_macro2::Span;
 use quote::quote;
 
-#[proc_macro_derive(DbEnum)]
+#[proc_macro_derive(DbEnum, attributes(rorm))]
 pub fn derive_db_enum(input: TokenStream) -> TokenStream {
     rorm_macro_impl::derive_db_enum(input.into()).into()
 }
@@ -14,11 +14,21 @@ pub fn derive_model(input: TokenStream) -> TokenStream {
     rorm_macro_impl::derive_model(input.into()).into()
 }
 
+#[proc_macro_derive(ModelEnum, attributes(rorm))]
+pub fn derive_model_enum(input: TokenStream) -> TokenStream {
+    rorm_macro_impl::derive_model_enum(input.into()).into()
+}
+
 #[proc_macro_derive(Patch, attributes(rorm))]
 pub fn derive_patch(input: TokenStream) -> TokenStream {
     rorm_macro_impl::derive_patch(input.into()).into()
 }
 
+#[proc_macro_derive(NewType, attributes(rorm))]
+pub fn derive_new_type(input: TokenStream) -> TokenStream {
+    rorm_macro_impl::derive_new_type(input.into()).into()
+}
+
 #[proc_macro_attribute]
 pub fn rorm_main(args: TokenStream, item: TokenStream) -> TokenStream {
     let main = syn::parse_macro_input!(item as syn::ItemFn);
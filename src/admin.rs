@@ -0,0 +1,528 @@
+//! A minimal web interface to browse and edit the registered models
+//!
+//! [`router`] returns an [axum](axum) router serving a page for every model in the [`registry`](crate::registry).
+//! It lists the model's rows page by page, filters them by exact column values
+//! and allows editing or deleting single rows:
+//!
+//! ```no_run
+//! # use rorm::Database;
+//! pub fn app(db: Database) -> axum::Router {
+//!     axum::Router::new().nest("/admin", rorm::admin::router(db, "/admin"))
+//! }
+//! ```
+//!
+//! The interface has no authentication of its own.
+//! Make sure to protect the routes before exposing them.
+//!
+//! Only columns storing strings, choices, numbers and booleans are shown,
+//! and rows can only be edited or deleted if their primary key is one of them.
+//! The edit form respects the `choices` and `max_length` annotations.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::Arc;
+
+use axum::extract::{Form, Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{Html, Redirect};
+use axum::routing::{get, post};
+use axum::Router;
+use rorm_db::choice::Choice;
+use rorm_db::database::{self, ColumnSelector};
+use rorm_db::executor::All;
+use rorm_db::sql::conditional::{BinaryCondition, Condition};
+use rorm_db::sql::limit_clause::LimitClause;
+use rorm_db::sql::ordering::{OrderByEntry, Ordering};
+use rorm_db::sql::value::{NullType, Value as SqlValue};
+use rorm_db::{Database, Error, Row};
+use rorm_declaration::imr;
+
+use crate::conditions::Value;
+use crate::registry;
+
+/// Number of rows shown per page
+const PAGE_SIZE: u64 = 50;
+
+/// Create a router serving the admin interface
+///
+/// `base` is the path the router will be [nested](Router::nest) at, used to generate links.
+pub fn router(db: Database, base: &str) -> Router {
+    let admin = Admin {
+        db,
+        base: base.trim_end_matches('/').to_string(),
+        models: registry::models()
+            .map(|model| (model.name.clone(), model))
+            .collect(),
+    };
+    Router::new()
+        .route("/", get(index))
+        .route("/:table", get(list))
+        .route("/:table/:key", get(edit).post(save))
+        .route("/:table/:key/delete", post(delete))
+        .with_state(Arc::new(admin))
+}
+
+/// State shared by the handlers
+struct Admin {
+    db: Database,
+    base: String,
+    models: HashMap<String, imr::Model>,
+}
+
+/// Result of a handler whose error is sent as response
+type HandlerResult<T> = Result<T, (StatusCode, String)>;
+
+impl Admin {
+    fn model(&self, table: &str) -> HandlerResult<&imr::Model> {
+        self.models
+            .get(table)
+            .ok_or_else(|| (StatusCode::NOT_FOUND, format!("Unknown model {table}")))
+    }
+
+    fn page(&self, title: &str, body: &str) -> Html<String> {
+        Html(format!(
+            "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>{title}</title></head>\
+            <body><nav><a href=\"{base}/\">Models</a></nav><h1>{title}</h1>{body}</body></html>",
+            title = escape(title),
+            base = self.base,
+        ))
+    }
+}
+
+async fn index(State(admin): State<Arc<Admin>>) -> Html<String> {
+    let mut tables: Vec<_> = admin.models.keys().collect();
+    tables.sort();
+
+    let mut body = String::from("<ul>");
+    for table in tables {
+        let _ = write!(
+            body,
+            "<li><a href=\"{}/{table}\">{table}</a></li>",
+            admin.base
+        );
+    }
+    body.push_str("</ul>");
+    admin.page("Models", &body)
+}
+
+async fn list(
+    State(admin): State<Arc<Admin>>,
+    Path(table): Path<String>,
+    Query(mut params): Query<HashMap<String, String>>,
+) -> HandlerResult<Html<String>> {
+    let model = admin.model(&table)?;
+    let page: u64 = params
+        .remove("page")
+        .and_then(|page| page.parse().ok())
+        .unwrap_or(0);
+    let fields = shown_fields(model);
+    let primary_key = primary_key(model);
+
+    let mut filters = Vec::new();
+    for field in &fields {
+        if let Some(input) = params.get(&field.name).filter(|input| !input.is_empty()) {
+            filters.push((
+                field.name.as_str(),
+                parse(field, input).map_err(bad_request)?,
+            ));
+        }
+    }
+    let conditions: Vec<_> = filters
+        .iter()
+        .map(|(column_name, value)| equals(column_name, value))
+        .collect();
+    let condition = Condition::Conjunction(conditions);
+
+    // Query one additional row to know whether there is a next page
+    let order_by: Vec<_> = primary_key
+        .map(|field| OrderByEntry {
+            ordering: Ordering::Asc,
+            table_name: None,
+            column_name: &field.name,
+        })
+        .into_iter()
+        .collect();
+    let rows = database::query::<All>(
+        &admin.db,
+        &table,
+        &selects(&fields),
+        &[],
+        (!filters.is_empty()).then_some(&condition),
+        &order_by,
+        Some(LimitClause {
+            limit: PAGE_SIZE + 1,
+            offset: Some(page.saturating_mul(PAGE_SIZE)),
+        }),
+    )
+    .await
+    .map_err(internal)?;
+
+    let mut body = String::from("<form method=\"get\"><table><tr>");
+    for field in &fields {
+        let _ = write!(body, "<th>{}</th>", escape(&field.name));
+    }
+    body.push_str("<th></th></tr><tr>");
+    for field in &fields {
+        let _ = write!(
+            body,
+            "<td><input name=\"{name}\" value=\"{value}\" placeholder=\"filter\"></td>",
+            name = escape(&field.name),
+            value = escape(params.get(&field.name).map_or("", String::as_str)),
+        );
+    }
+    body.push_str("<td><button>Filter</button></td></tr>");
+    for row in rows.iter().take(PAGE_SIZE as usize) {
+        let values = display_row(row, &fields).map_err(internal)?;
+        body.push_str("<tr>");
+        for value in &values {
+            let _ = write!(body, "<td>{}</td>", display(value.as_deref()));
+        }
+        match primary_key.and_then(|pk| fields.iter().position(|field| field.name == pk.name)) {
+            Some(index) if values[index].is_some() => {
+                let _ = write!(
+                    body,
+                    "<td><a href=\"{}/{table}/{}\">Edit</a></td>",
+                    admin.base,
+                    encode(values[index].as_deref().unwrap_or_default())
+                );
+            }
+            _ => body.push_str("<td></td>"),
+        }
+        body.push_str("</tr>");
+    }
+    body.push_str("</table></form><p>");
+
+    let query = |page: u64| {
+        let mut query = format!("page={page}");
+        for (name, value) in &params {
+            let _ = write!(query, "&{}={}", encode(name), encode(value));
+        }
+        query
+    };
+    if page > 0 {
+        let _ = write!(
+            body,
+            "<a href=\"?{}\">Previous</a> ",
+            escape(&query(page - 1))
+        );
+    }
+    let _ = write!(body, "Page {}", page + 1);
+    if rows.len() as u64 > PAGE_SIZE {
+        let _ = write!(body, " <a href=\"?{}\">Next</a>", escape(&query(page + 1)));
+    }
+    body.push_str("</p>");
+
+    Ok(admin.page(&table, &body))
+}
+
+async fn edit(
+    State(admin): State<Arc<Admin>>,
+    Path((table, raw_key)): Path<(String, String)>,
+) -> HandlerResult<Html<String>> {
+    let model = admin.model(&table)?;
+    let primary_key = primary_key(model).ok_or_else(not_editable)?;
+    let key = parse(primary_key, &raw_key).map_err(bad_request)?;
+    let fields = shown_fields(model);
+
+    let condition = equals(&primary_key.name, &key);
+    let rows = database::query::<All>(
+        &admin.db,
+        &table,
+        &selects(&fields),
+        &[],
+        Some(&condition),
+        &[],
+        None,
+    )
+    .await
+    .map_err(internal)?;
+    let row = rows
+        .first()
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "The row doesn't exist".to_string()))?;
+    let values = display_row(row, &fields).map_err(internal)?;
+
+    let mut body = String::from("<form method=\"post\"><table>");
+    for (field, value) in fields.iter().zip(&values) {
+        let name = escape(&field.name);
+        let value = value.as_deref().unwrap_or_default();
+        let _ = write!(
+            body,
+            "<tr><th><label for=\"{name}\">{name}</label></th><td>"
+        );
+        if field.name == primary_key.name || has(field, &imr::Annotation::AutoIncrement) {
+            body.push_str(&display(Some(value)));
+        } else if let Some(choices) = choices(field) {
+            let _ = write!(body, "<select id=\"{name}\" name=\"{name}\">");
+            if nullable(field) {
+                body.push_str("<option value=\"\"></option>");
+            }
+            for choice in choices {
+                let selected = if choice == value { " selected" } else { "" };
+                let choice = escape(choice);
+                let _ = write!(
+                    body,
+                    "<option value=\"{choice}\"{selected}>{choice}</option>"
+                );
+            }
+            body.push_str("</select>");
+        } else if matches!(field.db_type, imr::DbType::Boolean) {
+            let _ = write!(body, "<select id=\"{name}\" name=\"{name}\">");
+            if nullable(field) {
+                body.push_str("<option value=\"\"></option>");
+            }
+            for choice in ["true", "false"] {
+                let selected = if choice == value { " selected" } else { "" };
+                let _ = write!(body, "<option{selected}>{choice}</option>");
+            }
+            body.push_str("</select>");
+        } else {
+            let _ = write!(
+                body,
+                "<input id=\"{name}\" name=\"{name}\" value=\"{}\"",
+                escape(value)
+            );
+            if let Some(max_length) = max_length(field) {
+                let _ = write!(body, " maxlength=\"{max_length}\"");
+            }
+            if !nullable(field) {
+                body.push_str(" required");
+            }
+            body.push('>');
+        }
+        body.push_str("</td></tr>");
+    }
+    body.push_str("</table><button>Save</button></form>");
+    let _ = write!(
+        body,
+        "<form method=\"post\" action=\"{}/{table}/{}/delete\"><button>Delete</button></form>",
+        admin.base,
+        encode(&raw_key)
+    );
+
+    Ok(admin.page(&table, &body))
+}
+
+async fn save(
+    State(admin): State<Arc<Admin>>,
+    Path((table, key)): Path<(String, String)>,
+    Form(inputs): Form<HashMap<String, String>>,
+) -> HandlerResult<Redirect> {
+    let model = admin.model(&table)?;
+    let primary_key = primary_key(model).ok_or_else(not_editable)?;
+    let key = parse(primary_key, &key).map_err(bad_request)?;
+
+    let mut columns = Vec::new();
+    for field in shown_fields(model) {
+        if field.name == primary_key.name || has(field, &imr::Annotation::AutoIncrement) {
+            continue;
+        }
+        if let Some(input) = inputs.get(&field.name) {
+            columns.push((
+                field.name.as_str(),
+                parse(field, input).map_err(bad_request)?,
+            ));
+        }
+    }
+    if !columns.is_empty() {
+        let columns: Vec<_> = columns
+            .iter()
+            .map(|(name, value)| (*name, value.as_sql()))
+            .collect();
+        let condition = equals(&primary_key.name, &key);
+        database::update(&admin.db, &table, &columns, Some(&condition))
+            .await
+            .map_err(internal)?;
+    }
+    Ok(Redirect::to(&format!("{}/{table}", admin.base)))
+}
+
+async fn delete(
+    State(admin): State<Arc<Admin>>,
+    Path((table, key)): Path<(String, String)>,
+) -> HandlerResult<Redirect> {
+    let model = admin.model(&table)?;
+    let primary_key = primary_key(model).ok_or_else(not_editable)?;
+    let key = parse(primary_key, &key).map_err(bad_request)?;
+
+    let condition = equals(&primary_key.name, &key);
+    database::delete(&admin.db, &table, Some(&condition))
+        .await
+        .map_err(internal)?;
+    Ok(Redirect::to(&format!("{}/{table}", admin.base)))
+}
+
+/// Get the fields whose type is supported by the interface
+fn shown_fields(model: &imr::Model) -> Vec<&imr::Field> {
+    model
+        .fields
+        .iter()
+        .filter(|field| null_type(&field.db_type).is_some())
+        .collect()
+}
+
+/// Get the model's primary key if its type is supported by the interface
+fn primary_key(model: &imr::Model) -> Option<&imr::Field> {
+    model.fields.iter().find(|field| {
+        has(field, &imr::Annotation::PrimaryKey) && null_type(&field.db_type).is_some()
+    })
+}
+
+/// Get the [`NullType`] of a column type supported by the interface
+fn null_type(db_type: &imr::DbType) -> Option<NullType> {
+    Some(match db_type {
+        imr::DbType::VarChar => NullType::String,
+        imr::DbType::Choices => NullType::Choice,
+        imr::DbType::Int64 => NullType::I64,
+        imr::DbType::Int32 => NullType::I32,
+        imr::DbType::Int16 => NullType::I16,
+        imr::DbType::Boolean => NullType::Bool,
+        imr::DbType::Double => NullType::F64,
+        imr::DbType::Float => NullType::F32,
+        _ => return None,
+    })
+}
+
+fn has(field: &imr::Field, annotation: &imr::Annotation) -> bool {
+    field.annotations.contains(annotation)
+}
+
+fn nullable(field: &imr::Field) -> bool {
+    !has(field, &imr::Annotation::NotNull)
+}
+
+fn choices(field: &imr::Field) -> Option<&[String]> {
+    field
+        .annotations
+        .iter()
+        .find_map(|annotation| match annotation {
+            imr::Annotation::Choices(choices) => Some(choices.as_slice()),
+            _ => None,
+        })
+}
+
+fn max_length(field: &imr::Field) -> Option<i32> {
+    field
+        .annotations
+        .iter()
+        .find_map(|annotation| match annotation {
+            imr::Annotation::MaxLength(max_length) => Some(*max_length),
+            _ => None,
+        })
+}
+
+/// Parse a user's input into a value for the field's column
+///
+/// An empty input is `NULL` for nullable fields.
+fn parse(field: &imr::Field, input: &str) -> Result<Value<'static>, String> {
+    let Some(null_type) = null_type(&field.db_type) else {
+        return Err(format!("{} can't be edited", field.name));
+    };
+    if input.is_empty() && nullable(field) {
+        return Ok(Value::Null(null_type));
+    }
+    let invalid = |_| format!("Invalid value for {}: {input}", field.name);
+    Ok(match field.db_type {
+        imr::DbType::VarChar => Value::String(input.to_string().into()),
+        imr::DbType::Choices => Value::Choice(input.to_string().into()),
+        imr::DbType::Int64 => Value::I64(input.parse().map_err(invalid)?),
+        imr::DbType::Int32 => Value::I32(input.parse().map_err(invalid)?),
+        imr::DbType::Int16 => Value::I16(input.parse().map_err(invalid)?),
+        imr::DbType::Boolean => Value::Bool(input.parse().map_err(invalid)?),
+        imr::DbType::Double => Value::F64(input.parse().map_err(invalid)?),
+        imr::DbType::Float => Value::F32(input.parse().map_err(invalid)?),
+        _ => unreachable!("null_type returned Some"),
+    })
+}
+
+/// Decode a row's columns as strings
+fn display_row(row: &Row, fields: &[&imr::Field]) -> Result<Vec<Option<String>>, Error> {
+    let mut values = Vec::with_capacity(fields.len());
+    for (index, field) in fields.iter().enumerate() {
+        values.push(match field.db_type {
+            imr::DbType::VarChar => row.get::<Option<String>, _>(index)?,
+            imr::DbType::Choices => row.get::<Option<Choice>, _>(index)?.map(|choice| choice.0),
+            imr::DbType::Int64 => row.get::<Option<i64>, _>(index)?.map(|v| v.to_string()),
+            imr::DbType::Int32 => row.get::<Option<i32>, _>(index)?.map(|v| v.to_string()),
+            imr::DbType::Int16 => row.get::<Option<i16>, _>(index)?.map(|v| v.to_string()),
+            imr::DbType::Boolean => row.get::<Option<bool>, _>(index)?.map(|v| v.to_string()),
+            imr::DbType::Double => row.get::<Option<f64>, _>(index)?.map(|v| v.to_string()),
+            imr::DbType::Float => row.get::<Option<f32>, _>(index)?.map(|v| v.to_string()),
+            _ => None,
+        });
+    }
+    Ok(values)
+}
+
+fn selects<'a>(fields: &[&'a imr::Field]) -> Vec<ColumnSelector<'a>> {
+    fields
+        .iter()
+        .map(|field| ColumnSelector {
+            table_name: None,
+            column_name: &field.name,
+            select_alias: None,
+            aggregation: None,
+        })
+        .collect()
+}
+
+fn equals<'a>(column_name: &'a str, value: &'a Value<'_>) -> Condition<'a> {
+    Condition::BinaryCondition(BinaryCondition::Equals(Box::new([
+        Condition::Value(SqlValue::Column {
+            table_name: None,
+            column_name,
+        }),
+        Condition::Value(value.as_sql()),
+    ])))
+}
+
+/// Render a value which might be `NULL`
+fn display(value: Option<&str>) -> String {
+    match value {
+        Some(value) => escape(value),
+        None => "<i>null</i>".to_string(),
+    }
+}
+
+/// Escape a string to be included in html
+fn escape(string: &str) -> String {
+    let mut escaped = String::with_capacity(string.len());
+    for char in string.chars() {
+        match char {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(char),
+        }
+    }
+    escaped
+}
+
+/// Percent-encode a string to be included in an url's path or query
+fn encode(string: &str) -> String {
+    let mut encoded = String::with_capacity(string.len());
+    for byte in string.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            let _ = write!(encoded, "%{byte:02X}");
+        }
+    }
+    encoded
+}
+
+fn not_editable() -> (StatusCode, String) {
+    (
+        StatusCode::BAD_REQUEST,
+        "The model has no primary key which can be edited".to_string(),
+    )
+}
+
+fn bad_request(error: String) -> (StatusCode, String) {
+    (StatusCode::BAD_REQUEST, error)
+}
+
+fn internal(error: impl ToString) -> (StatusCode, String) {
+    (StatusCode::INTERNAL_SERVER_ERROR, error.to_string())
+}
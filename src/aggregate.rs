@@ -0,0 +1,284 @@
+//! Denormalized aggregates kept in sync by database triggers
+//!
+//! A model can store an aggregate over the rows referencing it
+//! (like a repository storing the total amount of stars it received)
+//! instead of computing it in every query.
+//! An [`Aggregate`] describes such a column and creates the triggers recomputing it
+//! whenever a referencing row is inserted, updated or deleted:
+//!
+//! ```no_run
+//! # use rorm::{Database, Error, FieldAccess, Model};
+//! # use rorm::aggregate::Aggregate;
+//! # use rorm::fields::types::ForeignModel;
+//! #[derive(Model)]
+//! pub struct Repo {
+//!     #[rorm(id)]
+//!     pub id: i64,
+//!
+//!     #[rorm(default = 0)]
+//!     pub stars_total: i64,
+//! }
+//!
+//! #[derive(Model)]
+//! pub struct Star {
+//!     #[rorm(id)]
+//!     pub id: i64,
+//!
+//!     pub repo: ForeignModel<Repo>,
+//!
+//!     pub amount: i64,
+//! }
+//!
+//! pub async fn setup(db: &Database) -> Result<(), Error> {
+//!     // stars_total = SUM(stars.amount)
+//!     let stars_total = Aggregate::new(Repo.stars_total, Star.amount.sum(), Star.repo);
+//!
+//!     // Keep `stars_total` in sync from now on
+//!     stars_total.create_triggers(db).await?;
+//!
+//!     // Compute `stars_total` for the already existing stars
+//!     stars_total.backfill(db).await
+//! }
+//! ```
+//!
+//! The triggers recompute the aggregate of the rows referenced by the changed row's old and new foreign key.
+//! A `SUM` over no rows is stored as `0`, the other functions store `NULL` which requires a nullable column.
+//!
+//! The migrator doesn't know about the triggers.
+//! Use [`Aggregate::create_triggers_sql`] to add them to a migration
+//! or call [`Aggregate::create_triggers`] after migrating, which replaces existing ones.
+//! MySQL doesn't allow a trigger to update the table it is defined on,
+//! so aggregating a model's own rows (i.e. the replies to a post) is only supported on Postgres and SQLite.
+
+use rorm_db::error::Error;
+use rorm_db::executor::{Executor, Nothing};
+use rorm_db::sql::DBImpl;
+
+use crate::crud::selector::AggregatedColumn;
+use crate::internal::field::access::FieldAccess;
+use crate::internal::field::foreign_model::ForeignModelTrait;
+use crate::internal::field::{Field, FieldProxy};
+use crate::model::Model;
+
+/// A column storing an aggregate over the rows referencing its model
+///
+/// See the [module docs](self) for an example.
+#[derive(Copy, Clone, Debug)]
+pub struct Aggregate {
+    /// The table storing the aggregate
+    target_table: &'static str,
+
+    /// The column storing the aggregate
+    target_column: &'static str,
+
+    /// The column referenced by the foreign key
+    target_key: &'static str,
+
+    /// The table containing the aggregated rows
+    source_table: &'static str,
+
+    /// The aggregated column
+    source_column: &'static str,
+
+    /// The column of the foreign key referencing the target's rows
+    source_key: &'static str,
+
+    /// The aggregation function's name
+    function: &'static str,
+}
+
+impl Aggregate {
+    /// Describe the column `target` storing the `aggregation` of the rows referencing it through `via`
+    ///
+    /// `aggregation` is one of [`FieldAccess`]'s aggregation functions (i.e. `Star.amount.sum()`).
+    pub fn new<T, A, R, Fk>(
+        _target: FieldProxy<T, T::Model>,
+        aggregation: AggregatedColumn<A, R>,
+        _via: FieldProxy<Fk, Fk::Model>,
+    ) -> Self
+    where
+        T: Field,
+        A: FieldAccess<Path = Fk::Model>,
+        Fk: Field<Type: ForeignModelTrait>,
+        <Fk::Type as ForeignModelTrait>::RelatedField: Field<Model = T::Model>,
+    {
+        Self {
+            target_table: <T::Model as Model>::TABLE,
+            target_column: T::NAME,
+            target_key: <<Fk::Type as ForeignModelTrait>::RelatedField as Field>::NAME,
+            source_table: <Fk::Model as Model>::TABLE,
+            source_column: A::Field::NAME,
+            source_key: Fk::NAME,
+            function: aggregation.alias,
+        }
+    }
+
+    /// Create the triggers keeping the aggregate in sync, replacing existing ones
+    pub async fn create_triggers<'ex>(&self, executor: impl Executor<'ex>) -> Result<(), Error> {
+        let mut guard = executor.ensure_transaction().await?;
+        let tx = guard.get_transaction();
+        for sql in self.create_triggers_sql(tx.dialect()) {
+            tx.execute::<Nothing>(sql, Vec::new()).await?;
+        }
+        guard.commit().await
+    }
+
+    /// Drop the triggers created by [`Aggregate::create_triggers`]
+    pub async fn drop_triggers<'ex>(&self, executor: impl Executor<'ex>) -> Result<(), Error> {
+        let mut guard = executor.ensure_transaction().await?;
+        let tx = guard.get_transaction();
+        for sql in self.drop_triggers_sql(tx.dialect()) {
+            tx.execute::<Nothing>(sql, Vec::new()).await?;
+        }
+        guard.commit().await
+    }
+
+    /// Recompute the aggregate of every row
+    ///
+    /// This is required once after creating the triggers if the referencing table already contains rows.
+    pub async fn backfill<'ex>(&self, executor: impl Executor<'ex>) -> Result<(), Error> {
+        let sql = self.backfill_sql(executor.dialect());
+        executor.execute::<Nothing>(sql, Vec::new()).await
+    }
+
+    /// Get the statements executed by [`Aggregate::create_triggers`]
+    pub fn create_triggers_sql(&self, dialect: DBImpl) -> Vec<String> {
+        let quote = Quote::new(&dialect);
+        let name = self.trigger_name();
+        let source = quote.name(self.source_table);
+        let mut statements = self.drop_triggers_sql(dialect);
+        if quote.postgres {
+            let function = quote.name(&name);
+            statements.extend([
+                format!(
+                    "CREATE OR REPLACE FUNCTION {function}() RETURNS TRIGGER AS $$ BEGIN \
+                        IF TG_OP <> 'DELETE' THEN {new}; END IF; \
+                        IF TG_OP <> 'INSERT' THEN {old}; END IF; \
+                        RETURN NULL; \
+                    END; $$ LANGUAGE plpgsql;",
+                    new = self.update_sql(quote, "NEW"),
+                    old = self.update_sql(quote, "OLD"),
+                ),
+                format!(
+                    "CREATE TRIGGER {function} AFTER INSERT OR UPDATE OR DELETE ON {source} \
+                    FOR EACH ROW EXECUTE FUNCTION {function}();"
+                ),
+            ]);
+        } else {
+            for (operation, rows) in Self::OPERATIONS {
+                let updates: String = rows
+                    .iter()
+                    .map(|row| format!("{}; ", self.update_sql(quote, row)))
+                    .collect();
+                statements.push(format!(
+                    "CREATE TRIGGER {trigger} AFTER {operation} ON {source} \
+                    FOR EACH ROW BEGIN {updates}END;",
+                    trigger = quote.name(&format!("{name}_{}", operation.to_lowercase())),
+                ));
+            }
+        }
+        statements
+    }
+
+    /// Get the statements executed by [`Aggregate::drop_triggers`]
+    pub fn drop_triggers_sql(&self, dialect: DBImpl) -> Vec<String> {
+        let quote = Quote::new(&dialect);
+        let name = self.trigger_name();
+        if quote.postgres {
+            let function = quote.name(&name);
+            vec![
+                format!(
+                    "DROP TRIGGER IF EXISTS {function} ON {source};",
+                    source = quote.name(self.source_table)
+                ),
+                format!("DROP FUNCTION IF EXISTS {function}();"),
+            ]
+        } else {
+            Self::OPERATIONS
+                .iter()
+                .map(|(operation, _)| {
+                    format!(
+                        "DROP TRIGGER IF EXISTS {};",
+                        quote.name(&format!("{name}_{}", operation.to_lowercase()))
+                    )
+                })
+                .collect()
+        }
+    }
+
+    /// Get the statement executed by [`Aggregate::backfill`]
+    pub fn backfill_sql(&self, dialect: DBImpl) -> String {
+        let quote = Quote::new(&dialect);
+        format!(
+            "UPDATE {target} SET {column} = {aggregate};",
+            target = quote.name(self.target_table),
+            column = quote.name(self.target_column),
+            aggregate = self.aggregate_sql(quote),
+        )
+    }
+
+    /// The operations triggering a recomputation and the rows whose foreign key's aggregate is recomputed
+    ///
+    /// Postgres uses a single trigger checking the operation instead.
+    const OPERATIONS: [(&'static str, &'static [&'static str]); 3] = [
+        ("INSERT", &["NEW"]),
+        ("UPDATE", &["OLD", "NEW"]),
+        ("DELETE", &["OLD"]),
+    ];
+
+    /// Name of the trigger and of postgres' trigger function
+    fn trigger_name(&self) -> String {
+        format!("{}_{}_aggregate", self.target_table, self.target_column)
+    }
+
+    /// Statement recomputing the aggregate of the row referenced by the trigger's `row`
+    fn update_sql(&self, quote: Quote, row: &str) -> String {
+        format!(
+            "UPDATE {target} SET {column} = {aggregate} WHERE {target}.{key} = {row}.{source_key}",
+            target = quote.name(self.target_table),
+            column = quote.name(self.target_column),
+            key = quote.name(self.target_key),
+            aggregate = self.aggregate_sql(quote),
+            source_key = quote.name(self.source_key),
+        )
+    }
+
+    /// Sub query computing the aggregate of the target's current row
+    fn aggregate_sql(&self, quote: Quote) -> String {
+        // The source is aliased to support aggregating the target's own table
+        let row = quote.name("__rorm_row");
+        let value = format!("{row}.{}", quote.name(self.source_column));
+        let aggregate = match self.function {
+            "sum" => format!("COALESCE(SUM({value}), 0)"),
+            function => format!("{}({value})", function.to_uppercase()),
+        };
+        format!(
+            "(SELECT {aggregate} FROM {source} AS {row} WHERE {row}.{source_key} = {target}.{key})",
+            source = quote.name(self.source_table),
+            source_key = quote.name(self.source_key),
+            target = quote.name(self.target_table),
+            key = quote.name(self.target_key),
+        )
+    }
+}
+
+/// Quotes identifiers for a dialect
+#[derive(Copy, Clone)]
+struct Quote {
+    postgres: bool,
+}
+impl Quote {
+    fn new(dialect: &DBImpl) -> Self {
+        Self {
+            postgres: matches!(dialect, DBImpl::Postgres),
+        }
+    }
+
+    fn name(self, name: &str) -> String {
+        if self.postgres {
+            format!("\"{name}\"")
+        } else {
+            format!("`{name}`")
+        }
+    }
+}
@@ -0,0 +1,87 @@
+//! SQLite's attached databases
+//!
+//! SQLite can open additional database files on a connection using `ATTACH DATABASE`.
+//! Their tables are referenced by prefixing them with the alias they have been attached as,
+//! which allows joining across files or moving rows into an archive file cheaply:
+//!
+//! ```no_run
+//! # use rorm::{Error, Model, Transaction};
+//! # use rorm::attach::{attach, copy_rows, detach};
+//! # #[derive(Model)] pub struct Post { #[rorm(id)] id: i64, #[rorm(max_length = 255)] title: String, }
+//! pub async fn archive_posts(tx: &mut Transaction) -> Result<u64, Error> {
+//!     attach(&mut *tx, "archive.sqlite3", "archive").await?;
+//!     // The archive has to contain the `post` table already
+//!     let copied = copy_rows::<Post>(&mut *tx, "main", "archive").await?;
+//!     detach(&mut *tx, "archive").await?;
+//!     Ok(copied)
+//! }
+//! ```
+//!
+//! Attaching is a property of the connection and not of the [`Database`](crate::Database),
+//! which is a pool of connections.
+//! Therefore, the functions in this module should be used on a [`Transaction`](crate::Transaction)
+//! which keeps using the same connection.
+//! Detach the database before committing, because the connection is returned to the pool afterward.
+//!
+//! The query builders always use the tables of the `main` database.
+//! Use [`attached_table`] to reference an attached table in raw sql.
+//!
+//! These functions are only supported on SQLite.
+
+use rorm_db::executor::AffectedRows;
+use rorm_db::sql::value::Value;
+use rorm_db::sql::DBImpl;
+use rorm_db::{database, Error, Executor};
+
+use crate::internal::sql::quote;
+use crate::model::Model;
+
+/// SQLite quotes identifiers like Postgres, whose dialect is available without the `all-drivers` feature
+const QUOTING: DBImpl = DBImpl::Postgres;
+
+/// Open the database file at `path` as `alias` on the executor's connection
+pub async fn attach<'ex>(
+    executor: impl Executor<'ex>,
+    path: &str,
+    alias: &str,
+) -> Result<(), Error> {
+    let sql = format!("ATTACH DATABASE ? AS {};", quote(QUOTING, alias));
+    database::raw_sql(executor, &sql, Some(&[Value::String(path)])).await?;
+    Ok(())
+}
+
+/// Close the database attached as `alias` on the executor's connection
+pub async fn detach<'ex>(executor: impl Executor<'ex>, alias: &str) -> Result<(), Error> {
+    let sql = format!("DETACH DATABASE {};", quote(QUOTING, alias));
+    database::raw_sql(executor, &sql, None).await?;
+    Ok(())
+}
+
+/// Get `M`'s table in the database attached as `alias` to be used in raw sql
+///
+/// The connection's own database is called `main`.
+pub fn attached_table<M: Model>(alias: &str) -> String {
+    format!("{}.{}", quote(QUOTING, alias), quote(QUOTING, M::TABLE))
+}
+
+/// Copy all rows of `M`'s table in the database attached as `from` to the one attached as `to`
+///
+/// Both tables have to exist and have the same columns.
+/// Returns the number of copied rows.
+pub async fn copy_rows<'ex, M: Model>(
+    executor: impl Executor<'ex>,
+    from: &str,
+    to: &str,
+) -> Result<u64, Error> {
+    let columns = M::columns()
+        .into_iter()
+        .map(|column| quote(QUOTING, column))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let sql = format!(
+        "INSERT INTO {} ({columns}) SELECT {columns} FROM {};",
+        attached_table::<M>(to),
+        attached_table::<M>(from)
+    );
+    executor.execute::<AffectedRows>(sql, Vec::new()).await
+}
@@ -0,0 +1,574 @@
+//! Audit log for models annotated with `#[rorm(audit)]`
+//!
+//! Every insert, update and delete performed through the crud builders on an audited model
+//! is recorded in a generated table named after the model's table with an `_audit` suffix.
+//!
+//! An entry stores:
+//! - the kind of [operation](AuditOperation)
+//! - the affected row's primary key
+//! - the row's old and new values as json
+//! - the [actor](with_actor) who caused the change
+//! - a unix timestamp taken from the database's clock
+//!
+//! The entries are written in the same transaction as the change they describe.
+//! Updates and deletes lock the affected rows using `FOR UPDATE` while reading their old values,
+//! if the database supports it.
+//! The old and new values are stored as json encoded binary columns, which aren't limited in length.
+//!
+//! ```no_run
+//! # use rorm::{Database, Error, Model, update, FieldAccess};
+//! # use rorm::audit::{history, with_actor};
+//! #[derive(Model)]
+//! #[rorm(audit)]
+//! pub struct User {
+//!     #[rorm(id)]
+//!     pub id: i64,
+//!
+//!     #[rorm(max_length = 255)]
+//!     pub name: String,
+//! }
+//!
+//! pub async fn rename_user(db: &Database, admin: i64, user: i64) -> Result<(), Error> {
+//!     with_actor(admin.to_string(), async {
+//!         update(db, User)
+//!             .set(User.name, "Alice".to_string())
+//!             .condition(User.id.equals(user))
+//!             .await
+//!     })
+//!     .await?;
+//!
+//!     for entry in history::<User>(db, &user).await? {
+//!         println!("{:?} by {:?}: {:?} -> {:?}", entry.operation, entry.actor, entry.old, entry.new);
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::future::Future;
+
+use rorm_db::database::{self, ColumnSelector};
+use rorm_db::executor::{All, Executor, Nothing, QueryStrategy};
+use rorm_db::row::RowError;
+use rorm_db::sql::conditional::{BinaryCondition, Condition as SqlCondition};
+use rorm_db::sql::ordering::{OrderByEntry, Ordering};
+use rorm_db::sql::value::{NullType, Value as SqlValue};
+use rorm_db::{DBImpl, Error, Row};
+use rorm_declaration::imr;
+
+use crate::conditions::Value;
+use crate::crud::decoder::Decoder;
+use crate::database::Capabilities;
+use crate::internal::field::{Field, SingleColumnField};
+use crate::internal::hmr::AsImr;
+use crate::internal::query_context::QueryContext;
+use crate::internal::sql::{placeholder, quote};
+use crate::internal::task_local::{TaskLocal, TaskLocalFuture};
+use crate::model::Model;
+use crate::transaction::Scoped;
+
+thread_local! {
+    static ACTOR_SLOT: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+static ACTOR: TaskLocal<String> = TaskLocal::new(&ACTOR_SLOT);
+
+/// Run a future with an actor which will be recorded in every audit entry written by it.
+///
+/// The actor is an arbitrary string identifying who caused a change, for example a user's id.
+pub fn with_actor<F: Future>(actor: impl Into<String>, future: F) -> WithActor<F> {
+    ACTOR.scope(actor.into(), future)
+}
+
+/// Get the actor set by the surrounding [`with_actor`] call
+pub fn current_actor() -> Option<String> {
+    ACTOR.get()
+}
+
+/// Future returned by [`with_actor`]
+pub type WithActor<F> = TaskLocalFuture<String, F>;
+
+/// The kind of change recorded by an [`AuditEntry`]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum AuditOperation {
+    /// The row has been inserted
+    Insert,
+    /// The row has been updated
+    Update,
+    /// The row has been deleted
+    Delete,
+}
+impl AuditOperation {
+    /// Get the operation's representation in the database
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            AuditOperation::Insert => "insert",
+            AuditOperation::Update => "update",
+            AuditOperation::Delete => "delete",
+        }
+    }
+
+    fn from_str(string: &str) -> Option<Self> {
+        match string {
+            "insert" => Some(AuditOperation::Insert),
+            "update" => Some(AuditOperation::Update),
+            "delete" => Some(AuditOperation::Delete),
+            _ => None,
+        }
+    }
+}
+
+/// A single recorded change of an audited model's row
+#[derive(Clone, Debug)]
+pub struct AuditEntry {
+    /// The entry's id which increases with every change
+    pub id: i64,
+
+    /// The kind of change
+    pub operation: AuditOperation,
+
+    /// The affected row's primary key encoded as json
+    pub primary_key: String,
+
+    /// The row's values before the change
+    ///
+    /// This is `None` for [`AuditOperation::Insert`].
+    pub old: Option<serde_json::Value>,
+
+    /// The row's values after the change
+    ///
+    /// This is `None` for [`AuditOperation::Delete`].
+    pub new: Option<serde_json::Value>,
+
+    /// The actor set by [`with_actor`] when the change happened
+    pub actor: Option<String>,
+
+    /// Unix timestamp in seconds of when the change happened
+    pub timestamp: i64,
+}
+
+/// Query all audit entries of a single row ordered from oldest to newest
+pub async fn history<'ex, M: Model>(
+    executor: impl Executor<'ex>,
+    primary_key: &<M::Primary as Field>::Type,
+) -> Result<Vec<AuditEntry>, Error> {
+    let table = audit_table::<M>();
+    let primary_key = value_to_json(&M::Primary::type_as_value(primary_key)).to_string();
+
+    let selects = COLUMNS.map(|column_name| ColumnSelector {
+        table_name: None,
+        column_name,
+        select_alias: None,
+        aggregation: None,
+    });
+    let condition = SqlCondition::BinaryCondition(BinaryCondition::Equals(Box::new([
+        SqlCondition::Value(SqlValue::Column {
+            table_name: None,
+            column_name: "primary_key",
+        }),
+        SqlCondition::Value(SqlValue::String(&primary_key)),
+    ])));
+    let order_by = OrderByEntry {
+        ordering: Ordering::Asc,
+        table_name: None,
+        column_name: "id",
+    };
+
+    let rows = database::query::<All>(
+        executor,
+        &table,
+        &selects,
+        &[],
+        Some(&condition),
+        &[order_by],
+        None,
+    )
+    .await?;
+    rows.iter()
+        .map(|row| decode_entry(row).map_err(Into::into))
+        .collect()
+}
+
+/// Columns of the audit table in the order used by [`decode_entry`] and [`write_entries`]
+const COLUMNS: [&str; 7] = [
+    "id",
+    "operation",
+    "primary_key",
+    "old",
+    "new",
+    "actor",
+    "timestamp",
+];
+
+fn decode_entry(row: &Row) -> Result<AuditEntry, RowError<'static>> {
+    let operation: String = row.get(1usize)?;
+    let decode_json = |index: usize| -> Result<Option<serde_json::Value>, RowError<'static>> {
+        let json: Option<Vec<u8>> = row.get(index)?;
+        json.map(|json| serde_json::from_slice(&json))
+            .transpose()
+            .map_err(|error| RowError::Decode {
+                index: index.into(),
+                source: error.into(),
+            })
+    };
+    Ok(AuditEntry {
+        id: row.get(0usize)?,
+        operation: AuditOperation::from_str(&operation).ok_or_else(|| RowError::Decode {
+            index: 1usize.into(),
+            source: format!("Unknown audit operation: {operation}").into(),
+        })?,
+        primary_key: row.get(2usize)?,
+        old: decode_json(3)?,
+        new: decode_json(4)?,
+        actor: row.get(5usize)?,
+        timestamp: row.get(6usize)?,
+    })
+}
+
+/// Get the name of a model's audit table
+pub fn audit_table<M: Model>() -> String {
+    format!("{}_audit", M::TABLE)
+}
+
+/// Returns the intermediate representation of a model's audit table
+///
+/// This function is registered by `#[rorm(audit)]` to expose the table to the migrator.
+#[doc(hidden)]
+pub fn get_audit_imr<M: Model>() -> imr::Model {
+    let source_defined_at = Some(M::SOURCE.as_imr());
+    let field = |name: &str, db_type, annotations| imr::Field {
+        name: name.to_string(),
+        db_type,
+        annotations,
+        source_defined_at: source_defined_at.clone(),
+    };
+    imr::Model {
+        name: audit_table::<M>(),
+        fields: vec![
+            field(
+                "id",
+                imr::DbType::Int64,
+                vec![imr::Annotation::PrimaryKey, imr::Annotation::AutoIncrement],
+            ),
+            field(
+                "operation",
+                imr::DbType::Choices,
+                vec![
+                    imr::Annotation::Choices(
+                        [
+                            AuditOperation::Insert,
+                            AuditOperation::Update,
+                            AuditOperation::Delete,
+                        ]
+                        .map(|op| op.as_str().to_string())
+                        .to_vec(),
+                    ),
+                    imr::Annotation::NotNull,
+                ],
+            ),
+            field(
+                "primary_key",
+                imr::DbType::VarChar,
+                vec![
+                    imr::Annotation::MaxLength(255),
+                    imr::Annotation::Index(None),
+                    imr::Annotation::NotNull,
+                ],
+            ),
+            field("old", imr::DbType::Binary, vec![]),
+            field("new", imr::DbType::Binary, vec![]),
+            field(
+                "actor",
+                imr::DbType::VarChar,
+                vec![imr::Annotation::MaxLength(255)],
+            ),
+            field(
+                "timestamp",
+                imr::DbType::Int64,
+                vec![imr::Annotation::NotNull],
+            ),
+        ],
+        source_defined_at,
+    }
+}
+
+/// Insert rows into an audited model's table and record them
+///
+/// `returning` has to contain the columns selected by `decoder` which decodes the full model.
+pub(crate) async fn insert_returning<'ex, M: Model>(
+    executor: Scoped<impl Executor<'ex>>,
+    columns: &[&str],
+    rows: &[&[SqlValue<'_>]],
+    returning: &[&str],
+    decoder: &impl Decoder<Result = M>,
+) -> Result<Vec<Row>, Error> {
+    let mut tx = executor.begin().await?;
+
+    let returned =
+        database::insert_bulk_returning(tx.executor(), M::TABLE, columns, rows, returning).await?;
+
+    let mut entries = Vec::with_capacity(returned.len());
+    for row in &returned {
+        entries.push((None, Some(model_to_json(&decoder.by_index(row)?))));
+    }
+    write_entries::<M>(tx.executor(), AuditOperation::Insert, entries).await?;
+
+    tx.commit().await?;
+    Ok(returned)
+}
+
+/// Update an audited model's rows and record their old and new values
+///
+/// `ctx` has to contain the condition referenced by `condition_index`.
+pub(crate) async fn update<'ex, M: Model>(
+    executor: Scoped<impl Executor<'ex>>,
+    columns: &[(&'static str, Value<'_>)],
+    ctx: &mut QueryContext<'_>,
+    condition_index: Option<usize>,
+) -> Result<u64, Error> {
+    let mut tx = executor.begin().await?;
+
+    ctx.load_all_deferred();
+    let decoder = M::select::<M>(ctx);
+    let condition = ctx.get_condition_opt(condition_index);
+    let old_rows = database::query::<All>(
+        ForUpdate {
+            executor: tx.executor(),
+            table: M::TABLE,
+        },
+        M::TABLE,
+        &ctx.get_selects(),
+        &ctx.get_joins(),
+        condition.as_ref(),
+        &[],
+        None,
+    )
+    .await?;
+
+    let sql_columns: Vec<_> = columns
+        .iter()
+        .map(|(name, value)| (*name, value.as_sql()))
+        .collect();
+    let updated =
+        database::update(tx.executor(), M::TABLE, &sql_columns, condition.as_ref()).await?;
+
+    let mut entries = Vec::with_capacity(old_rows.len());
+    for row in &old_rows {
+        let old = model_to_json(&decoder.by_index(row)?);
+        let mut new = old.clone();
+        if let serde_json::Value::Object(new) = &mut new {
+            for (name, value) in columns {
+                new.insert(name.to_string(), value_to_json(value));
+            }
+        }
+        entries.push((Some(old), Some(new)));
+    }
+    write_entries::<M>(tx.executor(), AuditOperation::Update, entries).await?;
+
+    tx.commit().await?;
+    Ok(updated)
+}
+
+/// Delete an audited model's rows and record their old values
+///
+/// `ctx` has to contain the condition referenced by `condition_index`.
+pub(crate) async fn delete<'ex, M: Model>(
+    executor: Scoped<impl Executor<'ex>>,
+    ctx: &mut QueryContext<'_>,
+    condition_index: Option<usize>,
+) -> Result<u64, Error> {
+    let mut tx = executor.begin().await?;
+
+    ctx.load_all_deferred();
+    let decoder = M::select::<M>(ctx);
+    let condition = ctx.get_condition_opt(condition_index);
+    let old_rows = database::query::<All>(
+        ForUpdate {
+            executor: tx.executor(),
+            table: M::TABLE,
+        },
+        M::TABLE,
+        &ctx.get_selects(),
+        &ctx.get_joins(),
+        condition.as_ref(),
+        &[],
+        None,
+    )
+    .await?;
+
+    let deleted = database::delete(tx.executor(), M::TABLE, condition.as_ref()).await?;
+
+    let mut entries = Vec::with_capacity(old_rows.len());
+    for row in &old_rows {
+        entries.push((Some(model_to_json(&decoder.by_index(row)?)), None));
+    }
+    write_entries::<M>(tx.executor(), AuditOperation::Delete, entries).await?;
+
+    tx.commit().await?;
+    Ok(deleted)
+}
+
+/// Write entries consisting of a row's old and new values into a model's audit table
+async fn write_entries<'ex, M: Model>(
+    executor: impl Executor<'ex>,
+    operation: AuditOperation,
+    entries: Vec<(Option<serde_json::Value>, Option<serde_json::Value>)>,
+) -> Result<(), Error> {
+    if entries.is_empty() {
+        return Ok(());
+    }
+
+    let actor = current_actor();
+    let mut values = Vec::with_capacity(entries.len() * (COLUMNS.len() - 2));
+    for (old, new) in entries {
+        let primary_key = old
+            .as_ref()
+            .or(new.as_ref())
+            .and_then(|row| row.get(M::Primary::NAME))
+            .map(ToString::to_string)
+            .unwrap_or_default();
+        let as_binary = |json: Option<serde_json::Value>| match json {
+            Some(json) => Value::Binary(Cow::Owned(json.to_string().into_bytes())),
+            None => Value::Null(NullType::Binary),
+        };
+        values.extend([
+            Value::Choice(Cow::Borrowed(operation.as_str())),
+            Value::String(Cow::Owned(primary_key)),
+            as_binary(old),
+            as_binary(new),
+            match &actor {
+                Some(actor) => Value::String(Cow::Borrowed(actor.as_str())),
+                None => Value::Null(NullType::String),
+            },
+        ]);
+    }
+
+    // The timestamp is computed by the database, so it is consistent across clients
+    // and available on targets without a system clock.
+    let dialect = executor.dialect();
+    let now = match dialect {
+        DBImpl::Postgres => "CAST(EXTRACT(EPOCH FROM NOW()) AS BIGINT)",
+        #[cfg(feature = "all-drivers")]
+        DBImpl::MySQL => "UNIX_TIMESTAMP()",
+        #[cfg(feature = "all-drivers")]
+        DBImpl::SQLite => "CAST(strftime('%s', 'now') AS INTEGER)",
+    };
+    let columns = &COLUMNS[1..];
+    let row_len = columns.len() - 1;
+    let rows = (0..values.len() / row_len)
+        .map(|row| {
+            let placeholders = (1..=row_len)
+                .map(|column| placeholder(dialect, row * row_len + column))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("({placeholders}, {now})")
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    let sql = format!(
+        "INSERT INTO {table} ({columns}) VALUES {rows};",
+        table = quote(dialect, &audit_table::<M>()),
+        columns = columns
+            .iter()
+            .map(|column| quote(dialect, column))
+            .collect::<Vec<_>>()
+            .join(", "),
+    );
+    let values = values.iter().map(Value::as_sql).collect();
+    executor.execute::<Nothing>(sql, values).await
+}
+
+/// An [`Executor`] locking the rows selected by its query
+///
+/// Only `table`'s rows are locked, so the query may contain outer joins.
+/// It has no effect on databases without `FOR UPDATE`.
+struct ForUpdate<E> {
+    executor: E,
+    table: &'static str,
+}
+
+impl<'executor, E> Executor<'executor> for ForUpdate<E>
+where
+    E: Executor<'executor>,
+{
+    fn execute<'data, 'result, Q>(
+        self,
+        query: String,
+        values: Vec<SqlValue<'data>>,
+    ) -> Q::Result<'result>
+    where
+        'executor: 'result,
+        'data: 'result,
+        Q: QueryStrategy,
+    {
+        let dialect = self.executor.dialect();
+        if !Capabilities::for_dialect(dialect).for_update {
+            return self.executor.execute::<Q>(query, values);
+        }
+        let query = query.trim_end().trim_end_matches(';');
+        let table = quote(dialect, self.table);
+        self.executor
+            .execute::<Q>(format!("{query} FOR UPDATE OF {table};"), values)
+    }
+
+    fn dialect(&self) -> DBImpl {
+        self.executor.dialect()
+    }
+
+    type EnsureTransactionFuture = E::EnsureTransactionFuture;
+
+    fn ensure_transaction(self) -> Self::EnsureTransactionFuture {
+        self.executor.ensure_transaction()
+    }
+}
+
+/// Convert a model instance into a json object mapping its columns to their values
+fn model_to_json<M: Model>(model: &M) -> serde_json::Value {
+    serde_json::Value::Object(
+        M::columns()
+            .into_iter()
+            .zip(model.references().iter())
+            .map(|(column, value)| (column.to_string(), value_to_json(value)))
+            .collect(),
+    )
+}
+
+/// Convert a single [`Value`] into json
+fn value_to_json(value: &Value) -> serde_json::Value {
+    use serde_json::Value as Json;
+    match value {
+        Value::Null(_) => Json::Null,
+        Value::String(v) | Value::Choice(v) => Json::String(v.to_string()),
+        Value::I64(v) => Json::from(*v),
+        Value::I32(v) => Json::from(*v),
+        Value::I16(v) => Json::from(*v),
+        Value::Bool(v) => Json::from(*v),
+        Value::F64(v) => Json::from(*v),
+        Value::F32(v) => Json::from(*v),
+        Value::Binary(v) => Json::String(v.iter().map(|byte| format!("{byte:02x}")).collect()),
+        #[cfg(feature = "chrono")]
+        Value::ChronoNaiveTime(v) => Json::String(v.to_string()),
+        #[cfg(feature = "chrono")]
+        Value::ChronoNaiveDate(v) => Json::String(v.to_string()),
+        #[cfg(feature = "chrono")]
+        Value::ChronoNaiveDateTime(v) => Json::String(v.to_string()),
+        #[cfg(feature = "chrono")]
+        Value::ChronoDateTime(v) => Json::String(v.to_rfc3339()),
+        #[cfg(feature = "time")]
+        Value::TimeDate(v) => Json::String(v.to_string()),
+        #[cfg(feature = "time")]
+        Value::TimeTime(v) => Json::String(v.to_string()),
+        #[cfg(feature = "time")]
+        Value::TimeOffsetDateTime(v) => Json::String(v.to_string()),
+        #[cfg(feature = "time")]
+        Value::TimePrimitiveDateTime(v) => Json::String(v.to_string()),
+        #[cfg(feature = "uuid")]
+        Value::Uuid(v) => Json::String(v.to_string()),
+        #[cfg(feature = "postgres-only")]
+        Value::MacAddress(v) => Json::String(v.to_string()),
+        #[cfg(feature = "postgres-only")]
+        Value::IpNetwork(v) => Json::String(v.to_string()),
+        #[cfg(feature = "postgres-only")]
+        Value::BitVec(v) => Json::String(format!("{:?}", v.as_ref())),
+    }
+}
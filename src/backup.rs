@@ -0,0 +1,138 @@
+//! Creating and restoring backups of a database
+//!
+//! SQLite databases can be backed up while in use through [`DatabaseExt::backup_to`]
+//! and restored by copying the backup over the database file using [`restore_sqlite`].
+//!
+//! Postgres doesn't support writing a backup through a regular connection,
+//! so [`pg_dump`] and [`pg_restore`] run the corresponding tools, which have to be installed.
+//! They block until the tool exits and should be called using your runtime's equivalent of `spawn_blocking`.
+//!
+//! ```no_run
+//! # use rorm::{Database, Error};
+//! use rorm::database::DatabaseExt;
+//!
+//! pub async fn nightly_backup(db: &Database, day: &str) -> Result<(), Error> {
+//!     db.backup_to(&format!("backups/{day}.sqlite3")).await
+//! }
+//! ```
+
+#[cfg(any(feature = "all-drivers", feature = "postgres-only"))]
+use std::ffi::OsStr;
+use std::fs;
+use std::io;
+use std::path::Path;
+#[cfg(any(feature = "all-drivers", feature = "postgres-only"))]
+use std::process::Command;
+
+use rorm_db::sql::value::Value;
+#[cfg(any(feature = "all-drivers", feature = "postgres-only"))]
+use rorm_db::DatabaseDriver;
+use rorm_db::{database, Error, Executor};
+
+#[cfg(doc)]
+use crate::database::DatabaseExt;
+
+/// Write a consistent copy of an SQLite database to the file at `path`
+///
+/// This uses `VACUUM INTO` which doesn't block concurrent readers or writers.
+/// The file must not exist yet.
+pub async fn backup_sqlite<'ex>(executor: impl Executor<'ex>, path: &str) -> Result<(), Error> {
+    database::raw_sql(executor, "VACUUM INTO ?;", Some(&[Value::String(path)])).await?;
+    Ok(())
+}
+
+/// Replace the SQLite database at `database` with the backup at `backup`
+///
+/// There must not be any open connection to the database while it is restored,
+/// i.e. it has to be called before [`Database::connect`](rorm_db::Database::connect)
+/// or after [`Database::close`](rorm_db::Database::close).
+pub fn restore_sqlite(backup: impl AsRef<Path>, database: impl AsRef<Path>) -> io::Result<()> {
+    let database = database.as_ref();
+    fs::copy(backup, database)?;
+
+    // Remove the journal of the replaced database which would otherwise be applied to the backup
+    for suffix in ["-wal", "-shm", "-journal"] {
+        let mut journal = database.as_os_str().to_owned();
+        journal.push(suffix);
+        match fs::remove_file(journal) {
+            Err(error) if error.kind() != io::ErrorKind::NotFound => return Err(error),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Write a backup of the postgres database configured by `driver` to the file at `path`
+///
+/// This runs `pg_dump` using its custom archive format, which can be restored using [`pg_restore`].
+#[cfg(any(feature = "all-drivers", feature = "postgres-only"))]
+pub fn pg_dump(driver: &DatabaseDriver, path: impl AsRef<Path>) -> io::Result<()> {
+    let mut command = pg_command("pg_dump", driver)?;
+    command
+        .arg("--format=custom")
+        .arg("--file")
+        .arg(path.as_ref());
+    run(command)
+}
+
+/// Restore the backup at `path` written by [`pg_dump`] into the postgres database configured by `driver`
+///
+/// Objects existing in the backup are dropped and recreated.
+#[cfg(any(feature = "all-drivers", feature = "postgres-only"))]
+pub fn pg_restore(driver: &DatabaseDriver, path: impl AsRef<Path>) -> io::Result<()> {
+    let mut command = pg_command("pg_restore", driver)?;
+    command
+        .arg("--clean")
+        .arg("--if-exists")
+        .arg("--single-transaction")
+        .arg(path.as_ref());
+    run(command)
+}
+
+/// Construct a command running one of postgres' client tools connected to the database configured by `driver`
+#[cfg(any(feature = "all-drivers", feature = "postgres-only"))]
+fn pg_command(program: impl AsRef<OsStr>, driver: &DatabaseDriver) -> io::Result<Command> {
+    #[allow(irrefutable_let_patterns)] // there is no other driver with only postgres enabled
+    let DatabaseDriver::Postgres {
+        name,
+        host,
+        port,
+        user,
+        password,
+    } = driver
+    else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "The database is not a postgres database",
+        ));
+    };
+
+    let mut command = Command::new(program);
+    command
+        .env("PGPASSWORD", password)
+        .arg("--host")
+        .arg(host)
+        .arg("--port")
+        .arg(port.to_string())
+        .arg("--username")
+        .arg(user)
+        .arg("--dbname")
+        .arg(name);
+    Ok(command)
+}
+
+/// Run a command and convert a non-zero exit code into an error containing its stderr
+#[cfg(any(feature = "all-drivers", feature = "postgres-only"))]
+fn run(mut command: Command) -> io::Result<()> {
+    let output = command.output()?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!(
+            "{:?} failed with {}: {}",
+            command.get_program(),
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))
+    }
+}
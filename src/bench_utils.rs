@@ -0,0 +1,63 @@
+//! Helpers for benchmarking rorm's overhead and comparing it with other libraries
+//!
+//! They are used by rorm's own benchmarks in `benches/`
+//! and are exposed by the `bench-utils` feature for benchmarks outside of rorm.
+//!
+//! To compare rorm with another library like sqlx or SeaORM,
+//! both should run against the same database and the same table:
+//!
+//! ```no_run
+//! # use rorm::{Database, Error, Model};
+//! # use rorm::bench_utils::{create_table, build_select};
+//! # use rorm::database::DatabaseExt;
+//! # #[derive(Model)] pub struct User { #[rorm(id)] id: i64, #[rorm(max_length = 255)] name: String, }
+//! pub async fn setup() -> Result<Database, Error> {
+//!     let db = Database::sqlite_in_memory().await?;
+//!     create_table::<User>(&db).await?;
+//!     Ok(db)
+//! }
+//!
+//! // Builds the same query as `query(db, User).condition(..)` without executing it
+//! pub fn query_overhead() {
+//!     build_select(User, Some(&User.name.equals("alice")));
+//! }
+//! ```
+
+use std::hint::black_box;
+
+use rorm_db::{Error, Executor};
+
+use crate::conditions::Condition;
+use crate::crud::selector::Selector;
+use crate::internal::query_context::QueryContext;
+use crate::model::Model;
+
+/// Create the table of the model `M` without running any migrations
+///
+/// The table is created like the migrator would create it,
+/// except for foreign keys and indexes which are not created.
+pub async fn create_table<'ex, M: Model>(executor: impl Executor<'ex>) -> Result<(), Error> {
+    crate::transaction::create_table(executor, M::get_imr(), false).await
+}
+
+/// Perform all the work a select query does before handing it over to `rorm-db`
+///
+/// This runs the selector, builds the condition
+/// and converts joins, columns and the condition into `rorm-sql`'s representation.
+///
+/// The returned decoder can be used to benchmark decoding rows
+/// returned by [`QueryBuilder::all_as_rows`](crate::crud::query::QueryBuilder::all_as_rows)
+/// for the same selector.
+pub fn build_select<'a, S: Selector>(
+    selector: S,
+    condition: Option<&dyn Condition<'a>>,
+) -> S::Decoder {
+    let mut ctx = QueryContext::new();
+    let decoder = selector.select(&mut ctx);
+    let condition = condition.map(|condition| ctx.add_condition(condition));
+    black_box(ctx.get_selects());
+    black_box(ctx.get_joins());
+    black_box(ctx.get_condition_opt(condition));
+    black_box(ctx.get_order_bys());
+    decoder
+}
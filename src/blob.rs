@@ -0,0 +1,260 @@
+//! Reading and writing binary columns in chunks
+//!
+//! Fields of type `Vec<u8>` storing files can grow large.
+//! Instead of materializing the whole value, the functions in this module transfer it in chunks:
+//!
+//! ```no_run
+//! # use rorm::{Database, Error, Model};
+//! # use rorm::blob::{read_chunks, write_chunks};
+//! #[derive(Model)]
+//! pub struct Upload {
+//!     #[rorm(id)]
+//!     pub id: i64,
+//!
+//!     #[rorm(defer)]
+//!     pub content: Vec<u8>,
+//! }
+//!
+//! pub async fn copy_content(db: &Database, from: i64, to: i64) -> Result<(), Error> {
+//!     let chunks = read_chunks(db, Upload.content, from, 64 * 1024);
+//!     write_chunks(db, Upload.content, &to, chunks).await?;
+//!     Ok(())
+//! }
+//! ```
+//!
+//! Chunks are read using `substring` and written by appending to the column's current value.
+//! Postgres' large object API is not used, because it requires the column to store an `oid`
+//! instead of the `bytea` used for `Vec<u8>`.
+
+use std::fmt;
+
+use futures::stream::{self, Stream, TryStream, TryStreamExt};
+use rorm_db::executor::{AffectedRows, Optional};
+use rorm_db::sql::DBImpl;
+use rorm_db::{Database, Error, Executor};
+
+use crate::conditions::Value;
+use crate::internal::field::{Field, FieldProxy, SingleColumnField};
+use crate::internal::sql::{placeholder, quote};
+use crate::model::Model;
+
+/// Type of a model's primary key
+type Key<M> = <<M as Model>::Primary as Field>::Type;
+
+/// Get the length in bytes of the value stored in `field` for the row with the primary key `key`
+///
+/// Returns `None` if there is no such row.
+pub async fn len<'ex, F>(
+    executor: impl Executor<'ex>,
+    _field: FieldProxy<F, F::Model>,
+    key: &Key<F::Model>,
+) -> Result<Option<i64>, Error>
+where
+    F: SingleColumnField<Type = Vec<u8>>,
+{
+    let dialect = executor.dialect();
+    let function = match dialect {
+        DBImpl::Postgres => "octet_length",
+        #[allow(unreachable_patterns)]
+        _ => "LENGTH",
+    };
+    let sql = format!(
+        "SELECT {function}({}) FROM {} WHERE {};",
+        quote(dialect, F::NAME),
+        quote(dialect, F::Model::TABLE),
+        where_key::<F::Model>(dialect, 1)
+    );
+    let key = <F::Model as Model>::Primary::type_as_value(key);
+    let row = executor
+        .execute::<Optional>(sql, vec![key.as_sql()])
+        .await?;
+    row.map(|row| row.get(0usize))
+        .transpose()
+        .map_err(Into::into)
+}
+
+/// Read `len` bytes starting at `offset` from the value stored in `field` for the row with the primary key `key`
+///
+/// The returned chunk is shorter than `len` if the value ends before.
+/// Returns `None` if there is no such row.
+pub async fn read_chunk<'ex, F>(
+    executor: impl Executor<'ex>,
+    _field: FieldProxy<F, F::Model>,
+    key: &Key<F::Model>,
+    offset: u64,
+    len: u64,
+) -> Result<Option<Vec<u8>>, Error>
+where
+    F: SingleColumnField<Type = Vec<u8>>,
+{
+    let key = <F::Model as Model>::Primary::type_as_value(key);
+    read_chunk_by_value::<F>(executor, &key, offset, len).await
+}
+
+/// Stream the value stored in `field` for the row with the primary key `key` in chunks of `chunk_size` bytes
+///
+/// Every chunk is read using its own query,
+/// so concurrent writes to the value might be observed half-way.
+/// The stream is empty if there is no such row.
+pub fn read_chunks<'db, F>(
+    db: &'db Database,
+    _field: FieldProxy<F, F::Model>,
+    key: Key<F::Model>,
+    chunk_size: u64,
+) -> impl Stream<Item = Result<Vec<u8>, Error>> + 'db
+where
+    F: SingleColumnField<Type = Vec<u8>>,
+{
+    let key = <F::Model as Model>::Primary::type_into_value(key);
+    stream::try_unfold(Some(0), move |offset| {
+        let key = key.clone();
+        async move {
+            let Some(offset) = offset else {
+                return Ok(None);
+            };
+            let Some(chunk) = read_chunk_by_value::<F>(db, &key, offset, chunk_size).await? else {
+                return Ok(None);
+            };
+            let next = (chunk.len() as u64 == chunk_size).then_some(offset + chunk_size);
+            if chunk.is_empty() {
+                Ok(None)
+            } else {
+                Ok(Some((chunk, next)))
+            }
+        }
+    })
+}
+
+/// Append `chunk` to the value stored in `field` for the row with the primary key `key`
+///
+/// Returns whether the row exists.
+pub async fn append_chunk<'ex, F>(
+    executor: impl Executor<'ex>,
+    _field: FieldProxy<F, F::Model>,
+    key: &Key<F::Model>,
+    chunk: &[u8],
+) -> Result<bool, Error>
+where
+    F: SingleColumnField<Type = Vec<u8>>,
+{
+    let dialect = executor.dialect();
+    let column = quote(dialect, F::NAME);
+    let appended = match dialect {
+        DBImpl::Postgres => format!("{column} || $1"),
+        #[cfg(feature = "all-drivers")]
+        DBImpl::MySQL => format!("CONCAT({column}, ?)"),
+        // `||` concatenates text on sqlite
+        #[cfg(feature = "all-drivers")]
+        DBImpl::SQLite => format!("CAST({column} || ? AS BLOB)"),
+    };
+    let sql = format!(
+        "UPDATE {} SET {column} = {appended} WHERE {};",
+        quote(dialect, F::Model::TABLE),
+        where_key::<F::Model>(dialect, 2)
+    );
+    let key = <F::Model as Model>::Primary::type_as_value(key);
+    let updated = executor
+        .execute::<AffectedRows>(
+            sql,
+            vec![Value::Binary(chunk.into()).as_sql(), key.as_sql()],
+        )
+        .await?;
+    Ok(updated > 0)
+}
+
+/// Replace the value stored in `field` for the row with the primary key `key` with the concatenated `chunks`
+///
+/// The chunks are written inside a single transaction, so the value is never observed half-way.
+/// If `chunks` yields an error, the transaction is rolled back and
+/// an [`Error::DecodeError`] containing the error's message is returned.
+///
+/// Returns whether the row exists.
+pub async fn write_chunks<'ex, F, S>(
+    executor: impl Executor<'ex>,
+    field: FieldProxy<F, F::Model>,
+    key: &Key<F::Model>,
+    chunks: S,
+) -> Result<bool, Error>
+where
+    F: SingleColumnField<Type = Vec<u8>>,
+    S: TryStream<Ok = Vec<u8>>,
+    S::Error: fmt::Display,
+{
+    let mut guard = executor.ensure_transaction().await?;
+    let tx = guard.get_transaction();
+
+    let dialect = tx.dialect();
+    let sql = format!(
+        "UPDATE {} SET {} = {} WHERE {};",
+        quote(dialect, F::Model::TABLE),
+        quote(dialect, F::NAME),
+        placeholder(dialect, 1),
+        where_key::<F::Model>(dialect, 2)
+    );
+    let key_value = <F::Model as Model>::Primary::type_as_value(key);
+    let updated = tx
+        .execute::<AffectedRows>(
+            sql,
+            vec![
+                Value::Binary(Vec::new().into()).as_sql(),
+                key_value.as_sql(),
+            ],
+        )
+        .await?;
+    if updated == 0 {
+        return Ok(false);
+    }
+
+    let mut chunks = std::pin::pin!(chunks.into_stream());
+    while let Some(chunk) = chunks
+        .try_next()
+        .await
+        .map_err(|error| Error::DecodeError(format!("Failed to read chunk: {error}")))?
+    {
+        append_chunk(&mut *tx, field, key, &chunk).await?;
+    }
+
+    guard.commit().await?;
+    Ok(true)
+}
+
+/// Implementation of [`read_chunk`] taking the primary key as [`Value`]
+async fn read_chunk_by_value<'ex, F>(
+    executor: impl Executor<'ex>,
+    key: &Value<'_>,
+    offset: u64,
+    len: u64,
+) -> Result<Option<Vec<u8>>, Error>
+where
+    F: SingleColumnField<Type = Vec<u8>>,
+{
+    let dialect = executor.dialect();
+    let column = quote(dialect, F::NAME);
+    // sql counts from 1
+    let start = offset + 1;
+    let substring = match dialect {
+        DBImpl::Postgres => format!("substring({column} FROM {start} FOR {len})"),
+        #[allow(unreachable_patterns)]
+        _ => format!("substr({column}, {start}, {len})"),
+    };
+    let sql = format!(
+        "SELECT {substring} FROM {} WHERE {};",
+        quote(dialect, F::Model::TABLE),
+        where_key::<F::Model>(dialect, 1)
+    );
+    let row = executor
+        .execute::<Optional>(sql, vec![key.as_sql()])
+        .await?;
+    row.map(|row| row.get(0usize))
+        .transpose()
+        .map_err(Into::into)
+}
+
+/// Render the condition comparing `M`'s primary key with the `index`th parameter
+fn where_key<M: Model>(dialect: DBImpl, index: usize) -> String {
+    format!(
+        "{} = {}",
+        quote(dialect, <M as Model>::Primary::NAME),
+        placeholder(dialect, index)
+    )
+}
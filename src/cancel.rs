@@ -0,0 +1,99 @@
+//! Cancelling long-running queries
+//!
+//! [`cancellable`] stops waiting for a query as soon as another future completes,
+//! e.g. a `tokio_util::sync::CancellationToken::cancelled()` triggered by a client disconnecting:
+//!
+//! ```no_run
+//! # use std::future::Future;
+//! # use rorm::{query, Database, Error, Model};
+//! # use rorm::cancel::{backend_pid, cancellable_on_server};
+//! # #[derive(Model)] pub struct Report { #[rorm(id)] id: i64, #[rorm(max_length = 255)] title: String, }
+//! pub async fn all_reports(
+//!     db: &Database,
+//!     disconnected: impl Future<Output = ()>,
+//! ) -> Result<Vec<Report>, Error> {
+//!     let mut tx = db.start_transaction().await?;
+//!     let pid = backend_pid(&mut tx).await?;
+//!     let reports = cancellable_on_server(db, pid, query(&mut tx, Report).all(), disconnected).await?;
+//!     tx.commit().await?;
+//!     Ok(reports)
+//! }
+//! ```
+//!
+//! Dropping a query's future only stops the client from waiting for it,
+//! while the database might keep working on it.
+//! On postgres [`cancellable_on_server`] additionally asks the server to cancel it.
+//! This requires knowing the query's connection, so the query has to run on a transaction
+//! whose connection's [`backend_pid`] has been retrieved beforehand.
+
+use std::future::Future;
+use std::pin::pin;
+
+use futures::future::{self, Either};
+use rorm_db::executor::One;
+use rorm_db::sql::value::Value;
+use rorm_db::transaction::Transaction;
+use rorm_db::{Error, Executor};
+
+/// Wait for `query` unless `cancel` completes first
+///
+/// If `cancel` completes first, `query` is dropped and an [`Error::DecodeError`] is returned.
+pub async fn cancellable<T>(
+    query: impl Future<Output = Result<T, Error>>,
+    cancel: impl Future<Output = ()>,
+) -> Result<T, Error> {
+    match future::select(pin!(query), pin!(cancel)).await {
+        Either::Left((result, _)) => result,
+        Either::Right(((), _)) => Err(cancelled()),
+    }
+}
+
+/// Wait for `query` unless `cancel` completes first in which case the query is cancelled by the server
+///
+/// `pid` is the [`backend_pid`] of the connection `query` runs on.
+/// The cancel request is sent through another of `executor`'s connections.
+///
+/// This is only supported on postgres.
+pub async fn cancellable_on_server<'ex, T>(
+    executor: impl Executor<'ex>,
+    pid: i32,
+    query: impl Future<Output = Result<T, Error>>,
+    cancel: impl Future<Output = ()>,
+) -> Result<T, Error> {
+    match future::select(pin!(query), pin!(cancel)).await {
+        Either::Left((result, _)) => result,
+        Either::Right(((), _)) => {
+            // The query is only dropped when returning, after the server has been asked to cancel it.
+            // Otherwise, the transaction's rollback would wait for the query to finish.
+            cancel_backend(executor, pid).await?;
+            Err(cancelled())
+        }
+    }
+}
+
+/// Get the process id of the postgres backend serving the transaction's connection
+pub async fn backend_pid(tx: &mut Transaction) -> Result<i32, Error> {
+    let row = tx
+        .execute::<One>("SELECT pg_backend_pid();".to_string(), Vec::new())
+        .await?;
+    Ok(row.get(0usize)?)
+}
+
+/// Ask postgres to cancel the query currently running on the backend with the process id `pid`
+///
+/// Returns whether the cancel request has been sent.
+/// It is sent even if the backend is idle, in which case it has no effect.
+pub async fn cancel_backend<'ex>(executor: impl Executor<'ex>, pid: i32) -> Result<bool, Error> {
+    let row = executor
+        .execute::<One>(
+            "SELECT pg_cancel_backend($1);".to_string(),
+            vec![Value::I32(pid)],
+        )
+        .await?;
+    Ok(row.get(0usize)?)
+}
+
+/// The error returned for a cancelled query
+fn cancelled() -> Error {
+    Error::DecodeError("The query has been cancelled".to_string())
+}
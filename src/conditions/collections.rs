@@ -9,9 +9,10 @@
 //! A static collection is best created using the [`and!`](macro@crate::and)
 //! and [`or!`](macro@crate::or) macros which use the same syntax as `vec!`.
 //! They will automatically work around `StaticCollection`'s size restriction
-//! by resorting to a `DynamicCollection` of boxes if needed.
+//! by nesting several collections if needed, so no heap allocation is required.
 //!
 //! Both collection types can either be use with conditions or `Option` of conditions.
+//! A static collection can even mix both.
 //! This second mode is useful when you're dealing with a lot of `Condition`s which are
 //! only added conditionally. This makes a static collection effectively into a dynamic one with
 //! a capacity known at compile time:
@@ -105,24 +106,13 @@ where
     }
 }
 
-impl<'a, T: Condition<'a>> Condition<'a> for DynamicCollection<T> {
+impl<'a, T: IsCondition<'a>> Condition<'a> for DynamicCollection<T> {
     fn build(&self, context: &mut QueryContext<'a>) {
         context
             .conditions
             .push(FlatCondition::StartCollection(self.operator));
         for cond in self.vector.iter() {
-            cond.build(context);
-        }
-        context.conditions.push(FlatCondition::EndCollection);
-    }
-}
-impl<'a, T: Condition<'a>> Condition<'a> for DynamicCollection<Option<T>> {
-    fn build(&self, context: &mut QueryContext<'a>) {
-        context
-            .conditions
-            .push(FlatCondition::StartCollection(self.operator));
-        for cond in self.vector.iter().flat_map(Option::as_ref) {
-            cond.build(context);
+            cond.build_item(context);
         }
         context.conditions.push(FlatCondition::EndCollection);
     }
@@ -132,7 +122,7 @@ impl<'a, T: Condition<'a>> Condition<'a> for DynamicCollection<Option<T>> {
 ///
 /// (See [module](self) for some general information)
 ///
-/// The generic parameter `T` is a tuple of conditions.
+/// The generic parameter `T` is a tuple of conditions or optional conditions.
 /// Only tuple with 8 elements or fewer are allowed.
 /// (See [Disadvantage](StaticCollection#disadvantage))
 ///
@@ -182,27 +172,13 @@ macro_rules! impl_static_collection {
     };
     (impl $($generic:ident),+) => {
         #[allow(non_snake_case)] // the macro is simpler when generic variable are reused as value variables
-        impl<'a, $($generic: Condition<'a>),+> Condition<'a> for StaticCollection<($($generic,)+)> {
+        impl<'a, $($generic: IsCondition<'a>),+> Condition<'a> for StaticCollection<($($generic,)+)> {
             fn build(&self, context: &mut QueryContext<'a>) {
                 context
                     .conditions
                     .push(FlatCondition::StartCollection(self.operator));
                 let ($($generic,)+) = &self.tuple;
-                $($generic.build(context);)+
-                context.conditions.push(FlatCondition::EndCollection);
-            }
-        }
-
-        #[allow(non_snake_case)] // the macro is simpler when generic variable are reused as value variables
-        impl<'a, $($generic: Condition<'a>),+> Condition<'a> for StaticCollection<($(Option<$generic>,)+)> {
-            fn build(&self, context: &mut QueryContext<'a>) {
-                context
-                    .conditions
-                    .push(FlatCondition::StartCollection(self.operator));
-                let ($($generic,)+) = &self.tuple;
-                $(if let Some(cond) = $generic {
-                    cond.build(context);
-                })+
+                $($generic.build_item(context);)+
                 context.conditions.push(FlatCondition::EndCollection);
             }
         }
@@ -218,20 +194,18 @@ impl_static_collection!(H, G, F, E, D, C, B, A);
 #[doc(hidden)]
 #[macro_export]
 macro_rules! create_collection {
+    // Joining a nested collection with the same operator is equivalent to joining its conditions directly
     ($method:ident, $H:expr, $G:expr, $F:expr, $E:expr, $D:expr, $C:expr, $B:expr, $A:expr, $($other:expr),+ $(,)?) => {
-        $crate::conditions::collections::DynamicCollection::ident(vec![
-            $crate::conditions::collections::Condition::boxed($H),
-            $crate::conditions::collections::Condition::boxed($G),
-            $crate::conditions::collections::Condition::boxed($F),
-            $crate::conditions::collections::Condition::boxed($E),
-            $crate::conditions::collections::Condition::boxed($D),
-            $crate::conditions::collections::Condition::boxed($C),
-            $crate::conditions::collections::Condition::boxed($B),
-            $crate::conditions::collections::Condition::boxed($A),
-            $(
-                $crate::conditions::collections::Condition::boxed($other),
-            )+
-        ])
+        $crate::conditions::collections::StaticCollection::$method((
+            $crate::conditions::collections::ensure_condition($H),
+            $crate::conditions::collections::ensure_condition($G),
+            $crate::conditions::collections::ensure_condition($F),
+            $crate::conditions::collections::ensure_condition($E),
+            $crate::conditions::collections::ensure_condition($D),
+            $crate::conditions::collections::ensure_condition($C),
+            $crate::conditions::collections::ensure_condition($B),
+            $crate::create_collection!($method, $A, $($other),+),
+        ))
     };
     ($method:ident, $($other:expr),+ $(,)?) => {
         $crate::conditions::collections::StaticCollection::$method(($(
@@ -262,12 +236,26 @@ macro_rules! and {
     };
 }
 
+/// Either a condition or an optional one which is skipped if it is `None`
 #[doc(hidden)]
-pub trait IsCondition {}
-impl<'c, C: Condition<'c>> IsCondition for C {}
-impl<'c, C: Condition<'c>> IsCondition for Option<C> {}
+pub trait IsCondition<'a>: Send + Sync {
+    /// Adds the condition to a query context unless it is `None`
+    fn build_item(&self, context: &mut QueryContext<'a>);
+}
+impl<'a, C: Condition<'a>> IsCondition<'a> for C {
+    fn build_item(&self, context: &mut QueryContext<'a>) {
+        self.build(context);
+    }
+}
+impl<'a, C: Condition<'a>> IsCondition<'a> for Option<C> {
+    fn build_item(&self, context: &mut QueryContext<'a>) {
+        if let Some(cond) = self {
+            cond.build(context);
+        }
+    }
+}
 
 #[doc(hidden)]
-pub fn ensure_condition<C: IsCondition>(value: C) -> C {
+pub fn ensure_condition<'a, C: IsCondition<'a>>(value: C) -> C {
     value
 }
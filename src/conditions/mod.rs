@@ -49,6 +49,12 @@ pub trait Condition<'a>: Send + Sync {
     }
 }
 
+/// A [`Condition`] whose concrete type has been erased using [`Condition::boxed`]
+pub type BoxedCondition<'a> = Box<dyn Condition<'a> + 'a>;
+
+/// A [`Condition`] whose concrete type has been erased using [`Condition::arc`]
+pub type ArcCondition<'a> = Arc<dyn Condition<'a> + 'a>;
+
 impl<'a> Condition<'a> for Box<dyn Condition<'a> + '_> {
     fn build(&self, context: &mut QueryContext<'a>) {
         self.as_ref().build(context);
@@ -156,6 +162,48 @@ pub enum Value<'a> {
     BitVec(crate::fields::types::postgres_only::BitCow<'a>),
 }
 impl<'a> Value<'a> {
+    /// Convert into a value owning all of its data
+    pub fn into_static(self) -> Value<'static> {
+        match self {
+            Value::Null(v) => Value::Null(v),
+            Value::String(v) => Value::String(Cow::Owned(v.into_owned())),
+            Value::Choice(v) => Value::Choice(Cow::Owned(v.into_owned())),
+            Value::I64(v) => Value::I64(v),
+            Value::I32(v) => Value::I32(v),
+            Value::I16(v) => Value::I16(v),
+            Value::Bool(v) => Value::Bool(v),
+            Value::F64(v) => Value::F64(v),
+            Value::F32(v) => Value::F32(v),
+            Value::Binary(v) => Value::Binary(Cow::Owned(v.into_owned())),
+            #[cfg(feature = "chrono")]
+            Value::ChronoNaiveTime(v) => Value::ChronoNaiveTime(v),
+            #[cfg(feature = "chrono")]
+            Value::ChronoNaiveDate(v) => Value::ChronoNaiveDate(v),
+            #[cfg(feature = "chrono")]
+            Value::ChronoNaiveDateTime(v) => Value::ChronoNaiveDateTime(v),
+            #[cfg(feature = "chrono")]
+            Value::ChronoDateTime(v) => Value::ChronoDateTime(v),
+            #[cfg(feature = "time")]
+            Value::TimeDate(v) => Value::TimeDate(v),
+            #[cfg(feature = "time")]
+            Value::TimeTime(v) => Value::TimeTime(v),
+            #[cfg(feature = "time")]
+            Value::TimeOffsetDateTime(v) => Value::TimeOffsetDateTime(v),
+            #[cfg(feature = "time")]
+            Value::TimePrimitiveDateTime(v) => Value::TimePrimitiveDateTime(v),
+            #[cfg(feature = "uuid")]
+            Value::Uuid(v) => Value::Uuid(v),
+            #[cfg(feature = "postgres-only")]
+            Value::MacAddress(v) => Value::MacAddress(v),
+            #[cfg(feature = "postgres-only")]
+            Value::IpNetwork(v) => Value::IpNetwork(v),
+            #[cfg(feature = "postgres-only")]
+            Value::BitVec(v) => Value::BitVec(crate::fields::types::postgres_only::BitCow::Owned(
+                v.as_ref().clone(),
+            )),
+        }
+    }
+
     /// Convert into an [`sql::Value`](value::Value) instead of an [`sql::Condition`](conditional::Condition) directly.
     pub fn as_sql(&self) -> value::Value {
         match self {
@@ -329,3 +377,58 @@ impl<'a, A: Condition<'a>> Condition<'a> for Unary<A> {
         self.fst_arg.build(context);
     }
 }
+
+/// Escape the wildcards `%` and `_` as well as the escape character `\` in a `LIKE` pattern
+///
+/// This allows embedding user input into a pattern without it matching more than itself:
+/// ```no_run
+/// # use rorm::Model;
+/// # use rorm::conditions::escape_like;
+/// # #[derive(Model)] struct User { #[rorm(id)] id: i64, #[rorm(max_length = 255)] name: String, }
+/// # let input = "";
+/// let condition = User.name.like(format!("{}%", escape_like(input)));
+/// ```
+///
+/// Postgres and MySQL use `\` as escape character by default.
+/// SQLite doesn't have a default escape character, so escaped patterns won't match there.
+pub fn escape_like(pattern: &str) -> String {
+    let mut escaped = String::with_capacity(pattern.len());
+    for char in pattern.chars() {
+        if matches!(char, '\\' | '%' | '_') {
+            escaped.push('\\');
+        }
+        escaped.push(char);
+    }
+    escaped
+}
+
+/// A string passed to [`FieldAccess::like`] which is escaped using [`escape_like`] before being used as pattern
+///
+/// I.e. `field.like(Escaped(text))` checks `field` to equal `text` while
+/// still following the `LIKE` operator's semantics (for example case insensitivity on MySQL).
+#[derive(Copy, Clone, Debug)]
+pub struct Escaped<'a>(pub &'a str);
+
+#[cfg(test)]
+mod test {
+    use super::escape_like;
+
+    #[test]
+    fn escape_like_plain() {
+        assert_eq!(escape_like(""), "");
+        assert_eq!(escape_like("hello world"), "hello world");
+    }
+
+    #[test]
+    fn escape_like_wildcards() {
+        assert_eq!(escape_like("100%"), "100\\%");
+        assert_eq!(escape_like("snake_case"), "snake\\_case");
+        assert_eq!(escape_like("%_%"), "\\%\\_\\%");
+    }
+
+    #[test]
+    fn escape_like_escape_character() {
+        assert_eq!(escape_like("C:\\"), "C:\\\\");
+        assert_eq!(escape_like("\\%"), "\\\\\\%");
+    }
+}
@@ -0,0 +1,171 @@
+//! Hash columns maintained by `#[rorm(hash_of = "..")]`
+//!
+//! A model can store a hash of a large field's content (like a post's body)
+//! to find rows with the same content without comparing the content itself.
+//! Annotating a `Vec<u8>` field with the field to hash lets the crud builders fill it:
+//!
+//! ```no_run
+//! # use rorm::{query, Database, Error, Model};
+//! # use rorm::content_hash;
+//! #[derive(Model)]
+//! pub struct Post {
+//!     #[rorm(id)]
+//!     pub id: i64,
+//!
+//!     #[rorm(max_length = 10000)]
+//!     pub body: String,
+//!
+//!     #[rorm(hash_of = "body")]
+//!     pub body_hash: Vec<u8>,
+//! }
+//!
+//! pub async fn find_duplicates(db: &Database, body: &str) -> Result<Vec<Post>, Error> {
+//!     query(db, Post)
+//!         .condition(content_hash::matches(Post.body_hash, body))
+//!         .all()
+//!         .await
+//! }
+//! ```
+//!
+//! The hash is the SHA-256 of the field's bytes i.e. a string's utf-8 encoding.
+//! It is computed in rust whenever an insert or update sets the hashed field,
+//! overwriting any value set for the hash field itself.
+//! If the hashed field is `NULL`, its hash is `NULL` as well,
+//! so the hash field has to be an `Option<Vec<u8>>` for a nullable field.
+//!
+//! Updates which don't set the hashed field don't touch the hash.
+//! Raw sql is not affected, so its hashes have to be computed using [`hash`].
+
+use std::borrow::Cow;
+
+use rorm_db::sql::value::NullType;
+use sha2::{Digest, Sha256};
+
+use crate::conditions::{Binary, BinaryOperator, Column, Value};
+use crate::internal::field::access::FieldAccess;
+use crate::internal::field::{Field, FieldProxy};
+use crate::model::Model;
+
+/// A hash column maintained by a model
+///
+/// It is generated by `#[rorm(hash_of = "..")]`, see [`Model::CONTENT_HASHES`].
+#[derive(Copy, Clone, Debug)]
+pub struct ContentHash {
+    /// The column storing the hash
+    pub column: &'static str,
+
+    /// The column whose value is hashed
+    pub source: &'static str,
+}
+
+impl ContentHash {
+    /// Describe the column `hash` storing the hash of `source`
+    #[doc(hidden)]
+    pub const fn new<Hash, Source, P, Q>(
+        _hash: FieldProxy<Hash, P>,
+        _source: FieldProxy<Source, Q>,
+    ) -> Self
+    where
+        Hash: Field<Type: HashColumn>,
+        Source: Field<Type: HashSource, Model = Hash::Model>,
+    {
+        Self {
+            column: Hash::NAME,
+            source: Source::NAME,
+        }
+    }
+}
+
+/// A field type which can store a content hash
+///
+/// It is implemented for `Vec<u8>` and `Option<Vec<u8>>`.
+pub trait HashColumn {}
+impl HashColumn for Vec<u8> {}
+impl HashColumn for Option<Vec<u8>> {}
+
+/// A field type whose content can be hashed
+///
+/// It is implemented for `String` and `Vec<u8>` as well as their `Option`s.
+pub trait HashSource {}
+impl HashSource for String {}
+impl HashSource for Vec<u8> {}
+impl HashSource for Option<String> {}
+impl HashSource for Option<Vec<u8>> {}
+
+/// Compute the hash stored by `#[rorm(hash_of = "..")]` for some content
+pub fn hash(content: impl AsRef<[u8]>) -> Vec<u8> {
+    Sha256::digest(content.as_ref()).to_vec()
+}
+
+/// Condition checking a hash field to match the hash of some content
+///
+/// `field` has to be annotated with `#[rorm(hash_of = "..")]`.
+pub fn matches<'a, A: FieldAccess>(
+    field: A,
+    content: impl AsRef<[u8]>,
+) -> Binary<Column<A>, Value<'a>> {
+    Binary {
+        operator: BinaryOperator::Equals,
+        fst_arg: Column(field),
+        snd_arg: Value::Binary(Cow::Owned(hash(content))),
+    }
+}
+
+/// Compute the hash of a hashed column's value
+fn hash_value(value: &Value<'_>) -> Value<'static> {
+    match value {
+        Value::String(string) => Value::Binary(Cow::Owned(hash(string.as_bytes()))),
+        Value::Binary(bytes) => Value::Binary(Cow::Owned(hash(bytes))),
+        _ => Value::Null(NullType::Binary),
+    }
+}
+
+/// Set the hash columns of the rows to insert
+///
+/// `values` contains the rows' values for `columns` one row after another.
+pub(crate) fn fill_insert<M: Model>(columns: &mut Vec<&'static str>, values: &mut Vec<Value<'_>>) {
+    for content_hash in M::CONTENT_HASHES {
+        let row_len = columns.len();
+        let Some(source) = columns.iter().position(|name| *name == content_hash.source) else {
+            continue;
+        };
+        match columns.iter().position(|name| *name == content_hash.column) {
+            Some(position) => {
+                for row in values.chunks_mut(row_len) {
+                    row[position] = hash_value(&row[source]);
+                }
+            }
+            None => {
+                columns.push(content_hash.column);
+                let mut filled = Vec::with_capacity(values.len() + values.len() / row_len);
+                let mut rows = std::mem::take(values).into_iter();
+                while !rows.as_slice().is_empty() {
+                    filled.extend(rows.by_ref().take(row_len));
+                    let hash = hash_value(&filled[filled.len() - row_len + source]);
+                    filled.push(hash);
+                }
+                *values = filled;
+            }
+        }
+    }
+}
+
+/// Set the hash columns of an update's columns
+pub(crate) fn fill_update<M: Model>(columns: &mut Vec<(&'static str, Value<'_>)>) {
+    for content_hash in M::CONTENT_HASHES {
+        let Some(hash) = columns
+            .iter()
+            .find(|(name, _)| *name == content_hash.source)
+            .map(|(_, value)| hash_value(value))
+        else {
+            continue;
+        };
+        match columns
+            .iter_mut()
+            .find(|(name, _)| *name == content_hash.column)
+        {
+            Some((_, value)) => *value = hash,
+            None => columns.push((content_hash.column, hash)),
+        }
+    }
+}
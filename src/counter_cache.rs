@@ -0,0 +1,177 @@
+//! Counter columns maintained by `#[rorm(counter_cache = "..")]`
+//!
+//! A model can store the number of rows referencing it (like a thread storing its number of posts)
+//! instead of counting them in every query.
+//! Annotating the referencing [`ForeignModel`](crate::fields::types::ForeignModel) with the counter field
+//! lets the crud builders keep this number up to date:
+//!
+//! ```no_run
+//! # use rorm::{insert, Database, Error, Model, Patch};
+//! # use rorm::fields::types::ForeignModel;
+//! #[derive(Model)]
+//! pub struct Thread {
+//!     #[rorm(id)]
+//!     pub id: i64,
+//!
+//!     #[rorm(default = 0)]
+//!     pub posts: i64,
+//! }
+//!
+//! #[derive(Model)]
+//! pub struct Post {
+//!     #[rorm(id)]
+//!     pub id: i64,
+//!
+//!     #[rorm(counter_cache = "Thread::F.posts")]
+//!     pub thread: ForeignModel<Thread>,
+//! }
+//!
+//! #[derive(Patch)]
+//! #[rorm(model = "Post")]
+//! pub struct NewPost {
+//!     pub thread: ForeignModel<Thread>,
+//! }
+//!
+//! pub async fn reply(db: &Database, thread: i64) -> Result<(), Error> {
+//!     // Increments the thread's `posts`
+//!     insert(db, Post)
+//!         .return_nothing()
+//!         .single(&NewPost { thread: ForeignModel(thread) })
+//!         .await
+//! }
+//! ```
+//!
+//! Inserting a row increments the counter of the row it references
+//! and deleting it decrements the counter again.
+//! The counters are updated in the same transaction as the insert or delete.
+//!
+//! Updating a row's foreign key doesn't move it to another counter
+//! and raw sql is not affected.
+
+use rorm_db::database;
+use rorm_db::error::Error;
+use rorm_db::executor::{All, Executor, Nothing};
+
+use crate::conditions::Value;
+use crate::crud::decoder::Decoder;
+use crate::internal::field::foreign_model::ForeignModelTrait;
+use crate::internal::field::{Field, FieldProxy};
+use crate::internal::query_context::QueryContext;
+use crate::internal::sql::{placeholder, quote};
+use crate::model::{Model, Patch};
+use crate::transaction::ScopedTransaction;
+
+/// A counter column maintained by a model's foreign key
+///
+/// It is generated by `#[rorm(counter_cache = "..")]`, see [`Model::COUNTER_CACHES`].
+#[derive(Copy, Clone, Debug)]
+pub struct CounterCache {
+    /// The column of the foreign key
+    pub column: &'static str,
+
+    /// The table referenced by the foreign key
+    pub table: &'static str,
+
+    /// The column referenced by the foreign key
+    pub key: &'static str,
+
+    /// The referenced table's column counting the rows referencing it
+    pub counter: &'static str,
+}
+
+impl CounterCache {
+    /// Describe the counter `counter` maintained by the foreign key `fk`
+    #[doc(hidden)]
+    pub const fn new<Fk, Counter, P, Q>(
+        _fk: FieldProxy<Fk, P>,
+        _counter: FieldProxy<Counter, Q>,
+    ) -> Self
+    where
+        Fk: Field<Type: ForeignModelTrait>,
+        Counter: Field<Model = <<Fk::Type as ForeignModelTrait>::RelatedField as Field>::Model>,
+    {
+        Self {
+            column: Fk::NAME,
+            table: <Counter::Model as Model>::TABLE,
+            key: <<Fk::Type as ForeignModelTrait>::RelatedField as Field>::NAME,
+            counter: Counter::NAME,
+        }
+    }
+}
+
+/// Increment the counters of the rows referenced by inserted rows
+///
+/// `rows` contains the values for `columns` of every inserted row.
+pub(crate) async fn increment<M: Model>(
+    tx: &mut ScopedTransaction<'_>,
+    columns: &[&'static str],
+    rows: &[&[Value<'_>]],
+) -> Result<(), Error> {
+    for cache in M::COUNTER_CACHES {
+        let Some(index) = columns.iter().position(|column| *column == cache.column) else {
+            continue;
+        };
+        for row in rows {
+            add(tx.executor(), cache, &row[index], 1).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Decrement the counters of the rows referenced by the rows about to be deleted
+///
+/// `ctx` has to contain the condition referenced by `condition_index`.
+pub(crate) async fn decrement<M: Model>(
+    tx: &mut ScopedTransaction<'_>,
+    ctx: &mut QueryContext<'_>,
+    condition_index: Option<usize>,
+) -> Result<(), Error> {
+    ctx.load_all_deferred();
+    let decoder = M::select::<M>(ctx);
+    let condition = ctx.get_condition_opt(condition_index);
+    let rows = database::query::<All>(
+        tx.executor(),
+        M::TABLE,
+        &ctx.get_selects(),
+        &ctx.get_joins(),
+        condition.as_ref(),
+        &[],
+        None,
+    )
+    .await?;
+
+    let columns = M::columns();
+    for row in &rows {
+        let model = decoder.by_index(row)?;
+        let values = model.references();
+        for cache in M::COUNTER_CACHES {
+            if let Some(index) = columns.iter().position(|column| *column == cache.column) {
+                add(tx.executor(), cache, &values[index], -1).await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Add `amount` to the counter of the row whose referenced column equals `key`
+async fn add<'ex>(
+    executor: impl Executor<'ex>,
+    cache: &CounterCache,
+    key: &Value<'_>,
+    amount: i64,
+) -> Result<(), Error> {
+    // A nullable foreign key doesn't have to reference any row
+    if matches!(key, Value::Null(_)) {
+        return Ok(());
+    }
+
+    let dialect = executor.dialect();
+    let sql = format!(
+        "UPDATE {table} SET {counter} = {counter} {amount:+} WHERE {key} = {placeholder};",
+        table = quote(dialect, cache.table),
+        counter = quote(dialect, cache.counter),
+        key = quote(dialect, cache.key),
+        placeholder = placeholder(dialect, 1),
+    );
+    executor.execute::<Nothing>(sql, vec![key.as_sql()]).await
+}
@@ -1,6 +1,13 @@
 //! This module provides primitives used by the various builder.
 
+use std::fmt;
+use std::ops::{Add, AddAssign};
+
+use rorm_db::Error;
+
+use crate::conditions::collections::CollectionOperator;
 use crate::conditions::Condition;
+use crate::internal::query_context::flat_conditions::FlatCondition;
 use crate::internal::query_context::QueryContext;
 use crate::sealed;
 
@@ -28,3 +35,95 @@ impl<'a, T: Condition<'a>> ConditionMarker<'a> for T {
         Some(context.add_condition(self))
     }
 }
+
+/// Build the conjunction of two optional conditions
+///
+/// Returns `None` if both conditions are `None`.
+pub(crate) fn build_both<'a>(
+    context: &mut QueryContext<'a>,
+    first: &impl ConditionMarker<'a>,
+    second: &impl ConditionMarker<'a>,
+) -> Option<usize> {
+    let index = context.conditions.len();
+    context
+        .conditions
+        .push(FlatCondition::StartCollection(CollectionOperator::And));
+    let first = first.build(context);
+    let second = second.build(context);
+    if first.is_none() && second.is_none() {
+        context.conditions.pop();
+        return None;
+    }
+    context.conditions.push(FlatCondition::EndCollection);
+    Some(index)
+}
+
+/// The number of rows changed by an update or delete query
+///
+/// It is returned by the terminals of [`UpdateBuilder`](crate::crud::update::UpdateBuilder)
+/// and [`DeleteBuilder`](crate::crud::delete::DeleteBuilder).
+///
+/// Use [`expect_affected`](AffectedRows::expect_affected) to catch conditions which silently matched nothing:
+///
+/// ```no_run
+/// # use rorm::{delete, Database, Error, Model};
+/// # #[derive(Model)] pub struct User { #[rorm(id)] id: i64, }
+/// pub async fn delete_user(db: &Database, id: i64) -> Result<(), Error> {
+///     delete(db, User)
+///         .condition(User.id.equals(id))
+///         .await?
+///         .expect_affected(1)?;
+///     Ok(())
+/// }
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AffectedRows(pub u64);
+
+impl AffectedRows {
+    /// Return an [`Error::UnexpectedAffectedRows`] if the number of affected rows is not `expected`
+    ///
+    /// Note that the query has already been executed.
+    /// Run it inside a transaction to roll it back when the number differs.
+    pub fn expect_affected(self, expected: u64) -> Result<Self, Error> {
+        if self.0 == expected {
+            Ok(self)
+        } else {
+            Err(Error::UnexpectedAffectedRows {
+                expected,
+                actual: self.0,
+            })
+        }
+    }
+}
+
+impl From<AffectedRows> for u64 {
+    fn from(affected: AffectedRows) -> Self {
+        affected.0
+    }
+}
+
+impl PartialEq<u64> for AffectedRows {
+    fn eq(&self, other: &u64) -> bool {
+        self.0 == *other
+    }
+}
+
+impl Add for AffectedRows {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self(self.0 + rhs.0)
+    }
+}
+
+impl AddAssign for AffectedRows {
+    fn add_assign(&mut self, rhs: Self) {
+        self.0 += rhs.0;
+    }
+}
+
+impl fmt::Display for AffectedRows {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
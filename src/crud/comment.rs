@@ -0,0 +1,127 @@
+//! Attributing queries to the code which executed them
+//!
+//! Every builder has a `comment` method which prepends an sql comment to its query:
+//!
+//! ```no_run
+//! # use rorm::{query, Database, Error, Model, FieldAccess};
+//! # #[derive(Model)] pub struct Thread { #[rorm(id)] id: i64, #[rorm(max_length = 255)] title: String, }
+//! pub async fn get_thread(db: &Database, id: i64) -> Result<Thread, Error> {
+//!     // Executes `/*handler='thread%3A%3Aget'*/ SELECT ...`
+//!     query(db, Thread)
+//!         .comment("handler=thread::get")
+//!         .condition(Thread.id.equals(id))
+//!         .one()
+//!         .await
+//! }
+//! ```
+//!
+//! The comment consists of `key=value` pairs separated by `,`.
+//! They are formatted according to [sqlcommenter](https://google.github.io/sqlcommenter/spec/)
+//! so tools like `pg_stat_statements` or log aggregators can attribute slow queries back to the application.
+//!
+//! Queries which the builder executes inside an implicitly started transaction
+//! (for example to write [audit](crate::audit) entries) are commented as well.
+//!
+//! # Trace context
+//! With the `tracing` feature enabled, the comment also contains the current span's
+//! [trace context](https://www.w3.org/TR/trace-context/#traceparent-header) as `traceparent`
+//! correlating the database's logs with distributed traces.
+//! The trace and span id are taken from the [tracing-opentelemetry](https://docs.rs/tracing-opentelemetry) layer,
+//! so spans without an OpenTelemetry context don't add a `traceparent`.
+
+use crate::transaction::Scoped;
+
+/// Prepend the comment rendered from `tags` to every query of `executor`
+///
+/// It is used by the builders' `comment` methods.
+/// See the [module docs](self) for the format of `tags`.
+pub(crate) fn comment<E>(executor: Scoped<E>, tags: &str) -> Scoped<E> {
+    let tags = parse_tags(tags);
+    executor.rewrite(move |query| {
+        let mut tags = tags.clone();
+        #[cfg(feature = "tracing")]
+        tags.extend(traceparent());
+        tags.sort();
+        format!("/*{}*/ {query}", tags.join(","))
+    })
+}
+
+/// Format the `key=value` pairs separated by `,` into `key='value'` pairs
+fn parse_tags(tags: &str) -> Vec<String> {
+    tags.split(',')
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+        .map(|tag| {
+            let (key, value) = tag.split_once('=').unwrap_or((tag, ""));
+            format_tag(key.trim(), value.trim())
+        })
+        .collect()
+}
+
+/// Get the `traceparent` tag of the current span
+#[cfg(feature = "tracing")]
+fn traceparent() -> Option<String> {
+    use opentelemetry::trace::TraceContextExt;
+    use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+    let context = tracing::Span::current().context();
+    let span = context.span();
+    let span_context = span.span_context();
+    span_context.is_valid().then(|| {
+        format_tag(
+            "traceparent",
+            &format!(
+                "00-{}-{}-{:02x}",
+                span_context.trace_id(),
+                span_context.span_id(),
+                span_context.trace_flags().to_u8()
+            ),
+        )
+    })
+}
+
+/// Format a single `key='value'` pair
+///
+/// Keys and values are url encoded, so the comment can't be terminated early.
+fn format_tag(key: &str, value: &str) -> String {
+    format!("{}='{}'", url_encode(key), url_encode(value))
+}
+
+/// Percent encode everything except unreserved characters
+fn url_encode(string: &str) -> String {
+    let mut encoded = String::with_capacity(string.len());
+    for byte in string.bytes() {
+        match byte {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod test {
+    use super::{format_tag, parse_tags, url_encode};
+
+    #[test]
+    fn encode() {
+        assert_eq!(url_encode("thread::get"), "thread%3A%3Aget");
+        assert_eq!(url_encode("a-b_c.d~e"), "a-b_c.d~e");
+        assert_eq!(url_encode("*/ DROP"), "%2A%2F%20DROP");
+    }
+
+    #[test]
+    fn tag() {
+        assert_eq!(format_tag("route", "/users"), "route='%2Fusers'");
+    }
+
+    #[test]
+    fn tags() {
+        assert_eq!(
+            parse_tags(" handler = thread::get ,, flag"),
+            ["handler='thread%3A%3Aget'", "flag=''"]
+        );
+    }
+}
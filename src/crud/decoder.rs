@@ -29,6 +29,18 @@ pub trait Decoder {
     fn by_index<'index>(&'index self, row: &'_ Row) -> Result<Self::Result, RowError<'index>>;
 }
 
+impl<D: Decoder + ?Sized> Decoder for Box<D> {
+    type Result = D::Result;
+
+    fn by_name<'index>(&'index self, row: &'_ Row) -> Result<Self::Result, RowError<'index>> {
+        self.as_ref().by_name(row)
+    }
+
+    fn by_index<'index>(&'index self, row: &'_ Row) -> Result<Self::Result, RowError<'index>> {
+        self.as_ref().by_index(row)
+    }
+}
+
 /// A [`Decoder`] which directly decodes a [`T: DecodedOwned`](DecodeOwned)
 pub struct DirectDecoder<T> {
     pub(crate) result: PhantomData<T>,
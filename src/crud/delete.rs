@@ -3,14 +3,40 @@
 use std::marker::PhantomData;
 
 use rorm_db::database;
+#[cfg(feature = "registry")]
+use rorm_db::database::ColumnSelector;
 use rorm_db::error::Error;
 use rorm_db::executor::Executor;
+#[cfg(feature = "registry")]
+use rorm_db::executor::{All, QueryStrategy};
+#[cfg(feature = "registry")]
+use rorm_db::sql::value::Value;
+#[cfg(feature = "registry")]
+use rorm_db::sql::DBImpl;
+#[cfg(feature = "registry")]
+use rorm_declaration::imr;
 
-use crate::conditions::{Condition, DynamicCollection};
+use crate::audit;
+use crate::conditions::{Column, Condition, DynamicCollection, In, InOperator};
+use crate::counter_cache;
+use crate::crud::builder::AffectedRows;
+use crate::crud::comment;
+use crate::crud::query::{self, KEYS_PER_QUERY};
 use crate::crud::selector::Selector;
+use crate::events::{self, ModelChange};
+use crate::internal::field::{Field, FieldProxy, SingleColumnField};
 use crate::internal::patch::{IntoPatchCow, PatchCow};
 use crate::internal::query_context::QueryContext;
-use crate::model::{Identifiable, Model};
+#[cfg(feature = "registry")]
+use crate::internal::sql::quote;
+use crate::model::{Identifiable, Model, ModelHooks};
+#[cfg(feature = "registry")]
+use crate::registry::{self, Relation};
+use crate::routing::Route;
+use crate::tenant;
+use crate::transaction::Scoped;
+#[cfg(feature = "registry")]
+use crate::transaction::ScopedTransaction;
 use crate::Patch;
 
 /// Create a DELETE query.
@@ -18,6 +44,7 @@ use crate::Patch;
 /// # Usage
 /// ```no_run
 /// # use rorm::{Model, Patch, Database, delete, FieldAccess};
+/// # use rorm::crud::builder::AffectedRows;
 /// # #[derive(Model)] pub struct User { #[rorm(id)] id: i64, age: i32, }
 /// # #[derive(Patch)] #[rorm(model = "User")] pub struct UserPatch { id: i64, }
 /// pub async fn delete_single_user(db: &Database, user: &UserPatch) {
@@ -33,7 +60,7 @@ use crate::Patch;
 ///         .unwrap();
 /// }
 /// pub async fn delete_underage(db: &Database) {
-///     let num_deleted: u64 = delete(db, User)
+///     let num_deleted: AffectedRows = delete(db, User)
 ///         .condition(User.age.less_equals(18))
 ///         .await
 ///         .unwrap();
@@ -50,14 +77,19 @@ use crate::Patch;
 /// - [`single`](DeleteBuilder::single): Delete a single row identified by a patch instance
 /// - [`bulk`](DeleteBuilder::bulk): Delete a bulk of rows identified by patch instances
 /// - [`condition`](DeleteBuilder::condition): Delete all rows matching a condition
+/// - [`by_primary_keys`](DeleteBuilder::by_primary_keys): Delete the rows identified by a list of primary keys
 /// - [`all`](DeleteBuilder::all): Unconditionally delete all rows
-pub fn delete<'ex, E, S>(executor: E, _: S) -> DeleteBuilder<E, S::Model>
+///
+/// With the `registry` feature, call [`cascade`](DeleteBuilder::cascade) before
+/// to delete the rows referencing the deleted ones as well.
+pub fn delete<'ex, R, S>(executor: R, _: S) -> DeleteBuilder<R::Executor, S::Model>
 where
-    E: Executor<'ex>,
+    R: Route<'ex, S::Model>,
     S: Selector<Model: Patch<ValueSpaceImpl = S>>,
 {
     DeleteBuilder {
-        executor,
+        executor: executor.route(),
+        cascade: false,
 
         _phantom: PhantomData,
     }
@@ -78,7 +110,8 @@ where
 ///
 #[must_use]
 pub struct DeleteBuilder<E, M> {
-    executor: E,
+    executor: Scoped<E>,
+    cascade: bool,
 
     _phantom: PhantomData<M>,
 }
@@ -100,10 +133,53 @@ where
     E: Executor<'ex>,
     M: Model,
 {
+    /// Prepend a comment to the query attributing it to the code executing it
+    ///
+    /// See [`comment`](crate::crud::comment) for the format of `tags`.
+    pub fn comment(self, tags: &str) -> Self {
+        Self {
+            executor: comment::comment(self.executor, tags),
+            ..self
+        }
+    }
+
+    /// Delete the rows referencing the deleted ones before deleting them
+    ///
+    /// The referencing rows are found using the foreign keys of the [registered](crate::registry) models
+    /// and deleted recursively inside a single transaction.
+    /// This emulates `ON DELETE CASCADE` for databases which don't enforce foreign keys,
+    /// like SQLite without `PRAGMA foreign_keys`, or whose foreign keys weren't declared with it.
+    ///
+    /// Foreign keys declared with `on_delete = "SetNull"` are set to `NULL` instead
+    /// and ones declared with `on_delete = "SetDefault"` are left to the database.
+    /// Relations forming a cycle, like a model referencing itself, are not followed.
+    ///
+    /// Only the query's model runs its hooks, audit and counter caches.
+    /// The referencing rows are deleted using plain sql.
+    ///
+    /// ```no_run
+    /// # use rorm::{delete, Database, Error, Model};
+    /// # use rorm::crud::builder::AffectedRows;
+    /// # use rorm::fields::types::ForeignModel;
+    /// # #[derive(Model)] pub struct User { #[rorm(id)] id: i64, }
+    /// # #[derive(Model)] pub struct Post { #[rorm(id)] id: i64, author: ForeignModel<User>, }
+    /// pub async fn delete_user(db: &Database, id: i64) -> Result<AffectedRows, Error> {
+    ///     // Deletes the user's posts as well
+    ///     delete(db, User).cascade().condition(User.id.equals(id)).await
+    /// }
+    /// ```
+    #[cfg(feature = "registry")]
+    pub fn cascade(self) -> Self {
+        Self {
+            cascade: true,
+            ..self
+        }
+    }
+
     /// Delete a single row identified by a patch instance
     ///
     /// Note: The patch only provides the primary key, its other values will be ignored.
-    pub async fn single<P>(self, patch: &P) -> Result<u64, Error>
+    pub async fn single<P>(self, patch: &P) -> Result<AffectedRows, Error>
     where
         P: Patch<Model = M> + Identifiable,
     {
@@ -122,7 +198,7 @@ where
     /// - `Vec<P>`
     /// - `&[P]`
     /// - A [`map`](Iterator::map) iterator yielding `P` or `&P`
-    pub async fn bulk<'p, I, P>(self, patches: I) -> Result<u64, Error>
+    pub async fn bulk<'p, I, P>(self, patches: I) -> Result<AffectedRows, Error>
     where
         I: IntoIterator,
         I::Item: IntoPatchCow<'p, Patch = P>,
@@ -140,27 +216,261 @@ where
             conditions.push(patch.as_condition());
         }
         if conditions.is_empty() {
-            Ok(0)
+            Ok(AffectedRows(0))
         } else {
             self.condition(DynamicCollection::or(conditions)).await
         }
     }
 
+    /// Delete all rows whose primary key is in `keys`
+    ///
+    /// Long lists of keys are split into multiple queries which are executed in a single transaction,
+    /// like in [`QueryBuilder::by_primary_keys`](crate::crud::query::QueryBuilder::by_primary_keys).
+    /// Each of these queries runs the model's hooks on its own.
+    ///
+    /// Returns the total number of deleted rows.
+    ///
+    /// ```no_run
+    /// # use rorm::{delete, Database, Error, Model};
+    /// # use rorm::crud::builder::AffectedRows;
+    /// # #[derive(Model)] pub struct User { #[rorm(id)] id: i64, }
+    /// pub async fn delete_users(db: &Database, ids: &[i64]) -> Result<AffectedRows, Error> {
+    ///     delete(db, User).by_primary_keys(ids).await
+    /// }
+    /// ```
+    pub async fn by_primary_keys<'k>(
+        self,
+        keys: impl IntoIterator<Item = &'k <M::Primary as Field>::Type>,
+    ) -> Result<AffectedRows, Error> {
+        let keys: Vec<_> = keys.into_iter().map(M::Primary::type_as_value).collect();
+        if keys.is_empty() {
+            return Ok(AffectedRows(0));
+        }
+
+        let mut tx = self.executor.begin().await?;
+        let mut deleted = AffectedRows(0);
+        for chunk in keys.chunks(KEYS_PER_QUERY) {
+            let builder = DeleteBuilder::<_, M> {
+                executor: tx.executor(),
+                cascade: self.cascade,
+
+                _phantom: PhantomData,
+            };
+            deleted += builder
+                .condition(In {
+                    operator: InOperator::In,
+                    fst_arg: Column(FieldProxy::<M::Primary, M>::new()),
+                    snd_arg: chunk.to_vec(),
+                })
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(deleted)
+    }
+
     /// Delete all rows matching a condition
-    pub async fn condition<'c, C: Condition<'c>>(self, condition: C) -> Result<u64, Error> {
+    pub async fn condition<'c, C: Condition<'c>>(
+        self,
+        condition: C,
+    ) -> Result<AffectedRows, Error> {
         let mut context = QueryContext::new();
-        let condition_index = context.add_condition(&condition);
-        database::delete(
-            self.executor,
+        let condition_index = tenant::build_condition::<M>(&mut context, |context| {
+            Some(context.add_condition(&condition))
+        })?;
+        self.execute(context, condition_index).await
+    }
+
+    /// Delete all rows
+    pub async fn all(self) -> Result<AffectedRows, Error> {
+        let mut context = QueryContext::new();
+        let condition_index = tenant::build_condition::<M>(&mut context, |_| None)?;
+        self.execute(context, condition_index).await
+    }
+
+    async fn execute(
+        self,
+        mut context: QueryContext<'_>,
+        condition_index: Option<usize>,
+    ) -> Result<AffectedRows, Error> {
+        context.check_joins()?;
+        let select_keys = M::Hooks::ENABLED || events::has_subscribers::<M>();
+        let deleted = if M::COUNTER_CACHES.is_empty() && !self.cascade && !select_keys {
+            delete_rows::<M>(self.executor, &mut context, condition_index).await?
+        } else {
+            let mut tx = self.executor.begin().await?;
+            let keys = if select_keys {
+                query::primary_keys::<M>(tx.executor(), &mut context, condition_index).await?
+            } else {
+                Vec::new()
+            };
+            M::Hooks::before_delete(tx.transaction(), &keys).await?;
+            if self.cascade {
+                #[cfg(feature = "registry")]
+                delete_dependents::<M>(&mut tx, &context, condition_index).await?;
+            }
+            counter_cache::decrement::<M>(&mut tx, &mut context, condition_index).await?;
+            let deleted = delete_rows::<M>(tx.executor(), &mut context, condition_index).await?;
+            M::Hooks::after_delete(tx.transaction(), &keys).await?;
+            events::publish::<M>(&tx, || ModelChange::Deleted { keys });
+            tx.commit().await?;
+            deleted
+        };
+        Ok(AffectedRows(deleted))
+    }
+}
+
+/// Delete the rows matching the condition referenced by `condition_index`
+async fn delete_rows<'ex, M: Model>(
+    executor: Scoped<impl Executor<'ex>>,
+    context: &mut QueryContext<'_>,
+    condition_index: Option<usize>,
+) -> Result<u64, Error> {
+    if M::AUDIT {
+        audit::delete::<M>(executor, context, condition_index).await
+    } else {
+        let condition = context.get_condition_opt(condition_index);
+        database::delete(executor, M::TABLE, condition.as_ref()).await
+    }
+}
+
+/// Delete the rows referencing the rows matching the condition referenced by `condition_index`
+///
+/// See [`DeleteBuilder::cascade`].
+#[cfg(feature = "registry")]
+async fn delete_dependents<M: Model>(
+    tx: &mut ScopedTransaction<'_>,
+    context: &QueryContext<'_>,
+    condition_index: Option<usize>,
+) -> Result<(), Error> {
+    let mut paths = Vec::new();
+    collect_dependents(&mut vec![M::TABLE.to_string()], &mut Vec::new(), &mut paths);
+
+    let dialect = tx.executor().dialect();
+    let joins = context.get_joins();
+    let condition = context.get_condition_opt(condition_index);
+    for path in paths {
+        let (last, parents) = path.split_last().expect("Paths are never empty");
+        let mut prefix = if matches!(last.on_delete, imr::ReferentialAction::SetNull) {
+            format!(
+                "UPDATE {table} SET {column} = NULL WHERE {column} IN (",
+                table = quote(dialect, &last.table),
+                column = quote(dialect, &last.column),
+            )
+        } else {
+            format!(
+                "DELETE FROM {} WHERE {} IN (",
+                quote(dialect, &last.table),
+                quote(dialect, &last.column),
+            )
+        };
+        for (relation, child) in parents.iter().zip(&path[1..]).rev() {
+            prefix += &format!(
+                "SELECT {} FROM {} WHERE {} IN (",
+                quote(dialect, &child.foreign_column),
+                quote(dialect, &relation.table),
+                quote(dialect, &relation.column),
+            );
+        }
+
+        // rorm-db renders the innermost query selecting the referenced column of the matching rows
+        let select = ColumnSelector {
+            table_name: Some(M::TABLE),
+            column_name: &path[0].foreign_column,
+            select_alias: None,
+            aggregation: None,
+        };
+        let executor = Subquery {
+            executor: tx.executor(),
+            prefix,
+            suffix: ")".repeat(path.len()),
+        };
+        database::query::<All>(
+            executor,
             M::TABLE,
-            Some(&context.get_condition(condition_index)),
+            &[select],
+            &joins,
+            condition.as_ref(),
+            &[],
+            None,
         )
-        .await
+        .await?;
     }
+    Ok(())
+}
 
-    /// Delete all rows
-    pub async fn all(self) -> Result<u64, Error> {
-        database::delete(self.executor, M::TABLE, None).await
+/// Recursively collect the paths of relations referencing the last table in `tables`
+///
+/// The paths are collected in the order their rows have to be deleted in, i.e. children first.
+#[cfg(feature = "registry")]
+fn collect_dependents(
+    tables: &mut Vec<String>,
+    path: &mut Vec<Relation>,
+    paths: &mut Vec<Vec<Relation>>,
+) {
+    let table = tables.last().expect("There is always the query's table");
+    for relation in registry::referencing(table) {
+        if tables.contains(&relation.table) {
+            continue;
+        }
+        match relation.on_delete {
+            imr::ReferentialAction::SetDefault => continue,
+            imr::ReferentialAction::SetNull => {
+                path.push(relation);
+            }
+            _ => {
+                tables.push(relation.table.clone());
+                path.push(relation);
+                collect_dependents(tables, path, paths);
+                tables.pop();
+            }
+        }
+        paths.push(path.clone());
+        path.pop();
+    }
+}
+
+/// An [`Executor`] embedding the query it executes as subquery
+///
+/// It is used by [`delete_dependents`] to reuse rorm-db's sql generation for the selected rows.
+#[cfg(feature = "registry")]
+struct Subquery<E> {
+    executor: E,
+
+    /// Sql preceding the subquery
+    prefix: String,
+
+    /// Sql following the subquery
+    suffix: String,
+}
+
+#[cfg(feature = "registry")]
+impl<'executor, E> Executor<'executor> for Subquery<E>
+where
+    E: Executor<'executor>,
+{
+    fn execute<'data, 'result, Q>(
+        self,
+        query: String,
+        values: Vec<Value<'data>>,
+    ) -> Q::Result<'result>
+    where
+        'executor: 'result,
+        'data: 'result,
+        Q: QueryStrategy,
+    {
+        let query = query.trim_end().trim_end_matches(';');
+        self.executor
+            .execute::<Q>(format!("{}{query}{};", self.prefix, self.suffix), values)
+    }
+
+    fn dialect(&self) -> DBImpl {
+        self.executor.dialect()
+    }
+
+    type EnsureTransactionFuture = E::EnsureTransactionFuture;
+
+    fn ensure_transaction(self) -> Self::EnsureTransactionFuture {
+        self.executor.ensure_transaction()
     }
 }
 
@@ -169,6 +479,9 @@ where
 #[macro_export]
 macro_rules! delete {
     ($db:expr, $model:path) => {
-        $crate::crud::delete::DeleteBuilder::<_, <$model as $crate::model::Patch>::Model>::new($db)
+        $crate::crud::delete::delete(
+            $db,
+            <<<$model as $crate::model::Patch>::Model as $crate::model::Patch>::ValueSpaceImpl as ::std::default::Default>::default(),
+        )
     };
 }
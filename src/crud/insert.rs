@@ -1,18 +1,36 @@
 //! Insert builder and macro
 
+use std::io;
 use std::marker::PhantomData;
 
 use rorm_db::database;
 use rorm_db::error::Error;
 use rorm_db::executor::Executor;
+#[cfg(feature = "all-drivers")]
+use rorm_db::executor::{All, One};
+use rorm_db::row::Row;
+use rorm_db::sql::value::Value as SqlValue;
+#[cfg(feature = "all-drivers")]
+use rorm_db::sql::DBImpl;
+use serde::de::DeserializeOwned;
 
+use crate::audit;
 use crate::conditions::Value;
+use crate::content_hash;
+use crate::counter_cache;
+use crate::crud::comment;
 use crate::crud::decoder::Decoder;
+use crate::crud::query::KEYS_PER_QUERY;
 use crate::crud::selector::Selector;
-use crate::internal::field::FieldProxy;
+use crate::events::{self, ModelChange};
+use crate::fields::traits::FieldType;
+use crate::internal::field::{Field, FieldProxy, SingleColumnField};
 use crate::internal::patch::{IntoPatchCow, PatchCow};
 use crate::internal::query_context::QueryContext;
-use crate::model::{Model, Patch};
+use crate::model::{Model, ModelHooks, Patch};
+use crate::routing::Route;
+use crate::tenant;
+use crate::transaction::Scoped;
 
 /// Create an INSERT query.
 ///
@@ -58,6 +76,13 @@ use crate::model::{Model, Patch};
 ///         .single(user)
 ///         .await?;
 ///
+///     // The return value can be changed at any point before executing the query
+///     let _: User = insert(db, User)
+///         .return_primary_key()
+///         .return_model()
+///         .single(user)
+///         .await?;
+///
 ///     // Return any patch instance (including the one used to insert and the model itself)
 ///     let _: UserPatch = insert(db, User)
 ///         .return_patch::<UserPatch>() // turbo fish not necessarily required but more readable
@@ -76,7 +101,7 @@ use crate::model::{Model, Patch};
 ///         .single(user)
 ///         .await?;
 ///
-///     // Return nothing
+///     // Return nothing, which executes a plain INSERT without any roundtrip to fetch the new row
 ///     let _: () = insert(db, User)
 ///         .return_nothing()
 ///         .single(user)
@@ -85,13 +110,13 @@ use crate::model::{Model, Patch};
 ///     Ok(())
 /// }
 ///```
-pub fn insert<'ex, E, S>(executor: E, selector: S) -> InsertBuilder<E, S::Model, S>
+pub fn insert<'ex, R, S>(executor: R, selector: S) -> InsertBuilder<R::Executor, S::Model, S>
 where
-    E: Executor<'ex>,
+    R: Route<'ex, S::Model>,
     S: Selector<Model: Patch<ValueSpaceImpl = S>>,
 {
     InsertBuilder {
-        executor,
+        executor: executor.route(),
         selector,
         model: PhantomData,
     }
@@ -116,7 +141,7 @@ where
 ///
 #[must_use]
 pub struct InsertBuilder<E, M, S> {
-    executor: E,
+    executor: Scoped<E>,
     selector: S,
     model: PhantomData<M>,
 }
@@ -131,10 +156,16 @@ where
     pub fn new(executor: E) -> Self {
         insert(executor, M::ValueSpaceImpl::default())
     }
+}
 
-    fn set_return<S>(self, selector: S) -> InsertBuilder<E, M, S>
+impl<'ex, E, M, S> InsertBuilder<E, M, S>
+where
+    E: Executor<'ex>,
+    M: Model,
+{
+    fn set_return<Return>(self, selector: Return) -> InsertBuilder<E, M, Return>
     where
-        S: Selector<Model = M>,
+        Return: Selector<Model = M>,
     {
         InsertBuilder {
             executor: self.executor,
@@ -143,7 +174,106 @@ where
         }
     }
 
+    /// Prepend a comment to the query attributing it to the code executing it
+    ///
+    /// See [`comment`](crate::crud::comment) for the format of `tags`.
+    pub fn comment(self, tags: &str) -> Self {
+        Self {
+            executor: comment::comment(self.executor, tags),
+            ..self
+        }
+    }
+
+    /// Insert the patches read from `reader` as json lines
+    ///
+    /// Every non-empty line has to contain the json representation of a single patch `P`,
+    /// which chooses the imported columns.
+    /// The patches are inserted in batches in a single transaction,
+    /// so either all or none of them are inserted.
+    /// `reader` is read from synchronously, so it should be buffered and not block for long.
+    ///
+    /// Returns the number of inserted rows.
+    ///
+    /// ```no_run
+    /// # use std::fs::File;
+    /// # use std::io::BufReader;
+    /// # use rorm::{insert, Database, Model, Patch};
+    /// # use serde::Deserialize;
+    /// # #[derive(Model)] pub struct User { #[rorm(id)] id: i64, #[rorm(max_length = 255)] name: String, }
+    /// #[derive(Patch, Deserialize)]
+    /// #[rorm(model = "User")]
+    /// pub struct NewUser {
+    ///     name: String,
+    /// }
+    ///
+    /// pub async fn load_users(db: &Database) -> Result<u64, Box<dyn std::error::Error>> {
+    ///     let file = BufReader::new(File::open("users.jsonl")?);
+    ///     Ok(insert(db, User).import_jsonl::<NewUser>(file).await?)
+    /// }
+    /// ```
+    pub async fn import_jsonl<P>(self, reader: impl io::BufRead) -> Result<u64, Error>
+    where
+        P: Patch<Model = M> + DeserializeOwned,
+    {
+        let patches = reader
+            .lines()
+            .filter(|line| !matches!(line, Ok(line) if line.trim().is_empty()))
+            .map(|line| serde_json::from_str(&line.map_err(import_error)?).map_err(import_error));
+        self.import::<P>(patches).await
+    }
+
+    /// Insert the patches read from `reader` as csv
+    ///
+    /// The first row has to be a header containing the names of `P`'s fields.
+    ///
+    /// See [`import_jsonl`](InsertBuilder::import_jsonl) for details.
+    #[cfg(feature = "csv")]
+    pub async fn import_csv<P>(self, reader: impl io::Read) -> Result<u64, Error>
+    where
+        P: Patch<Model = M> + DeserializeOwned,
+    {
+        let mut reader = csv::Reader::from_reader(reader);
+        let patches = reader
+            .deserialize()
+            .map(|patch| patch.map_err(import_error));
+        self.import::<P>(patches).await
+    }
+
+    /// Insert patches in batches in a single transaction
+    async fn import<P>(self, patches: impl Iterator<Item = Result<P, Error>>) -> Result<u64, Error>
+    where
+        P: Patch<Model = M>,
+    {
+        let rows_per_query = (KEYS_PER_QUERY / P::columns().len()).max(1);
+
+        let mut tx = self.executor.begin().await?;
+        let mut batch = Vec::with_capacity(rows_per_query);
+        let mut count = 0;
+        for patch in patches {
+            batch.push(patch?);
+            if batch.len() == rows_per_query {
+                count += batch.len() as u64;
+                insert(&mut tx, M::ValueSpaceImpl::default())
+                    .return_nothing()
+                    .bulk(std::mem::take(&mut batch))
+                    .await?;
+            }
+        }
+        if !batch.is_empty() {
+            count += batch.len() as u64;
+            insert(&mut tx, M::ValueSpaceImpl::default())
+                .return_nothing()
+                .bulk(batch)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(count)
+    }
+
     /// Remove the return value from the insert query reducing query time.
+    ///
+    /// The insert is executed as a plain `INSERT` without `RETURNING` or any following query.
+    /// Only audited models still have to select the inserted rows to record them.
     pub fn return_nothing(self) -> InsertReturningNothing<E, M> {
         InsertReturningNothing {
             executor: self.executor,
@@ -151,6 +281,13 @@ where
         }
     }
 
+    /// Return the whole model after performing the insert
+    ///
+    /// This is the default.
+    pub fn return_model(self) -> InsertBuilder<E, M, M::ValueSpaceImpl> {
+        self.set_return(Default::default())
+    }
+
     /// Return the tables primary key after performing the insert
     pub fn return_primary_key(self) -> InsertBuilder<E, M, FieldProxy<M::Primary, M>> {
         self.set_return(FieldProxy::new())
@@ -188,28 +325,73 @@ where
 
     /// Insert a single patch into the db
     pub async fn single<P: Patch<Model = M>>(self, patch: &P) -> Result<S::Result, Error> {
+        patch.validate()?;
+        self.single_values(P::columns(), patch.references()).await
+    }
+
+    /// Insert a single row consisting of `values` for `columns` into the db
+    ///
+    /// The values have to be validated by the caller.
+    pub(crate) async fn single_values(
+        self,
+        mut columns: Vec<&'static str>,
+        mut values: Vec<Value<'_>>,
+    ) -> Result<S::Result, Error> {
         // it is intentional to force the compile to evaluate the CHECK expression
         #[allow(clippy::let_unit_value)]
         let _check = Self::CHECK;
 
-        let columns = P::columns();
-        let values = patch.references();
-        let values: Vec<_> = values.iter().map(Value::as_sql).collect();
+        fill_generated_key::<M>(&mut columns, &mut values);
+        tenant::fill_insert::<M>(&mut columns, &mut values)?;
+        content_hash::fill_insert::<M>(&mut columns, &mut values);
+
+        let sql_values: Vec<_> = values.iter().map(Value::as_sql).collect();
 
         let mut ctx = QueryContext::new();
+        // The inserted values are known anyway
+        ctx.load_all_deferred();
         let decoder = self.selector.select(&mut ctx);
+        let audit_decoder = M::AUDIT.then(|| M::select::<M>(&mut ctx));
+        let key_decoder = returned_key_decoder::<M>(&columns, &mut ctx);
         let returning = ctx
             .get_returning()
             .expect("Should have been checked in set_select");
 
-        let row = database::insert_returning(
-            self.executor,
-            P::Model::TABLE,
-            &columns,
-            &values,
-            &returning,
-        )
-        .await?;
+        let audit_decoder = audit_decoder.as_ref();
+        let row = if !needs_transaction::<M>() {
+            insert_returning::<M>(
+                self.executor,
+                &columns,
+                &sql_values,
+                &returning,
+                audit_decoder,
+            )
+            .await?
+        } else {
+            let mut tx = self.executor.begin().await?;
+            M::Hooks::before_insert(tx.transaction(), &columns, &[&values]).await?;
+            let row = insert_returning::<M>(
+                tx.executor(),
+                &columns,
+                &sql_values,
+                &returning,
+                audit_decoder,
+            )
+            .await?;
+            counter_cache::increment::<M>(&mut tx, &columns, &[&values]).await?;
+            if M::Hooks::ENABLED {
+                let keys = inserted_keys::<M>(
+                    &columns,
+                    &[&values],
+                    key_decoder.as_ref(),
+                    std::slice::from_ref(&row),
+                )?;
+                M::Hooks::after_insert(tx.transaction(), &columns, &[&values], &keys).await?;
+            }
+            events::publish::<M>(&tx, || ModelChange::created(&columns, &[&values]));
+            tx.commit().await?;
+            row
+        };
         Ok(decoder.by_index(&row)?)
     }
 
@@ -223,6 +405,15 @@ where
     /// - `Vec<P>`
     /// - `&[P]`
     /// - A [`map`](Iterator::map) iterator yielding `P` or `&P`
+    ///
+    /// # Return value
+    /// The returned values are in the same order as the patches.
+    /// Use [`return_primary_key`](InsertBuilder::return_primary_key) to get the generated primary keys.
+    ///
+    /// MySQL doesn't support `RETURNING`, so the rows are re-selected after inserting them.
+    /// If the patches don't contain the primary key, the generated keys are computed from `LAST_INSERT_ID()`.
+    /// This requires MySQL's default `auto_increment_increment` of `1`
+    /// and an `innodb_autoinc_lock_mode` which allocates consecutive keys to a single statement.
     pub async fn bulk<'p, I, P>(self, patches: I) -> Result<Vec<S::Result>, Error>
     where
         I: IntoIterator,
@@ -236,39 +427,191 @@ where
         let mut values: Vec<Value<'p>> = Vec::new();
         for patch in patches {
             match patch.into_patch_cow() {
-                PatchCow::Borrowed(patch) => patch.push_references(&mut values),
-                PatchCow::Owned(patch) => patch.push_values(&mut values),
+                PatchCow::Borrowed(patch) => {
+                    patch.validate()?;
+                    patch.push_references(&mut values);
+                }
+                PatchCow::Owned(patch) => {
+                    patch.validate()?;
+                    patch.push_values(&mut values);
+                }
             }
         }
 
-        let columns = P::columns();
-        let values: Vec<_> = values.iter().map(Value::as_sql).collect();
-        let values_slices: Vec<_> = values.chunks(columns.len()).collect();
+        let mut columns = P::columns();
+        fill_generated_key::<M>(&mut columns, &mut values);
+        tenant::fill_insert::<M>(&mut columns, &mut values)?;
+        content_hash::fill_insert::<M>(&mut columns, &mut values);
+        let rows: Vec<_> = values.chunks(columns.len()).collect();
+        let sql_values: Vec<_> = values.iter().map(Value::as_sql).collect();
+        let values_slices: Vec<_> = sql_values.chunks(columns.len()).collect();
 
         let mut ctx = QueryContext::new();
+        // The inserted values are known anyway
+        ctx.load_all_deferred();
         let decoder = self.selector.select(&mut ctx);
+        let audit_decoder = M::AUDIT.then(|| M::select::<M>(&mut ctx));
+        let key_decoder = returned_key_decoder::<M>(&columns, &mut ctx);
         let returning = ctx
             .get_returning()
             .expect("Should have been checked in set_select");
 
-        let rows = database::insert_bulk_returning(
-            self.executor,
-            M::TABLE,
-            &columns,
-            &values_slices,
-            &returning,
-        )
-        .await?;
-        rows.iter()
+        let audit_decoder = audit_decoder.as_ref();
+        let returned = if !needs_transaction::<M>() {
+            insert_bulk_returning::<M>(
+                self.executor,
+                &columns,
+                &values_slices,
+                &returning,
+                audit_decoder,
+            )
+            .await?
+        } else {
+            let mut tx = self.executor.begin().await?;
+            M::Hooks::before_insert(tx.transaction(), &columns, &rows).await?;
+            let returned = insert_bulk_returning::<M>(
+                tx.executor(),
+                &columns,
+                &values_slices,
+                &returning,
+                audit_decoder,
+            )
+            .await?;
+            counter_cache::increment::<M>(&mut tx, &columns, &rows).await?;
+            if M::Hooks::ENABLED {
+                let keys = inserted_keys::<M>(&columns, &rows, key_decoder.as_ref(), &returned)?;
+                M::Hooks::after_insert(tx.transaction(), &columns, &rows, &keys).await?;
+            }
+            events::publish::<M>(&tx, || ModelChange::created(&columns, &rows));
+            tx.commit().await?;
+            returned
+        };
+        returned
+            .iter()
             .map(|row| decoder.by_index(row).map_err(Into::into))
             .collect()
     }
 }
 
+/// Generate the primary keys of the rows to insert if they don't set them
+///
+/// `values` contains the rows' values for `columns` one row after another.
+/// See [`FieldType::generate_key`].
+fn fill_generated_key<M: Model>(columns: &mut Vec<&'static str>, values: &mut Vec<Value<'_>>) {
+    let primary = M::Primary::NAME;
+    if columns.contains(&primary) {
+        return;
+    }
+    let mut generate =
+        || <M::Primary as Field>::Type::generate_key().map(M::Primary::type_into_value);
+    let Some(first) = generate() else {
+        return;
+    };
+
+    let row_len = columns.len();
+    columns.push(primary);
+    let mut keys = std::iter::once(first).chain(std::iter::from_fn(generate));
+    let mut filled = Vec::with_capacity(values.len() + values.len() / row_len.max(1));
+    let mut rows = std::mem::take(values).into_iter();
+    while !rows.as_slice().is_empty() {
+        filled.extend(rows.by_ref().take(row_len));
+        filled.extend(keys.next());
+    }
+    *values = filled;
+}
+
+/// Convert an error reading an imported patch
+fn import_error(error: impl std::fmt::Display) -> Error {
+    Error::DecodeError(format!("Failed to import a row: {error}"))
+}
+
+/// Insert a single row returning its `returning` columns
+///
+/// `audit_decoder` decodes the full model and is required if `M` is audited.
+async fn insert_returning<'ex, M: Model>(
+    executor: Scoped<impl Executor<'ex>>,
+    columns: &[&'static str],
+    values: &[SqlValue<'_>],
+    returning: &[&'static str],
+    audit_decoder: Option<&M::Decoder>,
+) -> Result<Row, Error> {
+    let Some(audit_decoder) = audit_decoder else {
+        return database::insert_returning(executor, M::TABLE, columns, values, returning).await;
+    };
+    let mut returned =
+        audit::insert_returning(executor, columns, &[values], returning, audit_decoder).await?;
+    Ok(returned
+        .pop()
+        .expect("A single insert should return a single row"))
+}
+
+/// Insert rows returning the `returning` columns of every row in the same order as `rows`
+///
+/// `audit_decoder` decodes the full model and is required if `M` is audited.
+/// See [`InsertBuilder::bulk`] for the fallback used on MySQL.
+async fn insert_bulk_returning<'ex, M: Model>(
+    executor: Scoped<impl Executor<'ex>>,
+    columns: &[&'static str],
+    rows: &[&[SqlValue<'_>]],
+    returning: &[&'static str],
+    audit_decoder: Option<&M::Decoder>,
+) -> Result<Vec<Row>, Error> {
+    if let Some(audit_decoder) = audit_decoder {
+        return audit::insert_returning(executor, columns, rows, returning, audit_decoder).await;
+    }
+    match executor.dialect() {
+        #[cfg(feature = "all-drivers")]
+        DBImpl::MySQL => {
+            if rows.is_empty() {
+                return Ok(Vec::new());
+            }
+
+            let mut tx = executor.begin().await?;
+            database::insert_bulk(tx.executor(), M::TABLE, columns, rows).await?;
+
+            let primary = <M as Model>::Primary::NAME;
+            let keys: Vec<SqlValue<'_>> = match columns.iter().position(|column| *column == primary)
+            {
+                Some(index) => rows.iter().map(|row| row[index]).collect(),
+                None => {
+                    // LAST_INSERT_ID() is unsigned which doesn't decode as i64
+                    let first: i64 = tx
+                        .executor()
+                        .execute::<One>(
+                            "SELECT CAST(LAST_INSERT_ID() AS SIGNED);".to_string(),
+                            Vec::new(),
+                        )
+                        .await?
+                        .get(0usize)?;
+                    (first..).take(rows.len()).map(SqlValue::I64).collect()
+                }
+            };
+
+            let placeholders = vec!["?"; keys.len()].join(", ");
+            let sql = format!(
+                "SELECT {} FROM `{}` WHERE `{primary}` IN ({placeholders}) ORDER BY FIELD(`{primary}`, {placeholders});",
+                returning
+                    .iter()
+                    .map(|column| format!("`{column}`"))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                M::TABLE,
+            );
+            let mut values = keys.clone();
+            values.extend(keys);
+            let returned = tx.executor().execute::<All>(sql, values).await?;
+
+            tx.commit().await?;
+            Ok(returned)
+        }
+        _ => database::insert_bulk_returning(executor, M::TABLE, columns, rows, returning).await,
+    }
+}
+
 /// Variation of [`InsertBuilder`] which performs an insert without returning anything
 #[must_use]
 pub struct InsertReturningNothing<E, M> {
-    executor: E,
+    executor: Scoped<E>,
     model: PhantomData<M>,
 }
 impl<'ex, E, M> InsertReturningNothing<E, M>
@@ -276,13 +619,23 @@ where
     E: Executor<'ex>,
     M: Model,
 {
+    /// See [`InsertBuilder::comment`]
+    pub fn comment(self, tags: &str) -> Self {
+        Self {
+            executor: comment::comment(self.executor, tags),
+            ..self
+        }
+    }
+
     /// See [`InsertBuilder::single`]
     pub async fn single<P: Patch<Model = M>>(self, patch: &P) -> Result<(), Error> {
-        let columns = P::columns();
-        let values = patch.references();
-        let values: Vec<_> = values.iter().map(Value::as_sql).collect();
-
-        database::insert(self.executor, M::TABLE, &columns, &values).await
+        patch.validate()?;
+        let mut columns = P::columns();
+        let mut values = patch.references();
+        fill_generated_key::<M>(&mut columns, &mut values);
+        tenant::fill_insert::<M>(&mut columns, &mut values)?;
+        content_hash::fill_insert::<M>(&mut columns, &mut values);
+        self.execute(&columns, &[&values]).await
     }
 
     /// See [`InsertBuilder::bulk`]
@@ -295,16 +648,143 @@ where
         let mut values: Vec<Value<'p>> = Vec::new();
         for patch in patches {
             match patch.into_patch_cow() {
-                PatchCow::Borrowed(patch) => patch.push_references(&mut values),
-                PatchCow::Owned(patch) => patch.push_values(&mut values),
+                PatchCow::Borrowed(patch) => {
+                    patch.validate()?;
+                    patch.push_references(&mut values);
+                }
+                PatchCow::Owned(patch) => {
+                    patch.validate()?;
+                    patch.push_values(&mut values);
+                }
             }
         }
+        if values.is_empty() {
+            return Ok(());
+        }
+
+        let mut columns = P::columns();
+        fill_generated_key::<M>(&mut columns, &mut values);
+        tenant::fill_insert::<M>(&mut columns, &mut values)?;
+        content_hash::fill_insert::<M>(&mut columns, &mut values);
+        let rows: Vec<_> = values.chunks(columns.len()).collect();
+        self.execute(&columns, &rows).await
+    }
+
+    /// Insert `rows` containing the values for `columns`, increment the counters they reference,
+    /// call the hooks and publish their event
+    async fn execute(self, columns: &[&'static str], rows: &[&[Value<'_>]]) -> Result<(), Error> {
+        let sql_values: Vec<_> = rows
+            .iter()
+            .map(|row| row.iter().map(Value::as_sql).collect::<Vec<_>>())
+            .collect();
+        let sql_rows: Vec<_> = sql_values.iter().map(Vec::as_slice).collect();
+
+        if !needs_transaction::<M>() {
+            return insert_rows::<M>(self.executor, columns, &sql_rows).await;
+        }
 
-        let columns = P::columns();
-        let values: Vec<_> = values.iter().map(Value::as_sql).collect();
-        let values_slices: Vec<_> = values.chunks(columns.len()).collect();
+        let mut tx = self.executor.begin().await?;
+        M::Hooks::before_insert(tx.transaction(), columns, rows).await?;
+        let mut ctx = QueryContext::new();
+        match returned_key_decoder::<M>(columns, &mut ctx) {
+            // The hooks require the generated keys to be returned
+            Some(key_decoder) => {
+                ctx.load_all_deferred();
+                let audit_decoder = M::AUDIT.then(|| M::select::<M>(&mut ctx));
+                let returning = ctx
+                    .get_returning()
+                    .expect("A model should always be a valid selector");
+                let returned = insert_bulk_returning::<M>(
+                    tx.executor(),
+                    columns,
+                    &sql_rows,
+                    &returning,
+                    audit_decoder.as_ref(),
+                )
+                .await?;
+                counter_cache::increment::<M>(&mut tx, columns, rows).await?;
+                let keys = inserted_keys::<M>(columns, rows, Some(&key_decoder), &returned)?;
+                M::Hooks::after_insert(tx.transaction(), columns, rows, &keys).await?;
+            }
+            None => {
+                insert_rows::<M>(tx.executor(), columns, &sql_rows).await?;
+                counter_cache::increment::<M>(&mut tx, columns, rows).await?;
+                if M::Hooks::ENABLED {
+                    let keys = inserted_keys::<M>(columns, rows, None, &[])?;
+                    M::Hooks::after_insert(tx.transaction(), columns, rows, &keys).await?;
+                }
+            }
+        }
+        events::publish::<M>(&tx, || ModelChange::created(columns, rows));
+        tx.commit().await
+    }
+}
 
-        database::insert_bulk(self.executor, M::TABLE, &columns, &values_slices).await
+/// Check whether an insert has to run in a transaction
+/// to maintain counter caches, call hooks or publish events
+fn needs_transaction<M: Model>() -> bool {
+    !M::COUNTER_CACHES.is_empty() || M::Hooks::ENABLED || events::has_subscribers::<M>()
+}
+
+/// Select the primary key of the inserted rows, if the hooks require it to be returned
+///
+/// Keys which are part of `columns` are taken from the inserted values instead.
+fn returned_key_decoder<M: Model>(
+    columns: &[&'static str],
+    ctx: &mut QueryContext<'_>,
+) -> Option<<<M::Primary as Field>::Type as FieldType>::Decoder> {
+    let key_set = columns.contains(&M::Primary::NAME);
+    (M::Hooks::ENABLED && !key_set).then(|| FieldProxy::<M::Primary, M>::new().select(ctx))
+}
+
+/// Get the primary keys of inserted rows for the hooks
+///
+/// `key_decoder` decodes the keys from the `returned` rows,
+/// if they are not part of `columns`. See [`returned_key_decoder`].
+fn inserted_keys<M: Model>(
+    columns: &[&'static str],
+    rows: &[&[Value<'_>]],
+    key_decoder: Option<&<<M::Primary as Field>::Type as FieldType>::Decoder>,
+    returned: &[Row],
+) -> Result<Vec<Value<'static>>, Error> {
+    match columns
+        .iter()
+        .position(|column| *column == M::Primary::NAME)
+    {
+        Some(index) => Ok(rows
+            .iter()
+            .map(|row| row[index].clone().into_static())
+            .collect()),
+        None => match key_decoder {
+            Some(key_decoder) => returned
+                .iter()
+                .map(|row| Ok(M::Primary::type_into_value(key_decoder.by_index(row)?)))
+                .collect(),
+            None => Ok(Vec::new()),
+        },
+    }
+}
+
+/// Insert rows without returning anything
+async fn insert_rows<'ex, M: Model>(
+    executor: Scoped<impl Executor<'ex>>,
+    columns: &[&'static str],
+    rows: &[&[SqlValue<'_>]],
+) -> Result<(), Error> {
+    if M::AUDIT {
+        // Auditing requires returning the inserted rows
+        let mut ctx = QueryContext::new();
+        ctx.load_all_deferred();
+        let decoder = M::select::<M>(&mut ctx);
+        let returning = ctx
+            .get_returning()
+            .expect("A model should always be a valid selector");
+        audit::insert_returning(executor, columns, rows, &returning, &decoder).await?;
+        Ok(())
+    } else if let [row] = rows {
+        database::insert(executor, M::TABLE, columns, row).await
+    } else {
+        database::insert_bulk(executor, M::TABLE, columns, rows).await
     }
 }
 
@@ -313,8 +793,9 @@ where
 #[macro_export]
 macro_rules! insert {
     ($db:expr, $patch:path) => {
-        $crate::crud::insert::InsertBuilder::<_, <$patch as $crate::model::Patch>::Model, _>::new(
+        $crate::crud::insert::insert(
             $db,
+            <<<$patch as $crate::model::Patch>::Model as $crate::model::Patch>::ValueSpaceImpl as ::std::default::Default>::default(),
         )
     };
 }
@@ -12,9 +12,17 @@
 //! [`update!`]: macro@crate::update
 //! [`delete!`]: macro@crate::delete
 pub mod builder;
+pub mod comment;
 pub mod decoder;
 pub mod delete;
 pub mod insert;
+pub mod prepared;
 pub mod query;
+pub mod recursive;
+pub mod refresh;
+pub mod save;
 pub mod selector;
+pub mod tracked;
+pub mod tree;
+pub mod truncate;
 pub mod update;
@@ -0,0 +1,305 @@
+//! Reusable select queries with typed placeholders
+//!
+//! Every execution of a [`QueryBuilder`](crate::crud::query::QueryBuilder) has to run its selector
+//! and condition against a fresh [`QueryContext`] to produce the joins, aliases and condition tree.
+//! In hot loops this work is repeated for every call even though only the bound values change.
+//!
+//! A [`StaticQuery`] performs this work once.
+//! Its condition is built from [`Placeholder`]s whose values are provided when executing it:
+//!
+//! ```no_run
+//! # use rorm::{Database, Error, Model};
+//! # use rorm::crud::prepared::StaticQuery;
+//! # #[derive(Model)] pub struct User { #[rorm(id)] id: i64, #[rorm(max_length = 255)] name: String, age: i16, }
+//! pub async fn find_users(db: &Database, names: &[String]) -> Result<(), Error> {
+//!     let mut query = StaticQuery::<_, (String, i16)>::new(User, |(name, min_age)| {
+//!         rorm::and![User.name.equals(name), User.age.greater_equals(min_age)]
+//!     });
+//!     for name in names {
+//!         let users = query.all(db, (name.clone(), 18i16)).await?;
+//!         println!("{name}: {}", users.len());
+//!     }
+//!     Ok(())
+//! }
+//! ```
+//!
+//! The sql string itself is still generated by `rorm-db` on every execution,
+//! because the dialect is only known once the query is executed on a concrete [`Executor`].
+//! Precomputing it at compile time would require `rorm-sql`'s builders to be usable in const contexts.
+
+use std::marker::PhantomData;
+
+use rorm_db::database;
+use rorm_db::error::Error;
+use rorm_db::executor::{All, Executor, One, Optional};
+
+use crate::conditions::{Binary, BinaryOperator, Column, Condition, Value};
+use crate::crud::decoder::Decoder;
+use crate::crud::selector::Selector;
+use crate::fields::traits::{Array, FieldEq, FieldOrd, FieldType};
+use crate::internal::field::access::FieldAccess;
+use crate::internal::query_context::flat_conditions::FlatCondition;
+use crate::internal::query_context::QueryContext;
+use crate::model::Model;
+
+/// A select query whose [`QueryContext`] is built once and which can be executed many times
+///
+/// `P` is a tuple of the [`FieldType`]s which have to be bound on every execution.
+/// See the [module docs](self) for an example.
+pub struct StaticQuery<S: Selector, P> {
+    ctx: QueryContext<'static>,
+    decoder: S::Decoder,
+    condition: Option<usize>,
+    params: PhantomData<fn(P)>,
+}
+
+impl<S, P> StaticQuery<S, P>
+where
+    S: Selector,
+    P: Params,
+{
+    /// Build a query selecting `selector` and filtering by the condition returned from `condition`
+    ///
+    /// The closure receives a [`Placeholder`] for every parameter in `P`.
+    pub fn new<C>(selector: S, condition: impl FnOnce(P::Placeholders) -> C) -> Self
+    where
+        C: Condition<'static>,
+    {
+        let mut ctx = QueryContext::new();
+        let decoder = selector.select(&mut ctx);
+        let condition = ctx.add_condition(&condition(P::placeholders()));
+        Self {
+            ctx,
+            decoder,
+            condition: Some(condition),
+            params: PhantomData,
+        }
+    }
+
+    /// Replace the placeholders' values with `params`
+    fn bind(&mut self, params: P) {
+        let values = params.into_values();
+        for &(param, index) in &self.ctx.placeholders {
+            self.ctx.values[index] = values[param].clone();
+        }
+    }
+
+    /// Retrieve and decode all matching rows
+    pub async fn all<'e>(
+        &mut self,
+        executor: impl Executor<'e>,
+        params: P,
+    ) -> Result<Vec<S::Result>, Error> {
+        self.ctx.check_joins()?;
+        self.bind(params);
+        database::query::<All>(
+            executor,
+            S::Model::TABLE,
+            self.ctx.get_selects().as_slice(),
+            self.ctx.get_joins().as_slice(),
+            self.ctx.get_condition_opt(self.condition).as_ref(),
+            &[],
+            None,
+        )
+        .await?
+        .into_iter()
+        .map(|x| self.decoder.by_name(&x).map_err(Into::into))
+        .collect::<Result<Vec<_>, _>>()
+    }
+
+    /// Retrieve and decode exactly one matching row
+    ///
+    /// An error is returned if no value could be retrieved.
+    pub async fn one<'e>(
+        &mut self,
+        executor: impl Executor<'e>,
+        params: P,
+    ) -> Result<S::Result, Error> {
+        self.ctx.check_joins()?;
+        self.bind(params);
+        let row = database::query::<One>(
+            executor,
+            S::Model::TABLE,
+            self.ctx.get_selects().as_slice(),
+            self.ctx.get_joins().as_slice(),
+            self.ctx.get_condition_opt(self.condition).as_ref(),
+            &[],
+            None,
+        )
+        .await?;
+        self.decoder.by_name(&row).map_err(Into::into)
+    }
+
+    /// Try to retrieve and decode a matching row
+    pub async fn optional<'e>(
+        &mut self,
+        executor: impl Executor<'e>,
+        params: P,
+    ) -> Result<Option<S::Result>, Error> {
+        self.ctx.check_joins()?;
+        self.bind(params);
+        let row = database::query::<Optional>(
+            executor,
+            S::Model::TABLE,
+            self.ctx.get_selects().as_slice(),
+            self.ctx.get_joins().as_slice(),
+            self.ctx.get_condition_opt(self.condition).as_ref(),
+            &[],
+            None,
+        )
+        .await?;
+        match row {
+            None => Ok(None),
+            Some(row) => Ok(Some(self.decoder.by_name(&row)?)),
+        }
+    }
+}
+
+/// A value in a [`StaticQuery`]'s condition which is provided when executing it
+///
+/// It can be compared with fields of type `T` using [`FieldAccess`]'s methods.
+pub struct Placeholder<T> {
+    param: usize,
+    field_type: PhantomData<fn() -> T>,
+}
+impl<T> Clone for Placeholder<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<T> Copy for Placeholder<T> {}
+
+impl<'a, T> Condition<'a> for Placeholder<T>
+where
+    T: FieldType<Columns = Array<1>>,
+{
+    fn build(&self, context: &mut QueryContext<'a>) {
+        let [null] = T::NULL;
+        let index = context.values.len();
+        context.values.push(Value::Null(null));
+        context.conditions.push(FlatCondition::Value(index));
+        context.placeholders.push((self.param, index));
+    }
+}
+
+/// Tuple of [`FieldType`]s which can be used as parameters of a [`StaticQuery`]
+pub trait Params {
+    /// Tuple of [`Placeholder`]s passed to [`StaticQuery::new`]
+    type Placeholders;
+
+    /// Create the placeholders
+    fn placeholders() -> Self::Placeholders;
+
+    /// Convert the parameters into their values in the placeholders' order
+    fn into_values(self) -> Vec<Value<'static>>;
+}
+
+macro_rules! impl_Params {
+    ($($index:tt: $param:ident),*) => {
+        impl<$($param),*> Params for ($($param,)*)
+        where
+            $($param: FieldType<Columns = Array<1>>,)*
+        {
+            type Placeholders = ($(Placeholder<$param>,)*);
+
+            fn placeholders() -> Self::Placeholders {
+                ($(Placeholder {
+                    param: $index,
+                    field_type: PhantomData,
+                },)*)
+            }
+
+            #[allow(clippy::vec_init_then_push)] // the macro can't build the vec in one expression
+            fn into_values(self) -> Vec<Value<'static>> {
+                #[allow(unused_mut)]
+                let mut values = Vec::new();
+                $(
+                    let [value] = self.$index.into_values();
+                    values.push(value);
+                )*
+                values
+            }
+        }
+    };
+}
+impl_Params!();
+impl_Params!(0: T0);
+impl_Params!(0: T0, 1: T1);
+impl_Params!(0: T0, 1: T1, 2: T2);
+impl_Params!(0: T0, 1: T1, 2: T2, 3: T3);
+impl_Params!(0: T0, 1: T1, 2: T2, 3: T3, 4: T4);
+impl_Params!(0: T0, 1: T1, 2: T2, 3: T3, 4: T4, 5: T5);
+impl_Params!(0: T0, 1: T1, 2: T2, 3: T3, 4: T4, 5: T5, 6: T6);
+impl_Params!(0: T0, 1: T1, 2: T2, 3: T3, 4: T4, 5: T5, 6: T6, 7: T7);
+
+// Impl FieldEq<Placeholder<Self>> iff FieldEq<Self>
+impl<'rhs, T> FieldEq<'rhs, Placeholder<T>> for T
+where
+    T: FieldEq<'rhs, T> + FieldType<Columns = Array<1>>,
+{
+    type EqCond<A: FieldAccess> = Binary<Column<A>, Placeholder<T>>;
+
+    fn field_equals<A: FieldAccess>(access: A, value: Placeholder<T>) -> Self::EqCond<A> {
+        Binary {
+            operator: BinaryOperator::Equals,
+            fst_arg: Column(access),
+            snd_arg: value,
+        }
+    }
+
+    type NeCond<A: FieldAccess> = Binary<Column<A>, Placeholder<T>>;
+
+    fn field_not_equals<A: FieldAccess>(access: A, value: Placeholder<T>) -> Self::NeCond<A> {
+        Binary {
+            operator: BinaryOperator::NotEquals,
+            fst_arg: Column(access),
+            snd_arg: value,
+        }
+    }
+}
+
+// Impl FieldOrd<Placeholder<Self>> iff FieldOrd<Self>
+impl<'rhs, T> FieldOrd<'rhs, Placeholder<T>> for T
+where
+    T: FieldOrd<'rhs, T> + FieldType<Columns = Array<1>>,
+{
+    type LtCond<A: FieldAccess> = Binary<Column<A>, Placeholder<T>>;
+
+    fn field_less_than<A: FieldAccess>(access: A, value: Placeholder<T>) -> Self::LtCond<A> {
+        Binary {
+            operator: BinaryOperator::Less,
+            fst_arg: Column(access),
+            snd_arg: value,
+        }
+    }
+
+    type LeCond<A: FieldAccess> = Binary<Column<A>, Placeholder<T>>;
+
+    fn field_less_equals<A: FieldAccess>(access: A, value: Placeholder<T>) -> Self::LeCond<A> {
+        Binary {
+            operator: BinaryOperator::LessOrEquals,
+            fst_arg: Column(access),
+            snd_arg: value,
+        }
+    }
+
+    type GtCond<A: FieldAccess> = Binary<Column<A>, Placeholder<T>>;
+
+    fn field_greater_than<A: FieldAccess>(access: A, value: Placeholder<T>) -> Self::GtCond<A> {
+        Binary {
+            operator: BinaryOperator::Greater,
+            fst_arg: Column(access),
+            snd_arg: value,
+        }
+    }
+
+    type GeCond<A: FieldAccess> = Binary<Column<A>, Placeholder<T>>;
+
+    fn field_greater_equals<A: FieldAccess>(access: A, value: Placeholder<T>) -> Self::GeCond<A> {
+        Binary {
+            operator: BinaryOperator::GreaterOrEquals,
+            fst_arg: Column(access),
+            snd_arg: value,
+        }
+    }
+}
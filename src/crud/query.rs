@@ -1,22 +1,50 @@
 //! Query builder and macro
 
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hash};
+use std::io;
+use std::marker::PhantomData;
 use std::ops::{Range, RangeInclusive, Sub};
+use std::pin::pin;
+use std::sync::Arc;
 
+use futures::future::{self, Either};
+use futures::stream::{self, TryChunksError};
+use futures::{TryFutureExt, TryStreamExt};
 use rorm_db::database;
 use rorm_db::error::Error;
-use rorm_db::executor::{All, Executor, One, Optional, Stream};
+use rorm_db::executor::{All, Executor, Nothing, One, Optional, QueryStrategy, Stream};
+use rorm_db::sql::aggregation::SelectAggregator;
 use rorm_db::sql::limit_clause::LimitClause;
 use rorm_db::sql::ordering::Ordering;
+use rorm_db::sql::value::Value as SqlValue;
+use rorm_db::sql::DBImpl;
+use rorm_db::{Database, Row};
+use serde::Serialize;
 
-use crate::conditions::Condition;
-use crate::crud::builder::ConditionMarker;
-use crate::crud::decoder::Decoder;
-use crate::crud::selector::Selector;
-use crate::internal::field::{Field, FieldProxy};
+use crate::conditions::collections::CollectionOperator;
+#[cfg(feature = "dyn-conditions")]
+use crate::conditions::ArcCondition;
+use crate::conditions::{Column, Condition, In, InOperator, Ternary, TernaryOperator, Value};
+use crate::crud::builder::{self, ConditionMarker};
+use crate::crud::comment;
+use crate::crud::decoder::{Decoder, DirectDecoder};
+use crate::crud::insert::insert;
+use crate::crud::selector::{AggregatedColumn, Selector, WithDeferred};
+use crate::fields::traits::{FieldMax, FieldMin};
+use crate::internal::field::access::FieldAccess;
+use crate::internal::field::{Field, FieldProxy, SingleColumnField};
+use crate::internal::query_context::flat_conditions::FlatCondition;
 use crate::internal::query_context::QueryContext;
+use crate::internal::random_token;
+pub use crate::internal::relation_path::Joined;
 use crate::internal::relation_path::Path;
-use crate::model::Model;
+use crate::model::{DefaultScope, Identifiable, Model, Patch};
+use crate::routing::Route;
 use crate::sealed;
+use crate::tenant;
+use crate::transaction::Scoped;
 
 /// Create a SELECT query.
 ///
@@ -58,7 +86,7 @@ use crate::sealed;
 ///         `.optional().await`
 ///
 ///     Each of these methods decodes the database's rows into the patch you specified in step 1.
-///     If you want to work with raw rows, each of the methods in step 4 has a `*_as_row` twin.
+///     If you want to work with raw rows, use [`all_as_rows`](QueryBuilder::all_as_rows).
 ///
 /// Example:
 /// ```no_run
@@ -92,17 +120,19 @@ use crate::sealed;
 ///     }
 /// }
 /// ```
-pub fn query<'ex, E, S>(executor: E, selector: S) -> QueryBuilder<E, S, (), ()>
+pub fn query<'ex, R, S>(executor: R, selector: S) -> QueryBuilder<R::Executor, S, (), ()>
 where
-    E: Executor<'ex>,
+    R: Route<'ex, S::Model>,
     S: Selector,
 {
     QueryBuilder {
-        executor,
+        executor: executor.route(),
         selector,
         condition: (),
         lim_off: (),
         modify_ctx: Vec::new(),
+        max_rows: None,
+        unscoped: false,
     }
 }
 
@@ -127,11 +157,13 @@ where
 ///     An optional limit and or offset to control the amount of queried rows.
 #[must_use]
 pub struct QueryBuilder<E, S, C, LO> {
-    executor: E,
+    executor: Scoped<E>,
     selector: S,
     condition: C,
     lim_off: LO,
-    modify_ctx: Vec<fn(&mut QueryContext)>,
+    modify_ctx: Vec<Arc<dyn Fn(&mut QueryContext) + Send + Sync>>,
+    max_rows: Option<u64>,
+    unscoped: bool,
 }
 
 impl<'ex, E, S> QueryBuilder<E, S, (), ()>
@@ -148,11 +180,29 @@ where
 
 impl<E, S, LO> QueryBuilder<E, S, (), LO> {
     /// Add a condition to the query
+    #[cfg(not(feature = "dyn-conditions"))]
     pub fn condition<'c, C: Condition<'c>>(self, condition: C) -> QueryBuilder<E, S, C, LO> {
+        self.set_condition(condition)
+    }
+
+    /// Add a condition to the query
+    ///
+    /// The `dyn-conditions` feature is enabled, so the condition's type is erased
+    /// and every query on the same selector shares the same builder type.
+    #[cfg(feature = "dyn-conditions")]
+    pub fn condition<'c, C: Condition<'c> + 'c>(
+        self,
+        condition: C,
+    ) -> QueryBuilder<E, S, ArcCondition<'c>, LO> {
+        self.set_condition(condition.arc())
+    }
+
+    /// Replace the condition marker `()` with `condition`
+    fn set_condition<C>(self, condition: C) -> QueryBuilder<E, S, C, LO> {
         #[rustfmt::skip]
-        let QueryBuilder { executor, selector, lim_off, modify_ctx, .. } = self;
+        let QueryBuilder { executor, selector, lim_off, modify_ctx, max_rows, unscoped, .. } = self;
         #[rustfmt::skip]
-        return QueryBuilder { executor, selector, condition, lim_off, modify_ctx, };
+        return QueryBuilder { executor, selector, condition, lim_off, modify_ctx, max_rows, unscoped, };
     }
 }
 
@@ -163,9 +213,9 @@ where
     /// Add a limit to the query
     pub fn limit(self, limit: u64) -> QueryBuilder<E, S, C, Limit<O>> {
         #[rustfmt::skip]
-        let QueryBuilder { executor, selector, condition,  lim_off, modify_ctx, } = self;
+        let QueryBuilder { executor, selector, condition,  lim_off, modify_ctx, max_rows, unscoped, } = self;
         #[rustfmt::skip]
-        return QueryBuilder { executor, selector, condition, lim_off: Limit { limit, offset: lim_off }, modify_ctx, };
+        return QueryBuilder { executor, selector, condition, lim_off: Limit { limit, offset: lim_off }, modify_ctx, max_rows, unscoped, };
     }
 }
 
@@ -176,10 +226,10 @@ where
     /// Add a offset to the query
     pub fn offset(self, offset: u64) -> QueryBuilder<E, S, C, LO::Result> {
         #[rustfmt::skip]
-        let QueryBuilder { executor, selector, condition, lim_off, modify_ctx, .. } = self;
+        let QueryBuilder { executor, selector, condition, lim_off, modify_ctx, max_rows, unscoped, } = self;
         let lim_off = lim_off.add_offset(offset);
         #[rustfmt::skip]
-        return QueryBuilder { executor, selector, condition, lim_off, modify_ctx, };
+        return QueryBuilder { executor, selector, condition, lim_off, modify_ctx, max_rows, unscoped, };
     }
 }
 
@@ -187,13 +237,94 @@ impl<E, S, C> QueryBuilder<E, S, C, ()> {
     /// Add a offset to the query
     pub fn range(self, range: impl FiniteRange<u64>) -> QueryBuilder<E, S, C, Limit<u64>> {
         #[rustfmt::skip]
-        let QueryBuilder { executor, selector, condition, modify_ctx,  .. } = self;
+        let QueryBuilder { executor, selector, condition, modify_ctx, max_rows, unscoped, .. } = self;
         let limit = Limit {
             limit: range.len(),
             offset: range.start(),
         };
         #[rustfmt::skip]
-        return QueryBuilder { executor, selector, condition, lim_off: limit, modify_ctx, };
+        return QueryBuilder { executor, selector, condition, lim_off: limit, modify_ctx, max_rows, unscoped, };
+    }
+}
+
+impl<E, S, C, LO> QueryBuilder<E, S, C, LO> {
+    /// Prepend a comment to the query attributing it to the code executing it
+    ///
+    /// See [`comment`](crate::crud::comment) for the format of `tags`.
+    pub fn comment(self, tags: &str) -> Self {
+        Self {
+            executor: comment::comment(self.executor, tags),
+            ..self
+        }
+    }
+
+    /// Fail the query if it would return more than `max_rows` rows
+    ///
+    /// This protects against accidentally unbounded result sets.
+    /// The query requests at most one row more than allowed (respecting any smaller [`limit`](QueryBuilder::limit))
+    /// and returns an [`Error::TooManyRows`] if it received it.
+    /// Its `actual` number of rows is therefore at most `max_rows + 1`.
+    ///
+    /// Only [`all`](QueryBuilder::all) and [`all_as_rows`](QueryBuilder::all_as_rows) are checked,
+    /// since [`stream`](QueryBuilder::stream) doesn't hold its rows in memory.
+    pub fn expect_at_most(mut self, max_rows: u64) -> Self {
+        self.max_rows = Some(max_rows);
+        self
+    }
+
+    /// Ignore the model's [default scope](DefaultScope)
+    ///
+    /// The query returns rows which would be filtered by the condition
+    /// registered using `#[rorm(default_scope)]`, for example soft deleted ones.
+    pub fn unscoped(mut self) -> Self {
+        self.unscoped = true;
+        self
+    }
+
+    /// Select a field annotated with `#[rorm(defer)]` which would be skipped otherwise
+    ///
+    /// Deferred fields are decoded as their type's [`Default`] when their model is queried as a whole.
+    /// This method loads one of them for every selected instance of its model:
+    ///
+    /// ```no_run
+    /// # use rorm::{query, Database, Error, Model, FieldAccess};
+    /// #[derive(Model)]
+    /// pub struct Document {
+    ///     #[rorm(id)]
+    ///     pub id: i64,
+    ///
+    ///     #[rorm(max_length = 255)]
+    ///     pub title: String,
+    ///
+    ///     #[rorm(defer)]
+    ///     pub content: Vec<u8>,
+    /// }
+    ///
+    /// pub async fn download(db: &Database, id: i64) -> Result<Document, Error> {
+    ///     query(db, Document)
+    ///         .with_deferred(Document.content)
+    ///         .condition(Document.id.equals(id))
+    ///         .one()
+    ///         .await
+    /// }
+    /// ```
+    ///
+    /// Selecting the field directly (i.e. `query(db, (Document.id, Document.content))`) always loads it.
+    pub fn with_deferred<F, P>(
+        self,
+        _field: FieldProxy<F, P>,
+    ) -> QueryBuilder<E, WithDeferred<S, F>, C, LO>
+    where
+        F: Field,
+    {
+        #[rustfmt::skip]
+        let QueryBuilder { executor, selector, condition, lim_off, modify_ctx, max_rows, unscoped, } = self;
+        let selector = WithDeferred {
+            selector,
+            field: PhantomData,
+        };
+        #[rustfmt::skip]
+        return QueryBuilder { executor, selector, condition, lim_off, modify_ctx, max_rows, unscoped, };
     }
 }
 
@@ -209,10 +340,8 @@ where
         F: Field,
         P: Path<Origin = S::Model>,
     {
-        self.modify_ctx.push(match order {
-            Ordering::Asc => |ctx: &mut QueryContext| ctx.order_by_field::<F, P>(Ordering::Asc),
-            Ordering::Desc => |ctx: &mut QueryContext| ctx.order_by_field::<F, P>(Ordering::Desc),
-        });
+        let modify = move |ctx: &mut QueryContext| ctx.order_by_field::<F, P>(order);
+        self.modify_ctx.push(Arc::new(modify));
         self
     }
 
@@ -237,6 +366,98 @@ where
     {
         self.order_by(field, Ordering::Desc)
     }
+
+    /// Join the model `M` on an arbitrary condition
+    ///
+    /// This allows joining tables without a declared [`ForeignModel`](crate::fields::types::ForeignModel).
+    /// The joined model's fields are accessed through [`Joined::fields`]
+    /// and can be used in the selector, the condition, the orderings and `on` itself:
+    ///
+    /// ```no_run
+    /// # use rorm::{query, Database, Error, Model, FieldAccess};
+    /// # use rorm::crud::query::Joined;
+    /// # #[derive(Model)] pub struct User { #[rorm(id)] id: i64, #[rorm(max_length = 255)] name: String, }
+    /// # #[derive(Model)] pub struct Post { #[rorm(id)] id: i64, #[rorm(max_length = 255)] author: String, #[rorm(max_length = 255)] title: String, }
+    /// pub async fn titles_with_author(db: &Database) -> Result<Vec<(String, i64)>, Error> {
+    ///     let user = Joined::<User, Post>::fields();
+    ///     query(db, (Post.title, user.id))
+    ///         .join::<User>(Post.author.equals(user.name))
+    ///         .all()
+    ///         .await
+    /// }
+    /// ```
+    ///
+    /// Since the condition is built whenever the query is executed, it has to be `'static`.
+    /// Comparing columns of both models is always possible.
+    ///
+    /// Using a [`Joined`] model's fields without joining it makes the query return an error.
+    pub fn join<M: Model>(
+        mut self,
+        on: impl for<'a> Condition<'a> + Send + Sync + 'static,
+    ) -> Self {
+        let modify = move |ctx: &mut QueryContext| ctx.join_model::<M, S::Model>(&on);
+        self.modify_ctx.push(Arc::new(modify));
+        self
+    }
+}
+
+impl<'c, E, S, C, LO> QueryBuilder<E, S, C, LO>
+where
+    S: Selector,
+    C: ConditionMarker<'c>,
+{
+    /// Run the selector and build the condition into `ctx`
+    ///
+    /// This is the only step of executing a query which depends on the selector and condition.
+    /// Everything afterwards is done by the returned [`QueryCore`].
+    ///
+    /// Building the condition fails if the model's rows can't be restricted to the current tenant.
+    /// The decoder is returned regardless, so [`stream`](QueryBuilder::stream) can yield the error.
+    #[allow(clippy::type_complexity)]
+    fn into_core(
+        self,
+        mut ctx: QueryContext<'c>,
+    ) -> (E, S::Decoder, LO, Result<QueryCore<'c>, Error>) {
+        #[rustfmt::skip]
+        let QueryBuilder { executor, selector, condition, lim_off, modify_ctx, unscoped, .. } = self;
+
+        let decoder = selector.select(&mut ctx);
+        let condition_index = build_condition::<S::Model>(&mut ctx, &condition, unscoped);
+        for modify in modify_ctx {
+            modify(&mut ctx);
+        }
+
+        let core = condition_index.and_then(|condition_index| {
+            ctx.check_joins()?;
+            Ok(QueryCore {
+                table: S::Model::TABLE,
+                ctx,
+                condition_index,
+            })
+        });
+        (executor, decoder, lim_off, core)
+    }
+
+    /// Build the query's condition restricted to the model's default scope and the current tenant
+    fn build_condition(&self, ctx: &mut QueryContext<'c>) -> Result<Option<usize>, Error> {
+        build_condition::<S::Model>(ctx, &self.condition, self.unscoped)
+    }
+}
+
+/// Build a query's condition restricted to the model's default scope and the current tenant
+fn build_condition<'c, M: Model>(
+    ctx: &mut QueryContext<'c>,
+    condition: &impl ConditionMarker<'c>,
+    unscoped: bool,
+) -> Result<Option<usize>, Error> {
+    tenant::build_condition::<M>(ctx, |ctx| {
+        if unscoped {
+            condition.build(ctx)
+        } else {
+            let scope = <M::DefaultScope as DefaultScope>::condition();
+            builder::build_both(ctx, condition, &scope)
+        }
+    })
 }
 
 impl<'e, 'c, E, S, C, LO> QueryBuilder<E, S, C, LO>
@@ -250,32 +471,66 @@ where
     where
         LO: LimitMarker,
     {
-        let mut ctx = QueryContext::new();
+        let max_rows = self.max_rows;
+        let (executor, decoder, lim_off, core) = self.into_core(QueryContext::new());
+        let core = core?;
 
-        let decoder = self.selector.select(&mut ctx);
-        let condition_index = self.condition.build(&mut ctx);
-        for modify in self.modify_ctx {
-            modify(&mut ctx);
-        }
+        let rows = core
+            .all(executor, limit_max_rows(lim_off.into_option(), max_rows))
+            .await?;
+        check_max_rows(&rows, max_rows)?;
+        rows.into_iter()
+            .map(|x| decoder.by_name(&x).map_err(Into::into))
+            .collect::<Result<Vec<_>, _>>()
+    }
 
-        let condition = ctx.get_condition_opt(condition_index);
+    /// Retrieve all matching rows without decoding them
+    ///
+    /// The selected columns can be accessed by their index in the selector.
+    /// Since the rows outlive this call, string and binary columns can be decoded
+    /// into `&str` and `&[u8]` borrowing from the row instead of allocating:
+    ///
+    /// ```no_run
+    /// # use rorm::{query, Database, Error, Model};
+    /// # #[derive(Model)] pub struct User { #[rorm(id)] id: i64, #[rorm(max_length = 255)] name: String, }
+    /// pub async fn count_name_lengths(db: &Database) -> Result<usize, Error> {
+    ///     let rows = query(db, User.name).all_as_rows().await?;
+    ///     let mut total = 0;
+    ///     for row in &rows {
+    ///         let name: &str = row.get(0)?;
+    ///         total += name.len();
+    ///     }
+    ///     Ok(total)
+    /// }
+    /// ```
+    pub async fn all_as_rows(self) -> Result<Vec<Row>, Error>
+    where
+        LO: LimitMarker,
+    {
+        let max_rows = self.max_rows;
+        let (executor, _, lim_off, core) = self.into_core(QueryContext::new());
+        let core = core?;
 
-        database::query::<All>(
-            self.executor,
-            S::Model::TABLE,
-            ctx.get_selects().as_slice(),
-            ctx.get_joins().as_slice(),
-            condition.as_ref(),
-            ctx.get_order_bys().as_slice(),
-            self.lim_off.into_option(),
-        )
-        .await?
-        .into_iter()
-        .map(|x| decoder.by_name(&x).map_err(Into::into))
-        .collect::<Result<Vec<_>, _>>()
+        let rows = core
+            .all(executor, limit_max_rows(lim_off.into_option(), max_rows))
+            .await?;
+        check_max_rows(&rows, max_rows)?;
+        Ok(rows)
     }
 
     /// Retrieve and decode the query as a stream
+    ///
+    /// The returned [`QueryStream`] owns everything the query borrows,
+    /// so it can be returned from functions or stored like the result of [`all`](QueryBuilder::all):
+    ///
+    /// ```no_run
+    /// # use futures::Stream;
+    /// # use rorm::{query, Database, Error, Model};
+    /// # #[derive(Model)] pub struct User { #[rorm(id)] id: i64, #[rorm(max_length = 255)] name: String, }
+    /// pub fn all_users(db: &Database) -> impl Stream<Item = Result<User, Error>> + '_ {
+    ///     query(db, User).stream()
+    /// }
+    /// ```
     pub fn stream<'stream>(self) -> QueryStream<'stream, 'c, S::Decoder>
     where
         'e: 'stream,
@@ -283,27 +538,212 @@ where
         S: 'stream,
         LO: LimitMarker,
     {
-        let mut ctx = QueryContext::new();
-
-        let decoder = self.selector.select(&mut ctx);
-        let condition_index = self.condition.build(&mut ctx);
-        for modify in self.modify_ctx {
-            modify(&mut ctx);
-        }
+        let (executor, decoder, lim_off, core) = self.into_core(QueryContext::new());
+        let QueryCore {
+            table,
+            ctx,
+            condition_index,
+        } = match core {
+            Ok(core) => core,
+            Err(error) => return QueryStream::failed(decoder, error),
+        };
 
         QueryStream::new(decoder, ctx, move |ctx| {
             database::query::<Stream>(
-                self.executor,
-                S::Model::TABLE,
+                executor,
+                table,
                 ctx.get_selects().as_slice(),
                 ctx.get_joins().as_slice(),
                 ctx.get_condition_opt(condition_index).as_ref(),
                 ctx.get_order_bys().as_slice(),
-                self.lim_off.into_option(),
+                lim_off.into_option(),
             )
         })
     }
 
+    /// Retrieve and decode the query as a stream of batches with up to `size` rows
+    ///
+    /// This amortizes the per-item overhead for pipelines processing the rows in batches,
+    /// for example inserting them somewhere else using a bulk insert.
+    /// Every batch except the last one contains exactly `size` rows.
+    ///
+    /// The rows are still streamed from the database one by one and only collected into batches afterward.
+    /// If an error occurs, the rows already collected for the current batch are discarded.
+    ///
+    /// ```no_run
+    /// # use futures::TryStreamExt;
+    /// # use rorm::{query, Database, Error, Model};
+    /// # #[derive(Model)] pub struct User { #[rorm(id)] id: i64, #[rorm(max_length = 255)] name: String, }
+    /// # async fn index(_users: Vec<User>) {}
+    /// pub async fn index_users(db: &Database) -> Result<(), Error> {
+    ///     let mut batches = std::pin::pin!(query(db, User).stream_chunks(1000));
+    ///     while let Some(users) = batches.try_next().await? {
+    ///         index(users).await;
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// # Panics
+    /// If `size` is zero.
+    pub fn stream_chunks<'stream>(
+        self,
+        size: usize,
+    ) -> impl futures::Stream<Item = Result<Vec<S::Result>, Error>> + 'stream
+    where
+        'e: 'stream,
+        'c: 'stream,
+        S: 'stream,
+        LO: LimitMarker,
+    {
+        assert!(size > 0, "The chunk size must be greater than zero");
+        self.stream()
+            .try_chunks(size)
+            .map_err(|TryChunksError(_, error)| error)
+    }
+
+    /// Retrieve and decode the query through a server-side cursor fetching `fetch_size` rows at a time
+    ///
+    /// On Postgres, the query is declared as cursor using `DECLARE ... CURSOR`
+    /// and every yielded batch is retrieved using its own `FETCH`.
+    /// Unlike [`stream`](QueryBuilder::stream), this bounds the memory used on both ends to a single batch
+    /// and doesn't keep a statement's result buffered while the previous batch is being processed.
+    ///
+    /// The cursor only exists inside a transaction.
+    /// If the query's executor isn't one, a transaction is started and committed once all rows have been fetched.
+    /// Otherwise, the cursor is closed once all rows have been fetched
+    /// or when the transaction ends, if the stream is dropped before.
+    ///
+    /// Other databases don't support cursors outside of stored procedures,
+    /// so this falls back to [`stream_chunks`](QueryBuilder::stream_chunks).
+    ///
+    /// ```no_run
+    /// # use futures::TryStreamExt;
+    /// # use rorm::{query, Database, Error, Model};
+    /// # #[derive(Model)] pub struct User { #[rorm(id)] id: i64, #[rorm(max_length = 255)] name: String, }
+    /// # async fn index(_users: Vec<User>) {}
+    /// pub async fn index_users(db: &Database) -> Result<(), Error> {
+    ///     let mut batches = std::pin::pin!(query(db, User).stream_cursor(1000));
+    ///     while let Some(users) = batches.try_next().await? {
+    ///         index(users).await;
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// # Panics
+    /// If `fetch_size` is zero.
+    pub fn stream_cursor<'stream>(
+        self,
+        fetch_size: u64,
+    ) -> impl futures::Stream<Item = Result<Vec<S::Result>, Error>> + 'stream
+    where
+        'e: 'stream,
+        'c: 'stream,
+        S: 'stream,
+        LO: LimitMarker,
+    {
+        assert!(fetch_size > 0, "The fetch size must be greater than zero");
+        #[allow(unreachable_patterns)]
+        match self.executor.dialect() {
+            DBImpl::Postgres => {}
+            _ => return Either::Right(self.stream_chunks(fetch_size as usize)),
+        }
+
+        let (executor, decoder, lim_off, core) = self.into_core(QueryContext::new());
+        let cursor = format!("\"rorm_cursor_{}\"", random_token());
+        let declared = async move {
+            let core = core?;
+            let mut tx = executor.begin().await?;
+            core.declare_cursor(tx.executor(), &cursor, lim_off.into_option())
+                .await?;
+
+            Ok::<_, Error>(stream::try_unfold(Some(tx), move |tx| {
+                let fetch = format!("FETCH {fetch_size} FROM {cursor};");
+                let close = format!("CLOSE {cursor};");
+                async move {
+                    let Some(mut tx) = tx else {
+                        return Ok(None);
+                    };
+                    let rows = tx.executor().execute::<All>(fetch, Vec::new()).await?;
+                    if (rows.len() as u64) < fetch_size {
+                        tx.executor().execute::<Nothing>(close, Vec::new()).await?;
+                        tx.commit().await?;
+                        Ok((!rows.is_empty()).then_some((rows, None)))
+                    } else {
+                        Ok(Some((rows, Some(tx))))
+                    }
+                }
+            }))
+        };
+
+        Either::Left(stream::once(declared).try_flatten().and_then(move |rows| {
+            future::ready(
+                rows.iter()
+                    .map(|row| decoder.by_name(row).map_err(Into::into))
+                    .collect(),
+            )
+        }))
+    }
+
+    /// Stream the matching rows into `writer` as json lines
+    ///
+    /// Every row is written as a single line containing its json representation.
+    /// The exported columns are chosen by the selector, for example a tuple of fields or a patch.
+    /// `writer` is written to synchronously, so it should be buffered and not block for long.
+    ///
+    /// Returns the number of written rows.
+    ///
+    /// ```no_run
+    /// # use std::fs::File;
+    /// # use std::io::BufWriter;
+    /// # use rorm::{query, Database, Model};
+    /// # #[derive(Model)] pub struct User { #[rorm(id)] id: i64, #[rorm(max_length = 255)] name: String, }
+    /// pub async fn dump_users(db: &Database) -> Result<u64, Box<dyn std::error::Error>> {
+    ///     let file = BufWriter::new(File::create("users.jsonl")?);
+    ///     Ok(query(db, (User.id, User.name)).export_jsonl(file).await?)
+    /// }
+    /// ```
+    pub async fn export_jsonl(self, mut writer: impl io::Write) -> Result<u64, Error>
+    where
+        S::Result: Serialize,
+        LO: LimitMarker,
+    {
+        let mut rows = pin!(self.stream());
+        let mut count = 0;
+        while let Some(row) = rows.try_next().await? {
+            serde_json::to_writer(&mut writer, &row).map_err(export_error)?;
+            writer.write_all(b"\n").map_err(export_error)?;
+            count += 1;
+        }
+        writer.flush().map_err(export_error)?;
+        Ok(count)
+    }
+
+    /// Stream the matching rows into `writer` as csv
+    ///
+    /// If the selector's result is a struct, like a model or patch,
+    /// a header row containing its field names is written first.
+    /// Otherwise, like for a tuple of fields, there is no header.
+    ///
+    /// See [`export_jsonl`](QueryBuilder::export_jsonl) for details.
+    #[cfg(feature = "csv")]
+    pub async fn export_csv(self, writer: impl io::Write) -> Result<u64, Error>
+    where
+        S::Result: Serialize,
+        LO: LimitMarker,
+    {
+        let mut writer = csv::Writer::from_writer(writer);
+        let mut rows = pin!(self.stream());
+        let mut count = 0;
+        while let Some(row) = rows.try_next().await? {
+            writer.serialize(&row).map_err(export_error)?;
+            count += 1;
+        }
+        writer.flush().map_err(export_error)?;
+        Ok(count)
+    }
+
     /// Retrieve and decode exactly one matching row
     ///
     /// An error is returned if no value could be retrieved.
@@ -311,24 +751,10 @@ where
     where
         LO: OffsetMarker,
     {
-        let mut ctx = QueryContext::new();
+        let (executor, decoder, lim_off, core) = self.into_core(QueryContext::new());
+        let core = core?;
 
-        let decoder = self.selector.select(&mut ctx);
-        let condition_index = self.condition.build(&mut ctx);
-        for modify in self.modify_ctx {
-            modify(&mut ctx);
-        }
-
-        let row = database::query::<One>(
-            self.executor,
-            S::Model::TABLE,
-            ctx.get_selects().as_slice(),
-            ctx.get_joins().as_slice(),
-            ctx.get_condition_opt(condition_index).as_ref(),
-            ctx.get_order_bys().as_slice(),
-            self.lim_off.into_option(),
-        )
-        .await?;
+        let row = core.one(executor, lim_off.into_option()).await?;
         decoder.by_name(&row).map_err(Into::into)
     }
 
@@ -337,29 +763,700 @@ where
     where
         LO: OffsetMarker,
     {
-        let mut ctx = QueryContext::new();
+        let (executor, decoder, lim_off, core) = self.into_core(QueryContext::new());
+        let core = core?;
+
+        let row = core.optional(executor, lim_off.into_option()).await?;
+        match row {
+            None => Ok(None),
+            Some(row) => Ok(Some(decoder.by_name(&row)?)),
+        }
+    }
+
+    /// Retrieve and decode the matching row with the smallest value in `field`
+    ///
+    /// This is a shorthand for `.order_asc(field).optional()`.
+    /// Orderings added before are more significant than `field`.
+    ///
+    /// ```no_run
+    /// # use rorm::{query, Database, Error, Model};
+    /// # #[derive(Model)] pub struct User { #[rorm(id)] id: i64, #[rorm(max_length = 255)] name: String, }
+    /// pub async fn oldest_and_newest(db: &Database) -> Result<Option<(User, User)>, Error> {
+    ///     let oldest = query(db, User).first(User.id).await?;
+    ///     let newest = query(db, User).last(User.id).await?;
+    ///     Ok(oldest.zip(newest))
+    /// }
+    /// ```
+    pub async fn first<F, P>(self, field: FieldProxy<F, P>) -> Result<Option<S::Result>, Error>
+    where
+        F: Field,
+        P: Path<Origin = S::Model>,
+        LO: OffsetMarker,
+    {
+        self.order_asc(field).optional().await
+    }
+
+    /// Retrieve and decode the matching row with the largest value in `field`
+    ///
+    /// This is a shorthand for `.order_desc(field).optional()`.
+    /// Orderings added before are more significant than `field`.
+    pub async fn last<F, P>(self, field: FieldProxy<F, P>) -> Result<Option<S::Result>, Error>
+    where
+        F: Field,
+        P: Path<Origin = S::Model>,
+        LO: OffsetMarker,
+    {
+        self.order_desc(field).optional().await
+    }
+}
+
+impl<'ex, S, C> QueryBuilder<&'ex Database, S, C, ()>
+where
+    S: Selector + Clone + 'ex,
+    C: ConditionMarker<'ex> + Sync + 'ex,
+{
+    /// Split the query into ranges of an integer key and run them concurrently
+    ///
+    /// The key has to be a non-nullable `i16`, `i32` or `i64`, see [`PartitionKey`].
+    /// The key's minimum and maximum are queried first.
+    /// Their range is then split into `partitions` equally sized ranges
+    /// which are each streamed on their own connection from the database's pool.
+    /// The rows are merged into a single stream in the order they arrive.
+    ///
+    /// Orderings only apply within each partition.
+    ///
+    /// ```no_run
+    /// # use futures::TryStreamExt;
+    /// # use rorm::{query, Database, Error, Model};
+    /// # #[derive(Model)] pub struct User { #[rorm(id)] id: i64, #[rorm(max_length = 255)] name: String, }
+    /// pub async fn export_users(db: &Database) -> Result<(), Error> {
+    ///     let mut users = query(db, User).partitioned_by(User.id, 8);
+    ///     while let Some(user) = users.try_next().await? {
+    ///         println!("{}", user.name);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn partitioned_by<F, P>(
+        self,
+        field: FieldProxy<F, P>,
+        partitions: usize,
+    ) -> impl futures::Stream<Item = Result<S::Result, Error>> + 'ex
+    where
+        F: Field<Type: PartitionKey>,
+        P: Path<Origin = S::Model>,
+    {
+        #[rustfmt::skip]
+        let QueryBuilder { executor, selector, condition, modify_ctx, unscoped, .. } = self;
+        let condition = Arc::new(condition);
+
+        async move {
+            #[rustfmt::skip]
+            let bounds = QueryBuilder {
+                executor: executor.clone(),
+                selector: (field.min(), field.max()),
+                condition: Partition { condition: condition.clone(), field, range: (i64::MIN, i64::MAX) },
+                lim_off: (),
+                modify_ctx: Vec::new(),
+                max_rows: None,
+                unscoped,
+            };
+            let bounds = bounds.one().await?;
+
+            let mut streams = Vec::new();
+            if let (Some(min), Some(max)) = bounds {
+                let (min, max): (i64, i64) = (min.into(), max.into());
+                let partitions = partitions.max(1) as i128;
+                let size = (max as i128 - min as i128) / partitions + 1;
+                for index in 0..partitions {
+                    let start = min as i128 + size * index;
+                    if start > max as i128 {
+                        break;
+                    }
+                    let end = (start + size - 1).min(max as i128);
+
+                    #[rustfmt::skip]
+                    let partition = QueryBuilder {
+                        executor: executor.clone(),
+                        selector: selector.clone(),
+                        condition: Partition { condition: condition.clone(), field, range: (start as i64, end as i64) },
+                        lim_off: (),
+                        modify_ctx: modify_ctx.clone(),
+                        max_rows: None,
+                        unscoped,
+                    };
+                    streams.push(Box::pin(partition.stream()));
+                }
+            }
+            Ok(futures::stream::select_all(streams))
+        }
+        .try_flatten_stream()
+    }
+}
 
-        let decoder = self.selector.select(&mut ctx);
-        let condition_index = self.condition.build(&mut ctx);
-        for modify in self.modify_ctx {
+impl<'ex, 'c, E, S, C> QueryBuilder<E, S, C, ()>
+where
+    E: Executor<'ex>,
+    S: Selector,
+    C: ConditionMarker<'c>,
+{
+    /// Retrieve a single page of the matching rows together with the total number of matching rows
+    ///
+    /// `page` is the zero based index of the page to retrieve and `per_page` its size.
+    /// The page's rows and the count are queried in a single transaction,
+    /// so they are consistent with each other.
+    ///
+    /// ```no_run
+    /// # use rorm::{query, Database, Error, Model};
+    /// # use rorm::crud::query::Page;
+    /// # #[derive(Model)] pub struct User { #[rorm(id)] id: i64, #[rorm(max_length = 255)] name: String, }
+    /// pub async fn list_users(db: &Database, page: u64) -> Result<Page<(i64, String)>, Error> {
+    ///     query(db, (User.id, User.name))
+    ///         .order_asc(User.id)
+    ///         .paginate(page, 25)
+    ///         .await
+    /// }
+    /// ```
+    pub async fn paginate(self, page: u64, per_page: u64) -> Result<Page<S::Result>, Error> {
+        let count_ctx = self.count_context()?;
+        let (executor, decoder, _, core) = self.into_core(QueryContext::new());
+        let core = core?;
+
+        let mut tx = executor.begin().await?;
+        let total = count_ctx.count(tx.executor()).await?;
+        let rows = core
+            .all(
+                tx.executor(),
+                Some(LimitClause {
+                    limit: per_page,
+                    offset: Some(page.saturating_mul(per_page)),
+                }),
+            )
+            .await?;
+        tx.commit().await?;
+
+        Ok(Page {
+            items: rows
+                .iter()
+                .map(|row| decoder.by_name(row))
+                .collect::<Result<_, _>>()?,
+            total,
+            page,
+            per_page,
+        })
+    }
+
+    /// Retrieve `amount` randomly chosen matching rows
+    ///
+    /// The rows are chosen using random offsets into the matching rows ordered by the query's orderings
+    /// and finally the primary key.
+    /// This executes a count and one query per returned row in a single transaction
+    /// instead of fetching every row (or at least every primary key) into memory.
+    ///
+    /// Returns fewer rows if less than `amount` rows match.
+    /// The rows are returned in no particular order.
+    ///
+    /// ```no_run
+    /// # use rorm::{query, Database, Error, Model, FieldAccess};
+    /// # #[derive(Model)] pub struct Post { #[rorm(id)] id: i64, #[rorm(max_length = 255)] title: String, featured: bool, }
+    /// pub async fn random_featured_post(db: &Database) -> Result<Option<Post>, Error> {
+    ///     let mut posts = query(db, Post)
+    ///         .condition(Post.featured.equals(true))
+    ///         .sample(1)
+    ///         .await?;
+    ///     Ok(posts.pop())
+    /// }
+    /// ```
+    pub async fn sample(self, amount: u64) -> Result<Vec<S::Result>, Error> {
+        let count_ctx = self.count_context()?;
+        let (executor, decoder, _, core) = self.into_core(QueryContext::new());
+        let mut core = core?;
+        // Make the offsets refer to the same rows across queries
+        core.ctx
+            .order_by_field::<<S::Model as Model>::Primary, S::Model>(Ordering::Asc);
+
+        let mut tx = executor.begin().await?;
+        let total = count_ctx.count(tx.executor()).await?;
+
+        let mut results = Vec::new();
+        for offset in random_offsets(amount.min(total), total) {
+            let row = core.one(tx.executor(), Some(offset)).await?;
+            results.push(decoder.by_name(&row)?);
+        }
+        tx.commit().await?;
+        Ok(results)
+    }
+
+    /// Build a query counting the matching rows
+    ///
+    /// The count query can't use the orderings, since it has no `GROUP BY`.
+    fn count_context(&self) -> Result<CountContext<'c>, Error> {
+        let mut ctx = QueryContext::new();
+        let decoder = AggregatedColumn::<FieldProxy<<S::Model as Model>::Primary, S::Model>, i64> {
+            sql: SelectAggregator::Count,
+            alias: "count",
+            field_access: PhantomData,
+            result: PhantomData,
+        }
+        .select(&mut ctx);
+        let condition_index = self.build_condition(&mut ctx)?;
+        for modify in &self.modify_ctx {
             modify(&mut ctx);
         }
+        ctx.check_joins()?;
+        Ok(CountContext {
+            table: S::Model::TABLE,
+            ctx,
+            decoder,
+            condition_index,
+        })
+    }
+}
 
-        let row = database::query::<Optional>(
-            self.executor,
-            S::Model::TABLE,
-            ctx.get_selects().as_slice(),
-            ctx.get_joins().as_slice(),
-            ctx.get_condition_opt(condition_index).as_ref(),
-            ctx.get_order_bys().as_slice(),
-            self.lim_off.into_option(),
+/// The part of a select query which doesn't depend on the [`QueryBuilder`]'s generics
+///
+/// It is created by [`QueryBuilder::into_core`] after running the selector and building the condition.
+/// Converting the context into `rorm-sql`'s representation and executing the query is implemented here,
+/// so it is compiled once per executor
+/// instead of once per combination of selector, condition and limit.
+struct QueryCore<'c> {
+    table: &'static str,
+    ctx: QueryContext<'c>,
+    condition_index: Option<usize>,
+}
+impl QueryCore<'_> {
+    /// Retrieve all matching rows
+    async fn all<'ex>(
+        &self,
+        executor: impl Executor<'ex>,
+        limit: Option<LimitClause>,
+    ) -> Result<Vec<Row>, Error> {
+        database::query::<All>(
+            executor,
+            self.table,
+            self.ctx.get_selects().as_slice(),
+            self.ctx.get_joins().as_slice(),
+            self.ctx.get_condition_opt(self.condition_index).as_ref(),
+            self.ctx.get_order_bys().as_slice(),
+            limit,
+        )
+        .await
+        .map_err(|error| self.ctx.explain_error(error))
+    }
+
+    /// Retrieve exactly one matching row
+    async fn one<'ex>(
+        &self,
+        executor: impl Executor<'ex>,
+        offset: Option<u64>,
+    ) -> Result<Row, Error> {
+        database::query::<One>(
+            executor,
+            self.table,
+            self.ctx.get_selects().as_slice(),
+            self.ctx.get_joins().as_slice(),
+            self.ctx.get_condition_opt(self.condition_index).as_ref(),
+            self.ctx.get_order_bys().as_slice(),
+            offset,
+        )
+        .await
+        .map_err(|error| self.ctx.explain_error(error))
+    }
+
+    /// Try to retrieve a matching row
+    async fn optional<'ex>(
+        &self,
+        executor: impl Executor<'ex>,
+        offset: Option<u64>,
+    ) -> Result<Option<Row>, Error> {
+        database::query::<Optional>(
+            executor,
+            self.table,
+            self.ctx.get_selects().as_slice(),
+            self.ctx.get_joins().as_slice(),
+            self.ctx.get_condition_opt(self.condition_index).as_ref(),
+            self.ctx.get_order_bys().as_slice(),
+            offset,
+        )
+        .await
+        .map_err(|error| self.ctx.explain_error(error))
+    }
+
+    /// Declare the cursor `cursor` retrieving all matching rows
+    async fn declare_cursor<'ex>(
+        &self,
+        executor: impl Executor<'ex>,
+        cursor: &str,
+        limit: Option<LimitClause>,
+    ) -> Result<(), Error> {
+        let executor = DeclareCursor {
+            executor,
+            cursor: cursor.to_string(),
+        };
+        database::query::<All>(
+            executor,
+            self.table,
+            self.ctx.get_selects().as_slice(),
+            self.ctx.get_joins().as_slice(),
+            self.ctx.get_condition_opt(self.condition_index).as_ref(),
+            self.ctx.get_order_bys().as_slice(),
+            limit,
+        )
+        .await
+        .map_err(|error| self.ctx.explain_error(error))?;
+        Ok(())
+    }
+}
+
+/// An [`Executor`] declaring a cursor for the query it executes
+///
+/// It is used by [`QueryCore::declare_cursor`] to reuse rorm-db's sql generation.
+struct DeclareCursor<E> {
+    executor: E,
+
+    /// The cursor's quoted name
+    cursor: String,
+}
+
+impl<'executor, E> Executor<'executor> for DeclareCursor<E>
+where
+    E: Executor<'executor>,
+{
+    fn execute<'data, 'result, Q>(
+        self,
+        query: String,
+        values: Vec<SqlValue<'data>>,
+    ) -> Q::Result<'result>
+    where
+        'executor: 'result,
+        'data: 'result,
+        Q: QueryStrategy,
+    {
+        let query = query.trim_end().trim_end_matches(';');
+        self.executor.execute::<Q>(
+            format!("DECLARE {} NO SCROLL CURSOR FOR {query};", self.cursor),
+            values,
+        )
+    }
+
+    fn dialect(&self) -> DBImpl {
+        self.executor.dialect()
+    }
+
+    type EnsureTransactionFuture = E::EnsureTransactionFuture;
+
+    fn ensure_transaction(self) -> Self::EnsureTransactionFuture {
+        self.executor.ensure_transaction()
+    }
+}
+
+/// Query counting the rows matching a [`QueryBuilder`] created by [`QueryBuilder::count_context`]
+struct CountContext<'c> {
+    table: &'static str,
+    ctx: QueryContext<'c>,
+    decoder: DirectDecoder<i64>,
+    condition_index: Option<usize>,
+}
+impl CountContext<'_> {
+    /// Execute the count
+    async fn count<'ex>(&self, executor: impl Executor<'ex>) -> Result<u64, Error> {
+        let row = database::query::<One>(
+            executor,
+            self.table,
+            self.ctx.get_selects().as_slice(),
+            self.ctx.get_joins().as_slice(),
+            self.ctx.get_condition_opt(self.condition_index).as_ref(),
+            &[],
+            None,
         )
         .await?;
-        match row {
-            None => Ok(None),
-            Some(row) => Ok(Some(decoder.by_name(&row)?)),
+        Ok(self.decoder.by_name(&row)? as u64)
+    }
+}
+
+/// Pick `amount` distinct random numbers from `0..total`
+///
+/// Uses Robert Floyd's sampling algorithm.
+fn random_offsets(amount: u64, total: u64) -> Vec<u64> {
+    let state = RandomState::new();
+    let mut offsets = Vec::with_capacity(amount as usize);
+    for upper in (total - amount)..total {
+        let offset = state.hash_one(upper) % (upper + 1);
+        if offsets.contains(&offset) {
+            offsets.push(upper);
+        } else {
+            offsets.push(offset);
         }
     }
+    offsets
+}
+
+impl<'ex, 'c, E, S, C> QueryBuilder<E, S, C, ()>
+where
+    E: Executor<'ex>,
+    S: Selector + Clone,
+    C: Condition<'c>,
+{
+    /// Retrieve and decode the row matching the condition or insert `patch` if there is none
+    ///
+    /// Returns the row and whether it has been inserted.
+    ///
+    /// The condition should match at most a single row (i.e. check a unique column)
+    /// and `patch` should match the condition.
+    /// The query and the insert are executed in a single transaction.
+    /// If a concurrent call inserts the same row in between, the insert fails with the unique constraint's error.
+    ///
+    /// Since the inserted row is returned using the selector, it may only select the model's own columns.
+    ///
+    /// ```no_run
+    /// # use rorm::{query, Database, Error, Model, Patch, FieldAccess};
+    /// # #[derive(Model)] pub struct Tag { #[rorm(id)] id: i64, #[rorm(max_length = 255, unique)] name: String, }
+    /// # #[derive(Patch)] #[rorm(model = "Tag")] pub struct NewTag { name: String, }
+    /// pub async fn get_tag(db: &Database, name: &str) -> Result<Tag, Error> {
+    ///     let (tag, _created) = query(db, Tag)
+    ///         .condition(Tag.name.equals(name))
+    ///         .get_or_insert(&NewTag { name: name.to_string() })
+    ///         .await?;
+    ///     Ok(tag)
+    /// }
+    /// ```
+    pub async fn get_or_insert<P>(self, patch: &P) -> Result<(S::Result, bool), Error>
+    where
+        P: Patch<Model = S::Model>,
+    {
+        let mut tx = self.executor.begin().await?;
+
+        let existing = QueryBuilder {
+            executor: tx.executor(),
+            selector: self.selector.clone(),
+            condition: self.condition,
+            lim_off: (),
+            modify_ctx: self.modify_ctx,
+            max_rows: None,
+            unscoped: self.unscoped,
+        }
+        .optional()
+        .await?;
+
+        let result = match existing {
+            Some(existing) => (existing, false),
+            None => {
+                let inserted = insert(&mut tx, <S::Model as Model>::ValueSpaceImpl::default())
+                    .return_tuple(self.selector)
+                    .single(patch)
+                    .await?;
+                (inserted, true)
+            }
+        };
+
+        tx.commit().await?;
+        Ok(result)
+    }
+}
+
+/// A single page of rows returned by [`QueryBuilder::paginate`]
+#[derive(Clone, Debug)]
+pub struct Page<T> {
+    /// The page's rows
+    pub items: Vec<T>,
+
+    /// The total number of rows on all pages
+    pub total: u64,
+
+    /// The zero based index of this page
+    pub page: u64,
+
+    /// The maximum number of rows per page
+    pub per_page: u64,
+}
+
+impl<T> Page<T> {
+    /// The total number of pages
+    pub fn pages(&self) -> u64 {
+        if self.per_page == 0 {
+            0
+        } else {
+            self.total.div_ceil(self.per_page)
+        }
+    }
+
+    /// Is there a page after this one?
+    pub fn has_next(&self) -> bool {
+        self.page.saturating_add(1) < self.pages()
+    }
+
+    /// Is there a page before this one?
+    pub fn has_previous(&self) -> bool {
+        self.page > 0
+    }
+}
+
+/// Restrict a query's limit to one row more than [`QueryBuilder::expect_at_most`]'s maximum
+fn limit_max_rows(limit: Option<LimitClause>, max_rows: Option<u64>) -> Option<LimitClause> {
+    let Some(max_rows) = max_rows else {
+        return limit;
+    };
+    let max_limit = max_rows.saturating_add(1);
+    match limit {
+        Some(LimitClause { limit, offset }) => Some(LimitClause {
+            limit: limit.min(max_limit),
+            offset,
+        }),
+        None => Some(LimitClause {
+            limit: max_limit,
+            offset: None,
+        }),
+    }
+}
+
+/// Check the rows received by a query against [`QueryBuilder::expect_at_most`]'s maximum
+fn check_max_rows(rows: &[Row], max_rows: Option<u64>) -> Result<(), Error> {
+    match max_rows {
+        Some(max_rows) if rows.len() as u64 > max_rows => Err(Error::TooManyRows {
+            limit: max_rows,
+            actual: rows.len() as u64,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Convert an error writing an exported row
+fn export_error(error: impl std::fmt::Display) -> Error {
+    Error::DecodeError(format!("Failed to export a row: {error}"))
+}
+
+/// Select the primary keys of the rows matching the condition referenced by `condition_index`
+///
+/// This is used by updates and deletes to report the affected rows to [events](crate::events).
+pub(crate) async fn primary_keys<'ex, M: Model>(
+    executor: impl Executor<'ex>,
+    ctx: &mut QueryContext<'_>,
+    condition_index: Option<usize>,
+) -> Result<Vec<Value<'static>>, Error> {
+    let decoder = FieldProxy::<M::Primary, M>::new().select(ctx);
+    let condition = ctx.get_condition_opt(condition_index);
+    let rows = database::query::<All>(
+        executor,
+        M::TABLE,
+        &ctx.get_selects(),
+        &ctx.get_joins(),
+        condition.as_ref(),
+        &[],
+        None,
+    )
+    .await?;
+    rows.iter()
+        .map(|row| Ok(M::Primary::type_into_value(decoder.by_index(row)?)))
+        .collect()
+}
+
+/// Maximum number of keys bound in a single query by [`QueryBuilder::by_primary_keys`]
+///
+/// This stays well below the smallest bind parameter limit of the supported databases (sqlite's 32766).
+pub(crate) const KEYS_PER_QUERY: usize = 10_000;
+
+impl<'ex, E, S> QueryBuilder<E, S, (), ()>
+where
+    E: Executor<'ex>,
+    S: Selector + Clone,
+    S::Result: Identifiable<Model = S::Model>,
+    <<S::Model as Model>::Primary as Field>::Type: Hash + Eq + Clone,
+{
+    /// Retrieve and decode all rows whose primary key is in `keys`
+    ///
+    /// Long lists of keys are split into multiple queries which are executed in a single transaction.
+    /// Keys without a matching row are missing from the returned map.
+    ///
+    /// ```no_run
+    /// # use rorm::{query, Database, Error, Model};
+    /// # #[derive(Model)] pub struct User { #[rorm(id)] id: i64, #[rorm(max_length = 255)] name: String, }
+    /// pub async fn print_names(db: &Database, ids: &[i64]) -> Result<(), Error> {
+    ///     let users = query(db, User).by_primary_keys(ids).await?;
+    ///     for id in ids {
+    ///         match users.get(id) {
+    ///             Some(user) => println!("{id}: {}", user.name),
+    ///             None => println!("{id}: unknown"),
+    ///         }
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn by_primary_keys<'k>(
+        self,
+        keys: impl IntoIterator<Item = &'k <<S::Model as Model>::Primary as Field>::Type>,
+    ) -> Result<HashMap<<<S::Model as Model>::Primary as Field>::Type, S::Result>, Error> {
+        let keys: Vec<_> = keys
+            .into_iter()
+            .map(<S::Model as Model>::Primary::type_as_value)
+            .collect();
+
+        let mut tx = self.executor.begin().await?;
+        let mut results = HashMap::with_capacity(keys.len());
+        for chunk in keys.chunks(KEYS_PER_QUERY) {
+            let rows = query(&mut tx, self.selector.clone())
+                .condition(In {
+                    operator: InOperator::In,
+                    fst_arg: Column(FieldProxy::<<S::Model as Model>::Primary, S::Model>::new()),
+                    snd_arg: chunk.to_vec(),
+                })
+                .all()
+                .await?;
+            results.extend(
+                rows.into_iter()
+                    .map(|row| (row.get_primary_key().clone(), row)),
+            );
+        }
+        tx.commit().await?;
+        Ok(results)
+    }
+}
+
+/// An integer field type which [`QueryBuilder::partitioned_by`] can split into ranges
+///
+/// It is implemented for `i16`, `i32` and `i64`.
+/// The ranges' bounds are computed as `i64` and compared against the column directly.
+pub trait PartitionKey:
+    FieldMin<Result = Option<Self>> + FieldMax<Result = Option<Self>> + Into<i64> + Copy
+{
+    sealed!(trait);
+}
+impl PartitionKey for i16 {
+    sealed!(impl);
+}
+impl PartitionKey for i32 {
+    sealed!(impl);
+}
+impl PartitionKey for i64 {
+    sealed!(impl);
+}
+
+/// Condition used by [`QueryBuilder::partitioned_by`] restricting the original condition to a range of keys
+struct Partition<C, A> {
+    condition: Arc<C>,
+    field: A,
+    range: (i64, i64),
+}
+impl<'a, C, A> Condition<'a> for Partition<C, A>
+where
+    C: ConditionMarker<'a> + Sync,
+    A: FieldAccess,
+{
+    fn build(&self, context: &mut QueryContext<'a>) {
+        context
+            .conditions
+            .push(FlatCondition::StartCollection(CollectionOperator::And));
+        ConditionMarker::build(&*self.condition, context);
+        Condition::build(
+            &Ternary {
+                operator: TernaryOperator::Between,
+                fst_arg: Column(self.field),
+                snd_arg: Value::I64(self.range.0),
+                trd_arg: Value::I64(self.range.1),
+            },
+            context,
+        );
+        context.conditions.push(FlatCondition::EndCollection);
+    }
 }
 
 #[doc(hidden)]
@@ -369,7 +1466,7 @@ macro_rules! query {
     ($db:expr, ($(
         $($model:ident)::+.$($field:ident).+ $(($($args:tt)?))? $(as $patch:ty)?
     ),+ $(,)?)) => {
-        $crate::crud::query::QueryBuilder::new(
+        $crate::crud::query::query(
             $db,
             ($(
                 $($model)::+.$($field).+ $(($($args)?))? $(.select_as::<$patch>())?,
@@ -377,7 +1474,7 @@ macro_rules! query {
         )
     };
     ($db:expr, $patch:ty) => {
-        $crate::crud::query::QueryBuilder::new(
+        $crate::crud::query::query(
             $db,
             <<$patch as $crate::model::Patch>::ValueSpaceImpl as ::std::default::Default>::default(),
         )
@@ -398,6 +1495,9 @@ mod query_stream {
 
     /// Self-referential struct storing the query's data next to the stream which borrows it.
     ///
+    /// It is returned by [`QueryBuilder::stream`](super::QueryBuilder::stream)
+    /// and yields the decoded rows.
+    ///
     /// ## Lifetimes
     /// - `'this` is the self-referential struct's lifetime
     /// - `'cond` is the [`dyn Condition<'cond>`](Condition)'s lifetime
@@ -405,12 +1505,18 @@ mod query_stream {
     #[pin_project::pin_project]
     #[allow(dead_code)] // The field's are never "read" because they are aliased before being assigned to the struct
     pub struct QueryStream<'this, 'cond: 'this, D> {
+        // The stream has to be dropped before the context it borrows,
+        // so it has to be declared first.
+        // It is `None` if the query failed before it could be executed.
+        #[pin]
+        stream: Option<<Stream as QueryStrategyResult>::Result<'this>>,
+
         decoder: D,
 
         ctx: Box<QueryContext<'cond>>,
 
-        #[pin]
-        stream: <Stream as QueryStrategyResult>::Result<'this>,
+        // Error which prevented the query from being executed
+        error: Option<Error>,
     }
 
     impl<'this, 'cond: 'this, D> QueryStream<'this, 'cond, D> {
@@ -434,26 +1540,43 @@ mod query_stream {
                 let stream = stream_builder(ctx_ref);
 
                 Self {
-                    ctx,
+                    stream: Some(stream),
                     decoder,
-                    stream,
+                    ctx,
+                    error: None,
                 }
             }
         }
+
+        /// Construct a stream which only yields `error`
+        pub(crate) fn failed(decoder: D, error: Error) -> Self {
+            Self {
+                stream: None,
+                decoder,
+                ctx: Box::new(QueryContext::new()),
+                error: Some(error),
+            }
+        }
     }
 
     impl<'this, 'cond, D: Decoder> futures::stream::Stream for QueryStream<'this, 'cond, D> {
         type Item = Result<D::Result, Error>;
 
         fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-            let mut projection = self.project();
-            projection.stream.as_mut().poll_next(cx).map(|option| {
+            let projection = self.project();
+            if let Some(error) = projection.error.take() {
+                return Poll::Ready(Some(Err(error)));
+            }
+            let Some(stream) = projection.stream.as_pin_mut() else {
+                return Poll::Ready(None);
+            };
+            stream.poll_next(cx).map(|option| {
                 option.map(|result| result.and_then(|row| Ok(projection.decoder.by_name(&row)?)))
             })
         }
     }
 }
-use query_stream::QueryStream;
+pub use query_stream::QueryStream;
 
 /// Finite alternative to [`RangeBounds`](std::ops::RangeBounds)
 ///
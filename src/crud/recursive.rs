@@ -0,0 +1,179 @@
+//! Recursive queries traversing self-referencing models
+//!
+//! Models like threaded posts reference themselves.
+//! Loading a whole thread would require one query per level using the normal [`query`](crate::query).
+//! The functions in this module load all [`descendants`] or [`ancestors`] of some rows
+//! using a single `WITH RECURSIVE` query instead:
+//!
+//! ```no_run
+//! # use rorm::{Database, Error, Model};
+//! # use rorm::crud::recursive::{ancestors, descendants};
+//! # use rorm::fields::types::ForeignModel;
+//! #[derive(Model)]
+//! pub struct Post {
+//!     #[rorm(id)]
+//!     pub id: i64,
+//!
+//!     #[rorm(max_length = 255)]
+//!     pub title: String,
+//!
+//!     pub reply_to: Option<ForeignModel<Post>>,
+//! }
+//!
+//! pub async fn print_thread(db: &Database, root: i64) -> Result<(), Error> {
+//!     // The root post and all replies to it, their replies and so on
+//!     for (id, title) in descendants(db, (Post.id, Post.title), Post.reply_to, [&root]).await? {
+//!         println!("{id}: {title}");
+//!     }
+//!
+//!     // The root post and all posts it replies to
+//!     let path = ancestors(db, Post.title, Post.reply_to, [&root]).await?;
+//!     println!("{}", path.join(" > "));
+//!
+//!     Ok(())
+//! }
+//! ```
+//!
+//! The traversal uses `UNION` instead of `UNION ALL`, so cycles in the data terminate.
+//! The order of the returned rows is unspecified.
+
+use rorm_db::error::Error;
+use rorm_db::executor::{All, Executor};
+
+use crate::crud::decoder::Decoder;
+use crate::crud::selector::Selector;
+use crate::internal::field::{Field, FieldProxy, SingleColumnField};
+use crate::internal::query_context::QueryContext;
+use crate::internal::relation_path::PathField;
+use crate::internal::sql::{placeholder, quote};
+use crate::model::Model;
+
+/// Type of a model's primary key
+type Key<M> = <<M as Model>::Primary as Field>::Type;
+
+/// Select the rows whose primary key is in `roots` and all rows referencing them through `relation` recursively
+///
+/// For a [`ForeignModel`](crate::fields::types::ForeignModel) `relation` these are the rows pointing to `roots`,
+/// the rows pointing to those and so on.
+///
+/// The `selector` may only select columns of its model itself (no related fields or aggregations),
+/// otherwise an error is returned.
+pub async fn descendants<'ex, 'k, E, S, F>(
+    executor: E,
+    selector: S,
+    _relation: FieldProxy<F, S::Model>,
+    roots: impl IntoIterator<Item = &'k Key<S::Model>>,
+) -> Result<Vec<S::Result>, Error>
+where
+    E: Executor<'ex>,
+    S: Selector,
+    F: Field + PathField<<F as Field>::Type>,
+    F::ParentField: Field<Model = S::Model>,
+    F::ChildField: Field<Model = S::Model>,
+{
+    traverse::<E, S, F::ParentField, F::ChildField>(executor, selector, roots).await
+}
+
+/// Select the rows whose primary key is in `roots` and all rows they reference through `relation` recursively
+///
+/// For a [`ForeignModel`](crate::fields::types::ForeignModel) `relation` these are the rows `roots` point to,
+/// the rows those point to and so on.
+///
+/// The `selector` may only select columns of its model itself (no related fields or aggregations),
+/// otherwise an error is returned.
+pub async fn ancestors<'ex, 'k, E, S, F>(
+    executor: E,
+    selector: S,
+    _relation: FieldProxy<F, S::Model>,
+    roots: impl IntoIterator<Item = &'k Key<S::Model>>,
+) -> Result<Vec<S::Result>, Error>
+where
+    E: Executor<'ex>,
+    S: Selector,
+    F: Field + PathField<<F as Field>::Type>,
+    F::ParentField: Field<Model = S::Model>,
+    F::ChildField: Field<Model = S::Model>,
+{
+    traverse::<E, S, F::ChildField, F::ParentField>(executor, selector, roots).await
+}
+
+/// Select the `roots` and every row whose `Next` column equals the `Prev` column of an already selected row
+pub(crate) async fn traverse<'ex, 'k, E, S, Next, Prev>(
+    executor: E,
+    selector: S,
+    roots: impl IntoIterator<Item = &'k Key<S::Model>>,
+) -> Result<Vec<S::Result>, Error>
+where
+    E: Executor<'ex>,
+    S: Selector,
+    Next: SingleColumnField,
+    Prev: SingleColumnField,
+{
+    let keys: Vec<_> = roots
+        .into_iter()
+        .map(<S::Model as Model>::Primary::type_as_value)
+        .collect();
+    if keys.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut ctx = QueryContext::new();
+    let decoder = selector.select(&mut ctx);
+    let Some(mut columns) = ctx.get_returning() else {
+        return Err(Error::DecodeError(format!(
+            "A recursive query on {} may only select its model's own columns",
+            S::Model::TABLE
+        )));
+    };
+    for column in [<S::Model as Model>::Primary::NAME, Next::NAME, Prev::NAME] {
+        if !columns.contains(&column) {
+            columns.push(column);
+        }
+    }
+
+    let dialect = executor.dialect();
+
+    let table = quote(dialect, S::Model::TABLE);
+    let tree = quote(dialect, "__rorm_tree");
+    let row = quote(dialect, "__rorm_row");
+    let inner_columns = columns
+        .iter()
+        .map(|column| format!("{row}.{}", quote(dialect, column)))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let outer_columns = ctx
+        .get_selects()
+        .iter()
+        .map(|select| {
+            format!(
+                "{tree}.{} AS {}",
+                quote(dialect, select.column_name),
+                quote(dialect, select.select_alias.unwrap_or(select.column_name))
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    let placeholders = (1..=keys.len())
+        .map(|index| placeholder(dialect, index))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let sql = format!(
+        "WITH RECURSIVE {tree} AS (\
+            SELECT {inner_columns} FROM {table} AS {row} WHERE {row}.{primary} IN ({placeholders}) \
+            UNION \
+            SELECT {inner_columns} FROM {table} AS {row} JOIN {tree} ON {row}.{next} = {tree}.{prev}\
+        ) SELECT {outer_columns} FROM {tree};",
+        primary = quote(dialect, <S::Model as Model>::Primary::NAME),
+        next = quote(dialect, Next::NAME),
+        prev = quote(dialect, Prev::NAME),
+    );
+    let values = keys.iter().map(|key| key.as_sql()).collect();
+
+    executor
+        .execute::<All>(sql, values)
+        .await?
+        .iter()
+        .map(|row| decoder.by_name(row).map_err(Into::into))
+        .collect()
+}
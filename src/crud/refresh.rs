@@ -0,0 +1,64 @@
+//! Reloading a model instance from the database
+
+use std::future::Future;
+
+use rorm_db::error::Error;
+use rorm_db::executor::Executor;
+
+use crate::crud::query::query;
+use crate::model::{Identifiable, Model};
+
+/// Reload a model instance from the database
+///
+/// This trait is implemented for every model.
+///
+/// ```no_run
+/// # use rorm::{Database, Error, Model};
+/// # use rorm::crud::refresh::Refresh;
+/// # #[derive(Model)] pub struct User { #[rorm(id)] id: i64, #[rorm(max_length = 255)] name: String, }
+/// pub async fn print_current_name(db: &Database, user: &mut User) -> Result<(), Error> {
+///     if user.refresh(db).await? {
+///         println!("{}", user.name);
+///     } else {
+///         println!("The user has been deleted");
+///     }
+///     Ok(())
+/// }
+/// ```
+pub trait Refresh: Model {
+    /// Query the row with the instance's primary key and overwrite the instance with it
+    ///
+    /// This picks up changes made by triggers, database defaults or concurrent updates.
+    /// Fields annotated with `#[rorm(defer)]` are not loaded and reset to their default.
+    ///
+    /// Returns whether the row still exists.
+    /// If it doesn't, the instance is left unchanged.
+    fn refresh<'ex>(
+        &mut self,
+        executor: impl Executor<'ex>,
+    ) -> impl Future<Output = Result<bool, Error>>;
+}
+
+impl<M> Refresh for M
+where
+    M: Model + Identifiable,
+{
+    fn refresh<'ex>(
+        &mut self,
+        executor: impl Executor<'ex>,
+    ) -> impl Future<Output = Result<bool, Error>> {
+        async move {
+            let row = query(executor, M::ValueSpaceImpl::default())
+                .condition(self.as_condition())
+                .optional()
+                .await?;
+            match row {
+                Some(row) => {
+                    *self = row;
+                    Ok(true)
+                }
+                None => Ok(false),
+            }
+        }
+    }
+}
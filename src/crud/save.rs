@@ -0,0 +1,94 @@
+//! Inserting or updating a model instance depending on its primary key
+
+use std::future::Future;
+
+use rorm_db::error::Error;
+use rorm_db::executor::Executor;
+
+use crate::crud::insert::insert;
+use crate::crud::update::update;
+use crate::internal::field::Field;
+use crate::model::{Identifiable, Model};
+
+/// Type of a model's primary key
+pub(crate) type Key<M> = <<M as Model>::Primary as Field>::Type;
+
+/// Write a model instance to the database
+///
+/// This trait is implemented for every model whose primary key has a [`Default`] value
+/// (i.e. `0` for `#[rorm(id)]`).
+///
+/// ```no_run
+/// # use rorm::{Database, Error, Model};
+/// # use rorm::crud::save::Save;
+/// # #[derive(Model)] pub struct User { #[rorm(id)] id: i64, #[rorm(max_length = 255)] name: String, }
+/// pub async fn rename_or_create(db: &Database, mut user: User, name: String) -> Result<User, Error> {
+///     user.name = name;
+///     user.save(db).await?;
+///     Ok(user)
+/// }
+/// ```
+pub trait Save: Model {
+    /// Insert the instance if its primary key is the default value or update the existing row otherwise
+    ///
+    /// When inserting, the primary key is left to the database and
+    /// the instance is replaced with the inserted row (containing the generated primary key).
+    /// When updating, every column except the primary key, `#[rorm(readonly)]` and `#[rorm(defer)]` fields is written.
+    /// (Deferred fields might not have been loaded.)
+    ///
+    /// Returns whether the instance has been inserted.
+    /// An error is returned if the primary key is set but there is no row to update.
+    fn save<'ex>(
+        &mut self,
+        executor: impl Executor<'ex>,
+    ) -> impl Future<Output = Result<bool, Error>>;
+}
+
+impl<M> Save for M
+where
+    M: Model + Identifiable,
+    Key<M>: Default + PartialEq,
+{
+    fn save<'ex>(
+        &mut self,
+        executor: impl Executor<'ex>,
+    ) -> impl Future<Output = Result<bool, Error>> {
+        async move {
+            if *self.get_primary_key() != Key::<M>::default() {
+                let deferred = M::deferred_columns();
+                let mut columns = M::columns();
+                columns.retain(|column| !deferred.contains(column));
+                let updated = update(executor, M::ValueSpaceImpl::default())
+                    .begin_dyn_set()
+                    .set_patch_columns(&*self, &columns)
+                    .finish_dyn_set();
+                let updated = match updated {
+                    Ok(builder) => builder.single(&*self).await?,
+                    // Nothing to write besides the primary key
+                    Err(_) => return Ok(false),
+                };
+                return if updated == 0 {
+                    Err(Error::DecodeError(format!(
+                        "Can't save {}: there is no row with the instance's primary key",
+                        M::TABLE
+                    )))
+                } else {
+                    Ok(false)
+                };
+            }
+
+            self.validate()?;
+            let primary = <M as Model>::Primary::NAME;
+            let (columns, values) = M::columns()
+                .into_iter()
+                .zip(self.references())
+                .filter(|(column, _)| *column != primary)
+                .unzip();
+            let inserted = insert(executor, M::ValueSpaceImpl::default())
+                .single_values(columns, values)
+                .await?;
+            *self = inserted;
+            Ok(true)
+        }
+    }
+}
@@ -31,6 +31,41 @@ pub trait Selector {
 
     /// Constructs a decoder and configures a [`QueryContext`] to query the required columns
     fn select(self, ctx: &mut QueryContext) -> Self::Decoder;
+
+    /// Convert the selector into a [`BoxedSelector`] to erase its concrete type
+    fn boxed(self) -> BoxedSelector<Self::Model, Self::Result>
+    where
+        Self: Sized + Send + 'static,
+        Self::Decoder: Send + Sync + 'static,
+    {
+        BoxedSelector {
+            select: Box::new(|ctx| Box::new(self.select(ctx))),
+            model: PhantomData,
+        }
+    }
+}
+
+/// A [`Selector`] whose concrete type has been erased using [`Selector::boxed`]
+///
+/// Every query selecting a `BoxedSelector<M, R>` shares the same [`QueryBuilder`](crate::crud::query::QueryBuilder) type,
+/// so its methods are only compiled once instead of once for every selector.
+/// This trades an allocation per query for less generated code.
+///
+/// Since its columns are unknown at compile time, it can't be used as the returning expression of an insert.
+pub struct BoxedSelector<M, R> {
+    #[allow(clippy::type_complexity)]
+    select: Box<dyn FnOnce(&mut QueryContext) -> Box<dyn Decoder<Result = R> + Send + Sync> + Send>,
+    model: PhantomData<fn() -> M>,
+}
+impl<M: Model, R> Selector for BoxedSelector<M, R> {
+    type Result = R;
+    type Model = M;
+    type Decoder = Box<dyn Decoder<Result = R> + Send + Sync>;
+    const INSERT_COMPATIBLE: bool = false;
+
+    fn select(self, ctx: &mut QueryContext) -> Self::Decoder {
+        (self.select)(ctx)
+    }
 }
 
 impl<F, P> Selector for FieldProxy<F, P>
@@ -115,3 +150,27 @@ macro_rules! selectable {
     };
 }
 rorm_macro::impl_tuple!(selectable, 1..33);
+
+/// [`Selector`] which additionally selects a field annotated with `#[rorm(defer)]`
+///
+/// It is created by [`QueryBuilder::with_deferred`](crate::crud::query::QueryBuilder::with_deferred).
+#[derive(Copy, Clone)]
+pub struct WithDeferred<S, F> {
+    pub(crate) selector: S,
+    pub(crate) field: PhantomData<F>,
+}
+impl<S, F> Selector for WithDeferred<S, F>
+where
+    S: Selector,
+    F: Field,
+{
+    type Result = S::Result;
+    type Model = S::Model;
+    type Decoder = S::Decoder;
+    const INSERT_COMPATIBLE: bool = S::INSERT_COMPATIBLE;
+
+    fn select(self, ctx: &mut QueryContext) -> Self::Decoder {
+        ctx.load_deferred::<F>();
+        self.selector.select(ctx)
+    }
+}
@@ -0,0 +1,150 @@
+//! Tracking which fields of a model instance have been modified
+//!
+//! A model annotated with `#[rorm(track_changes)]` can be wrapped in a [`Tracked`]
+//! whose [`save`](Tracked::save) only writes the columns which actually changed:
+//!
+//! ```no_run
+//! # use rorm::{Database, Error, Model};
+//! # use rorm::crud::tracked::Tracked;
+//! #[derive(Model, Clone)]
+//! #[rorm(track_changes)]
+//! pub struct User {
+//!     #[rorm(id)]
+//!     pub id: i64,
+//!
+//!     #[rorm(max_length = 255)]
+//!     pub name: String,
+//!
+//!     #[rorm(max_length = 255)]
+//!     pub bio: String,
+//! }
+//!
+//! pub async fn rename(db: &Database, user: User, name: String) -> Result<User, Error> {
+//!     let mut user = Tracked::new(user);
+//!     user.name = name;
+//!
+//!     // Executes `UPDATE user SET name = $1 WHERE id = $2`
+//!     user.save(db).await?;
+//!     Ok(user.into_inner())
+//! }
+//! ```
+//!
+//! Changes are detected by comparing the instance with a snapshot taken when it was wrapped,
+//! so the model has to implement [`Clone`] and every field has to implement [`PartialEq`].
+
+use std::ops::{Deref, DerefMut};
+
+use rorm_db::error::Error;
+use rorm_db::executor::Executor;
+
+use crate::crud::save::{Key, Save};
+use crate::crud::update::update;
+use crate::model::{Identifiable, Model};
+
+/// Compare two instances of a model field by field
+///
+/// This trait is implemented by `#[derive(Model)]` when the model is annotated with `#[rorm(track_changes)]`.
+pub trait TrackChanges: Model {
+    /// Push the columns of every field whose value differs between `self` and `original`
+    fn push_changed_columns(&self, original: &Self, columns: &mut Vec<&'static str>);
+}
+
+/// A model instance which remembers the values it has been loaded with
+///
+/// It dereferences to the wrapped instance, which can be read and modified as usual.
+pub struct Tracked<M> {
+    current: M,
+
+    /// Snapshot of the values last written to or read from the database
+    original: M,
+}
+
+impl<M: TrackChanges + Clone> Tracked<M> {
+    /// Start tracking changes to a model instance
+    ///
+    /// The instance is assumed to match its row in the database.
+    pub fn new(instance: M) -> Self {
+        Self {
+            original: instance.clone(),
+            current: instance,
+        }
+    }
+
+    /// Discard all modifications restoring the values the instance has been wrapped with
+    pub fn revert(&mut self) {
+        self.current = self.original.clone();
+    }
+
+    /// Write the modified columns to the database
+    ///
+    /// If the primary key is the default value, the instance is inserted using [`Save::save`].
+    /// Otherwise only the columns which changed are updated.
+    /// Afterward the instance is considered unmodified.
+    ///
+    /// Returns whether the instance has been inserted.
+    /// An error is returned if the primary key is set but there is no row to update.
+    pub async fn save<'ex>(&mut self, executor: impl Executor<'ex>) -> Result<bool, Error>
+    where
+        M: Identifiable,
+        Key<M>: Default + PartialEq,
+    {
+        if *self.current.get_primary_key() == Key::<M>::default() {
+            self.current.save(executor).await?;
+            self.original = self.current.clone();
+            return Ok(true);
+        }
+
+        let changed = self.changed_columns();
+        let updated = update(executor, M::ValueSpaceImpl::default())
+            .begin_dyn_set()
+            .set_patch_columns(&self.current, &changed)
+            .finish_dyn_set();
+        let updated = match updated {
+            Ok(builder) => builder.single(&self.current).await?,
+            // Nothing has changed
+            Err(_) => return Ok(false),
+        };
+        if updated == 0 {
+            return Err(Error::DecodeError(format!(
+                "Can't save {}: there is no row with the instance's primary key",
+                M::TABLE
+            )));
+        }
+        self.original = self.current.clone();
+        Ok(false)
+    }
+}
+
+impl<M: TrackChanges> Tracked<M> {
+    /// Get the columns whose values have been modified
+    pub fn changed_columns(&self) -> Vec<&'static str> {
+        let mut columns = Vec::new();
+        self.current
+            .push_changed_columns(&self.original, &mut columns);
+        columns
+    }
+
+    /// Check whether any field has been modified
+    pub fn is_changed(&self) -> bool {
+        !self.changed_columns().is_empty()
+    }
+
+    /// Stop tracking changes and get the instance back
+    pub fn into_inner(self) -> M {
+        self.current
+    }
+}
+
+impl<M> Deref for Tracked<M> {
+    type Target = M;
+
+    fn deref(&self) -> &Self::Target {
+        &self.current
+    }
+}
+
+impl<M> DerefMut for Tracked<M> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.current
+    }
+}
@@ -0,0 +1,123 @@
+//! Traversing models whose rows form a tree
+//!
+//! A model referencing itself through an optional [`ForeignModel`](crate::fields::types::ForeignModel)
+//! (like a post referencing the post it replies to) stores a tree.
+//! Implementing [`TreeModel`] for it by naming this field
+//! provides methods loading a row's [children](TreeModel::children),
+//! [descendants](TreeModel::descendants) and [ancestors](TreeModel::ancestors):
+//!
+//! ```no_run
+//! # use rorm::{field, Database, Error, Model};
+//! # use rorm::crud::tree::TreeModel;
+//! # use rorm::fields::types::ForeignModel;
+//! #[derive(Model)]
+//! pub struct Post {
+//!     #[rorm(id)]
+//!     pub id: i64,
+//!
+//!     #[rorm(max_length = 255)]
+//!     pub message: String,
+//!
+//!     pub reply_to: Option<ForeignModel<Post>>,
+//! }
+//!
+//! impl TreeModel for Post {
+//!     type Parent = field!(Post.reply_to);
+//! }
+//!
+//! pub async fn print_thread(db: &Database, post: i64) -> Result<(), Error> {
+//!     // The direct replies to the post
+//!     let replies = Post::children(db, Post.message, &post).await?;
+//!     println!("{} replies", replies.len());
+//!
+//!     // The post, all replies to it, their replies and so on
+//!     let thread = Post::descendants(db, Post.message, &post).await?;
+//!     println!("{} posts in the thread", thread.len());
+//!
+//!     // The post and the chain of posts it replies to
+//!     let chain = Post::ancestors(db, Post.message, &post).await?;
+//!     println!("{}", chain.join(" > "));
+//!
+//!     Ok(())
+//! }
+//! ```
+//!
+//! The descendants and ancestors are loaded using a single recursive query (see [`recursive`](super::recursive)).
+//! Every database supported by rorm implements `WITH RECURSIVE`,
+//! so the tree doesn't need an additional column storing each row's path.
+
+use std::future::Future;
+
+use rorm_db::error::Error;
+use rorm_db::executor::Executor;
+
+use crate::conditions::{Binary, BinaryOperator, Column};
+use crate::crud::query::query;
+use crate::crud::recursive::traverse;
+use crate::crud::selector::Selector;
+use crate::internal::field::{Field, FieldProxy, SingleColumnField};
+use crate::model::Model;
+
+/// Type of a model's primary key
+type Key<M> = <<M as Model>::Primary as Field>::Type;
+
+/// A model referencing itself through a field which stores each row's parent
+///
+/// See the [module docs](self) for an example.
+pub trait TreeModel: Model {
+    /// The field referencing a row's parent's primary key
+    ///
+    /// This is usually an `Option<ForeignModel<Self>>` which is `None` for the tree's roots.
+    type Parent: SingleColumnField<Model = Self>;
+
+    /// Select the rows whose parent is `parent`
+    fn children<'ex, 'k, E, S>(
+        executor: E,
+        selector: S,
+        parent: &'k Key<Self>,
+    ) -> impl Future<Output = Result<Vec<S::Result>, Error>>
+    where
+        E: Executor<'ex>,
+        S: Selector<Model = Self>,
+    {
+        query(executor, selector)
+            .condition(Binary {
+                operator: BinaryOperator::Equals,
+                fst_arg: Column(FieldProxy::<Self::Parent, Self>::new()),
+                snd_arg: Self::Primary::type_as_value(parent),
+            })
+            .all()
+    }
+
+    /// Select the row `root`, its children, their children and so on
+    ///
+    /// The `selector` may only select columns of the model itself (no related fields or aggregations),
+    /// otherwise an error is returned.
+    fn descendants<'ex, 'k, E, S>(
+        executor: E,
+        selector: S,
+        root: &'k Key<Self>,
+    ) -> impl Future<Output = Result<Vec<S::Result>, Error>>
+    where
+        E: Executor<'ex>,
+        S: Selector<Model = Self>,
+    {
+        traverse::<E, S, Self::Parent, Self::Primary>(executor, selector, [root])
+    }
+
+    /// Select the row `leaf`, its parent, its parent's parent and so on up to the tree's root
+    ///
+    /// The `selector` may only select columns of the model itself (no related fields or aggregations),
+    /// otherwise an error is returned.
+    fn ancestors<'ex, 'k, E, S>(
+        executor: E,
+        selector: S,
+        leaf: &'k Key<Self>,
+    ) -> impl Future<Output = Result<Vec<S::Result>, Error>>
+    where
+        E: Executor<'ex>,
+        S: Selector<Model = Self>,
+    {
+        traverse::<E, S, Self::Primary, Self::Parent>(executor, selector, [leaf])
+    }
+}
@@ -0,0 +1,163 @@
+//! Truncate builder removing all rows of a model's table at once
+
+use std::marker::PhantomData;
+
+use rorm_db::error::Error;
+#[cfg(feature = "all-drivers")]
+use rorm_db::executor::Optional;
+use rorm_db::executor::{Executor, Nothing};
+#[cfg(feature = "all-drivers")]
+use rorm_db::sql::value::Value;
+use rorm_db::sql::DBImpl;
+
+use crate::crud::selector::Selector;
+use crate::model::{Model, Patch};
+use crate::routing::Route;
+use crate::transaction::Scoped;
+
+/// Create a TRUNCATE query removing all rows of a model's table
+///
+/// Unlike [`delete(..).all()`](crate::crud::delete::DeleteBuilder::all),
+/// the rows are removed without scanning them, which is intended for cleaning up between tests
+/// or administrative tasks.
+/// Therefore, hooks, audit, counter caches, default scopes and tenants are not taken into account.
+///
+/// ```no_run
+/// # use rorm::{truncate, Database, Error, Model};
+/// # #[derive(Model)] pub struct User { #[rorm(id)] id: i64, }
+/// pub async fn reset_users(db: &Database) -> Result<(), Error> {
+///     truncate(db, User).restart_identity().execute().await
+/// }
+/// ```
+///
+/// The query depends on the database:
+/// - Postgres executes `TRUNCATE` with the builder's options.
+/// - MySQL executes `TRUNCATE` which always restarts the auto increment value.
+///     Cascading is not supported.
+/// - SQLite has no `TRUNCATE`, so `DELETE` is executed instead.
+///     Restarting the auto increment value resets the table's `sqlite_sequence` entry.
+///     Cascading is not supported.
+pub fn truncate<'ex, R, S>(executor: R, _: S) -> TruncateBuilder<R::Executor, S::Model>
+where
+    R: Route<'ex, S::Model>,
+    S: Selector<Model: Patch<ValueSpaceImpl = S>>,
+{
+    TruncateBuilder {
+        executor: executor.route(),
+        restart_identity: false,
+        cascade: false,
+
+        _phantom: PhantomData,
+    }
+}
+
+/// Builder for truncate queries
+///
+/// It is created by [`truncate`].
+///
+/// ## Generics
+/// - `E`: [`Executor`]
+///
+///     The executor to query with.
+///
+/// - `M`: [`Model`]
+///
+///     The model whose table to truncate.
+///
+#[must_use]
+pub struct TruncateBuilder<E, M> {
+    executor: Scoped<E>,
+    restart_identity: bool,
+    cascade: bool,
+
+    _phantom: PhantomData<M>,
+}
+
+impl<'ex, E, M> TruncateBuilder<E, M>
+where
+    E: Executor<'ex>,
+    M: Model,
+{
+    /// Restart the sequence generating the auto increment primary key
+    pub fn restart_identity(self) -> Self {
+        Self {
+            restart_identity: true,
+            ..self
+        }
+    }
+
+    /// Truncate the tables referencing the model's table as well
+    ///
+    /// This is only supported by Postgres, other databases will return an error.
+    pub fn cascade(self) -> Self {
+        Self {
+            cascade: true,
+            ..self
+        }
+    }
+
+    /// Execute the query
+    pub async fn execute(self) -> Result<(), Error> {
+        let mut tx = self.executor.begin().await?;
+        match tx.executor().dialect() {
+            DBImpl::Postgres => {
+                let mut sql = format!("TRUNCATE TABLE \"{}\"", M::TABLE);
+                if self.restart_identity {
+                    sql.push_str(" RESTART IDENTITY");
+                }
+                if self.cascade {
+                    sql.push_str(" CASCADE");
+                }
+                sql.push(';');
+                tx.executor().execute::<Nothing>(sql, Vec::new()).await?;
+            }
+            #[cfg(feature = "all-drivers")]
+            DBImpl::MySQL => {
+                require_no_cascade(self.cascade)?;
+                tx.executor()
+                    .execute::<Nothing>(format!("TRUNCATE TABLE `{}`;", M::TABLE), Vec::new())
+                    .await?;
+            }
+            #[cfg(feature = "all-drivers")]
+            DBImpl::SQLite => {
+                require_no_cascade(self.cascade)?;
+                tx.executor()
+                    .execute::<Nothing>(format!("DELETE FROM `{}`;", M::TABLE), Vec::new())
+                    .await?;
+
+                // The table only exists once a table using `AUTOINCREMENT` has been created
+                let sequences = if self.restart_identity {
+                    tx.executor().execute::<Optional>(
+                        "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'sqlite_sequence';"
+                            .to_string(),
+                        Vec::new(),
+                    )
+                    .await?
+                } else {
+                    None
+                };
+                if sequences.is_some() {
+                    tx.executor()
+                        .execute::<Nothing>(
+                            "DELETE FROM sqlite_sequence WHERE name = ?;".to_string(),
+                            vec![Value::String(M::TABLE)],
+                        )
+                        .await?;
+                }
+            }
+        }
+        tx.commit().await
+    }
+}
+
+/// Return an error if cascading has been requested from a database which doesn't support it
+#[cfg(feature = "all-drivers")]
+fn require_no_cascade(cascade: bool) -> Result<(), Error> {
+    if cascade {
+        Err(Error::DecodeError(
+            "Cascading truncates are only supported on postgres".to_string(),
+        ))
+    } else {
+        Ok(())
+    }
+}
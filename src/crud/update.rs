@@ -6,12 +6,22 @@ use rorm_db::database;
 use rorm_db::error::Error;
 use rorm_db::executor::Executor;
 
+use crate::audit;
 use crate::conditions::{Condition, DynamicCollection, Value};
+use crate::content_hash;
+use crate::crud::builder::AffectedRows;
+use crate::crud::comment;
+use crate::crud::query;
 use crate::crud::selector::Selector;
-use crate::internal::field::{FieldProxy, SingleColumnField};
+use crate::events::{self, ModelChange};
+use crate::internal::field::{Field, FieldProxy, SingleColumnField};
 use crate::internal::patch::{IntoPatchCow, PatchCow};
 use crate::internal::query_context::QueryContext;
-use crate::model::Identifiable;
+use crate::model::{Identifiable, ModelHooks};
+use crate::routing::Route;
+use crate::tenant;
+use crate::transaction::Scoped;
+use crate::validate::{ValidationError, ValidationErrors};
 use crate::{Model, Patch};
 
 /// Create a UPDATE query.
@@ -70,14 +80,18 @@ use crate::{Model, Patch};
 /// It will check the number of "sets" and return `Result` which is `Ok` for at least one and an
 /// `Err` for zero.
 /// Both variants contain the builder in "normal" mode to continue.
-pub fn update<'rf, 'e, E, S>(executor: E, _: S) -> UpdateBuilder<'rf, E, S::Model, columns::Empty>
+pub fn update<'rf, 'e, R, S>(
+    executor: R,
+    _: S,
+) -> UpdateBuilder<'rf, R::Executor, S::Model, columns::Empty>
 where
-    E: Executor<'e>,
+    R: Route<'e, S::Model>,
     S: Selector<Model: Patch<ValueSpaceImpl = S>>,
 {
     UpdateBuilder {
-        executor,
+        executor: executor.route(),
         columns: Vec::new(),
+        validation_errors: Vec::new(),
         _phantom: PhantomData,
     }
 }
@@ -104,8 +118,9 @@ where
 ///     Type state storing whether `set` has been called at least once.
 #[must_use]
 pub struct UpdateBuilder<'rf, E, M, C> {
-    executor: E,
+    executor: Scoped<E>,
     columns: Vec<(&'static str, Value<'rf>)>,
+    validation_errors: Vec<ValidationError>,
 
     _phantom: PhantomData<(M, C)>,
 }
@@ -131,13 +146,40 @@ where
 }
 
 impl<'rf, 'e, E, M, C> UpdateBuilder<'rf, E, M, C> {
+    /// Prepend a comment to the query attributing it to the code executing it
+    ///
+    /// See [`comment`](crate::crud::comment) for the format of `tags`.
+    pub fn comment(self, tags: &str) -> Self {
+        Self {
+            executor: comment::comment(self.executor, tags),
+            ..self
+        }
+    }
+
     fn set_column_state<C2>(self) -> UpdateBuilder<'rf, E, M, C2> {
         UpdateBuilder {
             executor: self.executor,
             columns: self.columns,
+            validation_errors: self.validation_errors,
             _phantom: PhantomData,
         }
     }
+
+    fn push_column<F: SingleColumnField>(&mut self, value: F::Type) {
+        const {
+            assert!(
+                !F::READONLY,
+                "Fields annotated with `#[rorm(readonly)]` can't be updated"
+            )
+        };
+        if let Err(kind) = F::validate(&value) {
+            self.validation_errors.push(ValidationError {
+                field: F::NAME,
+                kind,
+            });
+        }
+        self.columns.push((F::NAME, F::type_into_value(value)));
+    }
 }
 
 impl<'rf, E, M> UpdateBuilder<'rf, E, M, columns::Empty> {
@@ -159,7 +201,56 @@ impl<'rf, E, M> UpdateBuilder<'rf, E, M, columns::MaybeEmpty> {
     ///
     /// Can be called multiple times.
     pub fn set<F: SingleColumnField>(mut self, _field: FieldProxy<F, M>, value: F::Type) -> Self {
-        self.columns.push((F::NAME, F::type_into_value(value)));
+        self.push_column::<F>(value);
+        self
+    }
+
+    /// Add all columns of a patch except the primary key and `#[rorm(readonly)]` fields to update
+    ///
+    /// Can be called multiple times.
+    pub fn set_patch<P: Patch<Model = M>>(mut self, patch: &'rf P) -> Self
+    where
+        M: Model,
+    {
+        if let Err(ValidationErrors(errors)) = patch.validate() {
+            self.validation_errors.extend(errors);
+        }
+        let primary = <M as Model>::Primary::NAME;
+        let readonly = M::readonly_columns();
+        self.columns.extend(
+            P::columns()
+                .into_iter()
+                .zip(patch.references())
+                .filter(|(column, _)| *column != primary && !readonly.contains(column)),
+        );
+        self
+    }
+
+    /// Add the columns of a patch listed in `columns` to update
+    ///
+    /// The primary key and `#[rorm(readonly)]` fields are never updated, even if they are listed.
+    ///
+    /// Can be called multiple times.
+    pub fn set_patch_columns<P: Patch<Model = M>>(
+        mut self,
+        patch: &'rf P,
+        columns: &[&'static str],
+    ) -> Self
+    where
+        M: Model,
+    {
+        if let Err(ValidationErrors(errors)) = patch.validate() {
+            self.validation_errors.extend(errors);
+        }
+        let primary = <M as Model>::Primary::NAME;
+        let readonly = M::readonly_columns();
+        self.columns.extend(
+            P::columns()
+                .into_iter()
+                .zip(patch.references())
+                .filter(|(column, _)| *column != primary && !readonly.contains(column))
+                .filter(|(column, _)| columns.contains(column)),
+        );
         self
     }
 
@@ -206,7 +297,7 @@ where
         _field: FieldProxy<F, M>,
         value: F::Type,
     ) -> UpdateBuilder<'rf, E, M, columns::NonEmpty> {
-        self.columns.push((F::NAME, F::type_into_value(value)));
+        self.push_column::<F>(value);
         self.set_column_state()
     }
 }
@@ -219,7 +310,7 @@ where
     ///
     /// Can be called multiple times.
     pub fn set<F: SingleColumnField>(mut self, _field: FieldProxy<F, M>, value: F::Type) -> Self {
-        self.columns.push((F::NAME, F::type_into_value(value)));
+        self.push_column::<F>(value);
         self
     }
 }
@@ -232,7 +323,7 @@ where
     /// Update a single row identified by a patch instance
     ///
     /// Note: The patch only provides the primary key, its other values will be ignored.
-    pub async fn single<P>(self, patch: &P) -> Result<u64, Error>
+    pub async fn single<P>(self, patch: &P) -> Result<AffectedRows, Error>
     where
         P: Patch<Model = M> + Identifiable,
     {
@@ -251,7 +342,7 @@ where
     /// - `Vec<P>`
     /// - `&[P]`
     /// - A [`map`](Iterator::map) iterator yielding `P` or `&P`
-    pub async fn bulk<'p, I, P>(self, patches: I) -> Result<u64, Error>
+    pub async fn bulk<'p, I, P>(self, patches: I) -> Result<AffectedRows, Error>
     where
         I: IntoIterator,
         I::Item: IntoPatchCow<'p, Patch = P>,
@@ -269,33 +360,71 @@ where
             conditions.push(patch.as_condition());
         }
         if conditions.is_empty() {
-            Ok(0)
+            Ok(AffectedRows(0))
         } else {
             self.condition(DynamicCollection::or(conditions)).await
         }
     }
 
     /// Update all rows matching a condition
-    pub async fn condition<C: Condition<'rf>>(self, condition: C) -> Result<u64, Error> {
+    pub async fn condition<C: Condition<'rf>>(self, condition: C) -> Result<AffectedRows, Error> {
         let mut context = QueryContext::new();
-        let columns: Vec<_> = self
-            .columns
-            .iter()
-            .map(|(name, value)| (*name, value.as_sql()))
-            .collect();
-        let condition_index = context.add_condition(&condition);
-        let condition = context.get_condition(condition_index);
-        database::update(self.executor, M::TABLE, &columns, Some(&condition)).await
+        let condition_index = tenant::build_condition::<M>(&mut context, |context| {
+            Some(context.add_condition(&condition))
+        })?;
+        self.execute(context, condition_index).await
     }
 
     /// Update all rows
-    pub async fn all(self) -> Result<u64, Error> {
-        let columns: Vec<_> = self
-            .columns
+    pub async fn all(self) -> Result<AffectedRows, Error> {
+        let mut context = QueryContext::new();
+        let condition_index = tenant::build_condition::<M>(&mut context, |_| None)?;
+        self.execute(context, condition_index).await
+    }
+
+    async fn execute(
+        mut self,
+        mut context: QueryContext<'rf>,
+        condition_index: Option<usize>,
+    ) -> Result<AffectedRows, Error> {
+        ValidationErrors::from_vec(self.validation_errors)?;
+        context.check_joins()?;
+        content_hash::fill_update::<M>(&mut self.columns);
+        let updated = if M::Hooks::ENABLED || events::has_subscribers::<M>() {
+            let mut tx = self.executor.begin().await?;
+            let keys =
+                query::primary_keys::<M>(tx.executor(), &mut context, condition_index).await?;
+            M::Hooks::before_update(tx.transaction(), &self.columns, &keys).await?;
+            let updated =
+                update_rows::<M>(tx.executor(), &self.columns, &mut context, condition_index)
+                    .await?;
+            M::Hooks::after_update(tx.transaction(), &self.columns, &keys).await?;
+            events::publish::<M>(&tx, || ModelChange::updated(&self.columns, &keys));
+            tx.commit().await?;
+            updated
+        } else {
+            update_rows::<M>(self.executor, &self.columns, &mut context, condition_index).await?
+        };
+        Ok(AffectedRows(updated))
+    }
+}
+
+/// Update the rows matching the condition referenced by `condition_index`
+async fn update_rows<'ex, M: Model>(
+    executor: Scoped<impl Executor<'ex>>,
+    columns: &[(&'static str, Value<'_>)],
+    context: &mut QueryContext<'_>,
+    condition_index: Option<usize>,
+) -> Result<u64, Error> {
+    if M::AUDIT {
+        audit::update::<M>(executor, columns, context, condition_index).await
+    } else {
+        let columns: Vec<_> = columns
             .iter()
             .map(|(name, value)| (*name, value.as_sql()))
             .collect();
-        database::update(self.executor, M::TABLE, &columns, None).await
+        let condition = context.get_condition_opt(condition_index);
+        database::update(executor, M::TABLE, &columns, condition.as_ref()).await
     }
 }
 
@@ -304,6 +433,9 @@ where
 #[macro_export]
 macro_rules! update {
     ($db:expr, $model:path) => {
-        $crate::crud::update::UpdateBuilder::<_, $model, _>::new($db)
+        $crate::crud::update::update(
+            $db,
+            <$model as $crate::model::Patch>::ValueSpaceImpl::default(),
+        )
     };
 }
@@ -0,0 +1,243 @@
+//! Helpers for setting up and managing a [`Database`]
+
+use std::future::Future;
+use std::pin::pin;
+#[cfg(feature = "all-drivers")]
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+use std::time::Instant;
+
+use futures::future::{self, Either};
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+use rorm_db::executor::{Nothing, Optional};
+use rorm_db::sql::DBImpl;
+use rorm_db::{Database, Error, Executor};
+#[cfg(feature = "all-drivers")]
+use rorm_db::{DatabaseConfiguration, DatabaseDriver};
+
+/// Additional methods on [`Database`]
+///
+/// ```no_run
+/// # use rorm::{Database, Error};
+/// use rorm::database::DatabaseExt;
+///
+/// pub async fn setup_test_db() -> Result<Database, Error> {
+///     Database::sqlite_in_memory().await
+/// }
+/// ```
+pub trait DatabaseExt: Sized {
+    /// Connect to a new and empty SQLite database living in memory
+    ///
+    /// Unlike connecting to `:memory:`, every connection in the pool uses the same database
+    /// (by opening an in-memory database with a shared cache, whose name is unique within the process).
+    /// The pool always keeps one connection open, because the database is destroyed
+    /// when the last connection to it is closed.
+    ///
+    /// This is intended for tests, which can create one isolated database each.
+    #[cfg(feature = "all-drivers")]
+    fn sqlite_in_memory() -> impl Future<Output = Result<Self, Error>>;
+
+    /// Write a consistent copy of the database to the file at `path`
+    ///
+    /// This is only supported on SQLite, see [`backup`](crate::backup) for the other databases.
+    fn backup_to(&self, path: &str) -> impl Future<Output = Result<(), Error>>;
+
+    /// Execute a trivial query to check the database to be reachable
+    ///
+    /// Returns the query's round trip time.
+    ///
+    /// Not available on `wasm32-unknown-unknown` which doesn't provide a clock.
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    fn ping(&self) -> impl Future<Output = Result<Duration, Error>>;
+
+    /// Collect the database's status for a health check or readiness probe
+    ///
+    /// An error is returned if the database is not reachable.
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    fn health(&self) -> impl Future<Output = Result<Health, Error>>;
+
+    /// Get the features supported by the database
+    fn capabilities(&self) -> Capabilities;
+
+    /// Close the connection pool waiting for running queries until `timeout` completes
+    ///
+    /// The pool is closed for every clone of the database immediately,
+    /// so no new queries can be started and trying to do so returns an error.
+    /// Connections in use are closed once their queries have finished.
+    ///
+    /// `timeout` is usually a sleep from your async runtime, e.g. `tokio::time::sleep(Duration::from_secs(10))`.
+    ///
+    /// Returns whether all connections have been closed before `timeout` completed.
+    fn close_gracefully(self, timeout: impl Future<Output = ()>) -> impl Future<Output = bool>;
+}
+
+/// Features which are not supported by every database
+///
+/// It is returned by [`DatabaseExt::capabilities`] or [`Capabilities::for_dialect`]
+/// which can be used with an [`Executor`]'s [`dialect`](Executor::dialect):
+///
+/// ```no_run
+/// # use rorm::{Error, Executor};
+/// use rorm::database::Capabilities;
+///
+/// pub fn lock_clause(executor: &impl Executor<'_>) -> &'static str {
+///     if Capabilities::for_dialect(executor.dialect()).skip_locked {
+///         " FOR UPDATE SKIP LOCKED"
+///     } else {
+///         ""
+///     }
+/// }
+/// ```
+///
+/// The flags describe the oldest database versions supported by rorm
+/// (Postgres 12, MySQL 8 and SQLite 3.35).
+#[derive(Copy, Clone, Debug)]
+#[non_exhaustive]
+pub struct Capabilities {
+    /// Statements can return the rows they affected using `RETURNING`
+    pub returning: bool,
+
+    /// Strings can be compared case-insensitively using `ILIKE`
+    pub ilike: bool,
+
+    /// Strings can be matched against regular expressions without loading an extension
+    pub regexp: bool,
+
+    /// Window functions like `ROW_NUMBER() OVER (..)` are available
+    pub window_functions: bool,
+
+    /// Selected rows can be locked using `FOR UPDATE`
+    pub for_update: bool,
+
+    /// Locked rows can be skipped using `FOR UPDATE SKIP LOCKED`
+    pub skip_locked: bool,
+
+    /// Schema changes like `CREATE TABLE` can be rolled back as part of a transaction
+    pub transactional_ddl: bool,
+}
+
+impl Capabilities {
+    /// Get the features supported by a dialect
+    pub const fn for_dialect(dialect: DBImpl) -> Self {
+        match dialect {
+            DBImpl::Postgres => Self {
+                returning: true,
+                ilike: true,
+                regexp: true,
+                window_functions: true,
+                for_update: true,
+                skip_locked: true,
+                transactional_ddl: true,
+            },
+            #[cfg(feature = "all-drivers")]
+            DBImpl::MySQL => Self {
+                returning: false,
+                ilike: false,
+                regexp: true,
+                window_functions: true,
+                for_update: true,
+                skip_locked: true,
+                transactional_ddl: false,
+            },
+            #[cfg(feature = "all-drivers")]
+            DBImpl::SQLite => Self {
+                returning: true,
+                ilike: false,
+                regexp: false,
+                window_functions: true,
+                for_update: false,
+                skip_locked: false,
+                transactional_ddl: true,
+            },
+        }
+    }
+}
+
+/// Status of a database returned by [`DatabaseExt::health`]
+#[derive(Clone, Debug)]
+pub struct Health {
+    /// Round trip time of a trivial query
+    pub latency: Duration,
+
+    /// Name of the last migration applied by `rorm-cli`
+    ///
+    /// `None` if no migration has been applied yet.
+    pub last_migration: Option<String>,
+}
+
+impl DatabaseExt for Database {
+    #[cfg(feature = "all-drivers")]
+    fn sqlite_in_memory() -> impl Future<Output = Result<Self, Error>> {
+        /// Counter to give every in-memory database a unique name
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        let mut config = DatabaseConfiguration::new(DatabaseDriver::SQLite {
+            filename: format!("file:rorm-{id}?mode=memory&cache=shared"),
+        });
+        config.min_connections = 1;
+        Database::connect(config)
+    }
+
+    fn backup_to(&self, path: &str) -> impl Future<Output = Result<(), Error>> {
+        async move {
+            match self.dialect() {
+                #[cfg(feature = "all-drivers")]
+                DBImpl::SQLite => crate::backup::backup_sqlite(self, path).await,
+                #[allow(unreachable_patterns)]
+                _ => Err(Error::DecodeError(
+                    "Online backups are only supported on SQLite, see rorm::backup for alternatives"
+                        .to_string(),
+                )),
+            }
+        }
+    }
+
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    fn ping(&self) -> impl Future<Output = Result<Duration, Error>> {
+        async move {
+            let start = Instant::now();
+            self.execute::<Nothing>("SELECT 1;".to_string(), Vec::new())
+                .await?;
+            Ok(start.elapsed())
+        }
+    }
+
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    fn health(&self) -> impl Future<Output = Result<Health, Error>> {
+        async move {
+            let latency = self.ping().await?;
+
+            // The table doesn't exist before the first migration
+            let last_migration = self
+                .execute::<Optional>(
+                    "SELECT migration_name FROM _rorm__last_migration;".to_string(),
+                    Vec::new(),
+                )
+                .await
+                .ok()
+                .flatten()
+                .map(|row| row.get::<String, _>(0usize))
+                .transpose()?;
+
+            Ok(Health {
+                latency,
+                last_migration,
+            })
+        }
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::for_dialect(self.dialect())
+    }
+
+    fn close_gracefully(self, timeout: impl Future<Output = ()>) -> impl Future<Output = bool> {
+        async move {
+            match future::select(pin!(self.close()), pin!(timeout)).await {
+                Either::Left(((), _)) => true,
+                Either::Right(((), _)) => false,
+            }
+        }
+    }
+}
@@ -0,0 +1,201 @@
+//! In-process event bus notifying subscribers about changes to models
+//!
+//! After every successful insert, update or delete the crud builders publish a [`ModelEvent`]
+//! to all streams returned by [`subscribe`] for the affected model.
+//!
+//! Events are only published once the transaction containing the change has been committed:
+//!
+//! - Builders executed on a [`Database`](crate::Database) directly run in a transaction they start,
+//!   so their events are published as soon as the builder is done.
+//! - Transactions started with [`transaction::begin`](crate::transaction::begin) on a database
+//!   hold back the events of the queries executed on them until [`ScopedTransaction::commit`],
+//!   and discard them when the transaction is rolled back.
+//! - Changes made on a plain [`Transaction`](crate::Transaction), or on a [`ScopedTransaction`]
+//!   which [`transaction::begin`](crate::transaction::begin) borrowed from one,
+//!   don't publish any events, because the commit of the plain transaction can't be observed.
+//!   Start the transaction using [`transaction::begin`](crate::transaction::begin) instead.
+//!
+//! Only the insert, update and delete builders publish events.
+//! Changes made using raw sql or by [truncating](crate::crud::truncate) a table are not published.
+//!
+//! ```no_run
+//! # use futures::StreamExt;
+//! # use rorm::{Database, Error, Model, Patch, insert};
+//! # use rorm::events::{subscribe, ModelChange};
+//! # use rorm::transaction;
+//! # #[derive(Model)] pub struct User { #[rorm(id)] id: i64, #[rorm(max_length = 255)] name: String, }
+//! # #[derive(Patch)] #[rorm(model = "User")] pub struct NewUser { name: String, }
+//! pub async fn log_new_users() {
+//!     let mut events = subscribe::<User>();
+//!     while let Some(event) = events.next().await {
+//!         if let ModelChange::Created { rows, .. } = &event.change {
+//!             println!("{} users have been created", rows.len());
+//!         }
+//!     }
+//! }
+//!
+//! pub async fn create_users(db: &Database, users: &[NewUser]) -> Result<(), Error> {
+//!     let mut tx = transaction::begin(db).await?;
+//!     insert(&mut tx, User).return_nothing().bulk(users).await?;
+//!     // The event is published here
+//!     tx.commit().await
+//! }
+//! ```
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::fmt;
+use std::marker::PhantomData;
+use std::sync::{Arc, LazyLock, Mutex};
+
+use futures::channel::mpsc;
+
+use crate::conditions::Value;
+use crate::model::Model;
+use crate::transaction::ScopedTransaction;
+
+/// A change to the table of the model `M`
+pub struct ModelEvent<M: Model> {
+    /// The change which has been made
+    pub change: ModelChange,
+
+    model: PhantomData<fn() -> M>,
+}
+impl<M: Model> Clone for ModelEvent<M> {
+    fn clone(&self) -> Self {
+        Self {
+            change: self.change.clone(),
+            model: PhantomData,
+        }
+    }
+}
+impl<M: Model> fmt::Debug for ModelEvent<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ModelEvent")
+            .field("model", &M::TABLE)
+            .field("change", &self.change)
+            .finish()
+    }
+}
+
+/// The change to a model's table described by a [`ModelEvent`]
+#[derive(Clone, Debug)]
+pub enum ModelChange {
+    /// Rows have been inserted
+    Created {
+        /// The columns which have been set explicitly
+        columns: Vec<&'static str>,
+
+        /// The values for `columns` of every inserted row
+        rows: Vec<Vec<Value<'static>>>,
+    },
+
+    /// Rows have been updated
+    Updated {
+        /// The columns which have been set and their new values
+        columns: Vec<(&'static str, Value<'static>)>,
+
+        /// The primary keys of the updated rows
+        keys: Vec<Value<'static>>,
+    },
+
+    /// Rows have been deleted
+    Deleted {
+        /// The primary keys of the deleted rows
+        keys: Vec<Value<'static>>,
+    },
+}
+impl ModelChange {
+    pub(crate) fn created(columns: &[&'static str], rows: &[&[Value<'_>]]) -> Self {
+        ModelChange::Created {
+            columns: columns.to_vec(),
+            rows: rows
+                .iter()
+                .map(|row| row.iter().cloned().map(Value::into_static).collect())
+                .collect(),
+        }
+    }
+
+    pub(crate) fn updated(columns: &[(&'static str, Value<'_>)], keys: &[Value<'static>]) -> Self {
+        ModelChange::Updated {
+            columns: columns
+                .iter()
+                .map(|(name, value)| (*name, value.clone().into_static()))
+                .collect(),
+            keys: keys.to_vec(),
+        }
+    }
+}
+
+/// Stream of [`ModelEvent`]s returned by [`subscribe`]
+pub type EventStream<M> = mpsc::UnboundedReceiver<Arc<ModelEvent<M>>>;
+
+/// Sender of a subscription to `M`'s events
+type Subscriber<M> = mpsc::UnboundedSender<Arc<ModelEvent<M>>>;
+
+/// Subscribe to all changes of a model
+///
+/// The subscription ends when the returned stream is dropped.
+pub fn subscribe<M: Model>() -> EventStream<M> {
+    let (sender, receiver) = mpsc::unbounded();
+    with_subscribers::<M, _>(|subscribers| subscribers.push(sender));
+    receiver
+}
+
+/// Check whether a model has any subscribers
+///
+/// The builders only start a transaction to publish events if this is the case.
+pub(crate) fn has_subscribers<M: Model>() -> bool {
+    with_subscribers::<M, _>(|subscribers| !subscribers.is_empty())
+}
+
+/// Publish an event to a model's subscribers once `tx` has been committed
+///
+/// The event is only constructed if there are any subscribers.
+/// See the [module docs](self) for the transactions which don't publish events.
+pub(crate) fn publish<M: Model>(tx: &ScopedTransaction<'_>, change: impl FnOnce() -> ModelChange) {
+    let Some(events) = tx.events() else {
+        return;
+    };
+    if has_subscribers::<M>() {
+        let event = Arc::new(ModelEvent::<M> {
+            change: change(),
+            model: PhantomData,
+        });
+        events.0.lock().unwrap().push(Box::new(move || {
+            with_subscribers::<M, _>(|subscribers| {
+                subscribers.retain(|sender| sender.unbounded_send(event.clone()).is_ok())
+            })
+        }));
+    }
+}
+
+/// Events held back until the transaction they have been published in is committed
+///
+/// Every event is stored as function sending it to its model's subscribers.
+#[derive(Clone, Default)]
+pub(crate) struct EventBuffer(Arc<Mutex<Vec<Box<dyn FnOnce() + Send>>>>);
+impl EventBuffer {
+    /// Send the events to their model's subscribers
+    pub(crate) fn flush(self) {
+        let events = std::mem::take(&mut *self.0.lock().unwrap());
+        for send in events {
+            send();
+        }
+    }
+}
+
+/// Run `f` on the senders of all subscriptions to `M`
+fn with_subscribers<M: Model, T>(f: impl FnOnce(&mut Vec<Subscriber<M>>) -> T) -> T {
+    let mut subscribers = SUBSCRIBERS.lock().unwrap();
+    let subscribers = subscribers
+        .entry(TypeId::of::<M>())
+        .or_insert_with(|| Box::new(Vec::<Subscriber<M>>::new()));
+    f(subscribers
+        .downcast_mut()
+        .expect("The subscribers are stored by their model's TypeId"))
+}
+
+/// Senders of all subscriptions as `Vec<Subscriber<M>>` grouped by their model's [`TypeId`]
+static SUBSCRIBERS: LazyLock<Mutex<HashMap<TypeId, Box<dyn Any + Send>>>> =
+    LazyLock::new(Default::default);
@@ -0,0 +1,372 @@
+//! Stable api for implementing custom field types
+//!
+//! Implementing [`FieldType`] directly requires items from [`rorm::internal`](crate::internal)
+//! which are not considered public api and might change with every release.
+//!
+//! Most custom types are just a different representation of an existing single column type.
+//! For those, implement [`CustomFieldType`] and invoke [`impl_CustomFieldType!`](crate::impl_CustomFieldType):
+//!
+//! ```no_run
+//! use rorm::{FieldAccess, Model};
+//! use rorm::fields::custom::CustomFieldType;
+//!
+//! /// Amount of stars between 0 and 5
+//! #[derive(Copy, Clone, Debug)]
+//! pub struct StarsAmount(u8);
+//!
+//! impl CustomFieldType for StarsAmount {
+//!     type Inner = i16;
+//!
+//!     fn to_inner(&self) -> i16 {
+//!         self.0 as i16
+//!     }
+//!
+//!     fn from_inner(inner: i16) -> Result<Self, String> {
+//!         match inner {
+//!             0..=5 => Ok(Self(inner as u8)),
+//!             _ => Err(format!("{inner} is not a valid amount of stars")),
+//!         }
+//!     }
+//! }
+//! rorm::impl_CustomFieldType!(StarsAmount);
+//!
+//! #[derive(Model)]
+//! pub struct Review {
+//!     #[rorm(id)]
+//!     id: i64,
+//!
+//!     stars: StarsAmount,
+//! }
+//!
+//! // Conditions work just like with the inner type
+//! let condition = Review.stars.equals(StarsAmount(5));
+//! ```
+//!
+//! # Choosing an approach
+//!
+//! All approaches below store the value in a single column of an existing type
+//! and support the same conditions as that type:
+//!
+//! - [`derive(NewType)`](rorm_macro::NewType) for your own tuple struct wrapping a single field type,
+//!   like `struct Money(i64)`.
+//!   The conversion is generated, only invariants have to be written as constructor.
+//! - [`CustomFieldType`] for your own types whose representation differs from the stored type,
+//!   like `StarsAmount` above storing an `u8` as `i16`.
+//! - [`FieldAdapter`] for types defined in other crates, like `Duration` or `Url`.
+//!   The orphan rule prevents implementing [`CustomFieldType`] for them,
+//!   so the field's type is wrapped in [`Adapted`] naming the conversion instead.
+//!   This also allows storing the same type differently in different fields.
+//!
+//! Types which need more control (for example multiple columns) still have to implement [`FieldType`] themselves.
+//! The items they need are re-exported from this module and will stay stable.
+
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+
+use rorm_db::row::{DecodeOwned, RowError};
+use rorm_db::Row;
+
+pub use crate::conditions::Value;
+pub use crate::crud::decoder::Decoder;
+pub use crate::db::sql::value::NullType;
+pub use crate::fields::traits::{Array, Columns, FieldColumns, FieldEq, FieldOrd, FieldType};
+pub use crate::fields::utils::check::{shared_linter_check, string_check};
+pub use crate::fields::utils::get_annotations::forward_annotations;
+pub use crate::fields::utils::get_names::single_column_name;
+use crate::impl_FieldEq;
+pub use crate::internal::field::decoder::FieldDecoder;
+use crate::internal::field::{Field, FieldProxy};
+pub use crate::internal::hmr::annotations::Annotations;
+use crate::internal::query_context::QueryContext;
+use crate::internal::relation_path::Path;
+
+/// A type which is stored as another single column [`FieldType`]
+///
+/// Implement this trait and invoke [`impl_CustomFieldType!`](crate::impl_CustomFieldType)
+/// to use your type as field in models.
+pub trait CustomFieldType: Sized + 'static {
+    /// The type this type is stored as in the database
+    type Inner: FieldType<Columns = Array<1>> + DecodeOwned;
+
+    /// Convert into the type stored in the database
+    fn to_inner(&self) -> Self::Inner;
+
+    /// Convert from the type stored in the database
+    ///
+    /// The error is reported as [`RowError::Decode`] when decoding a row fails.
+    fn from_inner(inner: Self::Inner) -> Result<Self, String>;
+}
+
+/// [`FieldDecoder`] used by [`impl_CustomFieldType!`](crate::impl_CustomFieldType)
+pub struct CustomDecoder<T> {
+    column: String,
+    index: usize,
+    result: PhantomData<T>,
+}
+impl<T: CustomFieldType> Decoder for CustomDecoder<T> {
+    type Result = T;
+
+    fn by_name<'index>(&'index self, row: &'_ Row) -> Result<Self::Result, RowError<'index>> {
+        let inner: T::Inner = row.get(self.column.as_str())?;
+        T::from_inner(inner).map_err(|error| RowError::Decode {
+            index: self.column.as_str().into(),
+            source: error.into(),
+        })
+    }
+
+    fn by_index<'index>(&'index self, row: &'_ Row) -> Result<Self::Result, RowError<'index>> {
+        let inner: T::Inner = row.get(self.index)?;
+        T::from_inner(inner).map_err(|error| RowError::Decode {
+            index: self.index.into(),
+            source: error.into(),
+        })
+    }
+}
+impl<T: CustomFieldType> FieldDecoder for CustomDecoder<T> {
+    fn new<F, P>(ctx: &mut QueryContext, _: FieldProxy<F, P>) -> Self
+    where
+        F: Field<Type = Self::Result>,
+        P: Path,
+    {
+        let (index, column) = ctx.select_field::<F, P>();
+        Self {
+            column,
+            index,
+            result: PhantomData,
+        }
+    }
+}
+
+/// Convert a custom type into its single [`Value`]
+///
+/// This function is used by [`impl_CustomFieldType!`](crate::impl_CustomFieldType).
+pub fn into_value<'a, T: CustomFieldType>(value: &T) -> Value<'a> {
+    let [value] = <T::Inner as FieldType>::into_values(value.to_inner());
+    value
+}
+
+/// Implement [`FieldType`] and [`FieldEq`] for a type implementing [`CustomFieldType`]
+///
+/// Names, annotations and their checks are forwarded from the inner type.
+///
+/// Pass `ord` to also implement [`FieldOrd`] if the inner type implements it:
+/// `impl_CustomFieldType!(MyType, ord);`
+#[allow(non_snake_case)] // makes it clearer that a trait and which trait is meant
+#[macro_export]
+macro_rules! impl_CustomFieldType {
+    ($type:ty, ord) => {
+        $crate::impl_CustomFieldType!($type);
+
+        $crate::impl_FieldOrd!($type, $type, |value: $type| $crate::fields::custom::into_value(&value));
+        $crate::impl_FieldOrd!($type, &'rhs $type, |value: &'rhs $type| $crate::fields::custom::into_value(value));
+    };
+    ($type:ty) => {
+        impl $crate::fields::custom::FieldType for $type {
+            type Columns = $crate::fields::custom::Array<1>;
+
+            const NULL: $crate::fields::custom::FieldColumns<
+                Self,
+                $crate::fields::custom::NullType,
+            > = <<$type as $crate::fields::custom::CustomFieldType>::Inner as $crate::fields::custom::FieldType>::NULL;
+
+            fn into_values<'a>(
+                self,
+            ) -> $crate::fields::custom::FieldColumns<Self, $crate::fields::custom::Value<'a>> {
+                [$crate::fields::custom::into_value(&self)]
+            }
+
+            fn as_values(
+                &self,
+            ) -> $crate::fields::custom::FieldColumns<Self, $crate::fields::custom::Value<'_>> {
+                [$crate::fields::custom::into_value(self)]
+            }
+
+            type Decoder = $crate::fields::custom::CustomDecoder<Self>;
+
+            type GetNames = <<$type as $crate::fields::custom::CustomFieldType>::Inner as $crate::fields::custom::FieldType>::GetNames;
+
+            type GetAnnotations = <<$type as $crate::fields::custom::CustomFieldType>::Inner as $crate::fields::custom::FieldType>::GetAnnotations;
+
+            type Check = <<$type as $crate::fields::custom::CustomFieldType>::Inner as $crate::fields::custom::FieldType>::Check;
+        }
+
+        $crate::impl_FieldEq!(impl<'rhs> FieldEq<'rhs, $type> for $type {|value: $type| $crate::fields::custom::into_value(&value)});
+        $crate::impl_FieldEq!(impl<'rhs> FieldEq<'rhs, &'rhs $type> for $type {|value: &'rhs $type| $crate::fields::custom::into_value(value)});
+    };
+}
+
+/// A conversion storing values of the type `T` as another single column [`FieldType`]
+///
+/// Prefer [`CustomFieldType`] or [`derive(NewType)`](rorm_macro::NewType) for types defined in your own crate,
+/// see the [module docs](self) for a comparison.
+///
+/// It is used by wrapping the field's type in [`Adapted`]:
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// use rorm::Model;
+/// use rorm::fields::custom::{Adapted, FieldAdapter};
+///
+/// /// Stores a [`Duration`] as milliseconds
+/// pub struct Millis;
+///
+/// impl FieldAdapter<Duration> for Millis {
+///     type Stored = i64;
+///
+///     fn to_stored(value: &Duration) -> i64 {
+///         value.as_millis() as i64
+///     }
+///
+///     fn from_stored(stored: i64) -> Result<Duration, String> {
+///         u64::try_from(stored)
+///             .map(Duration::from_millis)
+///             .map_err(|_| format!("{stored} is not a valid duration"))
+///     }
+/// }
+///
+/// #[derive(Model)]
+/// pub struct Job {
+///     #[rorm(id)]
+///     id: i64,
+///
+///     timeout: Adapted<Duration, Millis>,
+/// }
+///
+/// let condition = Job.timeout.equals(Adapted::new(Duration::from_secs(60)));
+/// ```
+pub trait FieldAdapter<T>: 'static {
+    /// The type `T` is stored as in the database
+    type Stored: FieldType<Columns = Array<1>> + DecodeOwned;
+
+    /// Convert into the type stored in the database
+    fn to_stored(value: &T) -> Self::Stored;
+
+    /// Convert from the type stored in the database
+    ///
+    /// The error is reported as [`RowError::Decode`] when decoding a row fails.
+    fn from_stored(stored: Self::Stored) -> Result<T, String>;
+}
+
+/// A `T` which is stored in the database using the [`FieldAdapter`] `A`
+///
+/// It dereferences to the wrapped value.
+pub struct Adapted<T, A> {
+    /// The wrapped value
+    pub value: T,
+    adapter: PhantomData<fn() -> A>,
+}
+
+impl<T, A> Adapted<T, A> {
+    /// Wrap a value
+    pub const fn new(value: T) -> Self {
+        Self {
+            value,
+            adapter: PhantomData,
+        }
+    }
+
+    /// Unwrap the value
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T, A> From<T> for Adapted<T, A> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T, A> Deref for Adapted<T, A> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl<T, A> DerefMut for Adapted<T, A> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.value
+    }
+}
+
+impl<T: Clone, A> Clone for Adapted<T, A> {
+    fn clone(&self) -> Self {
+        Self::new(self.value.clone())
+    }
+}
+
+impl<T: Copy, A> Copy for Adapted<T, A> {}
+
+impl<T: Default, A> Default for Adapted<T, A> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: fmt::Debug, A> fmt::Debug for Adapted<T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+impl<T: PartialEq, A> PartialEq for Adapted<T, A> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T: Eq, A> Eq for Adapted<T, A> {}
+
+impl<T: Hash, A> Hash for Adapted<T, A> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.value.hash(state)
+    }
+}
+
+impl<T: 'static, A: FieldAdapter<T>> CustomFieldType for Adapted<T, A> {
+    type Inner = A::Stored;
+
+    fn to_inner(&self) -> Self::Inner {
+        A::to_stored(&self.value)
+    }
+
+    fn from_inner(inner: Self::Inner) -> Result<Self, String> {
+        A::from_stored(inner).map(Self::new)
+    }
+}
+
+impl<T: 'static, A: FieldAdapter<T>> FieldType for Adapted<T, A> {
+    type Columns = Array<1>;
+
+    const NULL: FieldColumns<Self, NullType> = <A::Stored as FieldType>::NULL;
+
+    fn into_values<'a>(self) -> FieldColumns<Self, Value<'a>> {
+        [into_value(&self)]
+    }
+
+    fn as_values(&self) -> FieldColumns<Self, Value<'_>> {
+        [into_value(self)]
+    }
+
+    type Decoder = CustomDecoder<Self>;
+
+    type GetNames = <A::Stored as FieldType>::GetNames;
+
+    type GetAnnotations = <A::Stored as FieldType>::GetAnnotations;
+
+    type Check = <A::Stored as FieldType>::Check;
+}
+
+impl_FieldEq!(impl<'rhs, T, A> FieldEq<'rhs, Adapted<T, A>> for Adapted<T, A> where A: FieldAdapter<T>, { |value: Adapted<T, A>| adapted_value::<T, A>(&value.value) });
+impl_FieldEq!(impl<'rhs, T, A> FieldEq<'rhs, &'rhs Adapted<T, A>> for Adapted<T, A> where A: FieldAdapter<T>, { |value: &'rhs Adapted<T, A>| adapted_value::<T, A>(&value.value) });
+
+/// Convert a value into the single [`Value`] its [`FieldAdapter`] stores it as
+fn adapted_value<'a, T, A: FieldAdapter<T>>(value: &T) -> Value<'a> {
+    let [value] = <A::Stored as FieldType>::into_values(A::to_stored(value));
+    value
+}
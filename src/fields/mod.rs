@@ -2,21 +2,35 @@
 //!
 //! # Std types
 //! - [`bool`]
+//! - [`i8`] (stored as `i16`)
 //! - [`i16`]
 //! - [`i32`]
 //! - [`i64`]
+//! - [`u8`] (stored as `i16`)
+//! - [`u16`] (stored as `i32`)
+//! - [`u32`] (stored as `i64`)
+//! - [`u64`] (stored as `i64`, larger values are rejected)
 //! - [`f32`]
 //! - [`f64`]
 //! - [`String`]
 //! - [`Vec<u8>`]
+//! - [`Cow<'static, str>`](std::borrow::Cow) (stored like `String`)
+//! - [`Cow<'static, [u8]>`](std::borrow::Cow) (stored like `Vec<u8>`)
 //! - [`Option<T>`] where `T` is on this list
 //!
 //! # Our types
 //! - [`ForeignModel<M>`](types::ForeignModel)
+//! - [`PolymorphicForeignModel<(A, B, ..)>`](types::PolymorphicForeignModel)
 //! - [`BackRef<M>`](types::BackRef) (doesn't work inside an [`Option<T>`])
 //! - [`Json<T>`](types::Json)
 //! - [`MsgPack<T>`](types::MsgPack) (requires the "msgpack" feature)
+//! - [`Binary<T, F>`](types::Binary) (its formats require the "msgpack", "bincode" or "postcard" feature)
+//! - [`Compressed<T, C>`](types::Compressed) (its algorithms require the "zstd" or "lz4" feature)
 //! - [`MaxStr`](types::MaxStr)
+//! - [`OneOf<C>`](types::OneOf)
+//! - [`Ulid`](types::Ulid) (generated when inserted as primary key)
+//! - [`Snowflake`](types::Snowflake) (generated when inserted as primary key)
+//! - [`BitFlags<T>`](types::BitFlags) (requires the "bitflags" feature)
 //!
 //! # chrono types (requires the "chrono" feature)
 //! - [`NaiveDateTime`](chrono::NaiveDateTime)
@@ -36,6 +50,9 @@
 //! # url types (requires the "url" feature)
 //! - [`Url`](url::Url)
 //!
+//! # Custom types
+//! See [`custom`] for how to implement your own field types.
+//!
 //! ---
 //!
 //! ```no_run
@@ -87,6 +104,7 @@
 //! }
 //! ```
 
+pub mod custom;
 pub mod traits;
 pub mod types;
 pub mod utils;
@@ -17,6 +17,7 @@ use crate::internal::hmr::annotations::Annotations;
 use crate::internal::query_context::QueryContext;
 use crate::internal::relation_path::Path;
 use crate::sealed;
+use crate::validate::ValidationErrorKind;
 
 pub mod aggregate;
 pub mod cmp;
@@ -56,6 +57,25 @@ pub trait FieldType: 'static {
         Result<(), ConstString<1024>>,
     >;
 
+    /// Check a value to be representable in the database
+    ///
+    /// This is called by the crud builders before inserting or updating a value
+    /// and reported just like a failed `#[rorm(validate(..))]`.
+    fn check_value(&self) -> Result<(), ValidationErrorKind> {
+        Ok(())
+    }
+
+    /// Generate a value for a primary key which isn't set by an insert
+    ///
+    /// Types like [`Ulid`](crate::fields::types::Ulid) generate their keys client-side,
+    /// all other types leave it to the database's auto increment or default.
+    fn generate_key() -> Option<Self>
+    where
+        Self: Sized,
+    {
+        None
+    }
+
     #[doc(hidden)]
     fn is_option<Private: crate::private::Private>() -> bool {
         false
@@ -119,6 +139,10 @@ impl<T: FieldType> FieldType for Option<T> {
     type GetAnnotations = T::GetAnnotations;
     type Check = T::Check;
 
+    fn check_value(&self) -> Result<(), ValidationErrorKind> {
+        self.as_ref().map_or(Ok(()), T::check_value)
+    }
+
     fn is_option<Private: crate::private::Private>() -> bool {
         true
     }
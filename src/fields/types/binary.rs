@@ -0,0 +1,260 @@
+//! The [`Binary<T, F>`] wrapper to store data in a compact binary format in the db
+
+use std::borrow::Cow;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+
+use rorm_db::sql::value::NullType;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::conditions::Value;
+use crate::fields::traits::{Array, FieldColumns, FieldType};
+use crate::fields::utils::check::shared_linter_check;
+use crate::fields::utils::get_annotations::forward_annotations;
+use crate::fields::utils::get_names::single_column_name;
+use crate::new_converting_decoder;
+use crate::validate::ValidationErrorKind;
+
+/// A serde data format used by [`Binary`]
+///
+/// rorm provides `MessagePack`, `Bincode` and `Postcard`
+/// behind the features `msgpack`, `bincode` and `postcard` respectively.
+pub trait BinaryFormat: 'static {
+    /// Serialize a value
+    fn serialize<T: Serialize>(value: &T) -> Result<Vec<u8>, String>;
+
+    /// Deserialize a value
+    fn deserialize<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, String>;
+}
+
+/// Stores data by serializing it to a binary format.
+///
+/// Unlike [`Json`](super::Json), the stored data is not human-readable,
+/// but more compact and faster to (de)serialize.
+/// This makes it a good fit for internal structs which are only read by the application itself.
+///
+/// The format is chosen using the type parameter `F`:
+///
+/// ```no_run
+/// # #[cfg(feature = "postcard")] mod example {
+/// # use std::collections::HashMap;
+/// use rorm::Model;
+/// use rorm::fields::types::{Binary, Postcard};
+///
+/// #[derive(Model)]
+/// pub struct Session {
+///     #[rorm(id)]
+///     pub id: i64,
+///
+///     pub data: Binary<HashMap<String, String>, Postcard>,
+/// }
+/// # }
+/// ```
+///
+/// Values which fail to serialize are rejected by inserts and updates
+/// with a [`ValidationErrorKind::Encoding`].
+///
+/// Note that formats like bincode and postcard are not self-describing.
+/// Changing `T` makes the values stored before undecodable.
+pub struct Binary<T: Serialize + DeserializeOwned, F: BinaryFormat>(pub T, PhantomData<fn() -> F>);
+
+impl<T: Serialize + DeserializeOwned, F: BinaryFormat> Binary<T, F> {
+    /// Wrap a value
+    pub const fn new(value: T) -> Self {
+        Self(value, PhantomData)
+    }
+
+    /// Unwrap into inner T value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+new_converting_decoder!(
+    pub BinaryDecoder<T: Serialize + DeserializeOwned, F: BinaryFormat>,
+    |value: Vec<u8>| -> Binary<T, F> {
+        F::deserialize(&value)
+            .map(Binary::new)
+            .map_err(|err| format!("Couldn't decode binary data: {err}"))
+    }
+);
+impl<T: Serialize + DeserializeOwned + 'static, F: BinaryFormat> FieldType for Binary<T, F> {
+    type Columns = Array<1>;
+
+    const NULL: FieldColumns<Self, NullType> = [NullType::Binary];
+
+    // A failed serialization is reported by `check_value` which aborts the query
+    fn into_values<'a>(self) -> FieldColumns<Self, Value<'a>> {
+        [Value::Binary(Cow::Owned(
+            F::serialize(&self.0).unwrap_or_default(),
+        ))]
+    }
+
+    fn as_values(&self) -> FieldColumns<Self, Value<'_>> {
+        [Value::Binary(Cow::Owned(
+            F::serialize(&self.0).unwrap_or_default(),
+        ))]
+    }
+
+    type Decoder = BinaryDecoder<T, F>;
+
+    type GetAnnotations = forward_annotations<1>;
+    type Check = shared_linter_check<1>;
+    type GetNames = single_column_name;
+
+    fn check_value(&self) -> Result<(), ValidationErrorKind> {
+        F::serialize(&self.0)
+            .map(drop)
+            .map_err(|message| ValidationErrorKind::Encoding { message })
+    }
+}
+
+new_converting_decoder!(
+    pub OptionBinaryDecoder<T: Serialize + DeserializeOwned, F: BinaryFormat>,
+    |value: Option<Vec<u8>>| -> Option<Binary<T, F>> {
+        value
+            .map(|value| {
+                F::deserialize(&value)
+                    .map(Binary::new)
+                    .map_err(|err| format!("Couldn't decode binary data: {err}"))
+            })
+            .transpose()
+    }
+);
+
+/// [`BinaryFormat`] using [rmp_serde]
+#[cfg(feature = "msgpack")]
+#[derive(Copy, Clone, Debug)]
+pub struct MessagePack;
+#[cfg(feature = "msgpack")]
+impl BinaryFormat for MessagePack {
+    fn serialize<T: Serialize>(value: &T) -> Result<Vec<u8>, String> {
+        rmp_serde::to_vec(value).map_err(|err| err.to_string())
+    }
+
+    fn deserialize<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, String> {
+        rmp_serde::from_slice(bytes).map_err(|err| err.to_string())
+    }
+}
+
+/// [`BinaryFormat`] using [bincode]
+#[cfg(feature = "bincode")]
+#[derive(Copy, Clone, Debug)]
+pub struct Bincode;
+#[cfg(feature = "bincode")]
+impl BinaryFormat for Bincode {
+    fn serialize<T: Serialize>(value: &T) -> Result<Vec<u8>, String> {
+        bincode::serialize(value).map_err(|err| err.to_string())
+    }
+
+    fn deserialize<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, String> {
+        bincode::deserialize(bytes).map_err(|err| err.to_string())
+    }
+}
+
+/// [`BinaryFormat`] using [postcard]
+#[cfg(feature = "postcard")]
+#[derive(Copy, Clone, Debug)]
+pub struct Postcard;
+#[cfg(feature = "postcard")]
+impl BinaryFormat for Postcard {
+    fn serialize<T: Serialize>(value: &T) -> Result<Vec<u8>, String> {
+        postcard::to_allocvec(value).map_err(|err| err.to_string())
+    }
+
+    fn deserialize<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, String> {
+        postcard::from_bytes(bytes).map_err(|err| err.to_string())
+    }
+}
+
+// Derives would require `F` to implement the traits as well
+impl<T: Serialize + DeserializeOwned + Clone, F: BinaryFormat> Clone for Binary<T, F> {
+    fn clone(&self) -> Self {
+        Self::new(self.0.clone())
+    }
+}
+impl<T: Serialize + DeserializeOwned + Copy, F: BinaryFormat> Copy for Binary<T, F> {}
+impl<T: Serialize + DeserializeOwned + fmt::Debug, F: BinaryFormat> fmt::Debug for Binary<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Binary").field(&self.0).finish()
+    }
+}
+impl<T: Serialize + DeserializeOwned + PartialEq, F: BinaryFormat> PartialEq for Binary<T, F> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+impl<T: Serialize + DeserializeOwned + Eq, F: BinaryFormat> Eq for Binary<T, F> {}
+impl<T: Serialize + DeserializeOwned + Hash, F: BinaryFormat> Hash for Binary<T, F> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
+
+// From
+impl<T: Serialize + DeserializeOwned, F: BinaryFormat> From<T> for Binary<T, F> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+// Deref
+impl<T: Serialize + DeserializeOwned, F: BinaryFormat> Deref for Binary<T, F> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+impl<T: Serialize + DeserializeOwned, F: BinaryFormat> DerefMut for Binary<T, F> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+// AsRef
+impl<T: Serialize + DeserializeOwned, F: BinaryFormat> AsRef<T> for Binary<T, F> {
+    fn as_ref(&self) -> &T {
+        &self.0
+    }
+}
+impl<T: Serialize + DeserializeOwned, F: BinaryFormat> AsMut<T> for Binary<T, F> {
+    fn as_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde::de::DeserializeOwned;
+    use serde::Serialize;
+
+    use super::{Binary, BinaryFormat};
+    use crate::fields::traits::FieldType;
+    use crate::validate::ValidationErrorKind;
+
+    struct Failing;
+    impl BinaryFormat for Failing {
+        fn serialize<T: Serialize>(_value: &T) -> Result<Vec<u8>, String> {
+            Err("failed".to_string())
+        }
+
+        fn deserialize<T: DeserializeOwned>(_bytes: &[u8]) -> Result<T, String> {
+            Err("failed".to_string())
+        }
+    }
+
+    #[test]
+    fn check_value() {
+        assert_eq!(
+            Binary::<u32, Failing>::new(1).check_value(),
+            Err(ValidationErrorKind::Encoding {
+                message: "failed".to_string()
+            })
+        );
+        assert_eq!(None::<Binary<u32, Failing>>.check_value(), Ok(()));
+    }
+}
@@ -0,0 +1,142 @@
+//! The [`BitFlags<T>`] wrapper to store [`bitflags`] in the db
+
+use std::ops::{Deref, DerefMut};
+
+use bitflags::Flags;
+use rorm_db::row::RowError;
+use rorm_db::Row;
+
+use crate::conditions::Value;
+use crate::crud::decoder::Decoder;
+use crate::fields::traits::{Array, FieldColumns, FieldType};
+use crate::impl_FieldEq;
+use crate::internal::field::decoder::FieldDecoder;
+use crate::internal::field::fake_field::FakeField;
+use crate::internal::field::{Field, FieldProxy};
+use crate::internal::query_context::QueryContext;
+use crate::internal::relation_path::Path;
+
+/// Stores a set of flags using their bits' integer representation.
+///
+/// Conditions can only compare the whole set of flags.
+///
+/// ```no_run
+/// use rorm::{FieldAccess, Model};
+/// use rorm::fields::types::BitFlags;
+///
+/// bitflags::bitflags! {
+///     #[derive(Copy, Clone)]
+///     pub struct Permissions: u8 {
+///         const READ = 1;
+///         const WRITE = 2;
+///     }
+/// }
+///
+/// #[derive(Model)]
+/// pub struct User {
+///     #[rorm(id)]
+///     pub id: i64,
+///
+///     pub permissions: BitFlags<Permissions>,
+/// }
+///
+/// let read_only = User.permissions.equals(BitFlags(Permissions::READ));
+/// ```
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct BitFlags<T: Flags>(pub T);
+
+impl<T: Flags> BitFlags<T> {
+    /// Unwrap into inner T value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: Flags> Deref for BitFlags<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T: Flags> DerefMut for BitFlags<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T> FieldType for BitFlags<T>
+where
+    T: Flags + 'static,
+    T::Bits: FieldType<Columns = Array<1>>,
+{
+    type Columns = Array<1>;
+
+    const NULL: FieldColumns<Self, rorm_db::sql::value::NullType> = T::Bits::NULL;
+
+    fn into_values<'a>(self) -> FieldColumns<Self, Value<'a>> {
+        self.0.bits().into_values()
+    }
+
+    fn as_values(&self) -> FieldColumns<Self, Value<'_>> {
+        self.0.bits().into_values()
+    }
+
+    type Decoder = BitFlagsDecoder<T>;
+
+    type GetAnnotations = <T::Bits as FieldType>::GetAnnotations;
+
+    type Check = <T::Bits as FieldType>::Check;
+
+    type GetNames = <T::Bits as FieldType>::GetNames;
+
+    fn check_value(&self) -> Result<(), crate::validate::ValidationErrorKind> {
+        self.0.bits().check_value()
+    }
+}
+
+impl_FieldEq!(impl<'rhs, T> FieldEq<'rhs, BitFlags<T>> for BitFlags<T> where T: Flags, {
+    |value: BitFlags<T>| { let [value] = value.into_values(); value }
+});
+
+/// [`FieldDecoder`] for [`BitFlags<T>`]
+pub struct BitFlagsDecoder<T>(<T::Bits as FieldType>::Decoder)
+where
+    T: Flags,
+    T::Bits: FieldType;
+impl<T> Decoder for BitFlagsDecoder<T>
+where
+    T: Flags,
+    T::Bits: FieldType,
+{
+    type Result = BitFlags<T>;
+
+    fn by_name<'index>(&'index self, row: &'_ Row) -> Result<Self::Result, RowError<'index>> {
+        self.0
+            .by_name(row)
+            .map(|bits| BitFlags(T::from_bits_retain(bits)))
+    }
+
+    fn by_index<'index>(&'index self, row: &'_ Row) -> Result<Self::Result, RowError<'index>> {
+        self.0
+            .by_index(row)
+            .map(|bits| BitFlags(T::from_bits_retain(bits)))
+    }
+}
+impl<T> FieldDecoder for BitFlagsDecoder<T>
+where
+    T: Flags,
+    T::Bits: FieldType<Columns = Array<1>>,
+{
+    fn new<F, P>(ctx: &mut QueryContext, _: FieldProxy<F, P>) -> Self
+    where
+        F: Field<Type = Self::Result>,
+        P: Path,
+    {
+        Self(<T::Bits as FieldType>::Decoder::new::<
+            FakeField<T::Bits, F>,
+            P,
+        >(ctx, FieldProxy::new()))
+    }
+}
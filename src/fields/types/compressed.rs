@@ -0,0 +1,366 @@
+//! The [`Compressed<T, C>`] wrapper to store large strings or blobs compressed in the db
+
+use std::borrow::Cow;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+
+use rorm_db::sql::value::NullType;
+
+use crate::conditions::Value;
+use crate::fields::traits::{Array, FieldColumns, FieldType};
+use crate::fields::utils::check::shared_linter_check;
+use crate::fields::utils::get_annotations::forward_annotations;
+use crate::fields::utils::get_names::single_column_name;
+use crate::new_converting_decoder;
+use crate::validate::ValidationErrorKind;
+
+/// A compression algorithm used by [`Compressed`]
+///
+/// rorm provides `Zstd` and `Lz4` behind the features `zstd` and `lz4` respectively.
+pub trait Compression: 'static {
+    /// The byte identifying the algorithm in the stored header
+    ///
+    /// It has to be unique among all algorithms,
+    /// `1` is used by `Zstd` and `2` by `Lz4`.
+    const ID: u8;
+
+    /// Compress some bytes
+    fn compress(bytes: &[u8]) -> Result<Vec<u8>, String>;
+
+    /// Decompress some bytes previously returned by [`Compression::compress`]
+    fn decompress(bytes: &[u8]) -> Result<Vec<u8>, String>;
+}
+
+/// A type which can be stored in a [`Compressed`]
+///
+/// It is implemented for `String` and `Vec<u8>`.
+pub trait Compressible: Sized + 'static {
+    /// Get the bytes to compress
+    fn as_bytes(&self) -> &[u8];
+
+    /// Reconstruct the value from its decompressed bytes
+    fn from_bytes(bytes: Vec<u8>) -> Result<Self, String>;
+}
+
+impl Compressible for String {
+    fn as_bytes(&self) -> &[u8] {
+        String::as_bytes(self)
+    }
+
+    fn from_bytes(bytes: Vec<u8>) -> Result<Self, String> {
+        String::from_utf8(bytes).map_err(|err| err.to_string())
+    }
+}
+
+impl Compressible for Vec<u8> {
+    fn as_bytes(&self) -> &[u8] {
+        self
+    }
+
+    fn from_bytes(bytes: Vec<u8>) -> Result<Self, String> {
+        Ok(bytes)
+    }
+}
+
+/// Stores a large string or blob compressed.
+///
+/// The value is compressed using `C` when it is written to the db
+/// and decompressed when it is read.
+/// This trades some cpu time for less storage and io for large bodies like articles or messages.
+///
+/// ```no_run
+/// # #[cfg(feature = "zstd")] mod example {
+/// use rorm::Model;
+/// use rorm::fields::types::{Compressed, Zstd};
+///
+/// #[derive(Model)]
+/// pub struct Post {
+///     #[rorm(id)]
+///     pub id: i64,
+///
+///     pub message: Compressed<String, Zstd>,
+/// }
+/// # }
+/// ```
+///
+/// The stored bytes start with a one byte header identifying the algorithm.
+/// Values are decompressed using the algorithm from their header,
+/// so `C` can be changed without migrating existing rows
+/// as long as the old algorithm's feature stays enabled.
+///
+/// Values which fail to compress are rejected by inserts and updates
+/// with a [`ValidationErrorKind::Encoding`].
+///
+/// Since the column only contains compressed bytes,
+/// conditions can't compare the field's value.
+pub struct Compressed<T: Compressible, C: Compression>(pub T, PhantomData<fn() -> C>);
+
+impl<T: Compressible, C: Compression> Compressed<T, C> {
+    /// Wrap a value
+    pub const fn new(value: T) -> Self {
+        Self(value, PhantomData)
+    }
+
+    /// Unwrap into inner T value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+/// Compress a value and prepend the header
+fn compress<C: Compression>(value: &[u8]) -> Result<Vec<u8>, String> {
+    let compressed = C::compress(value)?;
+    let mut bytes = Vec::with_capacity(compressed.len() + 1);
+    bytes.push(C::ID);
+    bytes.extend_from_slice(&compressed);
+    Ok(bytes)
+}
+
+/// Decompress a value using the algorithm from its header
+fn decompress<T: Compressible>(bytes: &[u8]) -> Result<T, String> {
+    let Some(&id) = bytes.first() else {
+        return Err("Couldn't decompress data: missing header".to_string());
+    };
+    let decompressed = match id {
+        #[cfg(feature = "zstd")]
+        <Zstd as Compression>::ID => Zstd::decompress(&bytes[1..]),
+        #[cfg(feature = "lz4")]
+        <Lz4 as Compression>::ID => Lz4::decompress(&bytes[1..]),
+        _ => Err(format!("unknown or disabled algorithm {id}")),
+    };
+    decompressed
+        .and_then(T::from_bytes)
+        .map_err(|err| format!("Couldn't decompress data: {err}"))
+}
+
+new_converting_decoder!(
+    pub CompressedDecoder<T: Compressible, C: Compression>,
+    |value: Vec<u8>| -> Compressed<T, C> {
+        decompress(&value).map(Compressed::new)
+    }
+);
+impl<T: Compressible, C: Compression> FieldType for Compressed<T, C> {
+    type Columns = Array<1>;
+
+    const NULL: FieldColumns<Self, NullType> = [NullType::Binary];
+
+    // A failed compression is reported by `check_value` which aborts the query
+    fn into_values<'a>(self) -> FieldColumns<Self, Value<'a>> {
+        [Value::Binary(Cow::Owned(
+            compress::<C>(self.0.as_bytes()).unwrap_or_default(),
+        ))]
+    }
+
+    fn as_values(&self) -> FieldColumns<Self, Value<'_>> {
+        [Value::Binary(Cow::Owned(
+            compress::<C>(self.0.as_bytes()).unwrap_or_default(),
+        ))]
+    }
+
+    type Decoder = CompressedDecoder<T, C>;
+
+    type GetAnnotations = forward_annotations<1>;
+    type Check = shared_linter_check<1>;
+    type GetNames = single_column_name;
+
+    fn check_value(&self) -> Result<(), ValidationErrorKind> {
+        compress::<C>(self.0.as_bytes())
+            .map(drop)
+            .map_err(|message| ValidationErrorKind::Encoding { message })
+    }
+}
+
+new_converting_decoder!(
+    pub OptionCompressedDecoder<T: Compressible, C: Compression>,
+    |value: Option<Vec<u8>>| -> Option<Compressed<T, C>> {
+        value
+            .map(|value| decompress(&value).map(Compressed::new))
+            .transpose()
+    }
+);
+
+/// [`Compression`] using [zstd]
+#[cfg(feature = "zstd")]
+#[derive(Copy, Clone, Debug)]
+pub struct Zstd;
+#[cfg(feature = "zstd")]
+impl Compression for Zstd {
+    const ID: u8 = 1;
+
+    fn compress(bytes: &[u8]) -> Result<Vec<u8>, String> {
+        zstd::encode_all(bytes, 0).map_err(|err| err.to_string())
+    }
+
+    fn decompress(bytes: &[u8]) -> Result<Vec<u8>, String> {
+        zstd::decode_all(bytes).map_err(|err| err.to_string())
+    }
+}
+
+/// [`Compression`] using [lz4_flex]
+#[cfg(feature = "lz4")]
+#[derive(Copy, Clone, Debug)]
+pub struct Lz4;
+#[cfg(feature = "lz4")]
+impl Compression for Lz4 {
+    const ID: u8 = 2;
+
+    fn compress(bytes: &[u8]) -> Result<Vec<u8>, String> {
+        Ok(lz4_flex::compress_prepend_size(bytes))
+    }
+
+    fn decompress(bytes: &[u8]) -> Result<Vec<u8>, String> {
+        lz4_flex::decompress_size_prepended(bytes).map_err(|err| err.to_string())
+    }
+}
+
+// Derives would require `C` to implement the traits as well
+impl<T: Compressible + Clone, C: Compression> Clone for Compressed<T, C> {
+    fn clone(&self) -> Self {
+        Self::new(self.0.clone())
+    }
+}
+impl<T: Compressible + fmt::Debug, C: Compression> fmt::Debug for Compressed<T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Compressed").field(&self.0).finish()
+    }
+}
+impl<T: Compressible + PartialEq, C: Compression> PartialEq for Compressed<T, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+impl<T: Compressible + Eq, C: Compression> Eq for Compressed<T, C> {}
+impl<T: Compressible + Hash, C: Compression> Hash for Compressed<T, C> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
+
+// From
+impl<T: Compressible, C: Compression> From<T> for Compressed<T, C> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+// Deref
+impl<T: Compressible, C: Compression> Deref for Compressed<T, C> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+impl<T: Compressible, C: Compression> DerefMut for Compressed<T, C> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+// AsRef
+impl<T: Compressible, C: Compression> AsRef<T> for Compressed<T, C> {
+    fn as_ref(&self) -> &T {
+        &self.0
+    }
+}
+impl<T: Compressible, C: Compression> AsMut<T> for Compressed<T, C> {
+    fn as_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{compress, decompress, Compressed, Compression};
+    use crate::fields::traits::FieldType;
+    use crate::validate::ValidationErrorKind;
+
+    struct Identity;
+    impl Compression for Identity {
+        const ID: u8 = 255;
+
+        fn compress(bytes: &[u8]) -> Result<Vec<u8>, String> {
+            Ok(bytes.to_vec())
+        }
+
+        fn decompress(bytes: &[u8]) -> Result<Vec<u8>, String> {
+            Ok(bytes.to_vec())
+        }
+    }
+
+    struct Failing;
+    impl Compression for Failing {
+        const ID: u8 = 254;
+
+        fn compress(_bytes: &[u8]) -> Result<Vec<u8>, String> {
+            Err("failed".to_string())
+        }
+
+        fn decompress(_bytes: &[u8]) -> Result<Vec<u8>, String> {
+            Err("failed".to_string())
+        }
+    }
+
+    #[test]
+    fn header() {
+        assert_eq!(
+            compress::<Identity>(b"abc"),
+            Ok(vec![255, b'a', b'b', b'c'])
+        );
+        assert_eq!(compress::<Identity>(b""), Ok(vec![255]));
+    }
+
+    #[test]
+    fn missing_header() {
+        assert_eq!(
+            decompress::<Vec<u8>>(&[]),
+            Err("Couldn't decompress data: missing header".to_string())
+        );
+    }
+
+    #[test]
+    fn unknown_algorithm() {
+        assert_eq!(
+            decompress::<Vec<u8>>(&[255, b'a']),
+            Err("Couldn't decompress data: unknown or disabled algorithm 255".to_string())
+        );
+    }
+
+    #[test]
+    fn check_value() {
+        assert_eq!(
+            Compressed::<Vec<u8>, Failing>::new(vec![1]).check_value(),
+            Err(ValidationErrorKind::Encoding {
+                message: "failed".to_string()
+            })
+        );
+        assert_eq!(
+            Compressed::<Vec<u8>, Identity>::new(vec![1]).check_value(),
+            Ok(())
+        );
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn zstd() {
+        let bytes = compress::<super::Zstd>(b"hello hello hello").unwrap();
+        assert_eq!(bytes[0], 1);
+        assert_eq!(
+            decompress::<String>(&bytes),
+            Ok("hello hello hello".to_string())
+        );
+    }
+
+    #[cfg(feature = "lz4")]
+    #[test]
+    fn lz4() {
+        let bytes = compress::<super::Lz4>(b"hello hello hello").unwrap();
+        assert_eq!(bytes[0], 2);
+        assert_eq!(
+            decompress::<String>(&bytes),
+            Ok("hello hello hello".to_string())
+        );
+    }
+}
@@ -1,13 +1,16 @@
 //! The [ForeignModel] field type
 
+use std::collections::HashMap;
 use std::fmt;
+use std::hash::{Hash, Hasher};
 
 use rorm_db::Executor;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-use crate::conditions::{Binary, BinaryOperator, Column};
-use crate::crud::query::query;
+use crate::conditions::{Binary, BinaryOperator, Column, In, InOperator};
+use crate::crud::query::{query, KEYS_PER_QUERY};
 use crate::internal::field::{FieldProxy, SingleColumnField};
-use crate::model::Model;
+use crate::model::{GetField, Model};
 use crate::Patch;
 
 /// Alias for [ForeignModelByField] which only takes a model uses to its primary key.
@@ -30,6 +33,48 @@ impl<FF: SingleColumnField> ForeignModelByField<FF> {
             .one()
             .await
     }
+
+    /// Queries the associated models of many foreign models at once
+    ///
+    /// The models are returned by the key they are referenced by.
+    /// Keys whose model doesn't exist are missing from the map.
+    ///
+    /// Long lists are split into multiple queries which are executed in a single transaction.
+    pub async fn query_many<'fm>(
+        executor: impl Executor<'_>,
+        foreign_models: impl IntoIterator<Item = &'fm Self>,
+    ) -> Result<HashMap<FF::Type, FF::Model>, crate::Error>
+    where
+        FF::Type: Hash + Eq + Clone,
+        FF::Model: GetField<FF>,
+    {
+        let keys: Vec<_> = foreign_models
+            .into_iter()
+            .map(|foreign_model| FF::type_as_value(&foreign_model.0))
+            .collect();
+
+        let mut guard = executor.ensure_transaction().await?;
+        let mut models = HashMap::with_capacity(keys.len());
+        for chunk in keys.chunks(KEYS_PER_QUERY) {
+            let rows = query(
+                guard.get_transaction(),
+                <FF::Model as Patch>::ValueSpaceImpl::default(),
+            )
+            .condition(In {
+                operator: InOperator::In,
+                fst_arg: Column(FieldProxy::<FF, FF::Model>::new()),
+                snd_arg: chunk.to_vec(),
+            })
+            .all()
+            .await?;
+            models.extend(
+                rows.into_iter()
+                    .map(|model| (model.borrow_field().clone(), model)),
+            );
+        }
+        guard.commit().await?;
+        Ok(models)
+    }
 }
 
 impl<FF: SingleColumnField> fmt::Debug for ForeignModelByField<FF>
@@ -49,3 +94,36 @@ where
     }
 }
 impl<FF: SingleColumnField> Copy for ForeignModelByField<FF> where FF::Type: Copy {}
+impl<FF: SingleColumnField> PartialEq for ForeignModelByField<FF>
+where
+    FF::Type: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+impl<FF: SingleColumnField> Eq for ForeignModelByField<FF> where FF::Type: Eq {}
+impl<FF: SingleColumnField> Hash for ForeignModelByField<FF>
+where
+    FF::Type: Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
+impl<FF: SingleColumnField> Serialize for ForeignModelByField<FF>
+where
+    FF::Type: Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+impl<'de, FF: SingleColumnField> Deserialize<'de> for ForeignModelByField<FF>
+where
+    FF::Type: Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        FF::Type::deserialize(deserializer).map(Self)
+    }
+}
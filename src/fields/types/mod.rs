@@ -3,27 +3,53 @@
 //! See [`rorm::fields`](crate::fields) for full list of supported field types
 
 mod back_ref;
+mod binary;
+#[cfg(feature = "bitflags")]
+mod bitflags;
 #[cfg(feature = "chrono")]
 mod chrono;
+mod compressed;
 mod foreign_model;
 mod json;
 mod max_str;
 pub mod max_str_impl;
 #[cfg(feature = "msgpack")]
 mod msgpack;
+mod one_of;
+mod polymorphic;
 #[cfg(feature = "postgres-only")]
 pub(crate) mod postgres_only;
+mod snowflake;
 mod std;
 #[cfg(feature = "time")]
 mod time;
+mod ulid;
 #[cfg(feature = "url")]
 mod url;
 #[cfg(feature = "uuid")]
 mod uuid;
 
+#[cfg(feature = "bitflags")]
+pub use self::bitflags::{BitFlags, BitFlagsDecoder};
 pub use back_ref::BackRef;
+#[cfg(feature = "bincode")]
+pub use binary::Bincode;
+#[cfg(feature = "msgpack")]
+pub use binary::MessagePack;
+#[cfg(feature = "postcard")]
+pub use binary::Postcard;
+pub use binary::{Binary, BinaryFormat};
+#[cfg(feature = "lz4")]
+pub use compressed::Lz4;
+#[cfg(feature = "zstd")]
+pub use compressed::Zstd;
+pub use compressed::{Compressed, Compressible, Compression};
 pub use foreign_model::{ForeignModel, ForeignModelByField};
 pub use json::Json;
 pub use max_str::MaxStr;
 #[cfg(feature = "msgpack")]
 pub use msgpack::MsgPack;
+pub use one_of::{ChoiceSet, OneOf};
+pub use polymorphic::{PolymorphicCondition, PolymorphicForeignModel, PolymorphicTargets};
+pub use snowflake::Snowflake;
+pub use ulid::Ulid;
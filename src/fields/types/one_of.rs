@@ -0,0 +1,287 @@
+use std::borrow::Cow;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::ops::Deref;
+
+use rorm_db::choice::Choice;
+use rorm_db::sql::value::NullType;
+use serde::de::Unexpected;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::conditions::Value;
+use crate::fields::traits::{Array, FieldColumns, FieldType};
+use crate::fields::utils::check::shared_linter_check;
+use crate::fields::utils::get_names::single_column_name;
+use crate::internal::hmr::annotations::{Annotations, Choices};
+use crate::{const_fn, impl_FieldEq, new_converting_decoder, Error};
+
+/// The strings a [`OneOf`] can store
+///
+/// Use [`choice_set!`](crate::choice_set) to declare a type implementing it.
+pub trait ChoiceSet: 'static {
+    /// The strings stored as the column's choices
+    const CHOICES: &'static [&'static str];
+}
+
+/// Declare a [`ChoiceSet`] to be used with [`OneOf`]
+///
+/// ```
+/// rorm::choice_set!(pub Status = ["open", "closed"]);
+/// ```
+#[macro_export]
+macro_rules! choice_set {
+    ($(#[$attr:meta])* $vis:vis $ident:ident = [$($choice:literal),+ $(,)?]) => {
+        $(#[$attr])*
+        #[derive(Copy, Clone, Debug)]
+        $vis struct $ident;
+        impl $crate::fields::types::ChoiceSet for $ident {
+            const CHOICES: &'static [&'static str] = &[$($choice),+];
+        }
+    };
+}
+
+/// Construct a [`OneOf`] from a literal which is checked at compile time
+///
+/// ```
+/// rorm::choice_set!(pub Status = ["open", "closed"]);
+///
+/// let open = rorm::one_of!(Status, "open");
+/// assert_eq!(open.as_str(), "open");
+/// ```
+///
+/// Any other string fails to compile:
+///
+/// ```compile_fail
+/// rorm::choice_set!(pub Status = ["open", "closed"]);
+///
+/// let open = rorm::one_of!(Status, "opened");
+/// ```
+#[macro_export]
+macro_rules! one_of {
+    ($choices:ty, $value:literal) => {{
+        const VALUE: $crate::fields::types::OneOf<$choices> =
+            match $crate::fields::types::OneOf::try_new($value) {
+                ::std::option::Option::Some(value) => value,
+                ::std::option::Option::None => ::std::panic!("The value is not one of the choices"),
+            };
+        VALUE
+    }};
+}
+
+/// One of the strings in a [`ChoiceSet`] stored as a choices column
+///
+/// This is a lightweight alternative to a [`DbEnum`](crate::DbEnum) for one-off choice columns:
+///
+/// ```no_run
+/// # use rorm::{query, Database, Error, Model};
+/// # use rorm::fields::types::OneOf;
+/// rorm::choice_set!(pub Status = ["open", "closed"]);
+///
+/// #[derive(Model)]
+/// pub struct Ticket {
+///     #[rorm(id)]
+///     pub id: i64,
+///
+///     pub status: OneOf<Status>,
+/// }
+///
+/// pub async fn open_tickets(db: &Database) -> Result<Vec<Ticket>, Error> {
+///     query(db, Ticket)
+///         .condition(Ticket.status.equals(rorm::one_of!(Status, "open")))
+///         .all()
+///         .await
+/// }
+/// ```
+pub struct OneOf<C: ChoiceSet> {
+    value: &'static str,
+    choices: PhantomData<fn() -> C>,
+}
+
+impl<C: ChoiceSet> OneOf<C> {
+    /// Wrap `value` returning an error if it is not one of the choices
+    ///
+    /// Use [`one_of!`](crate::one_of) to check a literal at compile time.
+    pub fn new(value: &str) -> Result<Self, Error> {
+        Self::try_new(value).ok_or_else(|| {
+            Error::DecodeError(format!(
+                "Invalid value '{value}' for choices {:?}",
+                C::CHOICES
+            ))
+        })
+    }
+
+    /// Wrap `value` returning `None` if it is not one of the choices
+    pub const fn try_new(value: &str) -> Option<Self> {
+        match Self::position(value) {
+            Some(index) => Some(Self {
+                value: C::CHOICES[index],
+                choices: PhantomData,
+            }),
+            None => None,
+        }
+    }
+
+    /// Get the stored choice
+    pub const fn as_str(&self) -> &'static str {
+        self.value
+    }
+
+    /// Find `value`'s index in the choices
+    const fn position(value: &str) -> Option<usize> {
+        let value = value.as_bytes();
+        let mut index = 0;
+        while index < C::CHOICES.len() {
+            let choice = C::CHOICES[index].as_bytes();
+            if choice.len() == value.len() {
+                let mut byte = 0;
+                while byte < value.len() && choice[byte] == value[byte] {
+                    byte += 1;
+                }
+                if byte == value.len() {
+                    return Some(index);
+                }
+            }
+            index += 1;
+        }
+        None
+    }
+}
+
+impl<C: ChoiceSet> Copy for OneOf<C> {}
+impl<C: ChoiceSet> Clone for OneOf<C> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<C: ChoiceSet> Deref for OneOf<C> {
+    type Target = str;
+    fn deref(&self) -> &Self::Target {
+        self.value
+    }
+}
+
+impl<C: ChoiceSet> Eq for OneOf<C> {}
+impl<C: ChoiceSet> PartialEq for OneOf<C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<C: ChoiceSet> Hash for OneOf<C> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.value.hash(state)
+    }
+}
+
+impl<C: ChoiceSet> fmt::Debug for OneOf<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+impl<C: ChoiceSet> fmt::Display for OneOf<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+impl<C: ChoiceSet> Serialize for OneOf<C> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.value.serialize(serializer)
+    }
+}
+
+impl<'de, C: ChoiceSet> Deserialize<'de> for OneOf<C> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = Cow::<'de, str>::deserialize(deserializer)?;
+        Self::try_new(&value).ok_or_else(|| {
+            <D::Error as serde::de::Error>::invalid_value(
+                Unexpected::Str(&value),
+                &format!("one of {:?}", C::CHOICES).as_str(),
+            )
+        })
+    }
+}
+
+impl<C: ChoiceSet> FieldType for OneOf<C> {
+    type Columns = Array<1>;
+
+    const NULL: FieldColumns<Self, NullType> = [NullType::String];
+
+    fn into_values<'a>(self) -> FieldColumns<Self, Value<'a>> {
+        [Value::Choice(Cow::Borrowed(self.value))]
+    }
+
+    fn as_values(&self) -> FieldColumns<Self, Value<'_>> {
+        [Value::Choice(Cow::Borrowed(self.value))]
+    }
+
+    type Decoder = OneOfDecoder<C>;
+    type GetAnnotations = one_of_annotations<C>;
+    type Check = shared_linter_check<1>;
+    type GetNames = single_column_name;
+}
+
+new_converting_decoder!(
+    pub OneOfDecoder<C: ChoiceSet>,
+    |value: Choice| -> OneOf<C> {
+        OneOf::try_new(&value.0)
+            .ok_or_else(|| format!("Invalid value '{}' for choices {:?}", value.0, C::CHOICES))
+    }
+);
+
+const_fn! {
+    /// Restricts the column to the [`ChoiceSet`]'s choices
+    pub fn one_of_annotations<C: ChoiceSet>(field: Annotations) -> [Annotations; 1] {
+        let mut field = field;
+        field.choices = Some(Choices(C::CHOICES));
+        [field]
+    }
+}
+
+impl_FieldEq!(impl<'rhs, C> FieldEq<'rhs, OneOf<C>> for OneOf<C> where C: ChoiceSet, { conv_choice });
+impl_FieldEq!(impl<'rhs, C> FieldEq<'rhs, Option<OneOf<C>>> for Option<OneOf<C>> where C: ChoiceSet, { conv_opt_choice });
+fn conv_choice<'a, C: ChoiceSet>(value: OneOf<C>) -> Value<'a> {
+    Value::Choice(Cow::Borrowed(value.value))
+}
+fn conv_opt_choice<'a, C: ChoiceSet>(value: Option<OneOf<C>>) -> Value<'a> {
+    value
+        .map(conv_choice)
+        .unwrap_or(Value::Null(NullType::String))
+}
+
+#[cfg(test)]
+mod test {
+    use super::OneOf;
+
+    crate::choice_set!(Status = ["open", "closed"]);
+
+    #[test]
+    fn new() {
+        assert_eq!(OneOf::<Status>::new("open").unwrap().as_str(), "open");
+        assert!(OneOf::<Status>::new("opened").is_err());
+        assert!(OneOf::<Status>::new("").is_err());
+    }
+
+    #[test]
+    fn try_new() {
+        assert_eq!(
+            OneOf::<Status>::try_new("closed").map(|value| value.as_str()),
+            Some("closed")
+        );
+        assert_eq!(OneOf::<Status>::try_new("close"), None);
+    }
+
+    #[test]
+    fn one_of() {
+        assert_eq!(crate::one_of!(Status, "closed").as_str(), "closed");
+    }
+}
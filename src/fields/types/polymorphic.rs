@@ -0,0 +1,427 @@
+//! The [`PolymorphicForeignModel`] field type
+
+use std::borrow::Cow;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+
+use rorm_db::choice::Choice;
+use rorm_db::row::{DecodeOwned, RowError};
+use rorm_db::sql::value::NullType;
+use rorm_db::{Error, Executor, Row};
+
+use crate::conditions::collections::CollectionOperator;
+use crate::conditions::{Binary, BinaryOperator, Column, Condition, Value};
+use crate::crud::decoder::Decoder;
+use crate::crud::query::query;
+use crate::fields::traits::{Array, FieldColumns, FieldType};
+use crate::fields::utils::check::shared_linter_check;
+use crate::fields::utils::const_fn::{ConstFn, Contains};
+use crate::internal::const_concat::ConstString;
+use crate::internal::field::decoder::FieldDecoder;
+use crate::internal::field::{Field, FieldProxy, SingleColumnField};
+use crate::internal::hmr::annotations::{Annotations, Choices};
+use crate::internal::query_context::flat_conditions::FlatCondition;
+use crate::internal::query_context::ids::PathId;
+use crate::internal::query_context::QueryContext;
+use crate::internal::relation_path::Path;
+use crate::model::Model;
+use crate::{const_fn, sealed, Patch};
+
+/// Stores a link to a model of one of several types in a field.
+///
+/// `T` is a tuple of the models which can be referenced, all of which have to share their primary key's type.
+/// The field is stored in two columns:
+/// - `{field}_type` contains the referenced model's table name
+/// - `{field}_key` contains the referenced model's primary key
+///
+/// Since the key column references different tables, the database can't enforce a foreign key constraint.
+///
+/// ```no_run
+/// # use rorm::{query, Database, Error, Model, FieldAccess};
+/// use rorm::fields::types::PolymorphicForeignModel;
+///
+/// #[derive(Model)]
+/// pub struct Post {
+///     #[rorm(id)]
+///     pub id: i64,
+/// }
+///
+/// #[derive(Model)]
+/// pub struct Photo {
+///     #[rorm(id)]
+///     pub id: i64,
+/// }
+///
+/// #[derive(Model)]
+/// pub struct Comment {
+///     #[rorm(id)]
+///     pub id: i64,
+///
+///     pub target: PolymorphicForeignModel<(Post, Photo)>,
+/// }
+///
+/// pub async fn comments_on_post(db: &Database, post: i64) -> Result<Vec<Comment>, Error> {
+///     query(db, Comment)
+///         .condition(Comment.target.references::<Post>(post))
+///         .all()
+///         .await
+/// }
+///
+/// pub async fn commented_post(db: &Database, comment: &Comment) -> Result<Option<Post>, Error> {
+///     comment.target.query::<Post>(db).await
+/// }
+/// ```
+pub struct PolymorphicForeignModel<T: PolymorphicTargets> {
+    table: &'static str,
+    key: T::Key,
+}
+
+impl<T: PolymorphicTargets> PolymorphicForeignModel<T> {
+    /// Reference the instance of `M` with the primary key `key`
+    ///
+    /// # Panics
+    /// If `M` is not one of the models in `T`
+    pub fn new<M>(key: T::Key) -> Self
+    where
+        M: Model,
+        M::Primary: Field<Type = T::Key>,
+    {
+        assert!(
+            T::TABLES.contains(&M::TABLE),
+            "{} can't be referenced by a PolymorphicForeignModel of {:?}",
+            M::TABLE,
+            T::TABLES
+        );
+        Self {
+            table: M::TABLE,
+            key,
+        }
+    }
+
+    /// Get the table name of the referenced model
+    pub fn table(&self) -> &'static str {
+        self.table
+    }
+
+    /// Get the primary key of the referenced instance
+    pub fn key(&self) -> &T::Key {
+        &self.key
+    }
+
+    /// Check whether an instance of `M` is referenced
+    pub fn is<M: Model>(&self) -> bool {
+        self.table == M::TABLE
+    }
+
+    /// Get the primary key of the referenced instance, if it is an instance of `M`
+    pub fn key_of<M: Model>(&self) -> Option<&T::Key> {
+        self.is::<M>().then_some(&self.key)
+    }
+
+    /// Queries the referenced instance, if it is an instance of `M`
+    pub async fn query<M>(&self, executor: impl Executor<'_>) -> Result<Option<M>, Error>
+    where
+        M: Model,
+        M::Primary: Field<Type = T::Key>,
+    {
+        if !self.is::<M>() {
+            return Ok(None);
+        }
+        query(executor, <M as Patch>::ValueSpaceImpl::default())
+            .condition(Binary {
+                operator: BinaryOperator::Equals,
+                fst_arg: Column(FieldProxy::<M::Primary, M>::new()),
+                snd_arg: T::key_as_value(&self.key),
+            })
+            .one()
+            .await
+            .map(Some)
+    }
+}
+
+/// The models a [`PolymorphicForeignModel`] can reference
+///
+/// This trait is implemented for tuples of 2 to 5 models sharing their primary key's type.
+pub trait PolymorphicTargets: 'static {
+    sealed!(trait);
+
+    /// The type of the models' primary keys
+    type Key: DecodeOwned;
+
+    /// The models' table names
+    const TABLES: &'static [&'static str];
+
+    /// The null type of the models' primary keys
+    const KEY_NULL: NullType;
+
+    /// The annotations of the first model's primary key
+    const KEY_ANNOTATION: Annotations;
+
+    /// Borrow a key as [`Value`]
+    fn key_as_value(key: &Self::Key) -> Value<'_>;
+
+    /// Convert a key into a static [`Value`]
+    fn key_into_value(key: Self::Key) -> Value<'static>;
+}
+
+macro_rules! impl_polymorphic_targets {
+    ($($first:ident $(, $rest:ident)+;)+) => {$(
+        impl<$first: Model, $($rest: Model),+> PolymorphicTargets for ($first, $($rest,)+)
+        where
+            <$first::Primary as Field>::Type: FieldType<Columns = Array<1>> + DecodeOwned,
+            $($rest::Primary: Field<Type = <$first::Primary as Field>::Type>,)+
+        {
+            sealed!(impl);
+
+            type Key = <$first::Primary as Field>::Type;
+
+            const TABLES: &'static [&'static str] = &[$first::TABLE, $($rest::TABLE),+];
+
+            const KEY_NULL: NullType = {
+                let [null] = <Self::Key as FieldType>::NULL;
+                null
+            };
+
+            const KEY_ANNOTATION: Annotations = <$first::Primary as SingleColumnField>::EFFECTIVE_ANNOTATION;
+
+            fn key_as_value(key: &Self::Key) -> Value<'_> {
+                <$first::Primary as SingleColumnField>::type_as_value(key)
+            }
+
+            fn key_into_value(key: Self::Key) -> Value<'static> {
+                <$first::Primary as SingleColumnField>::type_into_value(key)
+            }
+        }
+    )+};
+}
+
+impl_polymorphic_targets! {
+    A, B;
+    A, B, C;
+    A, B, C, D;
+    A, B, C, D, E;
+}
+
+impl<T: PolymorphicTargets> FieldType for PolymorphicForeignModel<T> {
+    type Columns = Array<2>;
+
+    const NULL: FieldColumns<Self, NullType> = [NullType::String, T::KEY_NULL];
+
+    fn into_values<'a>(self) -> FieldColumns<Self, Value<'a>> {
+        [
+            Value::Choice(Cow::Borrowed(self.table)),
+            T::key_into_value(self.key),
+        ]
+    }
+
+    fn as_values(&self) -> FieldColumns<Self, Value<'_>> {
+        [
+            Value::Choice(Cow::Borrowed(self.table)),
+            T::key_as_value(&self.key),
+        ]
+    }
+
+    type Decoder = PolymorphicForeignModelDecoder<T>;
+
+    type GetAnnotations = polymorphic_annotations<T>;
+
+    type Check = shared_linter_check<2>;
+
+    type GetNames = polymorphic_names;
+}
+
+const_fn! {
+    /// - restricts the type column to the targets' table names
+    /// - copies `max_length` from the targets' primary key to the key column
+    pub fn polymorphic_annotations<T: PolymorphicTargets>(field: Annotations) -> [Annotations; 2] {
+        let mut type_annos = field;
+        type_annos.choices = Some(Choices(T::TABLES));
+        let mut key_annos = field;
+        if key_annos.max_length.is_none() {
+            key_annos.max_length = T::KEY_ANNOTATION.max_length;
+        }
+        [type_annos, key_annos]
+    }
+}
+
+/// [`FieldType::GetNames`] for [`PolymorphicForeignModel`]
+///
+/// The columns are named `{field}_type` and `{field}_key`.
+#[allow(non_camel_case_types)]
+pub struct polymorphic_names;
+impl ConstFn<(&'static str,), [&'static str; 2]> for polymorphic_names {
+    type Body<Arg: Contains<(&'static str,)>> = PolymorphicNames<Arg>;
+}
+
+#[doc(hidden)]
+pub struct PolymorphicNames<Arg>(PhantomData<Arg>);
+impl<Arg: Contains<(&'static str,)>> PolymorphicNames<Arg> {
+    const TYPE: &'static ConstString<256> = &suffixed(Arg::ITEM.0, "_type");
+    const KEY: &'static ConstString<256> = &suffixed(Arg::ITEM.0, "_key");
+}
+impl<Arg: Contains<(&'static str,)>> Contains<[&'static str; 2]> for PolymorphicNames<Arg> {
+    const ITEM: [&'static str; 2] = [Self::TYPE.as_str(), Self::KEY.as_str()];
+}
+
+const fn suffixed(name: &str, suffix: &str) -> ConstString<256> {
+    let string = match ConstString::new().push_str(name) {
+        Some(string) => string,
+        None => panic!("The field's name is too long"),
+    };
+    match string.push_str(suffix) {
+        Some(string) => string,
+        None => panic!("The field's name is too long"),
+    }
+}
+
+/// [`FieldDecoder`] for [`PolymorphicForeignModel`]
+pub struct PolymorphicForeignModelDecoder<T> {
+    type_column: String,
+    type_index: usize,
+    key_column: String,
+    key_index: usize,
+    targets: PhantomData<(T,)>,
+}
+impl<T: PolymorphicTargets> PolymorphicForeignModelDecoder<T> {
+    fn table(table: Choice) -> Result<&'static str, String> {
+        T::TABLES
+            .iter()
+            .find(|target| **target == table.0)
+            .copied()
+            .ok_or_else(|| format!("Invalid value '{}' for polymorphic foreign model", table.0))
+    }
+}
+impl<T: PolymorphicTargets> Decoder for PolymorphicForeignModelDecoder<T> {
+    type Result = PolymorphicForeignModel<T>;
+
+    fn by_name<'index>(&'index self, row: &'_ Row) -> Result<Self::Result, RowError<'index>> {
+        let table =
+            Self::table(row.get(self.type_column.as_str())?).map_err(|error| RowError::Decode {
+                index: self.type_column.as_str().into(),
+                source: error.into(),
+            })?;
+        let key = row.get(self.key_column.as_str())?;
+        Ok(PolymorphicForeignModel { table, key })
+    }
+
+    fn by_index<'index>(&'index self, row: &'_ Row) -> Result<Self::Result, RowError<'index>> {
+        let table = Self::table(row.get(self.type_index)?).map_err(|error| RowError::Decode {
+            index: self.type_index.into(),
+            source: error.into(),
+        })?;
+        let key = row.get(self.key_index)?;
+        Ok(PolymorphicForeignModel { table, key })
+    }
+}
+impl<T: PolymorphicTargets> FieldDecoder for PolymorphicForeignModelDecoder<T> {
+    fn new<F, P>(ctx: &mut QueryContext, _: FieldProxy<F, P>) -> Self
+    where
+        F: Field<Type = Self::Result>,
+        P: Path,
+    {
+        let [type_column, key_column] = F::EFFECTIVE_NAMES;
+        let (type_index, type_column) = ctx.select_column::<P>(type_column);
+        let (key_index, key_column) = ctx.select_column::<P>(key_column);
+        Self {
+            type_column,
+            type_index,
+            key_column,
+            key_index,
+            targets: PhantomData,
+        }
+    }
+}
+
+impl<F, P, T> FieldProxy<F, P>
+where
+    F: Field<Type = PolymorphicForeignModel<T>>,
+    P: Path,
+    T: PolymorphicTargets,
+{
+    /// Check the field to reference the instance of `M` with the primary key `key`
+    ///
+    /// The condition doesn't match any row, if `M` is not one of the models in `T`.
+    pub fn references<M>(self, key: T::Key) -> PolymorphicCondition<'static, P>
+    where
+        M: Model,
+        M::Primary: Field<Type = T::Key>,
+    {
+        PolymorphicCondition {
+            columns: F::EFFECTIVE_NAMES,
+            table: M::TABLE,
+            key: T::key_into_value(key),
+            path: PhantomData,
+        }
+    }
+}
+
+/// [`Condition`] returned by [`FieldProxy::references`]
+pub struct PolymorphicCondition<'a, P> {
+    columns: [&'static str; 2],
+    table: &'static str,
+    key: Value<'a>,
+    path: PhantomData<fn() -> P>,
+}
+impl<'a, P: Path> Condition<'a> for PolymorphicCondition<'a, P> {
+    fn build(&self, context: &mut QueryContext<'a>) {
+        P::add_to_context(context);
+        let [type_column, key_column] = self.columns;
+        context
+            .conditions
+            .push(FlatCondition::StartCollection(CollectionOperator::And));
+        for (column, value) in [
+            (type_column, Value::Choice(Cow::Borrowed(self.table))),
+            (key_column, self.key.clone()),
+        ] {
+            context.conditions.extend([
+                FlatCondition::BinaryCondition(BinaryOperator::Equals),
+                FlatCondition::Column(PathId::of::<P>(), column),
+            ]);
+            value.build(context);
+        }
+        context.conditions.push(FlatCondition::EndCollection);
+    }
+}
+
+impl<T: PolymorphicTargets> fmt::Debug for PolymorphicForeignModel<T>
+where
+    T::Key: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PolymorphicForeignModel")
+            .field("table", &self.table)
+            .field("key", &self.key)
+            .finish()
+    }
+}
+impl<T: PolymorphicTargets> Clone for PolymorphicForeignModel<T>
+where
+    T::Key: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            table: self.table,
+            key: self.key.clone(),
+        }
+    }
+}
+impl<T: PolymorphicTargets> Copy for PolymorphicForeignModel<T> where T::Key: Copy {}
+impl<T: PolymorphicTargets> PartialEq for PolymorphicForeignModel<T>
+where
+    T::Key: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.table == other.table && self.key == other.key
+    }
+}
+impl<T: PolymorphicTargets> Eq for PolymorphicForeignModel<T> where T::Key: Eq {}
+impl<T: PolymorphicTargets> Hash for PolymorphicForeignModel<T>
+where
+    T::Key: Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.table.hash(state);
+        self.key.hash(state);
+    }
+}
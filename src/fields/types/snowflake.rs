@@ -0,0 +1,215 @@
+//! The [`Snowflake`] key type generated client-side
+
+use std::fmt;
+use std::sync::atomic::{AtomicU16, AtomicU64, Ordering};
+
+use rorm_db::sql::value::NullType;
+
+use crate::conditions::Value;
+use crate::fields::traits::{Array, FieldColumns, FieldType};
+use crate::fields::utils::check::shared_linter_check;
+use crate::fields::utils::get_annotations::forward_annotations;
+use crate::fields::utils::get_names::single_column_name;
+use crate::{impl_FieldEq, impl_FieldOrd, new_converting_decoder};
+
+/// Milliseconds since the unix epoch [`Snowflake`]'s timestamps start at (2020-01-01)
+const EPOCH: u64 = 1_577_836_800_000;
+
+/// Number of bits storing the node id
+const NODE_BITS: u32 = 10;
+
+/// Number of bits storing the sequence number
+const SEQUENCE_BITS: u32 = 12;
+
+/// The node id set by [`Snowflake::set_node_id`]
+static NODE_ID: AtomicU16 = AtomicU16::new(0);
+
+/// A 64 bit sortable identifier made of a timestamp, a node id and a sequence number
+///
+/// Its bits consist of
+/// - 41 bits storing the milliseconds since 2020-01-01
+/// - 10 bits storing the id of the node which generated it
+/// - 12 bits counting the ids generated by the node within the same millisecond
+///
+/// It is stored as `i64`, so newer ids sort after older ones.
+/// Unlike an auto increment, ids are generated without asking the database
+/// and unlike random UUIDs, inserts stay at the end of the primary key's index.
+///
+/// Every process generating ids has to use its own node id to avoid collisions:
+///
+/// ```no_run
+/// # use rorm::{insert, Database, Error, Model, Patch};
+/// use rorm::fields::types::Snowflake;
+///
+/// #[derive(Model)]
+/// pub struct Message {
+///     #[rorm(primary_key)]
+///     pub id: Snowflake,
+///
+///     #[rorm(max_length = 255)]
+///     pub text: String,
+/// }
+///
+/// #[derive(Patch)]
+/// #[rorm(model = "Message")]
+/// pub struct NewMessage {
+///     #[rorm(max_length = 255)]
+///     pub text: String,
+/// }
+///
+/// pub async fn send(db: &Database, text: String) -> Result<Snowflake, Error> {
+///     // Usually done once on startup using the node's configured id
+///     Snowflake::set_node_id(7);
+///
+///     // Inserts which don't set the primary key generate a new one
+///     insert(db, Message)
+///         .return_primary_key()
+///         .single(&NewMessage { text })
+///         .await
+/// }
+/// ```
+///
+/// If more than 4096 ids are generated within one millisecond,
+/// the following ids use the next millisecond's timestamp.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Snowflake(pub i64);
+
+impl Snowflake {
+    /// Set the node id used by all ids generated afterward
+    ///
+    /// # Panics
+    /// If `node_id` doesn't fit into 10 bits i.e. is larger than 1023.
+    pub fn set_node_id(node_id: u16) {
+        assert!(
+            node_id < 1 << NODE_BITS,
+            "A snowflake's node id has to be smaller than {}",
+            1 << NODE_BITS
+        );
+        NODE_ID.store(node_id, Ordering::Relaxed);
+    }
+
+    /// Generate a new id using the node id set by [`Snowflake::set_node_id`]
+    pub fn generate() -> Self {
+        // The timestamp followed by the sequence number of the last generated id
+        static LAST: AtomicU64 = AtomicU64::new(0);
+
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|duration| duration.as_millis() as u64)
+            .unwrap_or_default()
+            .saturating_sub(EPOCH);
+        let next = |last: u64| (now << SEQUENCE_BITS).max(last + 1);
+        let last = LAST
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |last| {
+                Some(next(last))
+            })
+            .unwrap_or_else(|last| last);
+        let next = next(last);
+
+        let timestamp = next >> SEQUENCE_BITS;
+        let sequence = next & ((1 << SEQUENCE_BITS) - 1);
+        let node_id = u64::from(NODE_ID.load(Ordering::Relaxed));
+        Self(
+            ((timestamp << (NODE_BITS + SEQUENCE_BITS)) | (node_id << SEQUENCE_BITS) | sequence)
+                as i64,
+        )
+    }
+
+    /// Get the milliseconds since the unix epoch the id was generated at
+    pub const fn timestamp_ms(&self) -> u64 {
+        ((self.0 as u64) >> (NODE_BITS + SEQUENCE_BITS)) + EPOCH
+    }
+
+    /// Get the id of the node which generated the id
+    pub const fn node_id(&self) -> u16 {
+        (((self.0 as u64) >> SEQUENCE_BITS) & ((1 << NODE_BITS) - 1)) as u16
+    }
+
+    /// Get the id's sequence number within its millisecond
+    pub const fn sequence(&self) -> u16 {
+        ((self.0 as u64) & ((1 << SEQUENCE_BITS) - 1)) as u16
+    }
+}
+
+impl fmt::Display for Snowflake {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl FieldType for Snowflake {
+    type Columns = Array<1>;
+
+    const NULL: FieldColumns<Self, NullType> = [NullType::I64];
+
+    fn into_values<'a>(self) -> FieldColumns<Self, Value<'a>> {
+        [Value::I64(self.0)]
+    }
+
+    fn as_values(&self) -> FieldColumns<Self, Value<'_>> {
+        [Value::I64(self.0)]
+    }
+
+    type Decoder = SnowflakeDecoder;
+
+    type GetAnnotations = forward_annotations<1>;
+    type Check = shared_linter_check<1>;
+    type GetNames = single_column_name;
+
+    fn generate_key() -> Option<Self> {
+        Some(Self::generate())
+    }
+}
+
+new_converting_decoder!(
+    #[doc(hidden)]
+    pub SnowflakeDecoder,
+    |value: i64| -> Snowflake {
+        Ok::<_, String>(Snowflake(value))
+    }
+);
+
+impl_FieldEq!(impl<'rhs> FieldEq<'rhs, Snowflake> for Snowflake { conv_snowflake });
+impl_FieldEq!(impl<'rhs> FieldEq<'rhs, Option<Snowflake>> for Option<Snowflake> { conv_opt_snowflake });
+impl_FieldOrd!(Snowflake, Snowflake, conv_snowflake);
+impl_FieldOrd!(Option<Snowflake>, Option<Snowflake>, conv_opt_snowflake);
+fn conv_snowflake<'a>(value: Snowflake) -> Value<'a> {
+    Value::I64(value.0)
+}
+fn conv_opt_snowflake<'a>(value: Option<Snowflake>) -> Value<'a> {
+    value
+        .map(conv_snowflake)
+        .unwrap_or(Value::Null(NullType::I64))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Snowflake, EPOCH};
+
+    #[test]
+    fn parts() {
+        let snowflake = Snowflake((5 << 22) | (7 << 12) | 3);
+        assert_eq!(snowflake.timestamp_ms(), EPOCH + 5);
+        assert_eq!(snowflake.node_id(), 7);
+        assert_eq!(snowflake.sequence(), 3);
+
+        let snowflake = Snowflake(i64::MAX);
+        assert_eq!(snowflake.node_id(), 1023);
+        assert_eq!(snowflake.sequence(), 4095);
+    }
+
+    #[test]
+    fn generate() {
+        let first = Snowflake::generate();
+        let second = Snowflake::generate();
+        assert!(first < second);
+        assert!(first.0 > 0);
+        assert!(second.timestamp_ms() >= first.timestamp_ms());
+    }
+
+    #[test]
+    #[should_panic]
+    fn invalid_node_id() {
+        Snowflake::set_node_id(1024);
+    }
+}
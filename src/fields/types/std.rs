@@ -1,9 +1,17 @@
 use std::borrow::Cow;
 
-use crate::conditions::Value;
+use crate::conditions::{escape_like, Binary, BinaryOperator, Column, Escaped, Value};
 use crate::db::sql::value::NullType;
+use crate::fields::traits::cmp::{FieldEq, FieldOrd};
+use crate::fields::traits::{Array, FieldColumns, FieldType};
+use crate::fields::utils::check::shared_linter_check;
+use crate::fields::utils::get_annotations::forward_annotations;
+use crate::fields::utils::get_names::single_column_name;
+use crate::internal::field::access::FieldAccess;
+use crate::validate::ValidationErrorKind;
 use crate::{
-    impl_FieldEq, impl_FieldMin_FieldMax, impl_FieldOrd, impl_FieldSum_FieldAvg, impl_FieldType,
+    impl_FieldEq, impl_FieldLike, impl_FieldMin_FieldMax, impl_FieldOrd, impl_FieldSum_FieldAvg,
+    impl_FieldType, new_converting_decoder,
 };
 
 impl_FieldType!(bool, Bool, Value::Bool);
@@ -73,6 +81,13 @@ impl_FieldOrd!(String, &'rhs str, conv_string);
 impl_FieldOrd!(String, &'rhs String, conv_string);
 impl_FieldOrd!(String, String, conv_string);
 impl_FieldOrd!(String, Cow<'rhs, str>, conv_string);
+impl_FieldLike!(String, &'rhs str, conv_string);
+impl_FieldLike!(String, &'rhs String, conv_string);
+impl_FieldLike!(String, String, conv_string);
+impl_FieldLike!(String, Cow<'rhs, str>, conv_string);
+impl_FieldLike!(String, Escaped<'rhs>, |value: Escaped<'rhs>| conv_string(
+    escape_like(value.0)
+));
 impl_FieldMin_FieldMax!(String);
 fn conv_string<'a>(value: impl Into<Cow<'a, str>>) -> Value<'a> {
     Value::String(value.into())
@@ -94,3 +109,272 @@ impl_FieldOrd!(Vec<u8>, Cow<'rhs, [u8]>, conv_bytes);
 fn conv_bytes<'a>(value: impl Into<Cow<'a, [u8]>>) -> Value<'a> {
     Value::Binary(value.into())
 }
+
+/// Implements [`FieldType`] for a [`Cow<'static, _>`] which is stored like its owned variant
+///
+/// This allows models to hold `&'static` defaults without allocating.
+/// Decoding always produces [`Cow::Owned`].
+macro_rules! impl_static_cow {
+    ($borrowed:ty, $owned:ty, $null_type:ident, $variant:ident, $decoder:ident) => {
+        impl FieldType for Cow<'static, $borrowed> {
+            type Columns = Array<1>;
+
+            const NULL: FieldColumns<Self, NullType> = [NullType::$null_type];
+
+            fn into_values<'a>(self) -> FieldColumns<Self, Value<'a>> {
+                [Value::$variant(self)]
+            }
+
+            #[inline(always)]
+            fn as_values(&self) -> FieldColumns<Self, Value<'_>> {
+                [Value::$variant(Cow::Borrowed(self.as_ref()))]
+            }
+
+            type Decoder = $decoder;
+
+            type GetAnnotations = forward_annotations<1>;
+
+            type Check = shared_linter_check<1>;
+
+            type GetNames = single_column_name;
+        }
+        new_converting_decoder!(
+            #[doc(hidden)]
+            pub $decoder,
+            |value: $owned| -> Cow<'static, $borrowed> {
+                Ok::<_, String>(Cow::Owned(value))
+            }
+        );
+        impl_FieldEq!(impl<'rhs> FieldEq<'rhs, &'rhs $borrowed> for Cow<'static, $borrowed> { |value: &'rhs $borrowed| Value::$variant(Cow::Borrowed(value)) });
+        impl_FieldEq!(impl<'rhs> FieldEq<'rhs, Cow<'rhs, $borrowed>> for Cow<'static, $borrowed> { Value::$variant });
+        impl_FieldEq!(impl<'rhs> FieldEq<'rhs, $owned> for Cow<'static, $borrowed> { |value: $owned| Value::$variant(Cow::Owned(value)) });
+        impl_FieldOrd!(Cow<'static, $borrowed>, &'rhs $borrowed, |value: &'rhs $borrowed| Value::$variant(Cow::Borrowed(value)));
+        impl_FieldOrd!(Cow<'static, $borrowed>, Cow<'rhs, $borrowed>, Value::$variant);
+        impl_FieldOrd!(Cow<'static, $borrowed>, $owned, |value: $owned| Value::$variant(Cow::Owned(value)));
+    };
+}
+impl_static_cow!(str, String, String, String, CowStrDecoder);
+impl_static_cow!([u8], Vec<u8>, Binary, Binary, CowBytesDecoder);
+
+/// Implements [`FieldType`] for an integer by storing it as a larger signed one
+///
+/// Every value of the integer fits into the signed type,
+/// so conditions, orderings and aggregations behave like on the integer itself.
+macro_rules! impl_widened_integer {
+    ($type:ty, $signed:ident, $null_type:ident, $decoder:ident, sum_result: $sum_result:ty) => {
+        impl FieldType for $type {
+            type Columns = Array<1>;
+
+            const NULL: FieldColumns<Self, NullType> = [NullType::$null_type];
+
+            fn into_values<'a>(self) -> FieldColumns<Self, Value<'a>> {
+                [Value::$null_type($signed::from(self))]
+            }
+
+            fn as_values(&self) -> FieldColumns<Self, Value<'_>> {
+                (*self).into_values()
+            }
+
+            type Decoder = $decoder;
+
+            type GetAnnotations = forward_annotations<1>;
+
+            type Check = shared_linter_check<1>;
+
+            type GetNames = single_column_name;
+        }
+        new_converting_decoder!(
+            #[doc(hidden)]
+            pub $decoder,
+            |value: $signed| -> $type {
+                <$type>::try_from(value).map_err(|_| {
+                    format!("{value} is out of range for {}", stringify!($type))
+                })
+            }
+        );
+        impl_FieldEq!(impl<'rhs> FieldEq<'rhs, $type> for $type { |value: $type| { let [value] = value.into_values(); value } });
+        impl_FieldEq!(impl<'rhs> FieldEq<'rhs, Option<$type>> for Option<$type> { |option: Self| { let [value] = option.into_values(); value } });
+        impl_FieldOrd!($type, $type, |value: $type| { let [value] = value.into_values(); value });
+        impl_FieldOrd!(Option<$type>, Option<$type>, |option: Self| { let [value] = option.into_values(); value });
+        impl_FieldSum_FieldAvg!($type, sum_result: $sum_result);
+    };
+}
+impl_widened_integer!(i8, i16, I16, I8Decoder, sum_result: i64);
+impl_widened_integer!(u8, i16, I16, U8Decoder, sum_result: i64);
+impl_widened_integer!(u16, i32, I32, U16Decoder, sum_result: i64);
+impl_widened_integer!(u32, i64, I64, U32Decoder, sum_result: f64);
+
+/// `u64` is stored as `i64`
+///
+/// Values above [`i64::MAX`] can't be stored and are rejected by [`FieldType::check_value`].
+/// Conditions comparing with such a value behave as if the column could store it,
+/// i.e. it is larger than every stored value.
+impl FieldType for u64 {
+    type Columns = Array<1>;
+
+    const NULL: FieldColumns<Self, NullType> = [NullType::I64];
+
+    fn into_values<'a>(self) -> FieldColumns<Self, Value<'a>> {
+        // A value above i64::MAX is reported by `check_value` which aborts the query
+        [Value::I64(i64::try_from(self).unwrap_or(i64::MAX))]
+    }
+
+    fn as_values(&self) -> FieldColumns<Self, Value<'_>> {
+        (*self).into_values()
+    }
+
+    type Decoder = U64Decoder;
+
+    type GetAnnotations = forward_annotations<1>;
+
+    type Check = shared_linter_check<1>;
+
+    type GetNames = single_column_name;
+
+    fn check_value(&self) -> Result<(), ValidationErrorKind> {
+        if i64::try_from(*self).is_ok() {
+            Ok(())
+        } else {
+            Err(ValidationErrorKind::Range {
+                min: None,
+                max: Some(i64::MAX as f64),
+            })
+        }
+    }
+}
+new_converting_decoder!(
+    #[doc(hidden)]
+    pub U64Decoder,
+    |value: i64| -> u64 {
+        u64::try_from(value).map_err(|_| format!("{value} is out of range for u64"))
+    }
+);
+impl_FieldSum_FieldAvg!(u64, sum_result: f64);
+
+/// Compare a `u64` column with `value`
+fn compare_u64<'rhs, A: FieldAccess>(
+    access: A,
+    operator: BinaryOperator,
+    value: Option<u64>,
+) -> Binary<Column<A>, Value<'rhs>> {
+    let (operator, snd_arg) = u64_operand(operator, value);
+    Binary {
+        operator,
+        fst_arg: Column(access),
+        snd_arg,
+    }
+}
+
+/// Convert the operator and value of a comparison with a `u64` column
+///
+/// The column only stores values up to [`i64::MAX`].
+/// A larger `value` is therefore replaced by [`i64::MAX`] and the operator adjusted to keep the result.
+fn u64_operand<'rhs>(
+    operator: BinaryOperator,
+    value: Option<u64>,
+) -> (BinaryOperator, Value<'rhs>) {
+    match value.map(i64::try_from) {
+        None => (operator, Value::Null(NullType::I64)),
+        Some(Ok(value)) => (operator, Value::I64(value)),
+        Some(Err(_)) => match operator {
+            // Can't be true for any stored value
+            BinaryOperator::Equals | BinaryOperator::Greater | BinaryOperator::GreaterOrEquals => {
+                (BinaryOperator::Greater, Value::I64(i64::MAX))
+            }
+            // Is true for every stored value
+            _ => (BinaryOperator::LessOrEquals, Value::I64(i64::MAX)),
+        },
+    }
+}
+macro_rules! impl_u64_cmp {
+    ($type:ty, $into_option:expr) => {
+        impl<'rhs> FieldEq<'rhs, $type> for $type {
+            type EqCond<A: FieldAccess> = Binary<Column<A>, Value<'rhs>>;
+            fn field_equals<A: FieldAccess>(access: A, value: $type) -> Self::EqCond<A> {
+                compare_u64(access, BinaryOperator::Equals, $into_option(value))
+            }
+
+            type NeCond<A: FieldAccess> = Binary<Column<A>, Value<'rhs>>;
+            fn field_not_equals<A: FieldAccess>(access: A, value: $type) -> Self::NeCond<A> {
+                compare_u64(access, BinaryOperator::NotEquals, $into_option(value))
+            }
+        }
+        impl<'rhs> FieldOrd<'rhs, $type> for $type {
+            type LtCond<A: FieldAccess> = Binary<Column<A>, Value<'rhs>>;
+            fn field_less_than<A: FieldAccess>(access: A, value: $type) -> Self::LtCond<A> {
+                compare_u64(access, BinaryOperator::Less, $into_option(value))
+            }
+
+            type LeCond<A: FieldAccess> = Binary<Column<A>, Value<'rhs>>;
+            fn field_less_equals<A: FieldAccess>(access: A, value: $type) -> Self::LeCond<A> {
+                compare_u64(access, BinaryOperator::LessOrEquals, $into_option(value))
+            }
+
+            type GtCond<A: FieldAccess> = Binary<Column<A>, Value<'rhs>>;
+            fn field_greater_than<A: FieldAccess>(access: A, value: $type) -> Self::GtCond<A> {
+                compare_u64(access, BinaryOperator::Greater, $into_option(value))
+            }
+
+            type GeCond<A: FieldAccess> = Binary<Column<A>, Value<'rhs>>;
+            fn field_greater_equals<A: FieldAccess>(access: A, value: $type) -> Self::GeCond<A> {
+                compare_u64(access, BinaryOperator::GreaterOrEquals, $into_option(value))
+            }
+        }
+    };
+}
+impl_u64_cmp!(u64, Some);
+impl_u64_cmp!(Option<u64>, std::convert::identity);
+
+#[cfg(test)]
+mod test {
+    use super::u64_operand;
+    use crate::conditions::{BinaryOperator, Value};
+    use crate::fields::traits::FieldType;
+
+    const MAX: u64 = i64::MAX as u64;
+
+    #[test]
+    fn u64_check_value() {
+        assert_eq!(0u64.check_value(), Ok(()));
+        assert_eq!(MAX.check_value(), Ok(()));
+        assert!((MAX + 1).check_value().is_err());
+        assert!(u64::MAX.check_value().is_err());
+        assert!(matches!(MAX.into_values(), [Value::I64(i64::MAX)]));
+    }
+
+    #[test]
+    fn u64_in_range_operand() {
+        assert!(matches!(
+            u64_operand(BinaryOperator::Less, Some(MAX)),
+            (BinaryOperator::Less, Value::I64(i64::MAX))
+        ));
+        assert!(matches!(
+            u64_operand(BinaryOperator::Equals, None),
+            (BinaryOperator::Equals, Value::Null(_))
+        ));
+    }
+
+    #[test]
+    fn u64_out_of_range_operand() {
+        for operator in [
+            BinaryOperator::Equals,
+            BinaryOperator::Greater,
+            BinaryOperator::GreaterOrEquals,
+        ] {
+            assert!(matches!(
+                u64_operand(operator, Some(MAX + 1)),
+                (BinaryOperator::Greater, Value::I64(i64::MAX))
+            ));
+        }
+        for operator in [
+            BinaryOperator::NotEquals,
+            BinaryOperator::Less,
+            BinaryOperator::LessOrEquals,
+        ] {
+            assert!(matches!(
+                u64_operand(operator, Some(u64::MAX)),
+                (BinaryOperator::LessOrEquals, Value::I64(i64::MAX))
+            ));
+        }
+    }
+}
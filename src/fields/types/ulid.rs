@@ -0,0 +1,226 @@
+//! The [`Ulid`] key type generated client-side
+
+use std::borrow::Cow;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Mutex, PoisonError};
+
+use rorm_db::sql::value::NullType;
+
+use crate::conditions::Value;
+use crate::fields::traits::{Array, FieldColumns, FieldType};
+use crate::fields::utils::check::shared_linter_check;
+use crate::fields::utils::get_annotations::forward_annotations;
+use crate::fields::utils::get_names::single_column_name;
+use crate::internal::random_u64;
+use crate::{impl_FieldEq, impl_FieldOrd, new_converting_decoder};
+
+/// Crockford's base32 alphabet used by [`Ulid`]'s string representation
+const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// Number of random bits following the timestamp
+const RANDOM_BITS: u32 = 80;
+
+/// A 128 bit universally unique lexicographically sortable identifier
+///
+/// It consists of the milliseconds since the unix epoch followed by 80 random bits
+/// and is stored as 16 big-endian bytes.
+/// Therefore, newer ids sort after older ones,
+/// which keeps inserts at the end of the primary key's index unlike random UUIDs.
+///
+/// When used as primary key, inserts which don't set it generate a new one:
+///
+/// ```no_run
+/// # use rorm::{insert, Database, Error, Model, Patch};
+/// use rorm::fields::types::Ulid;
+///
+/// #[derive(Model)]
+/// pub struct Event {
+///     #[rorm(primary_key)]
+///     pub id: Ulid,
+///
+///     #[rorm(max_length = 255)]
+///     pub name: String,
+/// }
+///
+/// #[derive(Patch)]
+/// #[rorm(model = "Event")]
+/// pub struct NewEvent {
+///     #[rorm(max_length = 255)]
+///     pub name: String,
+/// }
+///
+/// pub async fn record(db: &Database, name: String) -> Result<Ulid, Error> {
+///     insert(db, Event)
+///         .return_primary_key()
+///         .single(&NewEvent { name })
+///         .await
+/// }
+/// ```
+///
+/// Ids generated by the same process within the same millisecond increment the random part,
+/// so they are strictly increasing.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Ulid(pub u128);
+
+impl Ulid {
+    /// Generate a new id
+    pub fn generate() -> Self {
+        static LAST: Mutex<u128> = Mutex::new(0);
+
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|duration| duration.as_millis())
+            .unwrap_or_default();
+        let mut last = LAST.lock().unwrap_or_else(PoisonError::into_inner);
+        *last = if *last >> RANDOM_BITS >= now {
+            *last + 1
+        } else {
+            let random = ((u128::from(random_u64()) << 64) | u128::from(random_u64()))
+                & ((1 << RANDOM_BITS) - 1);
+            (now << RANDOM_BITS) | random
+        };
+        Self(*last)
+    }
+
+    /// Get the milliseconds since the unix epoch the id was generated at
+    pub const fn timestamp_ms(&self) -> u64 {
+        (self.0 >> RANDOM_BITS) as u64
+    }
+
+    /// Get the id's bytes as stored in the database
+    pub const fn to_bytes(&self) -> [u8; 16] {
+        self.0.to_be_bytes()
+    }
+
+    /// Construct an id from its bytes as stored in the database
+    pub const fn from_bytes(bytes: [u8; 16]) -> Self {
+        Self(u128::from_be_bytes(bytes))
+    }
+}
+
+/// Formats the id as 26 characters using Crockford's base32
+impl fmt::Display for Ulid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut string = [0; 26];
+        for (index, digit) in string.iter_mut().enumerate() {
+            *digit = ALPHABET[((self.0 >> (125 - 5 * index)) & 0x1f) as usize];
+        }
+        f.write_str(std::str::from_utf8(&string).unwrap_or_default())
+    }
+}
+
+impl FromStr for Ulid {
+    type Err = String;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        if string.len() != 26 {
+            return Err(format!("Expected 26 characters, got {}", string.len()));
+        }
+        let mut value: u128 = 0;
+        for (index, byte) in string.bytes().enumerate() {
+            let digit = ALPHABET
+                .iter()
+                .position(|digit| *digit == byte.to_ascii_uppercase())
+                .ok_or_else(|| format!("Invalid character {:?}", byte as char))?;
+            if index == 0 && digit > 7 {
+                return Err("The id is too large".to_string());
+            }
+            value = (value << 5) | digit as u128;
+        }
+        Ok(Self(value))
+    }
+}
+
+impl FieldType for Ulid {
+    type Columns = Array<1>;
+
+    const NULL: FieldColumns<Self, NullType> = [NullType::Binary];
+
+    fn into_values<'a>(self) -> FieldColumns<Self, Value<'a>> {
+        [Value::Binary(Cow::Owned(self.to_bytes().to_vec()))]
+    }
+
+    fn as_values(&self) -> FieldColumns<Self, Value<'_>> {
+        (*self).into_values()
+    }
+
+    type Decoder = UlidDecoder;
+
+    type GetAnnotations = forward_annotations<1>;
+    type Check = shared_linter_check<1>;
+    type GetNames = single_column_name;
+
+    fn generate_key() -> Option<Self> {
+        Some(Self::generate())
+    }
+}
+
+new_converting_decoder!(
+    #[doc(hidden)]
+    pub UlidDecoder,
+    |value: Vec<u8>| -> Ulid {
+        <[u8; 16]>::try_from(value.as_slice())
+            .map(Ulid::from_bytes)
+            .map_err(|_| format!("Expected 16 bytes for an ulid, got {}", value.len()))
+    }
+);
+
+impl_FieldEq!(impl<'rhs> FieldEq<'rhs, Ulid> for Ulid { conv_ulid });
+impl_FieldEq!(impl<'rhs> FieldEq<'rhs, Option<Ulid>> for Option<Ulid> { conv_opt_ulid });
+impl_FieldOrd!(Ulid, Ulid, conv_ulid);
+impl_FieldOrd!(Option<Ulid>, Option<Ulid>, conv_opt_ulid);
+fn conv_ulid<'a>(value: Ulid) -> Value<'a> {
+    let [value] = value.into_values();
+    value
+}
+fn conv_opt_ulid<'a>(value: Option<Ulid>) -> Value<'a> {
+    let [value] = value.into_values();
+    value
+}
+
+#[cfg(test)]
+mod test {
+    use super::Ulid;
+
+    #[test]
+    fn display() {
+        assert_eq!(Ulid(0).to_string(), "00000000000000000000000000");
+        assert_eq!(Ulid(u128::MAX).to_string(), "7ZZZZZZZZZZZZZZZZZZZZZZZZZ");
+        assert_eq!(Ulid(32).to_string(), "00000000000000000000000010");
+    }
+
+    #[test]
+    fn from_str() {
+        assert_eq!("00000000000000000000000010".parse(), Ok(Ulid(32)));
+        assert_eq!("7zzzzzzzzzzzzzzzzzzzzzzzzz".parse(), Ok(Ulid(u128::MAX)));
+        assert!("8ZZZZZZZZZZZZZZZZZZZZZZZZZ".parse::<Ulid>().is_err());
+        assert!("0000000000000000000000000U".parse::<Ulid>().is_err());
+        assert!("0000000000000000000000000".parse::<Ulid>().is_err());
+    }
+
+    #[test]
+    fn roundtrip() {
+        let ulid = Ulid::generate();
+        assert_eq!(ulid.to_string().parse(), Ok(ulid));
+        assert_eq!(Ulid::from_bytes(ulid.to_bytes()), ulid);
+    }
+
+    #[test]
+    fn bytes() {
+        let ulid = Ulid((1 << 120) | 2);
+        let mut bytes = [0; 16];
+        bytes[0] = 1;
+        bytes[15] = 2;
+        assert_eq!(ulid.to_bytes(), bytes);
+    }
+
+    #[test]
+    fn generate() {
+        let first = Ulid::generate();
+        let second = Ulid::generate();
+        assert!(first < second);
+        assert!(second.timestamp_ms() >= first.timestamp_ms());
+        assert!(first.timestamp_ms() > 1_577_836_800_000);
+    }
+}
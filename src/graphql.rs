@@ -0,0 +1,181 @@
+//! Integration with [async-graphql](async_graphql)
+//!
+//! Models can be exposed in a schema by deriving `SimpleObject` next to `Model`.
+//! A [`ForeignModel`](crate::fields::types::ForeignModel) field is resolved lazily as the model it references.
+//! The referenced models of all fields resolved in one request are loaded in batches by [`ModelLoader`],
+//! which has to be added to the schema's data:
+//!
+//! ```no_run
+//! # use async_graphql::dataloader::DataLoader;
+//! # use async_graphql::futures_util::future::BoxFuture;
+//! # use async_graphql::{EmptyMutation, EmptySubscription, Object, Schema, SimpleObject};
+//! # use rorm::fields::types::ForeignModel;
+//! # use rorm::graphql::ModelLoader;
+//! # use rorm::{query, Database, Model};
+//! #[derive(Model, SimpleObject, Clone)]
+//! pub struct User {
+//!     #[rorm(id)]
+//!     pub id: i64,
+//!
+//!     #[rorm(max_length = 255)]
+//!     pub name: String,
+//! }
+//!
+//! #[derive(Model, SimpleObject, Clone)]
+//! pub struct Post {
+//!     #[rorm(id)]
+//!     pub id: i64,
+//!
+//!     // Resolves to a `User` object
+//!     pub author: ForeignModel<User>,
+//! }
+//!
+//! pub struct QueryRoot;
+//!
+//! #[Object]
+//! impl QueryRoot {
+//!     async fn posts(&self, ctx: &async_graphql::Context<'_>) -> async_graphql::Result<Vec<Post>> {
+//!         let db = ctx.data::<Database>()?;
+//!         Ok(query(db, Post).all().await?)
+//!     }
+//! }
+//!
+//! // `spawn` is your runtime's spawn function, e.g. `tokio::spawn`
+//! pub fn schema(
+//!     db: Database,
+//!     spawn: impl Fn(BoxFuture<'static, ()>) + Send + Sync + 'static,
+//! ) -> Schema<QueryRoot, EmptyMutation, EmptySubscription> {
+//!     Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+//!         .data(DataLoader::new(ModelLoader::new(db.clone()), spawn))
+//!         .data(db)
+//!         .finish()
+//! }
+//! ```
+//!
+//! As input, a foreign model is given by the key it references.
+//! [`BackRef`](crate::fields::types::BackRef) fields are not supported
+//! and have to be skipped using `#[graphql(skip)]`.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::future::Future;
+use std::hash::Hash;
+use std::sync::Arc;
+
+use async_graphql::dataloader::{DataLoader, Loader};
+use async_graphql::parser::types::Field;
+use async_graphql::registry::Registry;
+use async_graphql::{
+    ContextSelectionSet, InputType, InputValueError, InputValueResult, OutputType, Positioned,
+    ServerError, ServerResult, Value,
+};
+use rorm_db::{Database, Error};
+
+use crate::fields::types::ForeignModelByField;
+use crate::internal::field::SingleColumnField;
+use crate::model::GetField;
+
+/// [`Loader`] querying the models referenced by [`ForeignModelByField`]s in batches
+///
+/// It is used by resolving a foreign model, see the [module docs](self) for an example.
+pub struct ModelLoader {
+    db: Database,
+}
+
+impl ModelLoader {
+    /// Create a loader running its queries on `db`
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+}
+
+impl<FF> Loader<ForeignModelByField<FF>> for ModelLoader
+where
+    FF: SingleColumnField + 'static,
+    FF::Type: Hash + Eq + Clone + Send + Sync + 'static,
+    FF::Model: GetField<FF> + Clone + Send + Sync + 'static,
+{
+    type Value = FF::Model;
+    type Error = Arc<Error>;
+
+    fn load(
+        &self,
+        keys: &[ForeignModelByField<FF>],
+    ) -> impl Future<Output = Result<HashMap<ForeignModelByField<FF>, FF::Model>, Self::Error>> + Send
+    {
+        async move {
+            let models = ForeignModelByField::query_many(&self.db, keys)
+                .await
+                .map_err(Arc::new)?;
+            Ok(models
+                .into_iter()
+                .map(|(key, model)| (ForeignModelByField(key), model))
+                .collect())
+        }
+    }
+}
+
+impl<FF> OutputType for ForeignModelByField<FF>
+where
+    FF: SingleColumnField + 'static,
+    FF::Type: Hash + Eq + Clone + Send + Sync + 'static,
+    FF::Model: OutputType + GetField<FF> + Clone + 'static,
+{
+    fn type_name() -> Cow<'static, str> {
+        <FF::Model as OutputType>::type_name()
+    }
+
+    fn create_type_info(registry: &mut Registry) -> String {
+        <FF::Model as OutputType>::create_type_info(registry)
+    }
+
+    fn resolve(
+        &self,
+        ctx: &ContextSelectionSet<'_>,
+        field: &Positioned<Field>,
+    ) -> impl Future<Output = ServerResult<Value>> + Send {
+        async move {
+            let loader = ctx
+                .data::<DataLoader<ModelLoader>>()
+                .map_err(|error| error.into_server_error(field.pos))?;
+            let model = loader
+                .load_one(self.clone())
+                .await
+                .map_err(|error| ServerError::new(error.to_string(), Some(field.pos)))?
+                .ok_or_else(|| {
+                    ServerError::new("The referenced model doesn't exist", Some(field.pos))
+                })?;
+            OutputType::resolve(&model, ctx, field).await
+        }
+    }
+}
+
+impl<FF> InputType for ForeignModelByField<FF>
+where
+    FF: SingleColumnField,
+    FF::Type: InputType,
+{
+    type RawValueType = <FF::Type as InputType>::RawValueType;
+
+    fn type_name() -> Cow<'static, str> {
+        <FF::Type as InputType>::type_name()
+    }
+
+    fn create_type_info(registry: &mut Registry) -> String {
+        <FF::Type as InputType>::create_type_info(registry)
+    }
+
+    fn parse(value: Option<Value>) -> InputValueResult<Self> {
+        <FF::Type as InputType>::parse(value)
+            .map(Self)
+            .map_err(InputValueError::propagate)
+    }
+
+    fn to_value(&self) -> Value {
+        InputType::to_value(&self.0)
+    }
+
+    fn as_raw_value(&self) -> Option<&Self::RawValueType> {
+        InputType::as_raw_value(&self.0)
+    }
+}
@@ -0,0 +1,167 @@
+//! Caching model instances by their primary key for the duration of a transaction
+//!
+//! Service flows spanning several functions tend to load the same rows over and over again.
+//! An [`IdentityMap`] wraps a transaction and remembers every instance it has loaded,
+//! so looking up the same primary key again returns the same instance without querying the database:
+//!
+//! ```no_run
+//! # use rorm::{Database, Error, Model};
+//! # use rorm::identity_map::IdentityMap;
+//! # #[derive(Model)] pub struct User { #[rorm(id)] id: i64, #[rorm(max_length = 255)] name: String, }
+//! pub async fn greet(db: &Database, sender: i64, receiver: i64) -> Result<String, Error> {
+//!     let mut tx = db.start_transaction().await?;
+//!     let mut map = IdentityMap::new(&mut tx);
+//!
+//!     // Both users are loaded once, even if `sender == receiver`
+//!     let sender = map.get::<User>(sender).await?;
+//!     let receiver = map.get::<User>(receiver).await?;
+//!
+//!     let greeting = match (sender, receiver) {
+//!         (Some(sender), Some(receiver)) => format!("{} greets {}", sender.name, receiver.name),
+//!         _ => "Nobody greets anybody".to_string(),
+//!     };
+//!     tx.commit().await?;
+//!     Ok(greeting)
+//! }
+//! ```
+//!
+//! Only the lookups made through the map are cached.
+//! Queries executed on [`IdentityMap::transaction`] bypass it,
+//! so rows modified that way should be removed using [`IdentityMap::forget`]
+//! or replaced using [`IdentityMap::insert`].
+//! Primary keys without a row are not cached and will be queried again.
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Arc;
+
+use rorm_db::transaction::Transaction;
+use rorm_db::Error;
+
+use crate::crud::query::query;
+use crate::internal::field::Field;
+use crate::model::{Identifiable, Model};
+
+/// Type of a model's primary key
+type Key<M> = <<M as Model>::Primary as Field>::Type;
+
+/// Caches the model instances loaded on a transaction by their primary key
+///
+/// See the [module docs](self) for an example.
+pub struct IdentityMap<'tx> {
+    tx: &'tx mut Transaction,
+
+    /// Maps a model's [`TypeId`] to its `HashMap<Key<M>, Arc<M>>`
+    models: HashMap<TypeId, Box<dyn Any + Send>>,
+}
+
+impl<'tx> IdentityMap<'tx> {
+    /// Start caching the instances loaded on `tx`
+    pub fn new(tx: &'tx mut Transaction) -> Self {
+        Self {
+            tx,
+            models: HashMap::new(),
+        }
+    }
+
+    /// Reborrow the transaction to execute queries which bypass the cache
+    pub fn transaction(&mut self) -> &mut Transaction {
+        self.tx
+    }
+
+    /// Get the instance of `M` with the primary key `key`
+    ///
+    /// Returns `None` if there is no such row.
+    pub async fn get<M>(&mut self, key: Key<M>) -> Result<Option<Arc<M>>, Error>
+    where
+        M: Model + Identifiable + Send + Sync,
+        M::ValueSpaceImpl: Clone,
+        Key<M>: Hash + Eq + Clone + Send,
+    {
+        let mut instances = self.get_many::<M>([&key]).await?;
+        Ok(instances.remove(&key))
+    }
+
+    /// Get the instances of `M` whose primary key is in `keys`
+    ///
+    /// Only the keys which are not cached yet are queried.
+    /// Keys without a matching row are missing from the returned map.
+    pub async fn get_many<'k, M>(
+        &mut self,
+        keys: impl IntoIterator<Item = &'k Key<M>>,
+    ) -> Result<HashMap<Key<M>, Arc<M>>, Error>
+    where
+        M: Model + Identifiable + Send + Sync,
+        M::ValueSpaceImpl: Clone,
+        Key<M>: Hash + Eq + Clone + Send,
+    {
+        let mut found = HashMap::new();
+        let mut missing = Vec::new();
+        let cached = self.instances::<M>();
+        for key in keys {
+            match cached.get(key) {
+                Some(instance) => {
+                    found.insert(key.clone(), instance.clone());
+                }
+                None => missing.push(key),
+            }
+        }
+
+        if !missing.is_empty() {
+            let loaded = query(&mut *self.tx, M::ValueSpaceImpl::default())
+                .by_primary_keys(missing)
+                .await?;
+            let cached = self.instances::<M>();
+            for (key, instance) in loaded {
+                let instance = Arc::new(instance);
+                cached.insert(key.clone(), instance.clone());
+                found.insert(key, instance);
+            }
+        }
+        Ok(found)
+    }
+
+    /// Cache an instance which has been loaded or modified without the map
+    ///
+    /// A previously cached instance with the same primary key is replaced.
+    pub fn insert<M>(&mut self, instance: M) -> Arc<M>
+    where
+        M: Model + Identifiable + Send + Sync,
+        Key<M>: Hash + Eq + Clone + Send,
+    {
+        let instance = Arc::new(instance);
+        self.instances::<M>()
+            .insert(instance.get_primary_key().clone(), instance.clone());
+        instance
+    }
+
+    /// Remove the instance of `M` with the primary key `key` from the cache
+    ///
+    /// The next lookup will query it again.
+    pub fn forget<M>(&mut self, key: &Key<M>)
+    where
+        M: Model + Send + Sync,
+        Key<M>: Hash + Eq + Send,
+    {
+        self.instances::<M>().remove(key);
+    }
+
+    /// Remove all cached instances
+    pub fn clear(&mut self) {
+        self.models.clear();
+    }
+
+    /// Get the cached instances of `M`
+    fn instances<M>(&mut self) -> &mut HashMap<Key<M>, Arc<M>>
+    where
+        M: Model + Send + Sync,
+        Key<M>: Hash + Eq + Send,
+    {
+        self.models
+            .entry(TypeId::of::<M>())
+            .or_insert_with(|| Box::new(HashMap::<Key<M>, Arc<M>>::new()))
+            .downcast_mut()
+            .expect("The map's entry should match its TypeId")
+    }
+}
@@ -1,10 +1,11 @@
 //! Experimental trait to hide a [`FieldProxy`]s two generics behind a single one.
 
+use std::borrow::Cow;
 use std::marker::PhantomData;
 
 use rorm_db::sql::aggregation::SelectAggregator;
 
-use crate::conditions::{Binary, Column, In, InOperator, Value};
+use crate::conditions::{Binary, BinaryOperator, Column, DynamicCollection, In, InOperator, Value};
 use crate::crud::selector::AggregatedColumn;
 use crate::fields::traits::{
     FieldAvg, FieldCount, FieldEq, FieldLike, FieldMax, FieldMin, FieldOrd, FieldRegexp, FieldSum,
@@ -173,6 +174,9 @@ pub trait FieldAccess: Copy + Sized + Send + Sync + 'static {
     }
 
     /// Compare the field to another value using `LIKE`
+    ///
+    /// Use [`Escaped`](crate::conditions::Escaped) or [`escape_like`](crate::conditions::escape_like)
+    /// to prevent wildcards in user input from being interpreted.
     fn like<'rhs, Rhs: 'rhs, Any>(
         self,
         rhs: Rhs,
@@ -194,6 +198,35 @@ pub trait FieldAccess: Copy + Sized + Send + Sync + 'static {
         <FieldType!()>::field_not_like(self, rhs)
     }
 
+    /// Check the binary field to start with the given bytes
+    ///
+    /// This is rendered as `field >= prefix AND field < successor`
+    /// where `successor` is the smallest byte string greater than every string starting with `prefix`.
+    /// Unlike `LIKE`, this doesn't depend on the backend and can use an index on the field.
+    fn starts_with_bytes<'rhs>(
+        self,
+        prefix: impl Into<Cow<'rhs, [u8]>>,
+    ) -> DynamicCollection<Binary<Column<Self>, Value<'rhs>>>
+    where
+        Self::Field: Field<Type = Vec<u8>>,
+    {
+        let prefix = prefix.into();
+        let successor = prefix_successor(&prefix);
+        let mut conditions = vec![Binary {
+            operator: BinaryOperator::GreaterOrEquals,
+            fst_arg: Column(self),
+            snd_arg: Value::Binary(prefix),
+        }];
+        if let Some(successor) = successor {
+            conditions.push(Binary {
+                operator: BinaryOperator::Less,
+                fst_arg: Column(self),
+                snd_arg: Value::Binary(Cow::Owned(successor)),
+            });
+        }
+        DynamicCollection::and(conditions)
+    }
+
     /// Compare the field to another value using `>=`
     fn regexp<'rhs, Rhs: 'rhs, Any>(
         self,
@@ -291,3 +324,26 @@ impl<F: Field, P: Path> FieldAccess for FieldProxy<F, P> {
     type Field = F;
     type Path = P;
 }
+
+/// Get the smallest byte string greater than every byte string starting with `prefix`
+///
+/// Returns `None` if there is no such string, i.e. `prefix` only consists of `0xFF`.
+fn prefix_successor(prefix: &[u8]) -> Option<Vec<u8>> {
+    let end = prefix.iter().rposition(|byte| *byte != u8::MAX)?;
+    let mut successor = prefix[..=end].to_vec();
+    successor[end] += 1;
+    Some(successor)
+}
+
+#[cfg(test)]
+mod test {
+    use super::prefix_successor;
+
+    #[test]
+    fn successor() {
+        assert_eq!(prefix_successor(b"abc"), Some(b"abd".to_vec()));
+        assert_eq!(prefix_successor(&[1, 0xFF, 0xFF]), Some(vec![2]));
+        assert_eq!(prefix_successor(&[0xFF, 0xFF]), None);
+        assert_eq!(prefix_successor(&[]), None);
+    }
+}
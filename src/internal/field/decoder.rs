@@ -2,7 +2,8 @@
 
 use std::marker::PhantomData;
 
-use rorm_db::row::DecodeOwned;
+use rorm_db::row::{DecodeOwned, RowError};
+use rorm_db::Row;
 
 use crate::crud::decoder::{Decoder, DirectDecoder, NoopDecoder};
 use crate::internal::field::{Field, FieldProxy};
@@ -49,6 +50,48 @@ where
     }
 }
 
+/// [`FieldDecoder`] wrapping the decoder of a field annotated with `#[rorm(defer)]`
+///
+/// Unless the [`QueryContext`] requests the field to be loaded,
+/// it is not selected and decoded as its type's [`Default`].
+pub struct DeferredDecoder<D>(Option<D>);
+impl<D> Decoder for DeferredDecoder<D>
+where
+    D: Decoder<Result: Default>,
+{
+    type Result = D::Result;
+
+    fn by_name<'index>(&'index self, row: &'_ Row) -> Result<Self::Result, RowError<'index>> {
+        match &self.0 {
+            Some(decoder) => decoder.by_name(row),
+            None => Ok(Default::default()),
+        }
+    }
+
+    fn by_index<'index>(&'index self, row: &'_ Row) -> Result<Self::Result, RowError<'index>> {
+        match &self.0 {
+            Some(decoder) => decoder.by_index(row),
+            None => Ok(Default::default()),
+        }
+    }
+}
+impl<D> FieldDecoder for DeferredDecoder<D>
+where
+    D: FieldDecoder<Result: Default>,
+{
+    fn new<F, P>(ctx: &mut QueryContext, field: FieldProxy<F, P>) -> Self
+    where
+        F: Field<Type = Self::Result>,
+        P: Path,
+    {
+        if ctx.is_deferred::<F>() {
+            Self(None)
+        } else {
+            Self(Some(D::new(ctx, field)))
+        }
+    }
+}
+
 #[doc(hidden)]
 #[macro_export]
 macro_rules! new_converting_decoder {
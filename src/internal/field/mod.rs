@@ -47,8 +47,9 @@ use rorm_declaration::imr;
 use crate::conditions::Value;
 use crate::internal::hmr::annotations::Annotations;
 use crate::internal::hmr::{AsImr, Source};
-use crate::internal::relation_path::{Path, PathField};
+use crate::internal::relation_path::{Aliased, Path, PathField};
 use crate::model::{ConstNew, Model};
+use crate::validate::{ValidationError, ValidationErrorKind};
 
 pub mod access;
 pub mod as_db_type;
@@ -94,11 +95,27 @@ pub trait Field: 'static + Copy {
     /// Location of the field in the source code
     const SOURCE: Source;
 
+    /// Is the field annotated with `#[rorm(readonly)]`?
+    ///
+    /// Readonly fields can be queried and inserted but are never written by updates.
+    const READONLY: bool = false;
+
+    /// Is the field annotated with `#[rorm(defer)]`?
+    ///
+    /// Deferred fields are not selected when querying their whole model,
+    /// unless requested using [`QueryBuilder::with_deferred`](crate::crud::query::QueryBuilder::with_deferred).
+    const DEFERRED: bool = false;
+
     /// Create a new instance
     ///
     /// Since `Self` is always a zero sized type, this is a noop.
     /// It exists to enable accessing field method through [`FieldProxy`] without having to forward every one.
     fn new() -> Self;
+
+    /// Check a value against its type's [`FieldType::check_value`] and the field's `#[rorm(validate(..))]` annotation
+    fn validate(value: &Self::Type) -> Result<(), ValidationErrorKind> {
+        value.check_value()
+    }
 }
 
 /// Pushes a [`Field`]'s columns as [`imr`] onto a vector.
@@ -272,6 +289,14 @@ impl<F: Field, P> FieldProxy<F, P> {
     pub fn field(&self) -> F {
         F::new()
     }
+
+    /// Check a value against the field's `#[rorm(validate(..))]` annotation
+    pub fn validate(_field: Self, value: &F::Type) -> Result<(), ValidationError> {
+        F::validate(value).map_err(|kind| ValidationError {
+            field: F::NAME,
+            kind,
+        })
+    }
 }
 impl<Field, Path> Clone for FieldProxy<Field, Path> {
     fn clone(&self) -> Self {
@@ -302,6 +327,22 @@ where
     }
 }
 
+impl<F, P> FieldProxy<F, P>
+where
+    F: Field + PathField<<F as Field>::Type>,
+    P: Path<Current = <F::ParentField as Field>::Model>,
+{
+    /// Access the related model's fields through a separate join
+    ///
+    /// Dereferencing the proxy (i.e. `Post.replies.author`) always uses the same join.
+    /// Different `N` can be used to join the same relation multiple times.
+    pub const fn alias<const N: usize>(
+        self,
+    ) -> &'static <<F::ChildField as Field>::Model as Model>::Fields<Aliased<(F, P), N>> {
+        ConstNew::REF
+    }
+}
+
 impl<T, F, P> ContainerField<T, P> for F
 where
     T: FieldType,
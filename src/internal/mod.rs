@@ -9,6 +9,8 @@ pub mod hmr;
 pub mod patch;
 pub mod query_context;
 pub mod relation_path;
+pub(crate) mod sql;
+pub mod task_local;
 
 pub use rorm_declaration::imr;
 
@@ -23,3 +25,32 @@ impl<F: Fn(&mut std::fmt::Formatter<'_>) -> std::fmt::Result> std::fmt::Display
         (self.0)(f)
     }
 }
+
+/// Generate a random token of 32 hex digits
+///
+/// The token is derived from std's randomly seeded [`RandomState`](std::collections::hash_map::RandomState).
+pub fn random_token() -> String {
+    format!("{:016x}{:016x}", random_u64(), random_u64())
+}
+
+/// Generate a random number
+///
+/// Like [`random_token`] it is derived from std's randomly seeded [`RandomState`](std::collections::hash_map::RandomState).
+/// It is not suitable for cryptographic purposes.
+pub fn random_u64() -> u64 {
+    use std::hash::{BuildHasher, Hasher};
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+    hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+    hasher.write_u32(std::process::id());
+    hasher.write_u128(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|duration| duration.as_nanos())
+            .unwrap_or_default(),
+    );
+    hasher.finish()
+}
@@ -129,3 +129,96 @@ impl<'v> QueryContext<'v> {
         })
     }
 }
+
+impl QueryContext<'_> {
+    /// Render the condition starting with `head` as human-readable sql-like text
+    ///
+    /// Columns are qualified using their aliases and values are rendered using their [`Debug`] implementation.
+    pub(super) fn write_condition_inner(
+        &self,
+        head: FlatCondition,
+        tail: &mut impl Iterator<Item = FlatCondition>,
+        out: &mut String,
+    ) -> Result<(), GetConditionError> {
+        use GetConditionError::*;
+
+        match head {
+            FlatCondition::StartCollection(op) => {
+                let (op, empty) = match op {
+                    CollectionOperator::And => (" AND ", "TRUE"),
+                    CollectionOperator::Or => (" OR ", "FALSE"),
+                };
+                let start = out.len();
+                out.push('(');
+                loop {
+                    let head = tail.next().ok_or(MissingNodes)?;
+                    if matches!(head, FlatCondition::EndCollection) {
+                        break;
+                    }
+                    if out.len() > start + 1 {
+                        out.push_str(op);
+                    }
+                    self.write_condition_inner(head, tail, out)?;
+                }
+                if out.len() == start + 1 {
+                    out.truncate(start);
+                    out.push_str(empty);
+                } else {
+                    out.push(')');
+                }
+            }
+            FlatCondition::EndCollection => return Err(CollectionEnd),
+            FlatCondition::UnaryCondition(op) => {
+                let (prefix, suffix) = match op {
+                    UnaryOperator::IsNull => ("", " IS NULL"),
+                    UnaryOperator::IsNotNull => ("", " IS NOT NULL"),
+                    UnaryOperator::Exists => ("EXISTS ", ""),
+                    UnaryOperator::NotExists => ("NOT EXISTS ", ""),
+                    UnaryOperator::Not => ("NOT ", ""),
+                };
+                out.push_str(prefix);
+                self.write_condition_inner(tail.next().ok_or(MissingNodes)?, tail, out)?;
+                out.push_str(suffix);
+            }
+            FlatCondition::BinaryCondition(op) => {
+                let op = match op {
+                    BinaryOperator::Equals => " = ",
+                    BinaryOperator::NotEquals => " <> ",
+                    BinaryOperator::Greater => " > ",
+                    BinaryOperator::GreaterOrEquals => " >= ",
+                    BinaryOperator::Less => " < ",
+                    BinaryOperator::LessOrEquals => " <= ",
+                    BinaryOperator::Like => " LIKE ",
+                    BinaryOperator::NotLike => " NOT LIKE ",
+                    BinaryOperator::Regexp => " REGEXP ",
+                    BinaryOperator::NotRegexp => " NOT REGEXP ",
+                };
+                self.write_condition_inner(tail.next().ok_or(MissingNodes)?, tail, out)?;
+                out.push_str(op);
+                self.write_condition_inner(tail.next().ok_or(MissingNodes)?, tail, out)?;
+            }
+            FlatCondition::TernaryCondition(op) => {
+                let [start, fst, snd, end] = match op {
+                    TernaryOperator::Between => ["", " BETWEEN ", " AND ", ""],
+                    TernaryOperator::NotBetween => ["", " NOT BETWEEN ", " AND ", ""],
+                };
+                out.push_str(start);
+                self.write_condition_inner(tail.next().ok_or(MissingNodes)?, tail, out)?;
+                out.push_str(fst);
+                self.write_condition_inner(tail.next().ok_or(MissingNodes)?, tail, out)?;
+                out.push_str(snd);
+                self.write_condition_inner(tail.next().ok_or(MissingNodes)?, tail, out)?;
+                out.push_str(end);
+            }
+            FlatCondition::Value(index) => {
+                let value = self.values.get(index).ok_or(UnknownValue)?;
+                out.push_str(&format!("{value:?}"));
+            }
+            FlatCondition::Column(table_name, column_name) => {
+                let alias = self.join_aliases.get(&table_name).ok_or(UnknownAlias)?;
+                out.push_str(&format!("{alias}.{column_name}"));
+            }
+        }
+        Ok(())
+    }
+}
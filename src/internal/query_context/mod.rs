@@ -1,5 +1,6 @@
 //! The query context holds some of a query's data which rorm-db borrows.
 
+use std::any::TypeId;
 use std::borrow::Cow;
 use std::collections::HashMap;
 use std::fmt;
@@ -7,13 +8,14 @@ use std::fmt::Write;
 
 use rorm_db::sql::join_table::JoinType;
 use rorm_db::sql::ordering::Ordering;
+use rorm_db::Error;
 
 use crate::conditions::{BinaryOperator, Condition, Value};
 use crate::crud::selector::AggregatedColumn;
 use crate::internal::field::Field;
 use crate::internal::query_context::flat_conditions::{FlatCondition, GetConditionError};
 use crate::internal::query_context::ids::PathId;
-use crate::internal::relation_path::{Path, PathField};
+use crate::internal::relation_path::{Aliased, Joined, Path, PathField};
 use crate::{FieldAccess, Model};
 
 pub mod flat_conditions;
@@ -31,6 +33,15 @@ pub struct QueryContext<'v> {
     order_bys: Vec<OrderBy>,
     pub(crate) conditions: Vec<FlatCondition>,
     pub(crate) values: Vec<Value<'v>>,
+
+    /// Parameter index and value index of every [`Placeholder`](crate::crud::prepared::Placeholder)
+    pub(crate) placeholders: Vec<(usize, usize)>,
+
+    /// Deferred fields which should be selected anyway
+    loaded_deferred: Vec<TypeId>,
+
+    /// Should all deferred fields be selected?
+    load_all_deferred: bool,
 }
 impl<'v> QueryContext<'v> {
     /// Create an empty context
@@ -38,13 +49,36 @@ impl<'v> QueryContext<'v> {
         Self::default()
     }
 
+    /// Select the deferred field `F` when its model is selected
+    pub fn load_deferred<F: Field>(&mut self) {
+        self.loaded_deferred.push(TypeId::of::<F>());
+    }
+
+    /// Select all deferred fields when their model is selected
+    pub fn load_all_deferred(&mut self) {
+        self.load_all_deferred = true;
+    }
+
+    /// Should the field `F` be skipped when selecting its model?
+    pub fn is_deferred<F: Field>(&self) -> bool {
+        F::DEFERRED && !self.load_all_deferred && !self.loaded_deferred.contains(&TypeId::of::<F>())
+    }
+
     /// Add a field to select returning its index and alias
     pub fn select_field<F: Field, P: Path>(&mut self) -> (usize, String) {
+        self.select_column::<P>(F::NAME)
+    }
+
+    /// Add a column to select returning its index and alias
+    ///
+    /// This is used by field types with more than one column,
+    /// whose columns' names differ from the field's name.
+    pub fn select_column<P: Path>(&mut self, column_name: &'static str) -> (usize, String) {
         P::add_to_context(self);
         let alias = format!("{}", NumberAsAZ(self.selects.len()));
         self.selects.push(Select {
             table_name: PathId::of::<P>(),
-            column_name: F::NAME,
+            column_name,
             select_alias: alias.clone(),
             aggregation: None,
         });
@@ -87,25 +121,120 @@ impl<'v> QueryContext<'v> {
         })
     }
 
+    /// Explicitly join the model `M` using the condition `on`
+    ///
+    /// Its fields can be accessed through [`Joined<M, O>`](Joined).
+    pub fn join_model<M: Model, O: Model>(&mut self, on: &(impl Condition<'v> + ?Sized)) {
+        self.add_joined_path::<M, O>();
+        let condition = self.add_condition(on);
+
+        let path_id = PathId::of::<Joined<M, O>>();
+        let join = self
+            .joins
+            .iter_mut()
+            .find(|join| join.join_alias == path_id)
+            .unwrap();
+        join.join_condition = Some(condition);
+    }
+
+    /// Check that every [`Joined`] model whose fields have been used was joined via [`QueryContext::join_model`]
+    pub fn check_joins(&self) -> Result<(), Error> {
+        match self.joins.iter().find(|join| join.join_condition.is_none()) {
+            None => Ok(()),
+            Some(join) => Err(Error::DecodeError(format!(
+                "Used the fields of {} which was not joined\n{}",
+                join.table_name,
+                self.join_tree()
+            ))),
+        }
+    }
+
     /// Create a vector borrowing the joins in rorm_db's format which can be passed to it as slice.
+    ///
+    /// [`Joined`] models which have not been joined are skipped, use [`QueryContext::check_joins`] to reject them.
     pub fn get_joins(&self) -> Vec<rorm_db::database::JoinTable> {
         self.joins
             .iter()
-            .map(
+            .filter_map(
                 |Join {
                      table_name,
                      join_alias,
                      join_condition,
-                 }| rorm_db::database::JoinTable {
-                    join_type: JoinType::Join,
-                    table_name,
-                    join_alias: self.join_aliases.get(join_alias).unwrap(),
-                    join_condition: Cow::Owned(self.get_condition(*join_condition)),
+                     ..
+                 }| {
+                    Some(rorm_db::database::JoinTable {
+                        join_type: JoinType::Join,
+                        table_name,
+                        join_alias: self.join_aliases.get(join_alias).unwrap(),
+                        join_condition: Cow::Owned(self.get_condition((*join_condition)?)),
+                    })
                 },
             )
             .collect()
     }
 
+    /// Describe the tables joined by this context for debugging
+    ///
+    /// The returned [`JoinTree`] lists every join with its alias and `ON` condition.
+    /// Its [`Display`](fmt::Display) implementation renders them as a tree following the relation paths:
+    ///
+    /// ```text
+    /// comment
+    /// └ JOIN user AS a ON a.id = comment.user
+    ///   └ JOIN group AS b ON b.id = a.group
+    /// ```
+    pub fn join_tree(&self) -> JoinTree<'_> {
+        let mut origins: Vec<_> = self
+            .join_aliases
+            .iter()
+            .filter(|(path_id, _)| !self.joins.iter().any(|join| join.join_alias == **path_id))
+            .map(|(_, alias)| alias.as_str())
+            .collect();
+        origins.sort_unstable();
+
+        let joins = self
+            .joins
+            .iter()
+            .map(|join| JoinInfo {
+                table: join.table_name,
+                alias: self
+                    .join_aliases
+                    .get(&join.join_alias)
+                    .map(String::as_str)
+                    .unwrap_or_default(),
+                via: join.via.map(|(parent, field)| {
+                    let parent = self.join_aliases.get(&parent).map(String::as_str);
+                    (parent.unwrap_or_default(), field)
+                }),
+                condition: join.join_condition.map(|index| {
+                    let mut condition = String::new();
+                    if let Err(error) = self.try_write_condition(index, &mut condition) {
+                        condition = format!("<{error:?}>");
+                    }
+                    condition
+                }),
+            })
+            .collect();
+        JoinTree { origins, joins }
+    }
+
+    /// Add the context's joins to an error caused by an ambiguous column
+    ///
+    /// Such errors are reported by the database, which doesn't know about the relation paths
+    /// the aliases have been generated for.
+    /// Other errors are returned unchanged.
+    pub fn explain_error(&self, error: Error) -> Error {
+        let message = error.to_string();
+        if message.contains("ambiguous") {
+            Error::DecodeError(format!(
+                "{message}\nThe query's joins are:\n{}",
+                self.join_tree()
+            ))
+        } else {
+            error
+        }
+    }
+
     /// Create a vector borrowing the selects in rorm_db's format which can be passed to it as slice.
     pub fn get_selects(&self) -> Vec<rorm_db::database::ColumnSelector> {
         self.selects
@@ -166,6 +295,28 @@ impl<'v> QueryContext<'v> {
             .expect("Got invalid condition index")
     }
 
+    /// Render a previously added `Condition` as human-readable text
+    ///
+    /// This is intended for debugging, see [`QueryContext::join_tree`].
+    ///
+    /// # Errors
+    /// In the same cases as [`QueryContext::try_get_condition`].
+    pub fn try_write_condition(
+        &self,
+        index: usize,
+        out: &mut String,
+    ) -> Result<(), GetConditionError> {
+        let (head, mut tail) = self
+            .conditions
+            .get(index..)
+            .and_then(|subslice| {
+                let mut nodes = subslice.iter().copied();
+                nodes.next().zip(Some(nodes))
+            })
+            .ok_or(GetConditionError::MissingNodes)?;
+        self.write_condition_inner(head, &mut tail, out)
+    }
+
     /// Shorthand for calling [`Self::get_condition`] on an optional index
     pub fn get_condition_opt(
         &self,
@@ -234,7 +385,28 @@ impl<'v> QueryContext<'v> {
         F: Field + PathField<<F as Field>::Type>,
         P: Path<Current = <F::ParentField as Field>::Model>,
     {
-        let path_id = PathId::of::<P::Step<F>>();
+        self.add_join::<F, P>(PathId::of::<P::Step<F>>())
+    }
+
+    /// **Use [`Path::add_to_context`], this method is its impl detail!**
+    ///
+    /// Add an [`Aliased`](crate::internal::relation_path::Aliased) relation path to the builder
+    ///
+    /// The join is the same as [`add_relation_path`](Self::add_relation_path)'s but uses a separate alias.
+    pub(crate) fn add_aliased_path<F, P, const N: usize>(&mut self) -> &str
+    where
+        F: Field + PathField<<F as Field>::Type>,
+        P: Path<Current = <F::ParentField as Field>::Model>,
+    {
+        self.add_join::<F, P>(PathId::of::<Aliased<(F, P), N>>())
+    }
+
+    /// Join the model `F` points to from `P` under the alias identified by `path_id`
+    fn add_join<F, P>(&mut self, path_id: PathId) -> &str
+    where
+        F: Field + PathField<<F as Field>::Type>,
+        P: Path<Current = <F::ParentField as Field>::Model>,
+    {
         if !self.join_aliases.contains_key(&path_id) {
             P::add_to_context(self);
             let alias = format!("{}", NumberAsAZ(self.join_aliases.len()));
@@ -243,7 +415,8 @@ impl<'v> QueryContext<'v> {
                 Join {
                     table_name: <<F as PathField<_>>::ChildField as Field>::Model::TABLE,
                     join_alias: path_id,
-                    join_condition: self.conditions.len(),
+                    join_condition: Some(self.conditions.len()),
+                    via: Some((PathId::of::<P>(), F::NAME)),
                 }
             });
             self.conditions.extend([
@@ -254,6 +427,26 @@ impl<'v> QueryContext<'v> {
         }
         self.join_aliases.get(&path_id).unwrap()
     }
+
+    /// **Use [`Path::add_to_context`], this method is its impl detail!**
+    ///
+    /// Add the alias for a [`Joined`] model to the builder
+    ///
+    /// The join's condition is added later by [`QueryContext::join_model`].
+    pub(crate) fn add_joined_path<M: Model, O: Model>(&mut self) -> &str {
+        let path_id = PathId::of::<Joined<M, O>>();
+        if !self.join_aliases.contains_key(&path_id) {
+            let alias = format!("{}", NumberAsAZ(self.join_aliases.len()));
+            self.join_aliases.insert(path_id, alias);
+            self.joins.push(Join {
+                table_name: M::TABLE,
+                join_alias: path_id,
+                join_condition: None,
+                via: None,
+            });
+        }
+        self.join_aliases.get(&path_id).unwrap()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -268,7 +461,96 @@ struct Select {
 struct Join {
     table_name: &'static str,
     join_alias: PathId,
-    join_condition: usize,
+
+    /// `None` until an explicitly [`Joined`] model's condition is added
+    join_condition: Option<usize>,
+
+    /// The path and relation field this join follows or `None` for an explicitly [`Joined`] model
+    via: Option<(PathId, &'static str)>,
+}
+
+/// The tables joined by a [`QueryContext`] as returned by [`QueryContext::join_tree`]
+#[derive(Clone, Debug)]
+pub struct JoinTree<'a> {
+    origins: Vec<&'a str>,
+    joins: Vec<JoinInfo<'a>>,
+}
+
+impl<'a> JoinTree<'a> {
+    /// Get the joins in the order they appear in the query
+    pub fn joins(&self) -> &[JoinInfo<'a>] {
+        &self.joins
+    }
+
+    /// Write the joins starting from `parent` indented by `depth`
+    fn fmt_children(&self, f: &mut fmt::Formatter<'_>, parent: &str, depth: usize) -> fmt::Result {
+        let children = self
+            .joins
+            .iter()
+            .filter(|join| join.via.map(|(via, _)| via) == Some(parent));
+        for join in children {
+            self.fmt_join(f, join, depth)?;
+        }
+        Ok(())
+    }
+
+    /// Write a single join and its children
+    fn fmt_join(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        join: &JoinInfo<'a>,
+        depth: usize,
+    ) -> fmt::Result {
+        write!(f, "\n{:width$}└ {join}", "", width = 2 * depth)?;
+        self.fmt_children(f, join.alias, depth + 1)
+    }
+}
+
+impl fmt::Display for JoinTree<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, origin) in self.origins.iter().enumerate() {
+            if index > 0 {
+                f.write_char('\n')?;
+            }
+            f.write_str(origin)?;
+            self.fmt_children(f, origin, 0)?;
+        }
+        // Explicitly joined models don't follow a relation
+        for join in self.joins.iter().filter(|join| join.via.is_none()) {
+            self.fmt_join(f, join, 0)?;
+        }
+        Ok(())
+    }
+}
+
+/// A single join as returned by [`QueryContext::join_tree`]
+#[derive(Clone, Debug)]
+pub struct JoinInfo<'a> {
+    /// The joined table's name
+    pub table: &'static str,
+
+    /// The alias the table is joined as
+    pub alias: &'a str,
+
+    /// The alias of the table the join starts from and the name of the relation field it follows
+    ///
+    /// This is `None` for models joined explicitly using [`QueryContext::join_model`].
+    pub via: Option<(&'a str, &'static str)>,
+
+    /// The join's `ON` condition rendered as text
+    ///
+    /// This is `None` for explicitly [`Joined`] models whose condition hasn't been added yet.
+    pub condition: Option<String>,
+}
+
+impl fmt::Display for JoinInfo<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "JOIN {} AS {}", self.table, self.alias)?;
+        match &self.condition {
+            Some(condition) => write!(f, " ON {condition}"),
+            None => f.write_str(" (not joined yet)"),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -1,7 +1,7 @@
 use crate::internal::field::foreign_model::{ForeignModelField, ForeignModelTrait};
 use crate::internal::field::{Field, SingleColumnField};
 use crate::internal::query_context::QueryContext;
-use crate::internal::relation_path::{Path, PathField};
+use crate::internal::relation_path::{Aliased, Joined, Path, PathField};
 use crate::prelude::{BackRef, ForeignModelByField};
 use crate::{sealed, Model};
 
@@ -45,6 +45,44 @@ where
     }
 }
 
+impl<F, P, const N: usize> Path for Aliased<(F, P), N>
+where
+    F: Field + PathField<<F as Field>::Type>,
+    P: Path<Current = <F::ParentField as Field>::Model>,
+{
+    sealed!(impl);
+
+    type Origin = P::Origin;
+
+    type Current = <(F, P) as Path>::Current;
+
+    type Step<F2> = (F2, Self)
+    where
+        F2: Field + PathField<<F2 as Field>::Type>,
+        F2::ParentField: Field<Model = Self::Current>;
+
+    fn add_to_context<'ctx>(context: &'ctx mut QueryContext) -> &'ctx str {
+        context.add_aliased_path::<F, P, N>()
+    }
+}
+
+impl<M: Model, O: Model> Path for Joined<M, O> {
+    sealed!(impl);
+
+    type Origin = O;
+
+    type Current = M;
+
+    type Step<F> = (F, Self)
+    where
+        F: Field + PathField<<F as Field>::Type>,
+        F::ParentField: Field<Model = Self::Current>;
+
+    fn add_to_context<'ctx>(context: &'ctx mut QueryContext) -> &'ctx str {
+        context.add_joined_path::<M, O>()
+    }
+}
+
 impl<FF, F> PathField<ForeignModelByField<FF>> for F
 where
     FF: SingleColumnField,
@@ -2,8 +2,11 @@
 
 mod impls;
 
+use std::marker::PhantomData;
+
 use crate::internal::field::{Field, SingleColumnField};
 use crate::internal::query_context::QueryContext;
+use crate::model::ConstNew;
 use crate::{sealed, Model};
 
 /// Trait to store a relation path in generics
@@ -51,6 +54,45 @@ use crate::{sealed, Model};
 ///
 /// As the example above showed, single path steps are represented as tuples.
 /// However, this should be treated as implementation detail and not depended on outside of this module.
+///
+/// ## Self references
+/// Every path gets its own alias, so a model referencing itself can be joined to any depth:
+/// ```
+/// # use rorm::prelude::*;
+/// #[derive(Model)]
+/// struct Post {
+///     #[rorm(id)]
+///     id: i64,
+///     #[rorm(max_length = 255)]
+///     title: String,
+///     reply_to: Option<ForeignModel<Post>>,
+/// }
+///
+/// // Joins the posts table three times
+/// let _ = (Post.title, Post.reply_to.title, Post.reply_to.reply_to.title);
+/// ```
+///
+/// ## Aliases
+/// Accessing the same relation twice reuses its join.
+/// To join it a second time (for example to check two conditions on different rows of a [`BackRef`](crate::fields::types::BackRef)),
+/// use [`FieldProxy::alias`](crate::internal::field::FieldProxy::alias) which wraps the path in [`Aliased`]:
+/// ```
+/// # use rorm::prelude::*;
+/// # #[derive(Model)]
+/// # struct Post {
+/// #     #[rorm(id)]
+/// #     id: i64,
+/// #     #[rorm(max_length = 255)]
+/// #     author: String,
+/// #     reply_to: Option<ForeignModel<Post>>,
+/// #     replies: BackRef<field!(Post.reply_to)>,
+/// # }
+/// // Posts with replies from alice and bob
+/// let _ = rorm::and![
+///     Post.replies.author.equals("alice"),
+///     Post.replies.alias::<1>().author.equals("bob"),
+/// ];
+/// ```
 pub trait Path: 'static {
     sealed!(trait);
 
@@ -83,6 +125,24 @@ pub trait Path: 'static {
     fn add_to_context<'ctx>(context: &'ctx mut QueryContext) -> &'ctx str;
 }
 
+/// A path which is joined under a separate alias than `P`
+///
+/// `N` distinguishes multiple aliases of the same path.
+/// See [`FieldProxy::alias`](crate::internal::field::FieldProxy::alias).
+pub struct Aliased<P, const N: usize>(PhantomData<P>);
+
+/// A model which is joined explicitly using [`QueryBuilder::join`](crate::crud::query::QueryBuilder::join)
+///
+/// `M` is the joined model and `O` the model the query selects from.
+pub struct Joined<M, O>(PhantomData<fn() -> (M, O)>);
+
+impl<M: Model, O: Model> Joined<M, O> {
+    /// Access the joined model's fields
+    pub const fn fields() -> &'static M::Fields<Self> {
+        ConstNew::REF
+    }
+}
+
 /// A field representing a db relation which can be used to construct paths.
 ///
 /// When applied to a path (using [`Path::Step`])
@@ -0,0 +1,65 @@
+//! Helpers for writing sql by hand
+//!
+//! They are used by the features which manage their own tables or use dialect specific statements.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rorm_db::sql::DBImpl;
+
+/// Quote an identifier for `dialect`
+pub(crate) fn quote(dialect: DBImpl, identifier: &str) -> String {
+    match dialect {
+        #[cfg(feature = "all-drivers")]
+        DBImpl::MySQL => format!("`{}`", identifier.replace('`', "``")),
+        #[allow(unreachable_patterns)]
+        _ => format!("\"{}\"", identifier.replace('"', "\"\"")),
+    }
+}
+
+/// Render the `index`th parameter (starting at 1) for `dialect`
+pub(crate) fn placeholder(dialect: DBImpl, index: usize) -> String {
+    match dialect {
+        DBImpl::Postgres => format!("${index}"),
+        #[allow(unreachable_patterns)]
+        _ => "?".to_string(),
+    }
+}
+
+/// Get the current unix timestamp in seconds
+pub(crate) fn unix_timestamp_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+/// Get the current unix timestamp in milliseconds
+#[cfg(feature = "locks")]
+pub(crate) fn unix_timestamp_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as i64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    use rorm_db::sql::DBImpl;
+
+    use super::{placeholder, quote};
+
+    #[test]
+    fn quoting() {
+        assert_eq!(quote(DBImpl::Postgres, "user"), "\"user\"");
+        assert_eq!(quote(DBImpl::Postgres, "a\"b"), "\"a\"\"b\"");
+        #[cfg(feature = "all-drivers")]
+        assert_eq!(quote(DBImpl::MySQL, "a`b"), "`a``b`");
+    }
+
+    #[test]
+    fn placeholders() {
+        assert_eq!(placeholder(DBImpl::Postgres, 2), "$2");
+        #[cfg(feature = "all-drivers")]
+        assert_eq!(placeholder(DBImpl::SQLite, 2), "?");
+    }
+}
@@ -0,0 +1,180 @@
+//! A minimal task-local storage similar to tokio's `task_local!`
+//!
+//! A value is attached to a future using [`TaskLocal::scope`].
+//! While the future is being polled or dropped, the value is moved into a thread-local slot
+//! and moved back out afterward, even if the future panics.
+//!
+//! ```
+//! # use rorm::internal::task_local::TaskLocal;
+//! thread_local! {
+//!     static SLOT: std::cell::RefCell<Option<u32>> = const { std::cell::RefCell::new(None) };
+//! }
+//! static VALUE: TaskLocal<u32> = TaskLocal::new(&SLOT);
+//! ```
+
+use std::cell::RefCell;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::thread::LocalKey;
+
+use pin_project::{pin_project, pinned_drop};
+
+/// A key for a task-local value of type `T`
+///
+/// It wraps a thread-local slot which holds the value while a [`TaskLocalFuture`] runs.
+pub struct TaskLocal<T: 'static> {
+    slot: &'static LocalKey<RefCell<Option<T>>>,
+}
+
+impl<T: 'static> TaskLocal<T> {
+    /// Construct a key from the thread-local slot to use
+    pub const fn new(slot: &'static LocalKey<RefCell<Option<T>>>) -> Self {
+        Self { slot }
+    }
+
+    /// Run a future with `value` set for this key
+    pub fn scope<F: Future>(&'static self, value: T, future: F) -> TaskLocalFuture<T, F> {
+        TaskLocalFuture {
+            key: self,
+            value: Some(value),
+            future: Some(future),
+        }
+    }
+
+    /// Access the value set by the surrounding [`scope`](Self::scope)
+    ///
+    /// `f` receives `None` if there is no surrounding scope.
+    pub fn with<R>(&'static self, f: impl FnOnce(Option<&T>) -> R) -> R {
+        self.slot.with(|slot| f(slot.borrow().as_ref()))
+    }
+
+    /// Get a copy of the value set by the surrounding [`scope`](Self::scope)
+    pub fn get(&'static self) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.with(|value| value.cloned())
+    }
+
+    /// Move `value` into the slot for the duration of `f`
+    fn enter<R>(&'static self, value: &mut Option<T>, f: impl FnOnce() -> R) -> R {
+        struct Guard<'a, T: 'static> {
+            slot: &'static LocalKey<RefCell<Option<T>>>,
+            value: &'a mut Option<T>,
+        }
+        impl<T: 'static> Drop for Guard<'_, T> {
+            fn drop(&mut self) {
+                self.slot
+                    .with(|slot| std::mem::swap(&mut *slot.borrow_mut(), self.value));
+            }
+        }
+
+        self.slot
+            .with(|slot| std::mem::swap(&mut *slot.borrow_mut(), value));
+        let _guard = Guard {
+            slot: self.slot,
+            value,
+        };
+        f()
+    }
+}
+
+/// Future returned by [`TaskLocal::scope`]
+#[pin_project(PinnedDrop)]
+pub struct TaskLocalFuture<T: 'static, F: Future> {
+    key: &'static TaskLocal<T>,
+    value: Option<T>,
+    #[pin]
+    future: Option<F>,
+}
+
+impl<T: 'static, F: Future> Future for TaskLocalFuture<T, F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let mut future = this.future;
+        this.key.enter(this.value, || {
+            let poll = future
+                .as_mut()
+                .as_pin_mut()
+                .expect("TaskLocalFuture polled after completion")
+                .poll(cx);
+            if poll.is_ready() {
+                future.set(None);
+            }
+            poll
+        })
+    }
+}
+
+#[pinned_drop]
+impl<T: 'static, F: Future> PinnedDrop for TaskLocalFuture<T, F> {
+    fn drop(self: Pin<&mut Self>) {
+        let this = self.project();
+        let mut future = this.future;
+        if future.is_some() {
+            this.key.enter(this.value, || future.set(None));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::RefCell;
+    use std::future::Future;
+    use std::pin::pin;
+    use std::task::{Context, Poll, Waker};
+
+    use super::TaskLocal;
+
+    thread_local! {
+        static SLOT: RefCell<Option<u32>> = const { RefCell::new(None) };
+    }
+    static VALUE: TaskLocal<u32> = TaskLocal::new(&SLOT);
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = pin!(future);
+        let mut cx = Context::from_waker(Waker::noop());
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        }
+    }
+
+    #[test]
+    fn scoped() {
+        assert_eq!(VALUE.get(), None);
+        assert_eq!(block_on(VALUE.scope(1, async { VALUE.get() })), Some(1));
+        assert_eq!(VALUE.get(), None);
+    }
+
+    #[test]
+    fn nested() {
+        let output = block_on(VALUE.scope(1, async {
+            let inner = VALUE.scope(2, async { VALUE.get() }).await;
+            (inner, VALUE.get())
+        }));
+        assert_eq!(output, (Some(2), Some(1)));
+    }
+
+    #[test]
+    fn dropped() {
+        struct Check;
+        impl Drop for Check {
+            fn drop(&mut self) {
+                assert_eq!(VALUE.get(), Some(3));
+            }
+        }
+
+        let check = Check;
+        let future = VALUE.scope(3, async move {
+            let _check = check;
+            std::future::pending::<()>().await;
+        });
+        drop(future);
+        assert_eq!(VALUE.get(), None);
+    }
+}
@@ -1,13 +1,55 @@
 //! Rorm is a rust orm.
 //!
 //! [List of all types valid as model fields](fields)
+//!
+//! All queries are executed on an [`Executor`] which is implemented by `&Database` and `&mut Transaction`.
+//! Functions taking an `impl Executor<'_>` can therefore be used inside and outside of transactions:
+//!
+//! ```no_run
+//! # use rorm::{query, Database, Error, Executor, Model, Transaction};
+//! # #[derive(Model)] pub struct User { #[rorm(id)] id: i64, #[rorm(max_length = 255)] name: String, }
+//! pub async fn count_users(executor: impl Executor<'_>) -> Result<usize, Error> {
+//!     Ok(query(executor, User.id).all().await?.len())
+//! }
+//!
+//! pub async fn example(db: &Database, tx: &mut Transaction) -> Result<(), Error> {
+//!     count_users(db).await?;
+//!     count_users(tx).await?;
+//!     Ok(())
+//! }
+//! ```
+//!
+//! The builders interact with the database only through [`Executor`].
+//! Its sql is rendered by rorm-sql for the dialect returned by [`Executor::dialect`].
+//! An executor can be wrapped by implementing the trait and forwarding to the inner executor.
+//! The builders wrap theirs in [`Scoped`](transaction::Scoped) which rewrites every query,
+//! including the ones in transactions the builders start, e.g. to add [comments](crud::comment).
+//!
+//! Crates which only run queries on models defined in another crate can disable the default
+//! features `derive` (the derive macros) and `registry` (collecting all models for the migrator):
+//!
+//! ```toml
+//! [dependencies]
+//! rorm = { version = "*", default-features = false, features = ["postgres-only"] }
+//! ```
+//!
+//! `derive` requires `registry`, because the derived models register themselves.
+//! Cargo compiles rorm once with the features of all its dependents,
+//! so this only takes effect if no crate in the dependency graph enables them.
+//!
+//! Every query is a distinct generic type, which is compiled separately.
+//! Crates with hundreds of queries can enable the feature `dyn-conditions` to reduce their compile times:
+//! [`QueryBuilder::condition`](crud::query::QueryBuilder::condition) then erases the condition's type
+//! into an [`ArcCondition`](conditions::ArcCondition) at the cost of an allocation per query.
+//! Selectors can be erased explicitly using [`Selector::boxed`](crud::selector::Selector::boxed).
 #![cfg_attr(all(doc, CHANNEL_NIGHTLY), feature(doc_auto_cfg))]
 #![warn(missing_docs)]
 
 #[cfg(all(feature = "all-drivers", feature = "postgres-only"))]
 compile_error!("You cannot enable postgres-only with other drivers active");
 
-pub use rorm_db::{Database, DatabaseConfiguration, DatabaseDriver, Error, Row};
+pub use rorm_db::transaction::Transaction;
+pub use rorm_db::{Database, DatabaseConfiguration, DatabaseDriver, Error, Executor, Row};
 
 pub use crate::internal::field::access::FieldAccess;
 pub use crate::model::{Model, Patch};
@@ -21,17 +63,22 @@ pub mod cli {
 pub mod db {
     pub use rorm_db::*;
 }
+#[cfg(feature = "registry")]
 #[doc(hidden)] // used by macros
 pub use linkme;
 /// Re-exported for use in parser structs of user
 pub use rorm_declaration::config;
-#[doc(hidden)] // used by macros
+/// Re-exported for inspecting models through [`registry`]
 pub use rorm_declaration::imr;
 
 /// A prelude of common types, traits and derive macros that are used by `rorm`
 pub mod prelude {
-    pub use rorm_macro::{DbEnum, Model, Patch};
+    pub use rorm_db::Executor;
+    #[cfg(feature = "derive")]
+    pub use rorm_macro::{DbEnum, Model, ModelEnum, NewType, Patch};
 
+    pub use crate::crud::refresh::Refresh;
+    pub use crate::crud::save::Save;
     pub use crate::field;
     pub use crate::fields::types::{BackRef, ForeignModel, ForeignModelByField};
     pub use crate::internal::field::access::FieldAccess;
@@ -41,23 +88,70 @@ pub mod prelude {
 pub use crate::crud::delete::delete;
 pub use crate::crud::insert::insert;
 pub use crate::crud::query::query;
+pub use crate::crud::truncate::truncate;
 pub use crate::crud::update::update;
 
+#[cfg(feature = "admin")]
+pub mod admin;
+pub mod aggregate;
+pub mod attach;
+pub mod audit;
+pub mod backup;
+#[cfg(feature = "bench-utils")]
+pub mod bench_utils;
+pub mod blob;
+pub mod cancel;
 pub mod conditions;
+pub mod content_hash;
+pub mod counter_cache;
 pub mod crud;
+pub mod database;
+pub mod events;
 pub mod fields;
+#[cfg(feature = "async-graphql")]
+pub mod graphql;
+pub mod identity_map;
 pub mod internal;
+pub mod loader;
+#[cfg(feature = "locks")]
+pub mod locks;
 pub mod model;
+pub mod model_enum;
+#[cfg(feature = "outbox")]
+pub mod outbox;
+pub mod partition;
+#[cfg(feature = "registry")]
+pub mod registry;
+pub mod routing;
+pub mod sequence;
+#[cfg(feature = "sessions")]
+pub mod sessions;
+pub mod sharding;
+pub mod tenant;
+pub mod time_partition;
+pub mod transaction;
+pub mod validate;
 
 /// This slice is populated by the [`Model`] macro with all models.
 ///
 /// [`Model`]: rorm_macro::Model
+#[cfg(feature = "registry")]
 #[allow(non_camel_case_types)]
 #[linkme::distributed_slice]
 #[doc(hidden)]
 pub static MODELS: [fn() -> imr::Model] = [..];
 
+/// The table and database name of every model annotated with `#[rorm(database = "..")]`
+///
+/// It is checked by [`DatabasesBuilder::build`](routing::DatabasesBuilder::build).
+#[cfg(feature = "registry")]
+#[allow(non_camel_case_types)]
+#[linkme::distributed_slice]
+#[doc(hidden)]
+pub static MODEL_DATABASES: [(&'static str, &'static str)] = [..];
+
 /// Write all models in the Intermediate Model Representation to a [writer](std::io::Write).
+#[cfg(feature = "registry")]
 pub fn write_models(writer: &mut impl std::io::Write) -> Result<(), String> {
     let imf = imr::InternalModelFormat {
         models: MODELS.iter().map(|func| func()).collect(),
@@ -69,6 +163,7 @@ pub fn write_models(writer: &mut impl std::io::Write) -> Result<(), String> {
 /// This should be used as a main function to produce the file for the migrator.
 ///
 /// See also [`rorm_main`]
+#[cfg(feature = "registry")]
 pub fn print_models() -> Result<(), String> {
     write_models(&mut std::io::stdout())
 }
@@ -141,6 +236,7 @@ macro_rules! field {
 /// fn main() {}
 /// ```
 #[deprecated(note = "Use `write_models` or `print_models` to implement it yourself")]
+#[cfg(feature = "derive")]
 pub use rorm_macro::rorm_main;
 /// ```no_run
 /// use rorm::DbEnum;
@@ -152,6 +248,21 @@ pub use rorm_macro::rorm_main;
 ///     Other,
 /// }
 /// ```
+///
+/// By default the variants are stored using their names.
+/// Use `#[rorm(repr = i16)]` (or `i32` / `i64`) to store their discriminants as integer instead:
+/// ```no_run
+/// use rorm::DbEnum;
+///
+/// #[derive(DbEnum)]
+/// #[rorm(repr = i16)]
+/// pub enum Priority {
+///     Low = 1,
+///     Medium,
+///     High = 10,
+/// }
+/// ```
+#[cfg(feature = "derive")]
 pub use rorm_macro::DbEnum;
 /// ```no_run
 /// use rorm::Model;
@@ -174,7 +285,67 @@ pub use rorm_macro::DbEnum;
 ///     age: i16,
 /// }
 /// ```
+#[cfg(feature = "derive")]
 pub use rorm_macro::Model;
+/// Store an enum whose variants contain named fields in a single table
+///
+/// See [`model_enum`] for details.
+///
+/// ```no_run
+/// use rorm::ModelEnum;
+///
+/// #[derive(ModelEnum)]
+/// pub enum Payment {
+///     Card {
+///         #[rorm(id)]
+///         id: i64,
+///         amount: i64,
+///         #[rorm(max_length = 4)]
+///         last_digits: String,
+///     },
+///     Transfer {
+///         #[rorm(id)]
+///         id: i64,
+///         amount: i64,
+///         #[rorm(max_length = 34)]
+///         iban: String,
+///     },
+/// }
+/// # fn main() {}
+/// ```
+#[cfg(feature = "derive")]
+pub use rorm_macro::ModelEnum;
+/// Use a tuple struct wrapping a single field type as field type itself
+///
+/// The wrapper is stored exactly like its inner type and supports the same annotations.
+/// Its conditions compare it using the inner type's representation.
+///
+/// - `#[rorm(constructor = "path")]` sets a function `fn(Inner) -> Result<Self, impl Display>`
+///   which checks the wrapper's invariants when decoding values from the database.
+/// - `#[rorm(ord)]` also implements the ordering comparisons if the inner type supports them.
+///
+/// This is a shorthand for implementing [`CustomFieldType`](crate::fields::custom::CustomFieldType).
+/// See [`rorm::fields::custom`](crate::fields::custom) for when to use which.
+///
+/// ```no_run
+/// use rorm::NewType;
+///
+/// #[derive(Clone, NewType)]
+/// #[rorm(constructor = "Money::new", ord)]
+/// pub struct Money(i64);
+///
+/// impl Money {
+///     pub fn new(cents: i64) -> Result<Self, &'static str> {
+///         if cents >= 0 {
+///             Ok(Self(cents))
+///         } else {
+///             Err("money can't be negative")
+///         }
+///     }
+/// }
+/// ```
+#[cfg(feature = "derive")]
+pub use rorm_macro::NewType;
 /// ```no_run
 /// use rorm::{Model, Patch};
 ///
@@ -210,4 +381,5 @@ pub use rorm_macro::Model;
 ///     age: i16,
 /// }
 /// ```
+#[cfg(feature = "derive")]
 pub use rorm_macro::Patch;
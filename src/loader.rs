@@ -0,0 +1,170 @@
+//! Batching of primary key lookups
+//!
+//! Servers resolving GraphQL-like requests often load the same model by its primary key
+//! from many independent resolvers (for example every [`ForeignModel`](crate::fields::types::ForeignModel)
+//! in a list of results).
+//! A [`Loader`] coalesces all [`Loader::load`] calls which are issued before the first of them
+//! gets polled again into a single query using [`by_primary_keys`](crate::crud::query::QueryBuilder::by_primary_keys):
+//!
+//! ```no_run
+//! # use rorm::{Database, Error, Model};
+//! # use rorm::loader::Loader;
+//! # #[derive(Model, Clone)] pub struct User { #[rorm(id)] id: i64, #[rorm(max_length = 255)] name: String, }
+//! pub async fn resolve_names(db: &Database, ids: &[i64]) -> Result<Vec<String>, Error> {
+//!     let loader = Loader::<User>::new(db);
+//!
+//!     // Executes a single query for all ids
+//!     let users = futures::future::try_join_all(ids.iter().map(|id| loader.load(*id))).await?;
+//!
+//!     Ok(users.into_iter().flatten().map(|user| user.name).collect())
+//! }
+//! ```
+
+use std::future::Future;
+use std::hash::Hash;
+use std::mem;
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::task::{Context, Poll};
+
+use futures::channel::oneshot;
+use rorm_db::{Database, Error};
+
+use crate::crud::query::query;
+use crate::internal::field::Field;
+use crate::model::{Identifiable, Model};
+
+/// Type of a model's primary key
+type Key<M> = <<M as Model>::Primary as Field>::Type;
+
+/// Channel to the [`Loader::load`] call waiting for a key
+type Request<M> = (Key<M>, oneshot::Sender<Result<Option<M>, String>>);
+
+/// Coalesces concurrent primary key lookups into batched queries
+///
+/// See the [module docs](self) for an example.
+pub struct Loader<'db, M: Model> {
+    db: &'db Database,
+    pending: Mutex<Pending<M>>,
+}
+
+/// The batch which is currently being collected
+struct Pending<M: Model> {
+    /// Is there a [`Loader::load`] call which will execute the batch?
+    scheduled: bool,
+
+    /// The keys which have been requested so far
+    requests: Vec<Request<M>>,
+}
+
+impl<'db, M> Loader<'db, M>
+where
+    M: Model + Identifiable + Clone,
+    M::ValueSpaceImpl: Clone,
+    Key<M>: Hash + Eq + Clone,
+{
+    /// Create a loader executing its queries on `db`
+    pub fn new(db: &'db Database) -> Self {
+        Self {
+            db,
+            pending: Mutex::new(Pending {
+                scheduled: false,
+                requests: Vec::new(),
+            }),
+        }
+    }
+
+    /// Load the model with the primary key `key`
+    ///
+    /// Returns `None` if there is no such model.
+    ///
+    /// If the batched query fails, every waiting call receives an [`Error::DecodeError`]
+    /// containing the original error's message.
+    pub async fn load(&self, key: Key<M>) -> Result<Option<M>, Error> {
+        loop {
+            let (sender, receiver) = oneshot::channel();
+            let is_leader = {
+                let mut pending = self.pending.lock().unwrap();
+                pending.requests.push((key.clone(), sender));
+                !mem::replace(&mut pending.scheduled, true)
+            };
+
+            if is_leader {
+                self.execute_batch().await;
+            }
+
+            match receiver.await {
+                Ok(result) => {
+                    return result.map_err(|error| {
+                        Error::DecodeError(format!("Batched query failed: {error}"))
+                    })
+                }
+                // The batch's leader has been dropped before it could answer, so start a new one
+                Err(oneshot::Canceled) => continue,
+            }
+        }
+    }
+
+    /// Wait for other calls to join the batch and then execute it
+    async fn execute_batch(&self) {
+        let guard = BatchGuard(Some(&self.pending));
+        YieldNow(false).await;
+        let requests = guard.take();
+
+        let keys: Vec<_> = requests.iter().map(|(key, _)| key).collect();
+        let result = query(self.db, M::ValueSpaceImpl::default())
+            .by_primary_keys(keys)
+            .await;
+
+        for (key, sender) in requests {
+            let _ = sender.send(match &result {
+                Ok(models) => Ok(models.get(&key).cloned()),
+                Err(error) => Err(error.to_string()),
+            });
+        }
+    }
+}
+
+impl<M: Model> Pending<M> {
+    /// Take the current batch's requests allowing the next call to start a new batch
+    fn take(&mut self) -> Vec<Request<M>> {
+        self.scheduled = false;
+        mem::take(&mut self.requests)
+    }
+}
+
+/// Drops the current batch if [`Loader::execute_batch`] is cancelled before taking it
+///
+/// This causes the waiting calls to start a new batch instead of waiting forever.
+struct BatchGuard<'a, M: Model>(Option<&'a Mutex<Pending<M>>>);
+impl<M: Model> BatchGuard<'_, M> {
+    fn take(mut self) -> Vec<Request<M>> {
+        self.0
+            .take()
+            .map(|pending| pending.lock().unwrap().take())
+            .unwrap_or_default()
+    }
+}
+impl<M: Model> Drop for BatchGuard<'_, M> {
+    fn drop(&mut self) {
+        if let Some(pending) = self.0.take() {
+            pending.lock().unwrap().take();
+        }
+    }
+}
+
+/// Future which returns [`Poll::Pending`] exactly once to let other tasks run
+struct YieldNow(bool);
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if self.0 {
+            Poll::Ready(())
+        } else {
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
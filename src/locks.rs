@@ -0,0 +1,270 @@
+//! Application-level locks coordinating several processes through the database
+//!
+//! A lock is identified by a name and held until it is [released](release) or its time to live expires.
+//! This allows running periodic jobs on only one of several instances of an application:
+//!
+//! ```no_run
+//! # use std::time::Duration;
+//! # use rorm::{Database, Error};
+//! # use rorm::locks::{acquire, release};
+//! # async fn send_newsletter(db: &Database) -> Result<(), Error> { Ok(()) }
+//! pub async fn cron(db: &Database) -> Result<(), Error> {
+//!     // Another instance is already sending the newsletter
+//!     let Some(lock) = acquire(db, "newsletter", Duration::from_secs(60)).await? else {
+//!         return Ok(());
+//!     };
+//!     send_newsletter(db).await?;
+//!     release(db, &lock).await?;
+//!     Ok(())
+//! }
+//! ```
+//!
+//! Jobs running longer than the time to live have to [renew](renew) their lock in time,
+//! otherwise another instance may acquire it.
+//! A lock whose holder crashed is released automatically once its time to live expired.
+//!
+//! Locks are acquired using a single atomic upsert, so this works the same on every database.
+//! The expiry is computed using the clock of the acquiring process,
+//! so the clocks of all processes should be roughly in sync.
+//!
+//! The locks are stored in the table [`LOCKS_TABLE`], which is registered for the migrator like a model.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use rorm_db::executor::{AffectedRows, Nothing, Optional};
+use rorm_db::sql::value::Value;
+use rorm_db::sql::DBImpl;
+use rorm_db::transaction::Transaction;
+use rorm_db::{Error, Executor};
+use rorm_declaration::imr;
+
+use crate::internal::random_token;
+use crate::internal::sql::{placeholder, quote, unix_timestamp_millis};
+
+/// The name of the locks' table
+pub const LOCKS_TABLE: &str = "rorm_locks";
+
+/// A lock held by this process
+///
+/// It is returned by [`acquire`] and can be [renewed](renew) or [released](release).
+#[derive(Clone, Debug)]
+pub struct Lock {
+    /// The lock's name
+    name: String,
+
+    /// Random token identifying this holder of the lock
+    token: String,
+
+    /// Unix timestamp in milliseconds after which the lock may be acquired by someone else
+    expires_at: i64,
+}
+
+impl Lock {
+    /// Get the lock's name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the point in time after which the lock may be acquired by someone else
+    pub fn expires_at(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis(self.expires_at as u64)
+    }
+}
+
+/// Try to acquire the lock `name` for `ttl`
+///
+/// Returns `None` if the lock is held by someone else and hasn't expired yet.
+pub async fn acquire<'ex>(
+    executor: impl Executor<'ex>,
+    name: &str,
+    ttl: Duration,
+) -> Result<Option<Lock>, Error> {
+    let mut guard = executor.ensure_transaction().await?;
+    let tx = guard.get_transaction();
+    let dialect = tx.dialect();
+
+    let now = unix_timestamp_millis();
+    let lock = Lock {
+        name: name.to_string(),
+        token: random_token(),
+        expires_at: now.saturating_add(ttl.as_millis() as i64),
+    };
+
+    let columns = format!(
+        "{}, {}, {}",
+        quote(dialect, "name"),
+        quote(dialect, "token"),
+        quote(dialect, "expires_at")
+    );
+    let table = quote(dialect, LOCKS_TABLE);
+    let token = quote(dialect, "token");
+    let expires_at = quote(dialect, "expires_at");
+    let sql = match dialect {
+        DBImpl::Postgres => format!(
+            "INSERT INTO {table} ({columns}) VALUES ($1, $2, $3) \
+            ON CONFLICT ({name}) DO UPDATE SET {token} = EXCLUDED.{token}, {expires_at} = EXCLUDED.{expires_at} \
+            WHERE {table}.{expires_at} <= $4;",
+            name = quote(dialect, "name"),
+        ),
+        #[cfg(feature = "all-drivers")]
+        DBImpl::SQLite => format!(
+            "INSERT INTO {table} ({columns}) VALUES (?, ?, ?) \
+            ON CONFLICT ({name}) DO UPDATE SET {token} = excluded.{token}, {expires_at} = excluded.{expires_at} \
+            WHERE {table}.{expires_at} <= ?;",
+            name = quote(dialect, "name"),
+        ),
+        // MySQL applies the assignments in order, so `expires_at` checks whether `token` has just been replaced
+        #[cfg(feature = "all-drivers")]
+        DBImpl::MySQL => format!(
+            "INSERT INTO {table} ({columns}) VALUES (?, ?, ?) ON DUPLICATE KEY UPDATE \
+            {token} = IF({expires_at} <= ?, VALUES({token}), {token}), \
+            {expires_at} = IF({token} = VALUES({token}), VALUES({expires_at}), {expires_at});"
+        ),
+    };
+    tx.execute::<Nothing>(
+        sql,
+        vec![
+            Value::String(&lock.name),
+            Value::String(&lock.token),
+            Value::I64(lock.expires_at),
+            Value::I64(now),
+        ],
+    )
+    .await?;
+
+    // The number of affected rows of an upsert differs between the databases
+    let acquired = current_token(tx, name).await?.as_deref() == Some(lock.token.as_str());
+    guard.commit().await?;
+    Ok(acquired.then_some(lock))
+}
+
+/// Extend the lock to expire `ttl` from now
+///
+/// Returns `false` if the lock has expired and been acquired by someone else in the meantime.
+/// An expired lock which hasn't been acquired by someone else is renewed.
+pub async fn renew<'ex>(
+    executor: impl Executor<'ex>,
+    lock: &mut Lock,
+    ttl: Duration,
+) -> Result<bool, Error> {
+    let mut guard = executor.ensure_transaction().await?;
+    let tx = guard.get_transaction();
+    let dialect = tx.dialect();
+
+    let expires_at = unix_timestamp_millis().saturating_add(ttl.as_millis() as i64);
+    let sql = format!(
+        "UPDATE {} SET {} = {} WHERE {};",
+        quote(dialect, LOCKS_TABLE),
+        quote(dialect, "expires_at"),
+        placeholder(dialect, 1),
+        where_held(dialect, 2)
+    );
+    tx.execute::<Nothing>(
+        sql,
+        vec![
+            Value::I64(expires_at),
+            Value::String(&lock.name),
+            Value::String(&lock.token),
+        ],
+    )
+    .await?;
+
+    // MySQL doesn't count rows whose value didn't change
+    let renewed = current_token(tx, &lock.name).await?.as_deref() == Some(lock.token.as_str());
+    guard.commit().await?;
+    if renewed {
+        lock.expires_at = expires_at;
+    }
+    Ok(renewed)
+}
+
+/// Release the lock so it can be acquired by someone else immediately
+///
+/// Returns `false` if the lock has expired and been acquired by someone else in the meantime.
+pub async fn release<'ex>(executor: impl Executor<'ex>, lock: &Lock) -> Result<bool, Error> {
+    let dialect = executor.dialect();
+    let sql = format!(
+        "DELETE FROM {} WHERE {};",
+        quote(dialect, LOCKS_TABLE),
+        where_held(dialect, 1)
+    );
+    let deleted = executor
+        .execute::<AffectedRows>(
+            sql,
+            vec![Value::String(&lock.name), Value::String(&lock.token)],
+        )
+        .await?;
+    Ok(deleted > 0)
+}
+
+/// Get the token of the lock `name`'s current holder
+async fn current_token(tx: &mut Transaction, name: &str) -> Result<Option<String>, Error> {
+    let dialect = tx.dialect();
+    let sql = format!(
+        "SELECT {} FROM {} WHERE {} = {};",
+        quote(dialect, "token"),
+        quote(dialect, LOCKS_TABLE),
+        quote(dialect, "name"),
+        placeholder(dialect, 1)
+    );
+    let row = tx
+        .execute::<Optional>(sql, vec![Value::String(name)])
+        .await?;
+    row.map(|row| row.get(0usize))
+        .transpose()
+        .map_err(Into::into)
+}
+
+/// Render the condition matching a lock by its name and token starting at the `index`th parameter
+fn where_held(dialect: DBImpl, index: usize) -> String {
+    format!(
+        "{} = {} AND {} = {}",
+        quote(dialect, "name"),
+        placeholder(dialect, index),
+        quote(dialect, "token"),
+        placeholder(dialect, index + 1)
+    )
+}
+
+/// Returns the intermediate representation of the locks' table
+///
+/// This function is registered to expose the table to the migrator.
+#[doc(hidden)]
+pub fn get_locks_imr() -> imr::Model {
+    let source_defined_at = Some(imr::Source {
+        file: file!().to_string(),
+        line: line!() as usize,
+        column: column!() as usize,
+    });
+    let field = |name: &str, db_type, annotations| imr::Field {
+        name: name.to_string(),
+        db_type,
+        annotations,
+        source_defined_at: source_defined_at.clone(),
+    };
+    imr::Model {
+        name: LOCKS_TABLE.to_string(),
+        fields: vec![
+            field(
+                "name",
+                imr::DbType::VarChar,
+                vec![imr::Annotation::MaxLength(255), imr::Annotation::PrimaryKey],
+            ),
+            field(
+                "token",
+                imr::DbType::VarChar,
+                vec![imr::Annotation::MaxLength(32), imr::Annotation::NotNull],
+            ),
+            field(
+                "expires_at",
+                imr::DbType::Int64,
+                vec![imr::Annotation::NotNull],
+            ),
+        ],
+        source_defined_at,
+    }
+}
+
+#[linkme::distributed_slice(crate::MODELS)]
+#[allow(non_upper_case_globals)]
+static __get_locks_imr: fn() -> imr::Model = get_locks_imr;
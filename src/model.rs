@@ -1,21 +1,28 @@
 //! This module holds traits and structs for working with models
 
+use std::future::Future;
 use std::marker::PhantomData;
 
+use rorm_db::transaction::Transaction;
+use rorm_db::Error;
 use rorm_declaration::imr;
 
 use crate::conditions::{Binary, BinaryOperator, Column, Value};
+use crate::content_hash::ContentHash;
+use crate::counter_cache::CounterCache;
+use crate::crud::builder::ConditionMarker;
 use crate::crud::decoder::Decoder;
 use crate::crud::selector::Selector;
 use crate::internal::field::{Field, FieldProxy, SingleColumnField};
 use crate::internal::hmr::{AsImr, Source};
 use crate::internal::query_context::QueryContext;
 use crate::internal::relation_path::Path;
+use crate::validate::Validate;
 
 /// Trait implemented on Patches i.e. a subset of a model's fields.
 ///
 /// Implemented by [`derive(Patch)`] as well as [`derive(Model)`].
-pub trait Patch: Sized + 'static {
+pub trait Patch: Validate + Sized + 'static {
     /// The model this patch is for
     type Model: Model;
 
@@ -39,8 +46,8 @@ pub trait Patch: Sized + 'static {
         columns
     }
 
-    /// Push the patch's columns onto a `Vec`
-    fn push_columns(columns: &mut Vec<&'static str>);
+    /// Push the patch's columns onto a `Vec` or any other buffer
+    fn push_columns(columns: &mut impl Extend<&'static str>);
 
     /// Create a [`Vec`] moving the patch's condition values
     fn values(self) -> Vec<Value<'static>> {
@@ -49,8 +56,10 @@ pub trait Patch: Sized + 'static {
         values
     }
 
-    /// Push the patch's condition values onto a [`Vec`]
-    fn push_values(self, values: &mut Vec<Value>);
+    /// Push the patch's condition values onto a [`Vec`] or any other buffer
+    ///
+    /// See [`Patch::push_references`].
+    fn push_values<'a>(self, values: &mut impl Extend<Value<'a>>);
 
     /// Create a [`Vec`] borrowing the patch's condition values
     fn references(&self) -> Vec<Value> {
@@ -59,8 +68,27 @@ pub trait Patch: Sized + 'static {
         values
     }
 
-    /// Push the patch's condition values onto a [`Vec`]
-    fn push_references<'a>(&'a self, values: &mut Vec<Value<'a>>);
+    /// Push the patch's condition values onto a [`Vec`] or any other buffer
+    ///
+    /// Unlike [`Patch::references`] this doesn't allocate a new `Vec` for every call.
+    /// Instead, the caller can reuse a buffer or pass a stack allocated one
+    /// (for example a `SmallVec` or an `ArrayVec`):
+    ///
+    /// ```no_run
+    /// # use rorm::Model;
+    /// # use rorm::conditions::Value;
+    /// # use rorm::model::Patch;
+    /// # #[derive(Model)] pub struct User { #[rorm(id)] id: i64, #[rorm(max_length = 255)] name: String, }
+    /// pub fn log_users(users: &[User]) {
+    ///     let mut values: Vec<Value> = Vec::new();
+    ///     for user in users {
+    ///         values.clear();
+    ///         user.push_references(&mut values);
+    ///         println!("{values:?}");
+    ///     }
+    /// }
+    /// ```
+    fn push_references<'a>(&'a self, values: &mut impl Extend<Value<'a>>);
 }
 
 /// [`Selector`] selecting a [`Patch`] through its [`Patch::select`] method
@@ -108,6 +136,16 @@ pub trait Model: Patch<Model = Self> {
     /// The primary key
     type Primary: Field<Model = Self> + SingleColumnField;
 
+    /// The model's lifecycle hooks which are invoked by the crud builders
+    ///
+    /// This is `Self` if the model is annotated with `#[rorm(hooks)]` and `()` otherwise.
+    type Hooks: ModelHooks;
+
+    /// The condition every query of the model is restricted to
+    ///
+    /// This is `Self` if the model is annotated with `#[rorm(default_scope)]` and `()` otherwise.
+    type DefaultScope: DefaultScope;
+
     /// A struct which "maps" field identifiers their descriptions (i.e. [`Field<T>`](crate::internal::field::Field)).
     ///
     /// The struct is constructed once in the [`Model::FIELDS`] constant.
@@ -125,12 +163,61 @@ pub trait Model: Patch<Model = Self> {
     /// The model's table name
     const TABLE: &'static str;
 
+    /// Is the model annotated with `#[rorm(audit)]`?
+    ///
+    /// See [`audit`](crate::audit) for details.
+    const AUDIT: bool = false;
+
+    /// The name of the database set by `#[rorm(database = "..")]`
+    ///
+    /// See [`Databases`](crate::routing::Databases) for details.
+    const DATABASE: Option<&'static str> = None;
+
+    /// The column of the field annotated with `#[rorm(tenant_key)]`
+    ///
+    /// See [`tenant`](crate::tenant) for details.
+    const TENANT_KEY: Option<&'static str> = None;
+
+    /// The counters maintained by the fields annotated with `#[rorm(counter_cache = "..")]`
+    ///
+    /// See [`counter_cache`](crate::counter_cache) for details.
+    const COUNTER_CACHES: &'static [CounterCache] = &[];
+
+    /// The hashes maintained by the fields annotated with `#[rorm(hash_of = "..")]`
+    ///
+    /// See [`content_hash`](crate::content_hash) for details.
+    const CONTENT_HASHES: &'static [ContentHash] = &[];
+
     /// Location of the model in the source code
     const SOURCE: Source;
 
     /// Push the model's fields' imr representation onto a vec
     fn push_fields_imr(fields: &mut Vec<imr::Field>);
 
+    /// Create a `Vec` containing the columns of the fields annotated with `#[rorm(readonly)]`
+    fn readonly_columns() -> Vec<&'static str> {
+        let mut columns = Vec::new();
+        Self::push_readonly_columns(&mut columns);
+        columns
+    }
+
+    /// Push the columns of the fields annotated with `#[rorm(readonly)]` onto a vec
+    ///
+    /// They are skipped when updating a row from a patch.
+    fn push_readonly_columns(_columns: &mut Vec<&'static str>) {}
+
+    /// Create a `Vec` containing the columns of the fields annotated with `#[rorm(defer)]`
+    fn deferred_columns() -> Vec<&'static str> {
+        let mut columns = Vec::new();
+        Self::push_deferred_columns(&mut columns);
+        columns
+    }
+
+    /// Push the columns of the fields annotated with `#[rorm(defer)]` onto a vec
+    ///
+    /// They are skipped when querying the whole model.
+    fn push_deferred_columns(_columns: &mut Vec<&'static str>) {}
+
     /// Returns the model's intermediate representation
     ///
     /// As library user you probably won't need this. You might want to look at [`write_models`].
@@ -147,6 +234,163 @@ pub trait Model: Patch<Model = Self> {
     }
 }
 
+/// Lifecycle hooks invoked by the crud builders
+///
+/// A model opts into its hooks by being annotated with `#[rorm(hooks)]`
+/// which requires it to implement this trait.
+/// Every method defaults to doing nothing, so only the required hooks have to be implemented.
+///
+/// The hooks are called inside the transaction executing the query, which they receive to run their own queries.
+/// An error returned from a hook aborts the query and rolls back the transaction, if the builder started it.
+/// `keys` contains the primary keys of the affected rows.
+///
+/// ```no_run
+/// # use rorm::{Model, Error, Transaction};
+/// # use rorm::conditions::Value;
+/// # use rorm::model::ModelHooks;
+/// #[derive(Model)]
+/// #[rorm(hooks)]
+/// struct User {
+///     #[rorm(id)]
+///     id: i64,
+/// }
+///
+/// impl ModelHooks for User {
+///     async fn after_delete(_tx: &mut Transaction, keys: &[Value<'static>]) -> Result<(), Error> {
+///         println!("Deleted the users {keys:?}");
+///         Ok(())
+///     }
+/// }
+/// ```
+pub trait ModelHooks {
+    /// Whether the builders have to call the hooks
+    ///
+    /// Only the implementation used by models without hooks disables them
+    /// to avoid starting transactions and selecting keys for nothing.
+    #[doc(hidden)]
+    const ENABLED: bool = true;
+
+    /// Called before inserting rows
+    ///
+    /// `rows` contains the values for `columns` of every row to be inserted.
+    fn before_insert(
+        tx: &mut Transaction,
+        columns: &[&'static str],
+        rows: &[&[Value<'_>]],
+    ) -> impl Future<Output = Result<(), Error>> + Send {
+        let _ = (tx, columns, rows);
+        async { Ok(()) }
+    }
+
+    /// Called after inserting rows
+    ///
+    /// `rows` contains the values for `columns` of every inserted row
+    /// and `keys` their primary keys in the same order.
+    fn after_insert(
+        tx: &mut Transaction,
+        columns: &[&'static str],
+        rows: &[&[Value<'_>]],
+        keys: &[Value<'static>],
+    ) -> impl Future<Output = Result<(), Error>> + Send {
+        let _ = (tx, columns, rows, keys);
+        async { Ok(()) }
+    }
+
+    /// Called before updating rows
+    ///
+    /// `columns` contains the columns to set and their new values.
+    fn before_update(
+        tx: &mut Transaction,
+        columns: &[(&'static str, Value<'_>)],
+        keys: &[Value<'static>],
+    ) -> impl Future<Output = Result<(), Error>> + Send {
+        let _ = (tx, columns, keys);
+        async { Ok(()) }
+    }
+
+    /// Called after updating rows
+    ///
+    /// `columns` contains the columns which have been set and their new values.
+    fn after_update(
+        tx: &mut Transaction,
+        columns: &[(&'static str, Value<'_>)],
+        keys: &[Value<'static>],
+    ) -> impl Future<Output = Result<(), Error>> + Send {
+        let _ = (tx, columns, keys);
+        async { Ok(()) }
+    }
+
+    /// Called before deleting rows
+    fn before_delete(
+        tx: &mut Transaction,
+        keys: &[Value<'static>],
+    ) -> impl Future<Output = Result<(), Error>> + Send {
+        let _ = (tx, keys);
+        async { Ok(()) }
+    }
+
+    /// Called after deleting rows
+    fn after_delete(
+        tx: &mut Transaction,
+        keys: &[Value<'static>],
+    ) -> impl Future<Output = Result<(), Error>> + Send {
+        let _ = (tx, keys);
+        async { Ok(()) }
+    }
+}
+
+/// Used as [`Model::Hooks`] by models which don't use hooks
+impl ModelHooks for () {
+    const ENABLED: bool = false;
+}
+
+/// A condition applied to every query of a model
+///
+/// A model opts into its default scope by being annotated with `#[rorm(default_scope)]`
+/// which requires it to implement this trait.
+/// Its queries then only return rows matching the condition,
+/// unless they are built with [`unscoped`](crate::crud::query::QueryBuilder::unscoped).
+///
+/// ```no_run
+/// # use rorm::{query, Database, Error, Model, FieldAccess};
+/// # use rorm::crud::builder::ConditionMarker;
+/// # use rorm::model::DefaultScope;
+/// #[derive(Model)]
+/// #[rorm(default_scope)]
+/// pub struct Post {
+///     #[rorm(id)]
+///     pub id: i64,
+///
+///     pub deleted: bool,
+/// }
+///
+/// impl DefaultScope for Post {
+///     fn condition<'a>() -> impl ConditionMarker<'a> {
+///         Post.deleted.equals(false)
+///     }
+/// }
+///
+/// pub async fn trash(db: &Database) -> Result<Vec<Post>, Error> {
+///     query(db, Post)
+///         .unscoped()
+///         .condition(Post.deleted.equals(true))
+///         .all()
+///         .await
+/// }
+/// ```
+///
+/// Only queries selecting from the model are restricted.
+/// Updates and deletes as well as joins to the model are not.
+pub trait DefaultScope {
+    /// Get the condition every row has to match
+    fn condition<'a>() -> impl ConditionMarker<'a>;
+}
+
+/// Used as [`Model::DefaultScope`] by models without a default scope
+impl DefaultScope for () {
+    fn condition<'a>() -> impl ConditionMarker<'a> {}
+}
+
 /// Expose a models' fields on the type level using indexes
 pub trait FieldByIndex<const INDEX: usize>: Model {
     /// The model's field at `INDEX`
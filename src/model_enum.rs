@@ -0,0 +1,226 @@
+//! Storing the variants of an enum in a single table
+//!
+//! `#[derive(ModelEnum)]` implements [`Model`] for an enum whose variants contain named fields.
+//! All variants are stored in one table whose columns are
+//! - the discriminator `kind` which stores the variant's name
+//! - the union of the variants' fields
+//!
+//! Fields declared by every variant are stored as is, the others are stored as nullable columns.
+//! A field declared by several variants has to have the same type in each of them
+//! and takes its annotations from its first declaration.
+//! The primary key has to be declared by every variant.
+//!
+//! Querying the enum decodes every row into the variant named by its discriminator.
+//! Inserting it writes the variant's name to the discriminator and `NULL` to the other variants' fields.
+//!
+//! The derive generates a marker type for every variant in a module named after the enum in snake case.
+//! They are used to restrict a query to a variant:
+//!
+//! ```no_run
+//! # use rorm::{query, Database, Error, ModelEnum};
+//! #[derive(ModelEnum)]
+//! pub enum Event {
+//!     Click {
+//!         #[rorm(id)]
+//!         id: i64,
+//!         x: i32,
+//!         y: i32,
+//!     },
+//!     View {
+//!         #[rorm(id)]
+//!         id: i64,
+//!
+//!         #[rorm(max_length = 255)]
+//!         page: String,
+//!     },
+//! }
+//!
+//! pub async fn clicks(db: &Database) -> Result<Vec<Event>, Error> {
+//!     query(db, Event)
+//!         .condition(Event.type_is::<event::Click>())
+//!         .all()
+//!         .await
+//! }
+//! # fn main() {}
+//! ```
+//!
+//! The discriminator's column can be renamed using `#[rorm(discriminator = "..")]` on the enum.
+
+use std::borrow::Cow;
+use std::fmt;
+use std::marker::PhantomData;
+
+use rorm_db::choice::Choice;
+use rorm_db::row::RowError;
+use rorm_db::sql::value::NullType;
+use rorm_db::Row;
+
+use crate::conditions::{Binary, BinaryOperator, Column, Value};
+use crate::crud::decoder::Decoder;
+use crate::fields::traits::{Array, FieldColumns, FieldType};
+use crate::fields::utils::check::shared_linter_check;
+use crate::fields::utils::get_names::single_column_name;
+use crate::internal::field::decoder::FieldDecoder;
+use crate::internal::field::fake_field::FakeField;
+use crate::internal::field::{Field, FieldProxy};
+use crate::internal::hmr::annotations::{Annotations, Choices};
+use crate::internal::query_context::QueryContext;
+use crate::internal::relation_path::Path;
+#[cfg(doc)]
+use crate::model::Model;
+use crate::{const_fn, new_converting_decoder};
+
+/// An enum whose variants are stored in a single table
+///
+/// It should only ever be generated using `#[derive(ModelEnum)]`.
+pub trait ModelEnum: 'static {
+    /// The names of the enum's variants which are stored in its discriminator
+    const VARIANTS: &'static [&'static str];
+}
+
+/// Marker type for one of a [`ModelEnum`]'s variants
+///
+/// It is generated by `#[derive(ModelEnum)]` in a module named after the enum.
+pub trait ModelEnumVariant: 'static {
+    /// The enum the variant belongs to
+    type Enum: ModelEnum;
+
+    /// The variant's name which is stored in the discriminator
+    const NAME: &'static str;
+}
+
+/// The field type of a [`ModelEnum`]'s discriminator
+///
+/// It stores the name of a row's variant.
+pub struct Discriminator<E> {
+    name: &'static str,
+    model_enum: PhantomData<fn() -> E>,
+}
+
+impl<E: ModelEnum> Discriminator<E> {
+    /// Get the discriminator of the variant `V`
+    pub fn of<V: ModelEnumVariant<Enum = E>>() -> Self {
+        Self {
+            name: V::NAME,
+            model_enum: PhantomData,
+        }
+    }
+
+    /// Get the name of the variant
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Check whether this is the discriminator of the variant `V`
+    pub fn is<V: ModelEnumVariant<Enum = E>>(&self) -> bool {
+        self.name == V::NAME
+    }
+}
+
+new_converting_decoder!(
+    pub DiscriminatorDecoder<E: ModelEnum>,
+    |value: Choice| -> Discriminator<E> {
+        E::VARIANTS
+            .iter()
+            .find(|variant| **variant == value.0)
+            .map(|&name| Discriminator {
+                name,
+                model_enum: PhantomData,
+            })
+            .ok_or_else(|| format!("Invalid variant '{}' in discriminator", value.0))
+    }
+);
+impl<E: ModelEnum> FieldType for Discriminator<E> {
+    type Columns = Array<1>;
+
+    const NULL: FieldColumns<Self, NullType> = [NullType::String];
+
+    fn into_values<'a>(self) -> FieldColumns<Self, Value<'a>> {
+        [Value::Choice(Cow::Borrowed(self.name))]
+    }
+
+    fn as_values(&self) -> FieldColumns<Self, Value<'_>> {
+        [Value::Choice(Cow::Borrowed(self.name))]
+    }
+
+    type Decoder = DiscriminatorDecoder<E>;
+
+    type GetAnnotations = discriminator_annotations<E>;
+
+    type Check = shared_linter_check<1>;
+
+    type GetNames = single_column_name;
+}
+
+const_fn! {
+    /// Restricts the column to the enum's variants
+    pub fn discriminator_annotations<E: ModelEnum>(field: Annotations) -> [Annotations; 1] {
+        let mut field = field;
+        field.choices = Some(Choices(E::VARIANTS));
+        [field]
+    }
+}
+
+impl<F, P, E> FieldProxy<F, P>
+where
+    F: Field<Type = Discriminator<E>>,
+    P: Path,
+    E: ModelEnum,
+{
+    /// Check the row to be of the variant `V`
+    pub fn is_variant<V: ModelEnumVariant<Enum = E>>(self) -> Binary<Column<Self>, Value<'static>> {
+        Binary {
+            operator: BinaryOperator::Equals,
+            fst_arg: Column(self),
+            snd_arg: Value::Choice(Cow::Borrowed(V::NAME)),
+        }
+    }
+}
+
+impl<E> fmt::Debug for Discriminator<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Discriminator").field(&self.name).finish()
+    }
+}
+impl<E> Clone for Discriminator<E> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<E> Copy for Discriminator<E> {}
+impl<E> PartialEq for Discriminator<E> {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+impl<E> Eq for Discriminator<E> {}
+
+/// [`Decoder`] for a variant's field which is not declared by every variant
+///
+/// Its column is nullable, but the variant's field is required to be set.
+#[doc(hidden)]
+pub struct VariantFieldDecoder<T: FieldType>(T::Decoder);
+impl<T: FieldType> VariantFieldDecoder<T> {
+    /// Construct the decoder and select the field's columns
+    pub fn new<F, P>(ctx: &mut QueryContext, _: FieldProxy<F, P>) -> Self
+    where
+        F: Field<Type = Option<T>>,
+        P: Path,
+    {
+        Self(T::Decoder::new::<FakeField<T, F>, P>(
+            ctx,
+            FieldProxy::new(),
+        ))
+    }
+}
+impl<T: FieldType> Decoder for VariantFieldDecoder<T> {
+    type Result = T;
+
+    fn by_name<'index>(&'index self, row: &'_ Row) -> Result<Self::Result, RowError<'index>> {
+        self.0.by_name(row)
+    }
+
+    fn by_index<'index>(&'index self, row: &'_ Row) -> Result<Self::Result, RowError<'index>> {
+        self.0.by_index(row)
+    }
+}
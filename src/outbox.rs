@@ -0,0 +1,234 @@
+//! Publishing events reliably using a transactional outbox
+//!
+//! Changing a row and publishing an event about it (i.e. to a message broker) can't be done atomically.
+//! Instead, the event is written to an outbox table in the same transaction as the change
+//! using [`InTransaction::enqueue`](crate::transaction::InTransaction::enqueue).
+//! A consumer then [polls](poll_outbox) the outbox, publishes the events and [acknowledges](acknowledge) them:
+//!
+//! ```no_run
+//! # use rorm::{insert, Database, Error, Model, Patch};
+//! # use rorm::outbox::{acknowledge, poll_outbox};
+//! # use rorm::transaction::InTransaction;
+//! # use serde::Serialize;
+//! # #[derive(Model)] pub struct Order { #[rorm(id)] id: i64, amount: i64, }
+//! # #[derive(Patch)] #[rorm(model = "Order")] pub struct NewOrder { amount: i64, }
+//! # async fn publish(topic: &str, payload: &serde_json::Value) -> Result<(), Error> { Ok(()) }
+//! #[derive(Serialize)]
+//! pub struct OrderPlaced {
+//!     pub id: i64,
+//! }
+//!
+//! pub async fn place_order(db: &Database, amount: i64) -> Result<(), Error> {
+//!     let mut tx = db.start_transaction().await?;
+//!     let id = insert(&mut tx, Order)
+//!         .return_primary_key()
+//!         .single(&NewOrder { amount })
+//!         .await?;
+//!     // Only stored if the order is stored as well
+//!     (&mut tx).enqueue("order_placed", &OrderPlaced { id }).await?;
+//!     tx.commit().await
+//! }
+//!
+//! pub async fn relay(db: &Database) -> Result<(), Error> {
+//!     let mut tx = db.start_transaction().await?;
+//!     let events = poll_outbox(&mut tx, 100).await?;
+//!     for event in &events {
+//!         publish(&event.topic, &event.payload).await?;
+//!     }
+//!     acknowledge(&mut tx, &events).await?;
+//!     tx.commit().await
+//! }
+//! ```
+//!
+//! [`poll_outbox`] locks the returned events using `FOR UPDATE SKIP LOCKED`,
+//! so several consumers can relay events concurrently without publishing any event twice.
+//! If a consumer fails before committing, its events are released and polled again,
+//! so events are published at least once.
+//! SQLite doesn't support row locks, but serializes writing transactions anyway.
+//!
+//! The outbox is stored in the table [`OUTBOX_TABLE`], which is registered for the migrator like a model.
+
+use rorm_db::executor::{All, Nothing};
+use rorm_db::row::RowError;
+use rorm_db::sql::value::Value;
+use rorm_db::sql::DBImpl;
+use rorm_db::transaction::Transaction;
+use rorm_db::{database, Error, Executor, Row};
+use rorm_declaration::imr;
+use serde::Serialize;
+
+use crate::internal::sql::{quote, unix_timestamp_secs};
+use crate::transaction::InTransaction;
+
+/// The name of the outbox's table
+pub const OUTBOX_TABLE: &str = "rorm_outbox";
+
+/// Columns of the outbox table in the order used by [`decode_event`]
+const COLUMNS: [&str; 4] = ["id", "topic", "payload", "created_at"];
+
+/// An event read from the outbox
+#[derive(Clone, Debug)]
+pub struct OutboxEvent {
+    /// The event's id which increases with every enqueued event
+    pub id: i64,
+
+    /// The topic the event has been enqueued with
+    pub topic: String,
+
+    /// The event serialized as json
+    pub payload: serde_json::Value,
+
+    /// Unix timestamp in seconds of when the event has been enqueued
+    pub created_at: i64,
+}
+
+/// Write an event to the outbox
+///
+/// See [`InTransaction::enqueue`].
+pub(crate) async fn enqueue<E: Serialize + ?Sized>(
+    tx: &mut Transaction,
+    topic: &str,
+    event: &E,
+) -> Result<(), Error> {
+    let payload = serde_json::to_vec(event).map_err(|error| {
+        Error::DecodeError(format!("Failed to serialize an outbox event: {error}"))
+    })?;
+    let created_at = unix_timestamp_secs();
+    database::insert(
+        tx,
+        OUTBOX_TABLE,
+        &COLUMNS[1..],
+        &[
+            Value::String(topic),
+            Value::Binary(&payload),
+            Value::I64(created_at),
+        ],
+    )
+    .await
+}
+
+/// Select and lock up to `limit` of the oldest events in the outbox
+///
+/// The events stay locked until the transaction ends.
+/// They should be [acknowledged](acknowledge) in the same transaction after publishing them.
+pub async fn poll_outbox(
+    mut tx: impl InTransaction<'_>,
+    limit: u64,
+) -> Result<Vec<OutboxEvent>, Error> {
+    let tx = tx.transaction();
+    let dialect = tx.dialect();
+    let locking = match dialect {
+        DBImpl::Postgres => " FOR UPDATE SKIP LOCKED",
+        #[cfg(feature = "all-drivers")]
+        DBImpl::MySQL => " FOR UPDATE SKIP LOCKED",
+        #[cfg(feature = "all-drivers")]
+        DBImpl::SQLite => "",
+    };
+    let sql = format!(
+        "SELECT {columns} FROM {table} ORDER BY {id} LIMIT {limit}{locking};",
+        columns = COLUMNS
+            .iter()
+            .map(|column| quote(dialect, column))
+            .collect::<Vec<_>>()
+            .join(", "),
+        table = quote(dialect, OUTBOX_TABLE),
+        id = quote(dialect, COLUMNS[0]),
+    );
+    let rows = tx.execute::<All>(sql, Vec::new()).await?;
+    rows.iter()
+        .map(|row| decode_event(row).map_err(Into::into))
+        .collect()
+}
+
+/// Remove published events from the outbox
+pub async fn acknowledge(
+    mut tx: impl InTransaction<'_>,
+    events: &[OutboxEvent],
+) -> Result<(), Error> {
+    if events.is_empty() {
+        return Ok(());
+    }
+
+    let tx = tx.transaction();
+    let dialect = tx.dialect();
+    let placeholders = (1..=events.len())
+        .map(|index| match dialect {
+            DBImpl::Postgres => format!("${index}"),
+            #[allow(unreachable_patterns)]
+            _ => "?".to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    let sql = format!(
+        "DELETE FROM {table} WHERE {id} IN ({placeholders});",
+        table = quote(dialect, OUTBOX_TABLE),
+        id = quote(dialect, COLUMNS[0]),
+    );
+    let ids = events.iter().map(|event| Value::I64(event.id)).collect();
+    tx.execute::<Nothing>(sql, ids).await
+}
+
+fn decode_event(row: &Row) -> Result<OutboxEvent, RowError<'static>> {
+    let payload: Vec<u8> = row.get(2usize)?;
+    Ok(OutboxEvent {
+        id: row.get(0usize)?,
+        topic: row.get(1usize)?,
+        payload: serde_json::from_slice(&payload).map_err(|error| RowError::Decode {
+            index: 2usize.into(),
+            source: error.into(),
+        })?,
+        created_at: row.get(3usize)?,
+    })
+}
+
+/// Returns the intermediate representation of the outbox's table
+///
+/// This function is registered to expose the table to the migrator.
+#[doc(hidden)]
+pub fn get_outbox_imr() -> imr::Model {
+    let source_defined_at = Some(imr::Source {
+        file: file!().to_string(),
+        line: line!() as usize,
+        column: column!() as usize,
+    });
+    let field = |name: &str, db_type, annotations| imr::Field {
+        name: name.to_string(),
+        db_type,
+        annotations,
+        source_defined_at: source_defined_at.clone(),
+    };
+    imr::Model {
+        name: OUTBOX_TABLE.to_string(),
+        fields: vec![
+            field(
+                "id",
+                imr::DbType::Int64,
+                vec![imr::Annotation::PrimaryKey, imr::Annotation::AutoIncrement],
+            ),
+            field(
+                "topic",
+                imr::DbType::VarChar,
+                vec![
+                    imr::Annotation::MaxLength(255),
+                    imr::Annotation::Index(None),
+                    imr::Annotation::NotNull,
+                ],
+            ),
+            field(
+                "payload",
+                imr::DbType::Binary,
+                vec![imr::Annotation::NotNull],
+            ),
+            field(
+                "created_at",
+                imr::DbType::Int64,
+                vec![imr::Annotation::NotNull],
+            ),
+        ],
+        source_defined_at,
+    }
+}
+
+#[linkme::distributed_slice(crate::MODELS)]
+#[allow(non_upper_case_globals)]
+static __get_outbox_imr: fn() -> imr::Model = get_outbox_imr;
@@ -0,0 +1,94 @@
+//! Postgres' declarative partitioning
+//!
+//! rorm's migrations don't create partitioned tables.
+//! A model's table has to be turned into one using a handwritten migration
+//! (`CREATE TABLE .. PARTITION BY RANGE(..)`) before partitions can be managed at runtime:
+//!
+//! ```no_run
+//! # use rorm::{Database, Error, Model};
+//! # use rorm::partition::create_partition;
+//! /// Partitioned by `RANGE(posted_at)` in a handwritten migration
+//! #[derive(Model)]
+//! pub struct Measurement {
+//!     #[rorm(primary_key)]
+//!     pub posted_at: i64,
+//!
+//!     pub value: f64,
+//! }
+//!
+//! pub async fn create_next_partition(db: &Database, start: i64, end: i64) -> Result<(), Error> {
+//!     create_partition::<Measurement>(
+//!         db,
+//!         &format!("measurement_{start}"),
+//!         &format!("FROM ({start}) TO ({end})"),
+//!     )
+//!     .await
+//! }
+//! ```
+//!
+//! Postgres requires the partition key to be part of every unique constraint.
+//! Since rorm's primary keys consist of a single column, the model has to be partitioned by its primary key.
+//!
+//! The partitions themselves are not part of the models and have to be managed at runtime using the functions in this module.
+//! Rows are queried and inserted through the partitioned model as usual.
+//!
+//! The `bounds` taken by these functions are written into the statement verbatim
+//! (`FOR VALUES <bounds>`) and must never contain user input.
+//!
+//! Models which are partitioned by time but don't want to include the timestamp in their primary key
+//! or need to run on other databases can use [`time_partition`](crate::time_partition) instead.
+
+use rorm_db::sql::DBImpl;
+use rorm_db::{database, Error, Executor};
+
+use crate::internal::sql::quote;
+use crate::model::Model;
+
+/// Create a new partition of `M` called `name` for the rows matching `bounds`
+///
+/// `bounds` is the partition bound specification following `FOR VALUES`
+/// i.e. `FROM (..) TO (..)`, `IN (..)` or `WITH (MODULUS .., REMAINDER ..)`.
+pub async fn create_partition<'ex, M: Model>(
+    executor: impl Executor<'ex>,
+    name: &str,
+    bounds: &str,
+) -> Result<(), Error> {
+    let sql = format!(
+        "CREATE TABLE {} PARTITION OF {} FOR VALUES {bounds};",
+        quote(DBImpl::Postgres, name),
+        quote(DBImpl::Postgres, M::TABLE)
+    );
+    database::raw_sql(executor, &sql, None).await?;
+    Ok(())
+}
+
+/// Attach the existing table `name` as partition of `M` for the rows matching `bounds`
+///
+/// See [`create_partition`] for the format of `bounds`.
+pub async fn attach_partition<'ex, M: Model>(
+    executor: impl Executor<'ex>,
+    name: &str,
+    bounds: &str,
+) -> Result<(), Error> {
+    let sql = format!(
+        "ALTER TABLE {} ATTACH PARTITION {} FOR VALUES {bounds};",
+        quote(DBImpl::Postgres, M::TABLE),
+        quote(DBImpl::Postgres, name)
+    );
+    database::raw_sql(executor, &sql, None).await?;
+    Ok(())
+}
+
+/// Detach the partition `name` from `M` keeping it as standalone table
+pub async fn detach_partition<'ex, M: Model>(
+    executor: impl Executor<'ex>,
+    name: &str,
+) -> Result<(), Error> {
+    let sql = format!(
+        "ALTER TABLE {} DETACH PARTITION {};",
+        quote(DBImpl::Postgres, M::TABLE),
+        quote(DBImpl::Postgres, name)
+    );
+    database::raw_sql(executor, &sql, None).await?;
+    Ok(())
+}
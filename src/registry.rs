@@ -0,0 +1,98 @@
+//! Inspecting the models registered by `#[derive(Model)]` at runtime
+//!
+//! Every model is described by its [intermediate representation](imr::Model),
+//! the same one which is written for the migrator by [`write_models`](crate::write_models).
+//! It contains the model's table name and its columns with their types and annotations.
+//!
+//! ```no_run
+//! use rorm::registry;
+//!
+//! pub fn print_schema() {
+//!     for model in registry::models() {
+//!         println!("{}", model.name);
+//!         for field in &model.fields {
+//!             println!("  {}: {:?}", field.name, field.db_type);
+//!         }
+//!         for relation in registry::relations(&model) {
+//!             println!("  {} -> {}.{}", relation.column, relation.foreign_table, relation.foreign_column);
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! Models annotated with `#[rorm(experimental_unregistered)]` are not included.
+//! Generated tables, like the ones storing an audited model's history, are.
+
+use rorm_declaration::imr;
+
+use crate::MODELS;
+
+/// Iterate over all registered models
+///
+/// The order is unspecified.
+pub fn models() -> impl Iterator<Item = imr::Model> {
+    MODELS.iter().map(|func| func())
+}
+
+/// Get the registered model stored in the table `table`
+pub fn model(table: &str) -> Option<imr::Model> {
+    models().find(|model| model.name == table)
+}
+
+/// A foreign key from one model's column to another model's column
+#[derive(Clone, Debug)]
+pub struct Relation {
+    /// Table containing the foreign key
+    pub table: String,
+
+    /// Column containing the foreign key
+    pub column: String,
+
+    /// Table referenced by the foreign key
+    pub foreign_table: String,
+
+    /// Column referenced by the foreign key
+    pub foreign_column: String,
+
+    /// Action taken when the referenced row is deleted
+    pub on_delete: imr::ReferentialAction,
+
+    /// Action taken when the referenced row's column is updated
+    pub on_update: imr::ReferentialAction,
+}
+
+/// Get the foreign keys declared by `model`
+///
+/// These are its [`ForeignModel`](crate::fields::types::ForeignModel) fields.
+pub fn relations(model: &imr::Model) -> Vec<Relation> {
+    model
+        .fields
+        .iter()
+        .flat_map(|field| {
+            field
+                .annotations
+                .iter()
+                .filter_map(move |annotation| match annotation {
+                    imr::Annotation::ForeignKey(foreign) => Some(Relation {
+                        table: model.name.clone(),
+                        column: field.name.clone(),
+                        foreign_table: foreign.table_name.clone(),
+                        foreign_column: foreign.column_name.clone(),
+                        on_delete: foreign.on_delete,
+                        on_update: foreign.on_update,
+                    }),
+                    _ => None,
+                })
+        })
+        .collect()
+}
+
+/// Get the foreign keys of all registered models referencing the table `table`
+///
+/// These are the relations a [`BackRef`](crate::fields::types::BackRef) on `table` could follow.
+pub fn referencing(table: &str) -> Vec<Relation> {
+    models()
+        .flat_map(|model| relations(&model))
+        .filter(|relation| relation.foreign_table == table)
+        .collect()
+}
@@ -0,0 +1,177 @@
+//! Storing models in different databases
+//!
+//! A model can be assigned to a named database using `#[rorm(database = "..")]`.
+//! Registering the connections in a [`Databases`] registry and passing it to the crud functions
+//! picks the right connection for every model automatically:
+//!
+//! ```no_run
+//! # use rorm::{query, Database, Error, FieldAccess, Model};
+//! # use rorm::routing::Databases;
+//! #[derive(Model)]
+//! pub struct User {
+//!     #[rorm(id)]
+//!     pub id: i64,
+//!
+//!     #[rorm(max_length = 255)]
+//!     pub name: String,
+//! }
+//!
+//! #[derive(Model)]
+//! #[rorm(database = "analytics")]
+//! pub struct PageView {
+//!     #[rorm(id)]
+//!     pub id: i64,
+//!
+//!     pub user: i64,
+//! }
+//!
+//! pub fn setup(main: Database, analytics: Database) -> Result<Databases, Error> {
+//!     Databases::builder(main)
+//!         .register("analytics", analytics)
+//!         .build()
+//! }
+//!
+//! pub async fn count_views(dbs: &Databases, user: i64) -> Result<usize, Error> {
+//!     // Runs on the default database
+//!     let user = query(dbs, User).condition(User.id.equals(user)).one().await?;
+//!
+//!     // Runs on the "analytics" database
+//!     let views = query(dbs, PageView)
+//!         .condition(PageView.user.equals(user.id))
+//!         .all()
+//!         .await?;
+//!     Ok(views.len())
+//! }
+//! ```
+//!
+//! Models without the annotation are stored in the default database.
+//! [`DatabasesBuilder::build`] checks that the databases of all models have been registered.
+//! Transactions and joins can't span multiple databases,
+//! so they have to be started on the handle returned by [`Databases::for_model`].
+
+use std::collections::HashMap;
+
+use rorm_db::transaction::Transaction;
+use rorm_db::{Database, Error, Executor};
+
+use crate::model::Model;
+use crate::transaction::{Scope, Scoped, ScopedTransaction};
+
+/// Registry of named database connections
+///
+/// See the [module docs](self) for an example.
+pub struct Databases {
+    default: Database,
+    named: HashMap<String, Database>,
+}
+
+impl Databases {
+    /// Start building a registry using `default` for every model without `#[rorm(database = "..")]`
+    pub fn builder(default: Database) -> DatabasesBuilder {
+        DatabasesBuilder {
+            default,
+            named: HashMap::new(),
+        }
+    }
+
+    /// Get the database used by models without `#[rorm(database = "..")]`
+    pub fn default_database(&self) -> &Database {
+        &self.default
+    }
+
+    /// Get a database by the name it has been registered with
+    pub fn get(&self, name: &str) -> Option<&Database> {
+        self.named.get(name)
+    }
+
+    /// Get the database storing the model `M`
+    ///
+    /// # Panics
+    /// If `M`'s database has not been registered.
+    /// [`DatabasesBuilder::build`] rules this out for every model using `#[derive(Model)]`,
+    /// so this can only happen for models implementing [`Model::DATABASE`] manually.
+    pub fn for_model<M: Model>(&self) -> &Database {
+        match M::DATABASE {
+            None => &self.default,
+            Some(name) => self.get(name).unwrap_or_else(|| {
+                panic!(
+                    "The database \"{name}\" used by {} has not been registered",
+                    M::TABLE
+                )
+            }),
+        }
+    }
+}
+
+/// Builder for [`Databases`] returned by [`Databases::builder`]
+pub struct DatabasesBuilder {
+    default: Database,
+    named: HashMap<String, Database>,
+}
+
+impl DatabasesBuilder {
+    /// Register the database used by models annotated with `#[rorm(database = name)]`
+    ///
+    /// Registering the same name again replaces the previous database.
+    pub fn register(mut self, name: impl Into<String>, db: Database) -> Self {
+        self.named.insert(name.into(), db);
+        self
+    }
+
+    /// Build the registry
+    ///
+    /// Returns an error if a model uses a database which has not been registered.
+    pub fn build(self) -> Result<Databases, Error> {
+        #[cfg(feature = "registry")]
+        for (table, name) in crate::MODEL_DATABASES {
+            if !self.named.contains_key(*name) {
+                return Err(Error::DecodeError(format!(
+                    "The database \"{name}\" used by {table} has not been registered"
+                )));
+            }
+        }
+        let Self { default, named } = self;
+        Ok(Databases { default, named })
+    }
+}
+
+/// Something which can provide the [`Executor`] to run queries on the model `M`
+///
+/// This trait is accepted by the crud functions instead of an [`Executor`].
+/// It is implemented for every [`Executor`], which is used as is,
+/// for [`&Databases`](Databases), which picks the model's database,
+/// and for [`&mut ScopedTransaction`](ScopedTransaction), which keeps its [`Scope`].
+pub trait Route<'ex, M> {
+    /// The executor used for `M`
+    type Executor: Executor<'ex>;
+
+    /// Get the executor used for `M` together with the [`Scope`] applied to its queries
+    fn route(self) -> Scoped<Self::Executor>;
+}
+
+impl<'ex, M, E> Route<'ex, M> for E
+where
+    E: Executor<'ex>,
+{
+    type Executor = E;
+
+    fn route(self) -> Scoped<Self::Executor> {
+        Scoped::new(self, Scope::default())
+    }
+}
+
+impl<'ex, M: Model> Route<'ex, M> for &'ex Databases {
+    type Executor = &'ex Database;
+
+    fn route(self) -> Scoped<Self::Executor> {
+        Scoped::new(self.for_model::<M>(), Scope::default())
+    }
+}
+
+impl<'ex, M> Route<'ex, M> for &'ex mut ScopedTransaction<'_> {
+    type Executor = &'ex mut Transaction;
+
+    fn route(self) -> Scoped<Self::Executor> {
+        self.executor()
+    }
+}
@@ -0,0 +1,102 @@
+//! Control over the sequence generating a model's auto increment primary key
+//!
+//! ```no_run
+//! # use rorm::{Database, Error, Model};
+//! # use rorm::sequence::{next_value, restart};
+//! #[derive(Model)]
+//! pub struct Order {
+//!     #[rorm(id)]
+//!     pub id: i64,
+//!
+//!     #[rorm(max_length = 255)]
+//!     pub customer: String,
+//! }
+//!
+//! pub async fn allocate_ids(db: &Database, amount: usize) -> Result<Vec<i64>, Error> {
+//!     let mut ids = Vec::with_capacity(amount);
+//!     for _ in 0..amount {
+//!         ids.push(next_value::<Order>(db).await?);
+//!     }
+//!     Ok(ids)
+//! }
+//!
+//! pub async fn after_import(db: &Database, highest_id: i64) -> Result<(), Error> {
+//!     restart::<Order>(db, highest_id + 1).await
+//! }
+//! ```
+//!
+//! Import pipelines can use [`next_value`] to allocate ids ahead of time
+//! and insert rows referencing each other without waiting for the database to generate them.
+//! After inserting rows with explicit ids, [`restart`] moves the sequence past them.
+
+use rorm_db::executor::{Nothing, One};
+use rorm_db::sql::value::Value;
+use rorm_db::sql::DBImpl;
+use rorm_db::{Error, Executor};
+
+use crate::internal::field::Field;
+use crate::model::Model;
+
+/// Fetch the next value of `M`'s primary key sequence without inserting a row
+///
+/// The value is reserved i.e. it won't be used by any following insert,
+/// even if the surrounding transaction is rolled back.
+///
+/// Only postgres has sequences which can be advanced on their own, the other databases will return an error.
+pub async fn next_value<'ex, M: Model>(executor: impl Executor<'ex>) -> Result<i64, Error> {
+    let table = format!("\"{}\"", M::TABLE);
+    let row = executor
+        .execute::<One>(
+            "SELECT nextval(pg_get_serial_sequence($1, $2));".to_string(),
+            vec![
+                Value::String(&table),
+                Value::String(<M as Model>::Primary::NAME),
+            ],
+        )
+        .await?;
+    Ok(row.get(0usize)?)
+}
+
+/// Restart `M`'s primary key sequence so the next generated value is `next`
+///
+/// On sqlite this requires the table to have been created with `AUTOINCREMENT`.
+pub async fn restart<'ex, M: Model>(executor: impl Executor<'ex>, next: i64) -> Result<(), Error> {
+    let mut guard = executor.ensure_transaction().await?;
+    let tx = guard.get_transaction();
+    match tx.dialect() {
+        DBImpl::Postgres => {
+            let table = format!("\"{}\"", M::TABLE);
+            tx.execute::<Nothing>(
+                "SELECT setval(pg_get_serial_sequence($1, $2), $3, false);".to_string(),
+                vec![
+                    Value::String(&table),
+                    Value::String(<M as Model>::Primary::NAME),
+                    Value::I64(next),
+                ],
+            )
+            .await?;
+        }
+        #[cfg(feature = "all-drivers")]
+        DBImpl::MySQL => {
+            tx.execute::<Nothing>(
+                format!("ALTER TABLE `{}` AUTO_INCREMENT = {next};", M::TABLE),
+                Vec::new(),
+            )
+            .await?;
+        }
+        #[cfg(feature = "all-drivers")]
+        DBImpl::SQLite => {
+            tx.execute::<Nothing>(
+                "DELETE FROM sqlite_sequence WHERE name = ?;".to_string(),
+                vec![Value::String(M::TABLE)],
+            )
+            .await?;
+            tx.execute::<Nothing>(
+                "INSERT INTO sqlite_sequence (name, seq) VALUES (?, ?);".to_string(),
+                vec![Value::String(M::TABLE), Value::I64(next - 1)],
+            )
+            .await?;
+        }
+    }
+    guard.commit().await
+}
@@ -0,0 +1,432 @@
+//! A session store backed by a rorm-managed table
+//!
+//! [`SessionStore`] creates, loads, refreshes and expires sessions storing arbitrary json data:
+//!
+//! ```no_run
+//! # use std::time::Duration;
+//! # use rorm::{Database, Error};
+//! # use rorm::sessions::SessionStore;
+//! # use serde::{Deserialize, Serialize};
+//! #[derive(Serialize, Deserialize)]
+//! pub struct Login {
+//!     pub user: i64,
+//! }
+//!
+//! pub async fn login(store: &SessionStore, user: i64) -> Result<String, Error> {
+//!     let session = store.create(&Login { user }).await?;
+//!     Ok(session.id)
+//! }
+//!
+//! pub async fn authenticate(store: &SessionStore, id: &str) -> Result<Option<i64>, Error> {
+//!     let Some(mut session) = store.load(id).await? else {
+//!         return Ok(None);
+//!     };
+//!     // Sliding expiration
+//!     store.refresh(&mut session).await?;
+//!     Ok(session.data::<Login>().ok().map(|login| login.user))
+//! }
+//!
+//! pub fn store(db: Database) -> SessionStore {
+//!     SessionStore::new(db, Duration::from_secs(24 * 60 * 60))
+//! }
+//! ```
+//!
+//! Session ids are generated by [`random_session_id`] by default.
+//! Use [`SessionStore::with_token_generator`] to generate them differently.
+//!
+//! Expired sessions are never loaded, but stay in the table until [`SessionStore::delete_expired`] is called.
+//!
+//! With the `tower-sessions` feature, [`SessionStore`] implements
+//! [tower-sessions'](tower_sessions_core) `SessionStore` and can be passed to its `SessionManagerLayer`.
+//!
+//! The sessions are stored in the table [`SESSIONS_TABLE`], which is registered for the migrator like a model.
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use rorm_db::executor::{AffectedRows, Nothing, Optional};
+use rorm_db::row::RowError;
+use rorm_db::sql::value::Value;
+use rorm_db::sql::DBImpl;
+use rorm_db::{Database, Error, Executor, Row};
+use rorm_declaration::imr;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::internal::sql::{placeholder, quote, unix_timestamp_secs};
+
+/// The name of the sessions' table
+pub const SESSIONS_TABLE: &str = "rorm_sessions";
+
+/// Columns of the sessions table in the order used by [`decode_session`]
+const COLUMNS: [&str; 3] = ["id", "data", "expires_at"];
+
+/// Generate a session id of 64 hex digits
+///
+/// The id's 256 bits are read from the operating system's secure random number generator.
+///
+/// # Panics
+/// If the operating system's random number generator is not available.
+pub fn random_session_id() -> String {
+    let mut bytes = [0u8; 32];
+    getrandom::getrandom(&mut bytes).expect("The os' random number generator failed");
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// A session loaded from or stored in a [`SessionStore`]
+#[derive(Clone, Debug)]
+pub struct Session {
+    /// The session's id which is sent to the client
+    pub id: String,
+
+    /// The data stored in the session
+    pub payload: serde_json::Value,
+
+    /// Unix timestamp in seconds after which the session is expired
+    pub expires_at: i64,
+}
+
+impl Session {
+    /// Deserialize the data stored in the session
+    pub fn data<T: DeserializeOwned>(&self) -> Result<T, serde_json::Error> {
+        T::deserialize(&self.payload)
+    }
+}
+
+/// Creates, loads, refreshes and expires [sessions](Session)
+///
+/// See the [module docs](self) for an example.
+pub struct SessionStore {
+    db: Database,
+
+    /// Time to live of created and refreshed sessions
+    ttl: Duration,
+
+    /// Generates the id of created sessions
+    generate_id: Arc<dyn Fn() -> String + Send + Sync>,
+}
+
+impl SessionStore {
+    /// Create a store whose sessions expire `ttl` after they have been created or refreshed
+    pub fn new(db: Database, ttl: Duration) -> Self {
+        Self {
+            db,
+            ttl,
+            generate_id: Arc::new(random_session_id),
+        }
+    }
+
+    /// Use `generate_id` to generate the ids of created sessions
+    ///
+    /// The ids are used to authenticate the sessions' clients,
+    /// so they have to be unpredictable and shouldn't exceed 255 characters.
+    pub fn with_token_generator(
+        mut self,
+        generate_id: impl Fn() -> String + Send + Sync + 'static,
+    ) -> Self {
+        self.generate_id = Arc::new(generate_id);
+        self
+    }
+
+    /// Create a new session storing `data`
+    pub async fn create<T: Serialize + ?Sized>(&self, data: &T) -> Result<Session, Error> {
+        let payload = serde_json::to_value(data).map_err(|error| {
+            Error::DecodeError(format!("Failed to serialize session data: {error}"))
+        })?;
+        let session = Session {
+            id: (self.generate_id)(),
+            payload,
+            expires_at: self.expiry(),
+        };
+        self.save(&session).await?;
+        Ok(session)
+    }
+
+    /// Load the session `id`
+    ///
+    /// Returns `None` if there is no such session or it has expired.
+    pub async fn load(&self, id: &str) -> Result<Option<Session>, Error> {
+        let dialect = (&self.db).dialect();
+        let sql = format!(
+            "SELECT {columns} FROM {table} WHERE {id} = {} AND {expires_at} > {};",
+            placeholder(dialect, 1),
+            placeholder(dialect, 2),
+            columns = COLUMNS
+                .iter()
+                .map(|column| quote(dialect, column))
+                .collect::<Vec<_>>()
+                .join(", "),
+            table = quote(dialect, SESSIONS_TABLE),
+            id = quote(dialect, COLUMNS[0]),
+            expires_at = quote(dialect, COLUMNS[2]),
+        );
+        let row = (&self.db)
+            .execute::<Optional>(
+                sql,
+                vec![Value::String(id), Value::I64(unix_timestamp_secs())],
+            )
+            .await?;
+        row.map(|row| decode_session(&row))
+            .transpose()
+            .map_err(Into::into)
+    }
+
+    /// Store the session, replacing its data and expiry if it already exists
+    pub async fn save(&self, session: &Session) -> Result<(), Error> {
+        let dialect = (&self.db).dialect();
+        let table = quote(dialect, SESSIONS_TABLE);
+        let columns = COLUMNS
+            .iter()
+            .map(|column| quote(dialect, column))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let id = quote(dialect, COLUMNS[0]);
+        let data = quote(dialect, COLUMNS[1]);
+        let expires_at = quote(dialect, COLUMNS[2]);
+        let sql = match dialect {
+            DBImpl::Postgres => format!(
+                "INSERT INTO {table} ({columns}) VALUES ($1, $2, $3) \
+                ON CONFLICT ({id}) DO UPDATE SET {data} = EXCLUDED.{data}, {expires_at} = EXCLUDED.{expires_at};"
+            ),
+            #[cfg(feature = "all-drivers")]
+            DBImpl::SQLite => format!(
+                "INSERT INTO {table} ({columns}) VALUES (?, ?, ?) \
+                ON CONFLICT ({id}) DO UPDATE SET {data} = excluded.{data}, {expires_at} = excluded.{expires_at};"
+            ),
+            #[cfg(feature = "all-drivers")]
+            DBImpl::MySQL => format!(
+                "INSERT INTO {table} ({columns}) VALUES (?, ?, ?) \
+                ON DUPLICATE KEY UPDATE {data} = VALUES({data}), {expires_at} = VALUES({expires_at});"
+            ),
+        };
+        let payload = serde_json::to_vec(&session.payload).map_err(|error| {
+            Error::DecodeError(format!("Failed to serialize session data: {error}"))
+        })?;
+        (&self.db)
+            .execute::<Nothing>(
+                sql,
+                vec![
+                    Value::String(&session.id),
+                    Value::Binary(&payload),
+                    Value::I64(session.expires_at),
+                ],
+            )
+            .await
+    }
+
+    /// Extend the session to expire the store's time to live from now
+    ///
+    /// Returns `false` if the session doesn't exist anymore.
+    pub async fn refresh(&self, session: &mut Session) -> Result<bool, Error> {
+        let dialect = (&self.db).dialect();
+        let expires_at = self.expiry();
+        let sql = format!(
+            "UPDATE {} SET {} = {} WHERE {} = {};",
+            quote(dialect, SESSIONS_TABLE),
+            quote(dialect, COLUMNS[2]),
+            placeholder(dialect, 1),
+            quote(dialect, COLUMNS[0]),
+            placeholder(dialect, 2),
+        );
+        let updated = (&self.db)
+            .execute::<AffectedRows>(
+                sql,
+                vec![Value::I64(expires_at), Value::String(&session.id)],
+            )
+            .await?;
+        session.expires_at = expires_at;
+        // MySQL doesn't count rows whose value didn't change i.e. refreshing twice in a second
+        Ok(updated > 0 || self.exists(&session.id).await?)
+    }
+
+    /// Expire the session `id` immediately
+    pub async fn expire(&self, id: &str) -> Result<(), Error> {
+        let dialect = (&self.db).dialect();
+        let sql = format!(
+            "DELETE FROM {} WHERE {} = {};",
+            quote(dialect, SESSIONS_TABLE),
+            quote(dialect, COLUMNS[0]),
+            placeholder(dialect, 1),
+        );
+        (&self.db)
+            .execute::<Nothing>(sql, vec![Value::String(id)])
+            .await
+    }
+
+    /// Delete all expired sessions
+    ///
+    /// Returns the number of deleted sessions.
+    pub async fn delete_expired(&self) -> Result<u64, Error> {
+        let dialect = (&self.db).dialect();
+        let sql = format!(
+            "DELETE FROM {} WHERE {} <= {};",
+            quote(dialect, SESSIONS_TABLE),
+            quote(dialect, COLUMNS[2]),
+            placeholder(dialect, 1),
+        );
+        (&self.db)
+            .execute::<AffectedRows>(sql, vec![Value::I64(unix_timestamp_secs())])
+            .await
+    }
+
+    /// Check whether the session `id` exists regardless of its expiry
+    async fn exists(&self, id: &str) -> Result<bool, Error> {
+        let dialect = (&self.db).dialect();
+        let sql = format!(
+            "SELECT {id} FROM {} WHERE {id} = {};",
+            quote(dialect, SESSIONS_TABLE),
+            placeholder(dialect, 1),
+            id = quote(dialect, COLUMNS[0]),
+        );
+        let row = (&self.db)
+            .execute::<Optional>(sql, vec![Value::String(id)])
+            .await?;
+        Ok(row.is_some())
+    }
+
+    /// Get the expiry of a session created or refreshed now
+    fn expiry(&self) -> i64 {
+        unix_timestamp_secs().saturating_add(self.ttl.as_secs() as i64)
+    }
+}
+
+impl fmt::Debug for SessionStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SessionStore")
+            .field("ttl", &self.ttl)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "tower-sessions")]
+#[async_trait::async_trait]
+impl tower_sessions_core::SessionStore for SessionStore {
+    async fn create(
+        &self,
+        record: &mut tower_sessions_core::session::Record,
+    ) -> tower_sessions_core::session_store::Result<()> {
+        // tower-sessions generates the id which might collide with an existing session
+        while self.exists(&record.id.to_string()).await.map_err(backend)? {
+            record.id = tower_sessions_core::session::Id::default();
+        }
+        tower_sessions_core::SessionStore::save(self, record).await
+    }
+
+    async fn save(
+        &self,
+        record: &tower_sessions_core::session::Record,
+    ) -> tower_sessions_core::session_store::Result<()> {
+        let payload = serde_json::to_value(&record.data).map_err(|error| {
+            tower_sessions_core::session_store::Error::Encode(error.to_string())
+        })?;
+        let session = Session {
+            id: record.id.to_string(),
+            payload,
+            expires_at: record.expiry_date.unix_timestamp(),
+        };
+        SessionStore::save(self, &session).await.map_err(backend)
+    }
+
+    async fn load(
+        &self,
+        id: &tower_sessions_core::session::Id,
+    ) -> tower_sessions_core::session_store::Result<Option<tower_sessions_core::session::Record>>
+    {
+        let Some(session) = SessionStore::load(self, &id.to_string())
+            .await
+            .map_err(backend)?
+        else {
+            return Ok(None);
+        };
+        let data = serde_json::from_value(session.payload).map_err(|error| {
+            tower_sessions_core::session_store::Error::Decode(error.to_string())
+        })?;
+        let expiry_date =
+            time::OffsetDateTime::from_unix_timestamp(session.expires_at).map_err(|error| {
+                tower_sessions_core::session_store::Error::Decode(error.to_string())
+            })?;
+        Ok(Some(tower_sessions_core::session::Record {
+            id: *id,
+            data,
+            expiry_date,
+        }))
+    }
+
+    async fn delete(
+        &self,
+        id: &tower_sessions_core::session::Id,
+    ) -> tower_sessions_core::session_store::Result<()> {
+        self.expire(&id.to_string()).await.map_err(backend)
+    }
+}
+
+/// Convert a database error into tower-sessions' error
+#[cfg(feature = "tower-sessions")]
+fn backend(error: Error) -> tower_sessions_core::session_store::Error {
+    tower_sessions_core::session_store::Error::Backend(error.to_string())
+}
+
+fn decode_session(row: &Row) -> Result<Session, RowError<'static>> {
+    let payload: Vec<u8> = row.get(1usize)?;
+    Ok(Session {
+        id: row.get(0usize)?,
+        payload: serde_json::from_slice(&payload).map_err(|error| RowError::Decode {
+            index: 1usize.into(),
+            source: error.into(),
+        })?,
+        expires_at: row.get(2usize)?,
+    })
+}
+
+/// Returns the intermediate representation of the sessions' table
+///
+/// This function is registered to expose the table to the migrator.
+#[doc(hidden)]
+pub fn get_sessions_imr() -> imr::Model {
+    let source_defined_at = Some(imr::Source {
+        file: file!().to_string(),
+        line: line!() as usize,
+        column: column!() as usize,
+    });
+    let field = |name: &str, db_type, annotations| imr::Field {
+        name: name.to_string(),
+        db_type,
+        annotations,
+        source_defined_at: source_defined_at.clone(),
+    };
+    imr::Model {
+        name: SESSIONS_TABLE.to_string(),
+        fields: vec![
+            field(
+                "id",
+                imr::DbType::VarChar,
+                vec![imr::Annotation::MaxLength(255), imr::Annotation::PrimaryKey],
+            ),
+            field("data", imr::DbType::Binary, vec![imr::Annotation::NotNull]),
+            field(
+                "expires_at",
+                imr::DbType::Int64,
+                vec![imr::Annotation::Index(None), imr::Annotation::NotNull],
+            ),
+        ],
+        source_defined_at,
+    }
+}
+
+#[linkme::distributed_slice(crate::MODELS)]
+#[allow(non_upper_case_globals)]
+static __get_sessions_imr: fn() -> imr::Model = get_sessions_imr;
+
+#[cfg(test)]
+mod test {
+    use super::random_session_id;
+
+    #[test]
+    fn session_id() {
+        let id = random_session_id();
+        assert_eq!(id.len(), 64);
+        assert!(id.bytes().all(|byte| byte.is_ascii_hexdigit()));
+        assert_ne!(id, random_session_id());
+    }
+}
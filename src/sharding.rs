@@ -0,0 +1,539 @@
+//! Splitting a model's rows across multiple tables by a shard key
+//!
+//! A model can be declared as sharded using `#[rorm(shard_by = "..", shards = ..)]`.
+//! Its rows are then stored in the tables `<table>_00` to `<table>_<shards - 1>`
+//! and the shard storing a row is computed from the field named by `shard_by`:
+//!
+//! ```no_run
+//! # use rorm::{insert, query, Database, Error, FieldAccess, Model, Patch};
+//! # use rorm::sharding::{all_shards, shard};
+//! #[derive(Model)]
+//! #[rorm(shard_by = "user", shards = 16)]
+//! pub struct Post {
+//!     #[rorm(id)]
+//!     pub id: i64,
+//!
+//!     pub user: i64,
+//!
+//!     #[rorm(max_length = 255)]
+//!     pub title: String,
+//! }
+//!
+//! #[derive(Patch)]
+//! #[rorm(model = "Post")]
+//! pub struct NewPost {
+//!     pub user: i64,
+//!
+//!     #[rorm(max_length = 255)]
+//!     pub title: String,
+//! }
+//!
+//! pub async fn publish(db: &Database, user: i64, title: String) -> Result<(), Error> {
+//!     // Inserted into `post_<user % 16>`
+//!     insert(shard(db, Post.user, &user), Post)
+//!         .return_nothing()
+//!         .single(&NewPost { user, title })
+//!         .await
+//! }
+//!
+//! pub async fn posts_of(db: &Database, user: i64) -> Result<Vec<Post>, Error> {
+//!     // Only queries the user's shard
+//!     query(shard(db, Post.user, &user), Post)
+//!         .condition(Post.user.equals(user))
+//!         .all()
+//!         .await
+//! }
+//!
+//! pub async fn search(db: &Database, title: &str) -> Result<Vec<Post>, Error> {
+//!     // Queries all shards concurrently
+//!     all_shards(db, Post, |db| {
+//!         query(db, Post)
+//!             .condition(Post.title.equals(title))
+//!             .all()
+//!     })
+//!     .await
+//! }
+//! ```
+//!
+//! The crud functions accept the [`Sharded`] route returned by [`shard`]
+//! which replaces the model's table with the shard's table in every query,
+//! including the ones executed inside transactions the builders start.
+//! Only references to the table are replaced, columns, aliases and literals named like it are kept.
+//! All rows written through it have to belong to its shard.
+//!
+//! The migrator only knows the model's table, which is used as template by [`create_shards`].
+//! Joins only rewrite the sharded model's table, so joining another shard's rows is not possible.
+//! [Aggregate](crate::aggregate) triggers are only created on the table they are created for.
+
+use std::future::Future;
+
+use futures::future::try_join_all;
+use rorm_db::executor::{Executor, Nothing, Optional};
+use rorm_db::sql::value::Value;
+use rorm_db::sql::DBImpl;
+use rorm_db::Error;
+
+use crate::crud::selector::Selector;
+use crate::internal::field::{Field, FieldProxy};
+use crate::model::{Model, Patch};
+use crate::routing::Route;
+use crate::transaction::Scoped;
+
+/// A model declared as sharded using `#[rorm(shard_by = "..", shards = ..)]`
+///
+/// This trait is implemented by the `Model` derive.
+pub trait ShardedModel: Model {
+    /// The field the rows are sharded by
+    type ShardField: Field<Model = Self, Type: ShardKey>;
+
+    /// The shard key and number of shards
+    const SHARDING: Sharding;
+}
+
+/// The sharding of a model declared by `#[rorm(shard_by = "..", shards = ..)]`
+///
+/// See [`ShardedModel::SHARDING`].
+#[derive(Copy, Clone, Debug)]
+pub struct Sharding {
+    /// The column the rows are sharded by
+    pub column: &'static str,
+
+    /// The number of shards
+    pub shards: u16,
+}
+
+impl Sharding {
+    /// Describe the sharding of `F`'s model by `F` into `shards` tables
+    #[doc(hidden)]
+    pub const fn new<F, P>(_field: FieldProxy<F, P>, shards: u16) -> Self
+    where
+        F: Field<Type: ShardKey>,
+    {
+        assert!(shards > 0, "A model needs at least one shard");
+        Self {
+            column: F::NAME,
+            shards,
+        }
+    }
+}
+
+/// A field type which can be used as shard key
+///
+/// Integers are distributed by their remainder,
+/// other types by a hash which is stable across processes and versions.
+pub trait ShardKey {
+    /// Get the index of the shard storing the rows with this key
+    fn shard(&self, shards: u16) -> u16;
+}
+macro_rules! impl_integer_shard_key {
+    ($($type:ty),+) => {$(
+        impl ShardKey for $type {
+            fn shard(&self, shards: u16) -> u16 {
+                i64::from(*self).rem_euclid(i64::from(shards)) as u16
+            }
+        }
+    )+};
+}
+impl_integer_shard_key!(i16, i32, i64);
+impl ShardKey for String {
+    fn shard(&self, shards: u16) -> u16 {
+        fnv1a(self.as_bytes(), shards)
+    }
+}
+impl ShardKey for Vec<u8> {
+    fn shard(&self, shards: u16) -> u16 {
+        fnv1a(self, shards)
+    }
+}
+#[cfg(feature = "uuid")]
+impl ShardKey for uuid::Uuid {
+    fn shard(&self, shards: u16) -> u16 {
+        fnv1a(self.as_bytes(), shards)
+    }
+}
+
+/// Hash some bytes using 64 bit FNV-1a and reduce it to a shard index
+fn fnv1a(bytes: &[u8], shards: u16) -> u16 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in bytes {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    (hash % u64::from(shards)) as u16
+}
+
+/// Get the name of `M`'s shard with the index `shard`
+pub fn shard_table<M: ShardedModel>(shard: u16) -> String {
+    shard_name(M::TABLE, M::SHARDING.shards, shard)
+}
+
+/// Get the name of `table`'s shard with the index `shard` out of `shards`
+///
+/// The index is padded to the width of the highest index but at least two digits.
+fn shard_name(table: &str, shards: u16, shard: u16) -> String {
+    let width = (shards - 1).to_string().len().max(2);
+    format!("{table}_{shard:0width$}")
+}
+
+/// Route a crud builder to the shard storing the rows whose shard key is `key`
+///
+/// `field` has to be the model's shard key.
+pub fn shard<F, P, R>(executor: R, _field: FieldProxy<F, P>, key: &F::Type) -> Sharded<R>
+where
+    F: Field<Type: ShardKey, Model: ShardedModel<ShardField = F>>,
+{
+    Sharded::new::<F::Model>(executor, key.shard(F::Model::SHARDING.shards))
+}
+
+/// Run a query on every shard of a model concurrently and concatenate their results
+///
+/// `query` is called once for every shard with an executor routed to it.
+pub async fn all_shards<'ex, E, S, T, Q, Fut>(executor: E, _: S, query: Q) -> Result<Vec<T>, Error>
+where
+    E: Executor<'ex> + Copy,
+    S: Selector<Model: Patch<ValueSpaceImpl = S> + ShardedModel>,
+    Q: FnMut(Sharded<E>) -> Fut,
+    Fut: Future<Output = Result<Vec<T>, Error>>,
+{
+    let shards = (0..S::Model::SHARDING.shards).map(shard_table::<S::Model>);
+    query_tables(executor, S::Model::TABLE, shards, query).await
+}
+
+/// Run a query on several copies of `table` concurrently and concatenate their results
+pub(crate) async fn query_tables<'ex, E, T, Q, Fut>(
+    executor: E,
+    table: &'static str,
+    copies: impl IntoIterator<Item = String>,
+    mut query: Q,
+) -> Result<Vec<T>, Error>
+where
+    E: Executor<'ex> + Copy,
+    Q: FnMut(Sharded<E>) -> Fut,
+    Fut: Future<Output = Result<Vec<T>, Error>>,
+{
+    let queries = copies
+        .into_iter()
+        .map(|copy| query(Sharded::with_table(executor, table, copy)));
+    let results = try_join_all(queries).await?;
+    Ok(results.into_iter().flatten().collect())
+}
+
+/// Create the tables of all shards of `M` which don't exist yet
+///
+/// The model's own table (created by the migrator) is used as template.
+/// On Postgres, the shards copy its columns, constraints and indexes using `LIKE .. INCLUDING ALL`,
+/// MySQL uses `CREATE TABLE .. LIKE ..` and SQLite copies its `CREATE TABLE` statement.
+pub async fn create_shards<'ex, M: ShardedModel>(
+    executor: impl Executor<'ex>,
+) -> Result<(), Error> {
+    let shards = (0..M::SHARDING.shards).map(shard_table::<M>);
+    create_copies(executor, M::TABLE, shards).await
+}
+
+/// Create copies of `table` which don't exist yet
+///
+/// See [`create_shards`] for how the copies are created.
+pub(crate) async fn create_copies<'ex>(
+    executor: impl Executor<'ex>,
+    table: &'static str,
+    copies: impl IntoIterator<Item = String>,
+) -> Result<(), Error> {
+    let mut guard = executor.ensure_transaction().await?;
+    let dialect = guard.get_transaction().dialect();
+    #[cfg(feature = "all-drivers")]
+    let template: Option<String> = match dialect {
+        DBImpl::SQLite => guard
+            .get_transaction()
+            .execute::<Optional>(
+                "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = ?;".to_string(),
+                vec![Value::String(table)],
+            )
+            .await?
+            .map(|row| row.get(0usize))
+            .transpose()?,
+        _ => None,
+    };
+
+    for copy in copies {
+        let sql = match dialect {
+            DBImpl::Postgres => {
+                format!("CREATE TABLE IF NOT EXISTS \"{copy}\" (LIKE \"{table}\" INCLUDING ALL);")
+            }
+            #[cfg(feature = "all-drivers")]
+            DBImpl::MySQL => format!("CREATE TABLE IF NOT EXISTS `{copy}` LIKE `{table}`;"),
+            #[cfg(feature = "all-drivers")]
+            DBImpl::SQLite => {
+                let template = template.as_deref().ok_or_else(|| {
+                    Error::DecodeError(format!("The table {table} doesn't exist"))
+                })?;
+                let sql = rename_table(template, table, &copy);
+                format!(
+                    "CREATE TABLE IF NOT EXISTS{};",
+                    sql.strip_prefix("CREATE TABLE").unwrap_or(&sql)
+                )
+            }
+        };
+        guard
+            .get_transaction()
+            .execute::<Nothing>(sql, Vec::new())
+            .await?;
+    }
+    guard.commit().await
+}
+
+/// Replace the references to the table `from` in `sql` with `to`
+///
+/// A table reference is a quoted identifier following one of [`TABLE_KEYWORDS`]
+/// or qualifying a column, unless `from` is also used as an alias.
+/// Columns, aliases and string literals named like the table are left alone.
+fn rename_table(sql: &str, from: &str, to: &str) -> String {
+    let tokens = tokenize(sql);
+    let previous = |index: usize| tokens[..index].iter().rev().find(|token| !token.is_space());
+    let next = |index: usize| tokens[index + 1..].iter().find(|token| !token.is_space());
+    let aliased = tokens.iter().enumerate().any(|(index, token)| {
+        token.is_identifier(from) && previous(index).is_some_and(|token| token.is_keyword("AS"))
+    });
+
+    let mut renamed = String::with_capacity(sql.len());
+    for (index, token) in tokens.iter().enumerate() {
+        let is_table = token.is_identifier(from)
+            && (previous(index).is_some_and(Token::is_table_keyword)
+                || !aliased && next(index).is_some_and(|token| token.raw() == "."));
+        match token {
+            Token::Identifier { quote, .. } if is_table => {
+                let escaped = to.replace(*quote, &format!("{quote}{quote}"));
+                renamed.push_str(&format!("{quote}{escaped}{quote}"));
+            }
+            _ => renamed.push_str(token.raw()),
+        }
+    }
+    renamed
+}
+
+/// The keywords which are followed by a table in the queries rorm generates
+const TABLE_KEYWORDS: &[&str] = &["FROM", "JOIN", "INTO", "UPDATE", "TABLE", "REFERENCES"];
+
+/// A token of a sql query as far as [`rename_table`] has to distinguish them
+enum Token<'a> {
+    /// A quoted identifier
+    Identifier {
+        raw: &'a str,
+        quote: char,
+        name: String,
+    },
+
+    /// A keyword or unquoted identifier
+    Word(&'a str),
+
+    /// A string literal, whitespace or a single other character
+    Other(&'a str),
+}
+
+impl<'a> Token<'a> {
+    /// Get the token as written in the query
+    fn raw(&self) -> &'a str {
+        match self {
+            Token::Identifier { raw, .. } | Token::Word(raw) | Token::Other(raw) => raw,
+        }
+    }
+
+    /// Check whether the token is whitespace
+    fn is_space(&self) -> bool {
+        matches!(self, Token::Other(raw) if raw.trim().is_empty())
+    }
+
+    /// Check whether the token is the keyword `keyword` ignoring its case
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self, Token::Word(word) if word.eq_ignore_ascii_case(keyword))
+    }
+
+    /// Check whether the token is one of [`TABLE_KEYWORDS`]
+    fn is_table_keyword(&self) -> bool {
+        TABLE_KEYWORDS
+            .iter()
+            .any(|keyword| self.is_keyword(keyword))
+    }
+
+    /// Check whether the token is a quoted identifier for `identifier`
+    fn is_identifier(&self, identifier: &str) -> bool {
+        matches!(self, Token::Identifier { name, .. } if name == identifier)
+    }
+}
+
+/// Split a sql query into [`Token`]s
+fn tokenize(sql: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut chars = sql.char_indices().peekable();
+    while let Some((start, char)) = chars.next() {
+        let mut name = String::new();
+        match char {
+            '\'' | '"' | '`' => loop {
+                match chars.next() {
+                    // A doubled quote is an escaped quote
+                    Some((_, next)) if next == char => {
+                        if chars.next_if(|&(_, next)| next == char).is_none() {
+                            break;
+                        }
+                        name.push(char);
+                    }
+                    Some((_, next)) => name.push(next),
+                    None => break,
+                }
+            },
+            _ if char.is_alphanumeric() || char == '_' => {
+                while chars
+                    .next_if(|&(_, next)| next.is_alphanumeric() || next == '_')
+                    .is_some()
+                {}
+            }
+            _ => {}
+        }
+
+        let end = chars.peek().map_or(sql.len(), |&(index, _)| index);
+        let raw = &sql[start..end];
+        tokens.push(match char {
+            '"' | '`' => Token::Identifier {
+                raw,
+                quote: char,
+                name,
+            },
+            _ if char.is_alphanumeric() || char == '_' => Token::Word(raw),
+            _ => Token::Other(raw),
+        });
+    }
+    tokens
+}
+
+/// A [`Route`] which replaces a model's table with one of its shards in every query
+///
+/// It is created by [`shard`] and [`all_shards`]
+/// as well as by [`time_partition`](crate::time_partition) routing to one of a model's partitions.
+pub struct Sharded<R> {
+    route: R,
+
+    /// The model's table
+    table: &'static str,
+
+    /// The shard's table
+    shard: String,
+}
+
+impl<R> Sharded<R> {
+    /// Wrap a route to route queries on `M` to the shard with the index `shard`
+    pub fn new<M: ShardedModel>(route: R, shard: u16) -> Self {
+        Self::with_table(route, M::TABLE, shard_table::<M>(shard))
+    }
+
+    /// Wrap a route to replace `table` with `copy`
+    pub(crate) fn with_table(route: R, table: &'static str, copy: String) -> Self {
+        Self {
+            route,
+            table,
+            shard: copy,
+        }
+    }
+}
+
+impl<'ex, M, R> Route<'ex, M> for Sharded<R>
+where
+    R: Route<'ex, M>,
+{
+    type Executor = R::Executor;
+
+    fn route(self) -> Scoped<Self::Executor> {
+        let Self {
+            route,
+            table,
+            shard,
+        } = self;
+        route
+            .route()
+            .rewrite(move |query| rename_table(&query, table, &shard))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{fnv1a, rename_table, shard_name, ShardKey};
+
+    #[test]
+    fn integer_keys() {
+        assert_eq!(17i64.shard(16), 1);
+        assert_eq!((-1i32).shard(16), 15);
+        assert_eq!(i16::MIN.shard(3), 1);
+    }
+
+    #[test]
+    fn hashed_keys() {
+        // Reference values of 64 bit FNV-1a
+        assert_eq!(fnv1a(b"", u16::MAX), (0xcbf29ce484222325u64 % 65535) as u16);
+        assert_eq!(
+            fnv1a(b"a", u16::MAX),
+            (0xaf63dc4c8601ec8cu64 % 65535) as u16
+        );
+        assert_eq!("a".to_string().shard(16), 12);
+        assert_eq!(b"a".to_vec().shard(16), 12);
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(shard_name("post", 1, 0), "post_00");
+        assert_eq!(shard_name("post", 16, 7), "post_07");
+        assert_eq!(shard_name("post", 1000, 7), "post_007");
+    }
+
+    #[test]
+    fn rename() {
+        assert_eq!(
+            rename_table(
+                "SELECT \"post\".\"id\" FROM \"post\" JOIN \"post_tag\";",
+                "post",
+                "post_03"
+            ),
+            "SELECT \"post_03\".\"id\" FROM \"post_03\" JOIN \"post_tag\";"
+        );
+        assert_eq!(
+            rename_table("DELETE FROM `post`;", "post", "post_03"),
+            "DELETE FROM `post_03`;"
+        );
+        assert_eq!(
+            rename_table(
+                "CREATE TABLE \"post\" (\"id\" INTEGER, \"parent\" INTEGER REFERENCES \"post\");",
+                "post",
+                "post_03"
+            ),
+            "CREATE TABLE \"post_03\" (\"id\" INTEGER, \"parent\" INTEGER REFERENCES \"post_03\");"
+        );
+    }
+
+    #[test]
+    fn rename_only_tables() {
+        // A column named like the table
+        assert_eq!(
+            rename_table(
+                "UPDATE \"post\" SET \"post\" = ? WHERE \"post\".\"post\" = ?;",
+                "post",
+                "post_03"
+            ),
+            "UPDATE \"post_03\" SET \"post\" = ? WHERE \"post_03\".\"post\" = ?;"
+        );
+        // An alias named like the table
+        assert_eq!(
+            rename_table(
+                "SELECT \"post\".\"id\" FROM \"user\" AS \"post\";",
+                "post",
+                "post_03"
+            ),
+            "SELECT \"post\".\"id\" FROM \"user\" AS \"post\";"
+        );
+        // Literals containing the table
+        assert_eq!(
+            rename_table(
+                "SELECT 'FROM \"post\"', 'it''s `post`' FROM `post`;",
+                "post",
+                "post_03"
+            ),
+            "SELECT 'FROM \"post\"', 'it''s `post`' FROM `post_03`;"
+        );
+    }
+}
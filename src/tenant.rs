@@ -0,0 +1,179 @@
+//! Separating the rows of different tenants stored in the same table
+//!
+//! A model's field can be annotated with `#[rorm(tenant_key)]` to store the tenant a row belongs to.
+//! The crud builders then only operate on the rows of the tenant set by the surrounding [`with_tenant`] call:
+//! - queries, updates and deletes get the condition `tenant_key = <tenant>` added
+//! - inserts set the tenant key to the tenant, even if the patch contains another value
+//!
+//! ```no_run
+//! # use rorm::{insert, query, Database, Error, Model, Patch};
+//! # use rorm::conditions::Value;
+//! # use rorm::tenant::with_tenant;
+//! #[derive(Model)]
+//! pub struct Invoice {
+//!     #[rorm(id)]
+//!     pub id: i64,
+//!
+//!     #[rorm(tenant_key)]
+//!     pub company: i64,
+//!
+//!     pub amount: i64,
+//! }
+//!
+//! #[derive(Patch)]
+//! #[rorm(model = "Invoice")]
+//! pub struct NewInvoice {
+//!     pub amount: i64,
+//! }
+//!
+//! pub async fn handle_request(db: &Database, company: i64) -> Result<Vec<Invoice>, Error> {
+//!     with_tenant(Value::I64(company), async {
+//!         // Stored with `company` as tenant
+//!         insert(db, Invoice).return_nothing().single(&NewInvoice { amount: 42 }).await?;
+//!
+//!         // Only returns the invoices of `company`
+//!         query(db, Invoice).all().await
+//!     })
+//!     .await
+//! }
+//! ```
+//!
+//! Using a model with a tenant key outside of [`with_tenant`] or [`without_tenant`] returns an [`Error::MissingTenant`],
+//! so a forgotten tenant can't leak the rows of all tenants.
+//!
+//! Only the model a builder operates on is restricted.
+//! Models reached through joins or foreign models have to be filtered explicitly.
+//! Raw sql is not affected either.
+
+use std::cell::RefCell;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use pin_project::pin_project;
+use rorm_db::Error;
+
+use crate::conditions::collections::CollectionOperator;
+use crate::conditions::{BinaryOperator, Value};
+use crate::internal::query_context::flat_conditions::FlatCondition;
+use crate::internal::query_context::ids::PathId;
+use crate::internal::query_context::QueryContext;
+use crate::internal::task_local::{TaskLocal, TaskLocalFuture};
+use crate::model::Model;
+
+thread_local! {
+    static SCOPE_SLOT: RefCell<Option<Scope>> = const { RefCell::new(None) };
+}
+static SCOPE: TaskLocal<Scope> = TaskLocal::new(&SCOPE_SLOT);
+
+/// The tenant set by [`with_tenant`] or [`without_tenant`]
+#[derive(Clone, Debug)]
+enum Scope {
+    Tenant(Value<'static>),
+    Unrestricted,
+}
+
+/// Run a future whose crud builders only operate on the rows of `tenant`
+///
+/// `tenant` is the value stored in the fields annotated with `#[rorm(tenant_key)]`.
+pub fn with_tenant<F: Future>(tenant: Value<'static>, future: F) -> WithTenant<F> {
+    WithTenant(SCOPE.scope(Scope::Tenant(tenant), future))
+}
+
+/// Run a future whose crud builders operate on the rows of all tenants
+///
+/// This is intended for administrative tasks like migrating data.
+/// Inserts into a model with a tenant key have to set it themselves.
+pub fn without_tenant<F: Future>(future: F) -> WithTenant<F> {
+    WithTenant(SCOPE.scope(Scope::Unrestricted, future))
+}
+
+/// Get the tenant set by the surrounding [`with_tenant`] call
+pub fn current_tenant() -> Option<Value<'static>> {
+    SCOPE.with(|scope| match scope {
+        Some(Scope::Tenant(tenant)) => Some(tenant.clone()),
+        _ => None,
+    })
+}
+
+/// Future returned by [`with_tenant`] and [`without_tenant`]
+#[pin_project]
+pub struct WithTenant<F: Future>(#[pin] TaskLocalFuture<Scope, F>);
+impl<F: Future> Future for WithTenant<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.project().0.poll(cx)
+    }
+}
+
+/// Get the tenant `M`'s rows have to be restricted to together with `M`'s tenant key
+///
+/// Returns an [`Error::MissingTenant`] if `M` has a tenant key and neither [`with_tenant`] nor [`without_tenant`] has been used.
+fn restriction<M: Model>() -> Result<Option<(&'static str, Value<'static>)>, Error> {
+    let Some(column) = M::TENANT_KEY else {
+        return Ok(None);
+    };
+    SCOPE.with(|scope| match scope {
+        Some(Scope::Tenant(tenant)) => Ok(Some((column, tenant.clone()))),
+        Some(Scope::Unrestricted) => Ok(None),
+        None => Err(Error::MissingTenant { table: M::TABLE }),
+    })
+}
+
+/// Build a crud builder's condition restricting it to the current tenant's rows
+///
+/// `build` adds the builder's own condition to the context returning its index.
+pub(crate) fn build_condition<'a, M: Model>(
+    ctx: &mut QueryContext<'a>,
+    build: impl FnOnce(&mut QueryContext<'a>) -> Option<usize>,
+) -> Result<Option<usize>, Error> {
+    let Some((column, tenant)) = restriction::<M>()? else {
+        return Ok(build(ctx));
+    };
+
+    let index = ctx.conditions.len();
+    ctx.conditions
+        .push(FlatCondition::StartCollection(CollectionOperator::And));
+    build(ctx);
+    ctx.add_origin_path::<M>();
+    ctx.conditions.extend([
+        FlatCondition::BinaryCondition(BinaryOperator::Equals),
+        FlatCondition::Column(PathId::of::<M>(), column),
+    ]);
+    ctx.conditions.push(FlatCondition::Value(ctx.values.len()));
+    ctx.values.push(tenant);
+    ctx.conditions.push(FlatCondition::EndCollection);
+    Ok(Some(index))
+}
+
+/// Set the tenant key of the rows to insert to the current tenant
+///
+/// `values` contains the rows' values for `columns` one row after another.
+pub(crate) fn fill_insert<M: Model>(
+    columns: &mut Vec<&'static str>,
+    values: &mut Vec<Value<'_>>,
+) -> Result<(), Error> {
+    let Some((column, tenant)) = restriction::<M>()? else {
+        return Ok(());
+    };
+    let row_len = columns.len();
+    match columns.iter().position(|name| *name == column) {
+        Some(position) => {
+            for row in values.chunks_mut(row_len) {
+                row[position] = tenant.clone();
+            }
+        }
+        None => {
+            columns.push(column);
+            let mut filled = Vec::with_capacity(values.len() + values.len() / row_len.max(1));
+            let mut rows = std::mem::take(values).into_iter();
+            while !rows.as_slice().is_empty() {
+                filled.extend(rows.by_ref().take(row_len));
+                filled.push(tenant.clone());
+            }
+            *values = filled;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,367 @@
+//! Splitting a model's rows across one table per time period
+//!
+//! Append-only models like logs, metrics or events can be declared as time-partitioned
+//! using `#[rorm(time_partition_by = "..", period = "..")]`.
+//! Its rows are then stored in one table per hour, day or month
+//! (`<table>_2024010113`, `<table>_20240101` or `<table>_202401`)
+//! and the table storing a row is computed from the timestamp field named by `time_partition_by`:
+//!
+//! ```no_run
+//! # use chrono::{DateTime, Utc};
+//! # use rorm::{insert, query, Database, Error, FieldAccess, Model, Patch};
+//! # use rorm::time_partition::{create_upcoming, partition, recent};
+//! #[derive(Model)]
+//! #[rorm(time_partition_by = "logged_at", period = "day")]
+//! pub struct LogEntry {
+//!     #[rorm(id)]
+//!     pub id: i64,
+//!
+//!     pub logged_at: DateTime<Utc>,
+//!
+//!     #[rorm(max_length = 255)]
+//!     pub message: String,
+//! }
+//!
+//! #[derive(Patch)]
+//! #[rorm(model = "LogEntry")]
+//! pub struct NewLogEntry {
+//!     pub logged_at: DateTime<Utc>,
+//!
+//!     #[rorm(max_length = 255)]
+//!     pub message: String,
+//! }
+//!
+//! pub async fn prepare(db: &Database) -> Result<(), Error> {
+//!     // Creates today's and tomorrow's table
+//!     create_upcoming::<LogEntry>(db, 1).await
+//! }
+//!
+//! pub async fn log(db: &Database, message: String) -> Result<(), Error> {
+//!     let logged_at = Utc::now();
+//!     // Inserted into `log_entry_<YYYYMMDD>`
+//!     insert(partition(db, LogEntry.logged_at, &logged_at), LogEntry)
+//!         .return_nothing()
+//!         .single(&NewLogEntry { logged_at, message })
+//!         .await
+//! }
+//!
+//! pub async fn last_week(db: &Database, message: &str) -> Result<Vec<LogEntry>, Error> {
+//!     // Queries the tables of today and the 6 days before
+//!     recent(db, LogEntry, 7, |db| {
+//!         query(db, LogEntry)
+//!             .condition(LogEntry.message.equals(message))
+//!             .all()
+//!     })
+//!     .await
+//! }
+//! ```
+//!
+//! Periods are computed in UTC.
+//! The migrator only creates the model's table which is used as template for the periods' tables.
+//! [`create_upcoming`] has to be called regularly (for example on startup and from a daily task)
+//! to create the tables before the first row of their period is inserted.
+//! Old periods can be removed by dropping their tables.
+//!
+//! The [`Sharded`] route returned by [`partition`] replaces the model's table with the period's table,
+//! so the same restrictions as for [sharding](crate::sharding) apply.
+
+use std::collections::HashSet;
+use std::future::Future;
+
+use rorm_db::executor::{All, Executor};
+use rorm_db::sql::value::Value;
+use rorm_db::sql::DBImpl;
+use rorm_db::Error;
+
+use crate::crud::selector::Selector;
+use crate::internal::field::{Field, FieldProxy};
+use crate::internal::sql::unix_timestamp_secs;
+use crate::model::{Model, Patch};
+use crate::sharding::{create_copies, query_tables, Sharded};
+
+/// A model declared as time-partitioned using `#[rorm(time_partition_by = "..", period = "..")]`
+///
+/// This trait is implemented by the `Model` derive.
+pub trait TimePartitionedModel: Model {
+    /// The timestamp field the rows are partitioned by
+    type PartitionField: Field<Model = Self, Type: PartitionTime>;
+
+    /// The timestamp field and period
+    const TIME_PARTITION: TimePartition;
+}
+
+/// The time partitioning of a model declared by `#[rorm(time_partition_by = "..", period = "..")]`
+///
+/// See [`TimePartitionedModel::TIME_PARTITION`].
+#[derive(Copy, Clone, Debug)]
+pub struct TimePartition {
+    /// The column storing the timestamp the rows are partitioned by
+    pub column: &'static str,
+
+    /// The period covered by each table
+    pub period: Period,
+}
+
+impl TimePartition {
+    /// Describe the partitioning of `F`'s model by `F` into one table per `period`
+    #[doc(hidden)]
+    pub const fn new<F, P>(_field: FieldProxy<F, P>, period: Period) -> Self
+    where
+        F: Field<Type: PartitionTime>,
+    {
+        Self {
+            column: F::NAME,
+            period,
+        }
+    }
+}
+
+/// The period covered by each table of a time-partitioned model
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Period {
+    /// `period = "hour"` using tables suffixed with `_YYYYMMDDHH`
+    Hour,
+
+    /// `period = "day"` using tables suffixed with `_YYYYMMDD`
+    Day,
+
+    /// `period = "month"` using tables suffixed with `_YYYYMM`
+    Month,
+}
+
+impl Period {
+    /// Get the start of the period containing a unix timestamp
+    pub fn start(self, timestamp: i64) -> i64 {
+        match self {
+            Period::Hour => timestamp - timestamp.rem_euclid(3600),
+            Period::Day => timestamp - timestamp.rem_euclid(86400),
+            Period::Month => {
+                let (year, month, _) = civil_from_days(timestamp.div_euclid(86400));
+                days_from_civil(year, month, 1) * 86400
+            }
+        }
+    }
+
+    /// Get the start of the period following the one containing a unix timestamp
+    pub fn next(self, timestamp: i64) -> i64 {
+        match self {
+            Period::Hour => self.start(timestamp) + 3600,
+            Period::Day => self.start(timestamp) + 86400,
+            Period::Month => {
+                let (year, month, _) = civil_from_days(timestamp.div_euclid(86400));
+                let (year, month) = if month == 12 {
+                    (year + 1, 1)
+                } else {
+                    (year, month + 1)
+                };
+                days_from_civil(year, month, 1) * 86400
+            }
+        }
+    }
+
+    /// Get the start of the period preceding the one containing a unix timestamp
+    pub fn previous(self, timestamp: i64) -> i64 {
+        self.start(self.start(timestamp) - 1)
+    }
+
+    /// Get the suffix of the table storing the rows of the period containing a unix timestamp
+    pub fn suffix(self, timestamp: i64) -> String {
+        let (year, month, day) = civil_from_days(timestamp.div_euclid(86400));
+        let hour = timestamp.rem_euclid(86400) / 3600;
+        match self {
+            Period::Hour => format!("{year:04}{month:02}{day:02}{hour:02}"),
+            Period::Day => format!("{year:04}{month:02}{day:02}"),
+            Period::Month => format!("{year:04}{month:02}"),
+        }
+    }
+}
+
+/// A field type which can be used to partition a model by time
+pub trait PartitionTime {
+    /// Get the seconds since the unix epoch
+    fn unix_timestamp(&self) -> i64;
+}
+#[cfg(feature = "chrono")]
+impl PartitionTime for chrono::DateTime<chrono::Utc> {
+    fn unix_timestamp(&self) -> i64 {
+        self.timestamp()
+    }
+}
+#[cfg(feature = "chrono")]
+impl PartitionTime for chrono::NaiveDateTime {
+    fn unix_timestamp(&self) -> i64 {
+        use chrono::{Datelike, Timelike};
+        days_from_civil(i64::from(self.year()), self.month(), self.day()) * 86400
+            + i64::from(self.num_seconds_from_midnight())
+    }
+}
+#[cfg(feature = "time")]
+impl PartitionTime for time::OffsetDateTime {
+    fn unix_timestamp(&self) -> i64 {
+        time::OffsetDateTime::unix_timestamp(*self)
+    }
+}
+#[cfg(feature = "time")]
+impl PartitionTime for time::PrimitiveDateTime {
+    fn unix_timestamp(&self) -> i64 {
+        self.assume_utc().unix_timestamp()
+    }
+}
+
+/// Convert days since the unix epoch into a year, month and day
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month as u32, day as u32)
+}
+
+/// Convert a year, month and day into days since the unix epoch
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let shifted_month = i64::from((month + 9) % 12);
+    let day_of_year = (153 * shifted_month + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// Get the name of `M`'s table storing the rows of the period containing a unix timestamp
+fn table_at<M: TimePartitionedModel>(timestamp: i64) -> String {
+    format!(
+        "{}_{}",
+        M::TABLE,
+        M::TIME_PARTITION.period.suffix(timestamp)
+    )
+}
+
+/// Get the name of `M`'s table storing the rows of the period containing `time`
+pub fn partition_table<M: TimePartitionedModel>(time: &impl PartitionTime) -> String {
+    table_at::<M>(time.unix_timestamp())
+}
+
+/// Route a crud builder to the table storing the rows of the period containing `time`
+///
+/// `field` has to be the model's timestamp field.
+pub fn partition<F, P, R>(executor: R, _field: FieldProxy<F, P>, time: &F::Type) -> Sharded<R>
+where
+    F: Field<Type: PartitionTime, Model: TimePartitionedModel<PartitionField = F>>,
+{
+    Sharded::with_table(executor, F::Model::TABLE, partition_table::<F::Model>(time))
+}
+
+/// Create the tables of the current period and the `ahead` following ones which don't exist yet
+///
+/// The model's own table is used as template, see [`create_shards`](crate::sharding::create_shards).
+pub async fn create_upcoming<'ex, M: TimePartitionedModel>(
+    executor: impl Executor<'ex>,
+    ahead: u32,
+) -> Result<(), Error> {
+    let period = M::TIME_PARTITION.period;
+    let mut start = period.start(unix_timestamp_secs());
+    let mut tables = Vec::new();
+    for _ in 0..=ahead {
+        tables.push(table_at::<M>(start));
+        start = period.next(start);
+    }
+    create_copies(executor, M::TABLE, tables).await
+}
+
+/// Run a query on the tables of the current period and the `periods - 1` preceding ones
+/// and concatenate their results
+///
+/// `query` is called once for every table with an executor routed to it.
+/// The tables are queried concurrently and their results are returned starting with the newest period.
+/// Periods whose table doesn't exist are skipped.
+pub async fn recent<'ex, E, S, T, Q, Fut>(
+    executor: E,
+    _: S,
+    periods: u32,
+    query: Q,
+) -> Result<Vec<T>, Error>
+where
+    E: Executor<'ex> + Copy,
+    S: Selector<Model: Patch<ValueSpaceImpl = S> + TimePartitionedModel>,
+    Q: FnMut(Sharded<E>) -> Fut,
+    Fut: Future<Output = Result<Vec<T>, Error>>,
+{
+    let period = S::Model::TIME_PARTITION.period;
+    let mut start = period.start(unix_timestamp_secs());
+    let mut tables = Vec::new();
+    for _ in 0..periods {
+        tables.push(table_at::<S::Model>(start));
+        start = period.previous(start);
+    }
+
+    let existing = existing_tables(executor, S::Model::TABLE).await?;
+    tables.retain(|table| existing.contains(table));
+    query_tables(executor, S::Model::TABLE, tables, query).await
+}
+
+/// Get the tables whose name starts with `table`
+async fn existing_tables<'ex>(
+    executor: impl Executor<'ex>,
+    table: &str,
+) -> Result<HashSet<String>, Error> {
+    let pattern = format!("{table}_%");
+    let sql = match executor.dialect() {
+        DBImpl::Postgres => {
+            "SELECT tablename FROM pg_tables WHERE schemaname = current_schema() AND tablename LIKE $1;"
+        }
+        #[cfg(feature = "all-drivers")]
+        DBImpl::MySQL => {
+            "SELECT table_name FROM information_schema.tables WHERE table_schema = DATABASE() AND table_name LIKE ?;"
+        }
+        #[cfg(feature = "all-drivers")]
+        DBImpl::SQLite => "SELECT name FROM sqlite_master WHERE type = 'table' AND name LIKE ?;",
+    };
+    let rows = executor
+        .execute::<All>(sql.to_string(), vec![Value::String(&pattern)])
+        .await?;
+    rows.iter()
+        .map(|row| row.get(0usize).map_err(Into::into))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::Period;
+
+    /// 2024-01-01 13:00:59 UTC
+    const NEW_YEAR: i64 = 1704067200 + 13 * 3600 + 59;
+
+    #[test]
+    fn suffix() {
+        assert_eq!(Period::Hour.suffix(NEW_YEAR), "2024010113");
+        assert_eq!(Period::Day.suffix(NEW_YEAR), "20240101");
+        assert_eq!(Period::Month.suffix(NEW_YEAR), "202401");
+        assert_eq!(Period::Day.suffix(1709164800), "20240229");
+        assert_eq!(Period::Hour.suffix(-1), "1969123123");
+    }
+
+    #[test]
+    fn start() {
+        assert_eq!(Period::Hour.start(NEW_YEAR), 1704067200 + 13 * 3600);
+        assert_eq!(Period::Day.start(NEW_YEAR), 1704067200);
+        assert_eq!(Period::Month.start(1710046800), 1709251200);
+    }
+
+    #[test]
+    fn next_and_previous() {
+        assert_eq!(Period::Month.next(1734220800), 1735689600);
+        assert_eq!(Period::Month.previous(1710046800), 1706745600);
+        assert_eq!(Period::Day.previous(NEW_YEAR), 1704067200 - 86400);
+    }
+}
@@ -0,0 +1,435 @@
+//! Requiring a transaction at compile time
+//!
+//! All crud functions ([`query`](crate::query), [`insert`](crate::insert), [`update`](crate::update)
+//! and [`delete`](crate::delete)) accept any [`Executor`] i.e. `&Database` as well as `&mut Transaction`.
+//!
+//! Helpers which execute several queries and rely on them being applied atomically
+//! can take an [`InTransaction`] instead. This moves the responsibility to start
+//! (and commit) the transaction to the caller, so the helper can be composed with other
+//! queries inside the same transaction:
+//!
+//! ```no_run
+//! # use rorm::{delete, insert, Database, Error, Model, Patch};
+//! # use rorm::transaction::InTransaction;
+//! # #[derive(Model)] pub struct User { #[rorm(id)] id: i64, #[rorm(max_length = 255)] name: String, }
+//! # #[derive(Patch)] #[rorm(model = "User")] pub struct NewUser { name: String, }
+//! /// Replaces all users
+//! ///
+//! /// Only a transaction can be passed, so there is never a moment without any users.
+//! pub async fn replace_users(
+//!     mut tx: impl InTransaction<'_>,
+//!     users: &[NewUser],
+//! ) -> Result<(), Error> {
+//!     delete(tx.transaction(), User).all().await?;
+//!     insert(tx.transaction(), User).return_nothing().bulk(users).await?;
+//!     Ok(())
+//! }
+//!
+//! pub async fn reset(db: &Database, users: &[NewUser]) -> Result<(), Error> {
+//!     let mut tx = db.start_transaction().await?;
+//!     replace_users(&mut tx, users).await?;
+//!     tx.commit().await
+//! }
+//! ```
+//!
+//! # Row-level security
+//!
+//! Postgres' row-level security policies can restrict the rows visible to a query
+//! based on settings of the current transaction.
+//! [`InTransaction::set_context`] sets such a setting for the rest of the transaction,
+//! so every query run on it by the crud builders is subject to the policies:
+//!
+//! ```no_run
+//! # use rorm::{query, Database, Error, Model};
+//! # use rorm::transaction::InTransaction;
+//! # #[derive(Model)] pub struct Document { #[rorm(id)] id: i64, #[rorm(max_length = 255)] title: String, owner: i64, }
+//! // CREATE POLICY owner_only ON document
+//! //     USING (owner = current_setting('app.user_id')::bigint);
+//! pub async fn own_documents(db: &Database, user: i64) -> Result<Vec<Document>, Error> {
+//!     let mut tx = db.start_transaction().await?;
+//!     (&mut tx).set_context("app.user_id", &user.to_string()).await?;
+//!     let documents = query(&mut tx, Document).all().await?;
+//!     tx.commit().await?;
+//!     Ok(documents)
+//! }
+//! ```
+//!
+//! Since the settings are reset when the transaction ends,
+//! they never leak to other users of the pooled connection.
+//! Keep in mind that policies don't apply to the table's owner unless `FORCE ROW LEVEL SECURITY` is set.
+//!
+//! # Scopes
+//!
+//! The crud builders wrap their executor in a [`Scoped`] which rewrites every query,
+//! for example to add a [comment](crate::crud::comment) or to replace a table with its [shard](crate::sharding).
+//! [`Executor::ensure_transaction`] returns a plain transaction,
+//! so the builders start their transactions using [`Scoped::begin`] instead,
+//! whose [`ScopedTransaction`] keeps rewriting the queries executed inside it.
+//!
+//! A [`ScopedTransaction`] also holds back the [events](crate::events) of its queries until it is committed.
+//! Use [`begin`] to start one yourself.
+
+use std::future::Future;
+use std::sync::Arc;
+
+use rorm_db::executor::{Nothing, One, QueryStrategy};
+use rorm_db::sql::create_table::CreateTable;
+use rorm_db::sql::value::Value;
+use rorm_db::sql::DBImpl;
+use rorm_db::transaction::{Transaction, TransactionGuard};
+use rorm_db::{database, Error, Executor};
+use rorm_declaration::imr;
+
+use crate::events::EventBuffer;
+use crate::model::Model;
+
+/// An [`Executor`] which is guaranteed to run its queries inside a transaction
+///
+/// This trait is sealed and only implemented for `&mut Transaction`.
+pub trait InTransaction<'executor>: Executor<'executor> + private::Sealed {
+    /// Reborrow the transaction to execute a query while keeping `self` usable
+    fn transaction(&mut self) -> &mut Transaction;
+
+    /// Defer checking deferrable constraints until the transaction is committed
+    ///
+    /// rorm's migrations don't create deferrable constraints,
+    /// they have to be declared as `DEFERRABLE` by a handwritten migration.
+    /// This allows inserting rows which reference each other in a single transaction.
+    /// Only postgres supports deferring constraints, the other databases will return an error.
+    fn set_constraints_deferred(&mut self) -> impl Future<Output = Result<(), Error>> + '_ {
+        async {
+            database::raw_sql(self.transaction(), "SET CONSTRAINTS ALL DEFERRED;", None).await?;
+            Ok(())
+        }
+    }
+
+    /// Set the setting `name` to `value` until the end of the transaction
+    ///
+    /// This is equivalent to `SET LOCAL name = value`, but `value` is passed as a bind parameter.
+    /// `name` should contain a dot (like `app.user_id`) to declare a custom setting.
+    /// Its value can be read in sql using `current_setting('app.user_id')`
+    /// or from rust using [`InTransaction::get_context`].
+    ///
+    /// See the [module docs](self) for using it with row-level security.
+    /// This is only supported on postgres.
+    fn set_context<'a>(
+        &'a mut self,
+        name: &'a str,
+        value: &'a str,
+    ) -> impl Future<Output = Result<(), Error>> + 'a {
+        async move {
+            let tx = self.transaction();
+            require_postgres(tx.dialect())?;
+            tx.execute::<Nothing>(
+                "SELECT set_config($1, $2, true);".to_string(),
+                vec![Value::String(name), Value::String(value)],
+            )
+            .await
+        }
+    }
+
+    /// Get the value of the setting `name`
+    ///
+    /// Returns `None` if a custom setting has not been set.
+    ///
+    /// This is only supported on postgres.
+    fn get_context<'a>(
+        &'a mut self,
+        name: &'a str,
+    ) -> impl Future<Output = Result<Option<String>, Error>> + 'a {
+        async move {
+            let tx = self.transaction();
+            require_postgres(tx.dialect())?;
+            let row = tx
+                .execute::<One>(
+                    "SELECT current_setting($1, true);".to_string(),
+                    vec![Value::String(name)],
+                )
+                .await?;
+            // An unset custom setting is an empty string after it has been set in another transaction
+            let value: Option<String> = row.get(0usize)?;
+            Ok(value.filter(|value| !value.is_empty()))
+        }
+    }
+
+    /// Create a temporary table for the model `M` which only exists on this transaction's connection
+    ///
+    /// The table is named after the model and created like the migrator would create it,
+    /// except for foreign keys and indexes which are not created.
+    ///
+    /// Afterwards all crud builders can be used with `M` on this transaction.
+    /// This supports staging workflows where rows are bulk loaded into a temporary table
+    /// and then merged into the actual one.
+    /// `M` should therefore be a model which is not part of the migrations
+    /// (`#[rorm(experimental_unregistered)]`).
+    ///
+    /// The table exists until it is dropped using [`InTransaction::drop_temp_table`]
+    /// or the connection is closed.
+    /// Since connections are returned to the pool after the transaction, it should always be dropped explicitly.
+    fn create_temp_table<M: Model>(&mut self) -> impl Future<Output = Result<(), Error>> + '_ {
+        create_table(self.transaction(), M::get_imr(), true)
+    }
+
+    /// Drop a temporary table created by [`InTransaction::create_temp_table`]
+    fn drop_temp_table<M: Model>(&mut self) -> impl Future<Output = Result<(), Error>> + '_ {
+        async {
+            let tx = self.transaction();
+            let sql = match tx.dialect() {
+                DBImpl::Postgres => format!("DROP TABLE \"{}\";", M::TABLE),
+                #[allow(unreachable_patterns)]
+                _ => format!("DROP TABLE `{}`;", M::TABLE),
+            };
+            tx.execute::<Nothing>(sql, Vec::new()).await
+        }
+    }
+
+    /// Write `event` serialized as json to the outbox, so it is only published if the transaction is committed
+    ///
+    /// See [`outbox`](crate::outbox) for details.
+    #[cfg(feature = "outbox")]
+    fn enqueue<'a, E: serde::Serialize + ?Sized>(
+        &'a mut self,
+        topic: &'a str,
+        event: &'a E,
+    ) -> impl Future<Output = Result<(), Error>> + 'a {
+        crate::outbox::enqueue(self.transaction(), topic, event)
+    }
+}
+
+impl<'executor> InTransaction<'executor> for &'executor mut Transaction {
+    fn transaction(&mut self) -> &mut Transaction {
+        self
+    }
+}
+
+/// Rewriting applied to every query executed through a [`Scoped`] executor
+///
+/// See the [module docs](self#scopes).
+#[derive(Clone, Default)]
+pub struct Scope {
+    /// Functions rewriting a query's sql in the order they are applied
+    rewrites: Vec<Arc<dyn Fn(String) -> String + Send + Sync>>,
+
+    /// The events held back until the surrounding transaction is committed
+    events: Option<EventBuffer>,
+}
+
+impl Scope {
+    /// Rewrite every query using `rewrite` before applying the already added rewrites
+    pub fn rewrite(mut self, rewrite: impl Fn(String) -> String + Send + Sync + 'static) -> Self {
+        self.rewrites.insert(0, Arc::new(rewrite));
+        self
+    }
+
+    /// Apply all rewrites to a query's sql
+    fn apply(&self, query: String) -> String {
+        self.rewrites
+            .iter()
+            .fold(query, |query, rewrite| rewrite(query))
+    }
+}
+
+/// An [`Executor`] applying a [`Scope`] to its queries and the transactions it starts
+///
+/// Every crud builder wraps its executor in it, see [`Route`](crate::routing::Route).
+#[derive(Clone)]
+pub struct Scoped<E> {
+    executor: E,
+    scope: Scope,
+}
+
+impl<E> Scoped<E> {
+    /// Wrap an executor applying `scope` to its queries
+    pub fn new(executor: E, scope: Scope) -> Self {
+        Self { executor, scope }
+    }
+
+    /// Add a rewrite to the scope
+    ///
+    /// See [`Scope::rewrite`].
+    pub fn rewrite(self, rewrite: impl Fn(String) -> String + Send + Sync + 'static) -> Self {
+        Self {
+            executor: self.executor,
+            scope: self.scope.rewrite(rewrite),
+        }
+    }
+}
+
+impl<'executor, E> Scoped<E>
+where
+    E: Executor<'executor>,
+{
+    /// Start a transaction, unless the executor already is one, which keeps applying the scope
+    ///
+    /// This replaces [`Executor::ensure_transaction`] which would start a plain transaction.
+    pub async fn begin(self) -> Result<ScopedTransaction<'executor>, Error> {
+        let guard = self.executor.ensure_transaction().await?;
+        let owned = matches!(guard, TransactionGuard::Owned(_));
+        let mut scope = self.scope;
+        if owned {
+            scope.events = Some(EventBuffer::default());
+        }
+        Ok(ScopedTransaction {
+            guard,
+            scope,
+            owned,
+        })
+    }
+}
+
+impl<'executor, E> Executor<'executor> for Scoped<E>
+where
+    E: Executor<'executor>,
+{
+    fn execute<'data, 'result, Q>(
+        self,
+        query: String,
+        values: Vec<Value<'data>>,
+    ) -> Q::Result<'result>
+    where
+        'executor: 'result,
+        'data: 'result,
+        Q: QueryStrategy,
+    {
+        self.executor.execute::<Q>(self.scope.apply(query), values)
+    }
+
+    fn dialect(&self) -> DBImpl {
+        self.executor.dialect()
+    }
+
+    type EnsureTransactionFuture = E::EnsureTransactionFuture;
+
+    /// Start a plain transaction which doesn't apply the scope
+    ///
+    /// Use [`Scoped::begin`] to keep it.
+    fn ensure_transaction(self) -> Self::EnsureTransactionFuture {
+        self.executor.ensure_transaction()
+    }
+}
+
+/// A transaction started by [`Scoped::begin`] which applies the scope to its queries
+///
+/// Pass `&mut ScopedTransaction` to the crud functions to execute queries on it.
+pub struct ScopedTransaction<'executor> {
+    guard: TransactionGuard<'executor>,
+    scope: Scope,
+
+    /// Has the transaction been started by [`Scoped::begin`]?
+    owned: bool,
+}
+
+impl ScopedTransaction<'_> {
+    /// Get the underlying transaction, whose queries don't apply the scope
+    pub fn transaction(&mut self) -> &mut Transaction {
+        self.guard.get_transaction()
+    }
+
+    /// Get an executor applying the scope to the transaction
+    pub(crate) fn executor(&mut self) -> Scoped<&mut Transaction> {
+        Scoped {
+            executor: self.guard.get_transaction(),
+            scope: self.scope.clone(),
+        }
+    }
+
+    /// Get the buffer holding back the events published inside the transaction
+    ///
+    /// A plain [`Transaction`] passed to the builders doesn't have one.
+    pub(crate) fn events(&self) -> Option<&EventBuffer> {
+        self.scope.events.as_ref()
+    }
+
+    /// Commit the transaction, if it has been started by [`Scoped::begin`], and publish its events
+    ///
+    /// If the executor already was a transaction, committing it is left to its owner.
+    /// Dropping the transaction without committing it rolls it back and discards its events.
+    pub async fn commit(self) -> Result<(), Error> {
+        self.guard.commit().await?;
+        if self.owned {
+            if let Some(events) = self.scope.events {
+                events.flush();
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Start a transaction, unless the executor already is one, which publishes its events when it is committed
+///
+/// Pass `&mut` the returned transaction to the crud functions to execute queries on it.
+/// See the [events](crate::events) for an example.
+///
+/// If `executor` is a plain [`Transaction`], its commit can't be observed,
+/// so the events of the queries executed on the returned transaction are discarded.
+pub async fn begin<'executor>(
+    executor: impl Executor<'executor>,
+) -> Result<ScopedTransaction<'executor>, Error> {
+    Scoped::new(executor, Scope::default()).begin().await
+}
+
+/// Return an error for transaction settings if `dialect` is not postgres
+fn require_postgres(dialect: DBImpl) -> Result<(), Error> {
+    match dialect {
+        DBImpl::Postgres => Ok(()),
+        #[allow(unreachable_patterns)]
+        _ => Err(Error::DecodeError(
+            "Transaction settings are only supported on postgres".to_string(),
+        )),
+    }
+}
+
+/// Create the table of `model` using the statements the migrator would generate
+///
+/// Foreign keys and indexes are not created,
+/// because temporary tables can't reference other tables and index names are global on some databases.
+pub(crate) async fn create_table<'ex>(
+    executor: impl Executor<'ex>,
+    mut model: imr::Model,
+    temporary: bool,
+) -> Result<(), Error> {
+    for field in &mut model.fields {
+        field.annotations.retain(|annotation| {
+            !matches!(
+                annotation,
+                imr::Annotation::ForeignKey(_)
+                    | imr::Annotation::OnDelete(_)
+                    | imr::Annotation::OnUpdate(_)
+                    | imr::Annotation::Index(_)
+            )
+        });
+    }
+
+    let dialect = executor.dialect();
+    let mut create_table = dialect.create_table(&model.name);
+    for field in &model.fields {
+        create_table = create_table.add_column(dialect.create_column(
+            &model.name,
+            &field.name,
+            field.db_type,
+            &field.annotations,
+        ));
+    }
+    let statements = create_table.build()?;
+
+    let mut guard = executor.ensure_transaction().await?;
+    for (index, (sql, values)) in statements.into_iter().enumerate() {
+        // The first statement creates the table, the others add triggers to it
+        let sql = match sql.strip_prefix("CREATE TABLE") {
+            Some(rest) if temporary && index == 0 => format!("CREATE TEMPORARY TABLE{rest}"),
+            _ => sql,
+        };
+        guard
+            .get_transaction()
+            .execute::<Nothing>(sql, values)
+            .await?;
+    }
+    guard.commit().await
+}
+
+mod private {
+    use rorm_db::transaction::Transaction;
+
+    pub trait Sealed {}
+    impl Sealed for &mut Transaction {}
+}
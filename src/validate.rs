@@ -0,0 +1,316 @@
+//! Validation of values before they are written to the database
+//!
+//! Fields can be annotated with `#[rorm(validate(..))]` to check their values
+//! whenever they are inserted or updated:
+//! - `range(min = <number>, max = <number>)` checks a number to be inside the inclusive range
+//! - `regex = "<pattern>"` checks a string to match a regex (requires the `regex` feature)
+//! - `email` checks a string to look like an email address
+//!
+//! ```no_run
+//! # use rorm::{Model, Patch};
+//! # use rorm::validate::Validate;
+//! #[derive(Model)]
+//! pub struct User {
+//!     #[rorm(id)]
+//!     pub id: i64,
+//!
+//!     #[rorm(max_length = 255, validate(email))]
+//!     pub email: String,
+//!
+//!     #[rorm(validate(range(min = 0, max = 150)))]
+//!     pub age: i16,
+//! }
+//!
+//! #[derive(Patch)]
+//! #[rorm(model = "User")]
+//! pub struct NewUser {
+//!     pub email: String,
+//!     pub age: i16,
+//! }
+//!
+//! let errors = NewUser {
+//!     email: "not an email".to_string(),
+//!     age: 200,
+//! }
+//! .validate()
+//! .unwrap_err();
+//! assert_eq!(errors.0.len(), 2);
+//! ```
+//!
+//! The crud builders run the checks before executing their query.
+//! A failed validation is reported as [`Error::ValidationError`] containing the [`ValidationErrors`]:
+//!
+//! ```no_run
+//! # use rorm::{insert, Database, Error, Model, Patch};
+//! # use rorm::validate::ValidationErrors;
+//! # #[derive(Model)] pub struct User { #[rorm(id)] id: i64, #[rorm(validate(range(max = 150)))] age: i16, }
+//! # #[derive(Patch)] #[rorm(model = "User")] pub struct NewUser { age: i16, }
+//! pub async fn register(db: &Database, user: NewUser) -> Result<Option<ValidationErrors>, Error> {
+//!     match insert(db, User).return_nothing().single(&user).await {
+//!         Ok(()) => Ok(None),
+//!         Err(Error::ValidationError(error)) => {
+//!             Ok(error.downcast::<ValidationErrors>().ok().map(|errors| *errors))
+//!         }
+//!         Err(error) => Err(error),
+//!     }
+//! }
+//! ```
+
+use std::borrow::Cow;
+use std::fmt;
+
+use rorm_db::Error;
+
+use crate::fields::types::MaxStr;
+
+/// Check a patch's values against its fields' `#[rorm(validate(..))]` annotations
+///
+/// This trait is implemented by [`derive(Patch)`](rorm_macro::Patch) as well as [`derive(Model)`](rorm_macro::Model).
+pub trait Validate {
+    /// Check all values returning every failed check
+    fn validate(&self) -> Result<(), ValidationErrors>;
+}
+
+/// A single failed check of a field's value
+#[derive(Clone, Debug, PartialEq)]
+pub struct ValidationError {
+    /// The db name of the field whose value is invalid
+    pub field: &'static str,
+
+    /// The check which failed
+    pub kind: ValidationErrorKind,
+}
+
+/// The check which failed in a [`ValidationError`]
+#[derive(Clone, Debug, PartialEq)]
+pub enum ValidationErrorKind {
+    /// The number is outside `range(..)`
+    Range {
+        /// The inclusive lower bound
+        min: Option<f64>,
+        /// The inclusive upper bound
+        max: Option<f64>,
+    },
+
+    /// The string doesn't match `regex = ".."`
+    Regex {
+        /// The regex's pattern
+        pattern: &'static str,
+    },
+
+    /// The string is not an email address
+    Email,
+
+    /// The value couldn't be encoded into its column
+    ///
+    /// This is reported by [`FieldType::check_value`](crate::fields::traits::FieldType::check_value)
+    /// for types like [`Binary`](crate::fields::types::Binary) whose serialization can fail.
+    Encoding {
+        /// The encoder's error message
+        message: String,
+    },
+}
+
+/// All failed checks of a patch returned by [`Validate::validate`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ValidationErrors(pub Vec<ValidationError>);
+
+impl ValidationErrors {
+    /// Returns `Ok` if there are no errors
+    pub fn from_vec(errors: Vec<ValidationError>) -> Result<(), Self> {
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(Self(errors))
+        }
+    }
+}
+
+impl fmt::Display for ValidationErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationErrorKind::Range { min, max } => {
+                write!(f, "value is out of range")?;
+                match (min, max) {
+                    (Some(min), Some(max)) => write!(f, " {min}..={max}"),
+                    (Some(min), None) => write!(f, " {min}.."),
+                    (None, Some(max)) => write!(f, " ..={max}"),
+                    (None, None) => Ok(()),
+                }
+            }
+            ValidationErrorKind::Regex { pattern } => {
+                write!(f, "value doesn't match the regex `{pattern}`")
+            }
+            ValidationErrorKind::Email => write!(f, "value is not an email address"),
+            ValidationErrorKind::Encoding { message } => {
+                write!(f, "value couldn't be encoded: {message}")
+            }
+        }
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid {}: {}", self.field, self.kind)
+    }
+}
+
+impl fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, error) in self.0.iter().enumerate() {
+            if index > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{error}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ValidationError {}
+impl std::error::Error for ValidationErrors {}
+
+impl From<ValidationErrors> for Error {
+    fn from(errors: ValidationErrors) -> Self {
+        Error::ValidationError(Box::new(errors))
+    }
+}
+
+/// A field's type whose values can be checked by `range(..)`
+pub trait ValidateNumber {
+    /// Get the value as number or `None` if it is null
+    fn as_number(&self) -> Option<f64>;
+}
+
+/// A field's type whose values can be checked by `regex = ".."` and `email`
+pub trait ValidateStr {
+    /// Get the value as string or `None` if it is null
+    fn as_str(&self) -> Option<&str>;
+}
+
+macro_rules! impl_ValidateNumber {
+    ($($type:ty),+) => {$(
+        impl ValidateNumber for $type {
+            fn as_number(&self) -> Option<f64> {
+                Some(*self as f64)
+            }
+        }
+    )+};
+}
+impl_ValidateNumber!(i8, i16, i32, i64, u8, u16, u32, u64, f32, f64);
+
+impl<T: ValidateNumber> ValidateNumber for Option<T> {
+    fn as_number(&self) -> Option<f64> {
+        self.as_ref().and_then(T::as_number)
+    }
+}
+
+impl ValidateStr for String {
+    fn as_str(&self) -> Option<&str> {
+        Some(String::as_str(self))
+    }
+}
+
+impl ValidateStr for Cow<'static, str> {
+    fn as_str(&self) -> Option<&str> {
+        Some(self)
+    }
+}
+
+impl<const MAX_LEN: usize, Impl, Str> ValidateStr for MaxStr<MAX_LEN, Impl, Str>
+where
+    Str: std::ops::Deref<Target = str>,
+{
+    fn as_str(&self) -> Option<&str> {
+        Some(&**self)
+    }
+}
+
+impl<T: ValidateStr> ValidateStr for Option<T> {
+    fn as_str(&self) -> Option<&str> {
+        self.as_ref().and_then(T::as_str)
+    }
+}
+
+/// Check a number to be inside an inclusive range
+///
+/// This function is called by the code generated for `range(..)`.
+pub fn range<T: ValidateNumber + ?Sized>(
+    value: &T,
+    min: Option<f64>,
+    max: Option<f64>,
+) -> Result<(), ValidationErrorKind> {
+    let Some(number) = value.as_number() else {
+        return Ok(());
+    };
+    if min.is_some_and(|min| number < min) || max.is_some_and(|max| number > max) {
+        Err(ValidationErrorKind::Range { min, max })
+    } else {
+        Ok(())
+    }
+}
+
+/// Check a string to look like an email address
+///
+/// This only performs a simple syntactic check i.e. a non-empty local part
+/// followed by a single `@` and a domain containing a dot.
+///
+/// This function is called by the code generated for `email`.
+pub fn email<T: ValidateStr + ?Sized>(value: &T) -> Result<(), ValidationErrorKind> {
+    let Some(string) = value.as_str() else {
+        return Ok(());
+    };
+    let valid = string.split_once('@').is_some_and(|(local, domain)| {
+        !local.is_empty()
+            && !domain.contains('@')
+            && domain
+                .split_once('.')
+                .is_some_and(|(head, tail)| !head.is_empty() && !tail.is_empty())
+            && !string.contains(char::is_whitespace)
+    });
+    if valid {
+        Ok(())
+    } else {
+        Err(ValidationErrorKind::Email)
+    }
+}
+
+/// A regex which is compiled on its first use
+///
+/// This type is used by the code generated for `regex = ".."`.
+#[cfg(feature = "regex")]
+pub struct LazyRegex {
+    pattern: &'static str,
+    regex: std::sync::OnceLock<regex::Regex>,
+}
+
+#[cfg(feature = "regex")]
+impl LazyRegex {
+    /// Construct a new regex which is not compiled yet
+    pub const fn new(pattern: &'static str) -> Self {
+        Self {
+            pattern,
+            regex: std::sync::OnceLock::new(),
+        }
+    }
+
+    /// Check a string to match the regex
+    ///
+    /// # Panics
+    /// If the pattern is not a valid regex.
+    pub fn check<T: ValidateStr + ?Sized>(&self, value: &T) -> Result<(), ValidationErrorKind> {
+        let Some(string) = value.as_str() else {
+            return Ok(());
+        };
+        let regex = self.regex.get_or_init(|| {
+            regex::Regex::new(self.pattern).expect("Invalid regex in #[rorm(validate(regex))]")
+        });
+        if regex.is_match(string) {
+            Ok(())
+        } else {
+            Err(ValidationErrorKind::Regex {
+                pattern: self.pattern,
+            })
+        }
+    }
+}
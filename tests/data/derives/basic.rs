@@ -1,5 +1,7 @@
 use rorm::DbEnum;
 use rorm::Model;
+use rorm::ModelEnum;
+use rorm::NewType;
 use rorm::Patch;
 
 #[derive(Model)]
@@ -8,6 +10,20 @@ pub struct BasicModel {
     pub id: i64,
 }
 
+#[derive(ModelEnum)]
+pub enum BasicModelEnum {
+    Foo {
+        #[rorm(id)]
+        id: i64,
+        foo: i32,
+    },
+    Bar {
+        #[rorm(id)]
+        id: i64,
+        bar: Option<i32>,
+    },
+}
+
 #[derive(Patch)]
 #[rorm(model = "BasicModel")]
 pub struct BasicPatch {}
@@ -19,4 +35,29 @@ enum BasicEnum {
     Baz,
 }
 
+#[derive(DbEnum)]
+#[rorm(repr = i16)]
+enum IntEnum {
+    Foo,
+    Bar = 5,
+    Baz,
+    Negative = -1,
+}
+
+#[derive(NewType)]
+pub struct BasicNewType(i64);
+
+#[derive(NewType)]
+#[rorm(constructor = "CheckedNewType::new", ord)]
+pub struct CheckedNewType(i16);
+impl CheckedNewType {
+    pub fn new(value: i16) -> Result<Self, &'static str> {
+        if value >= 0 {
+            Ok(Self(value))
+        } else {
+            Err("value must not be negative")
+        }
+    }
+}
+
 fn main() {}
@@ -13,32 +13,22 @@ const _: () = {
         fn into_values<'a>(
             self,
         ) -> ::rorm::fields::traits::FieldColumns<Self, ::rorm::conditions::Value<'a>> {
-            [
-                ::rorm::conditions::Value::Choice(
-                    ::std::borrow::Cow::Borrowed(
-                        match self {
-                            Self::Foo => stringify!(Foo),
-                            Self::Bar => stringify!(Bar),
-                            Self::Baz => stringify!(Baz),
-                        },
-                    ),
-                ),
-            ]
+            let value = match self {
+                Self::Foo => stringify!(Foo),
+                Self::Bar => stringify!(Bar),
+                Self::Baz => stringify!(Baz),
+            };
+            [::rorm::conditions::Value::Choice(::std::borrow::Cow::Borrowed(value))]
         }
         fn as_values(
             &self,
         ) -> ::rorm::fields::traits::FieldColumns<Self, ::rorm::conditions::Value<'_>> {
-            [
-                ::rorm::conditions::Value::Choice(
-                    ::std::borrow::Cow::Borrowed(
-                        match self {
-                            Self::Foo => stringify!(Foo),
-                            Self::Bar => stringify!(Bar),
-                            Self::Baz => stringify!(Baz),
-                        },
-                    ),
-                ),
-            ]
+            let value = match self {
+                Self::Foo => stringify!(Foo),
+                Self::Bar => stringify!(Bar),
+                Self::Baz => stringify!(Baz),
+            };
+            [::rorm::conditions::Value::Choice(::std::borrow::Cow::Borrowed(value))]
         }
         type Decoder = __BasicEnum_Decoder;
         type GetAnnotations = get_db_enum_annotations;
@@ -61,6 +61,8 @@ impl ::std::ops::Deref for __BasicModel_ValueSpaceImpl {
 }
 impl ::rorm::model::Model for BasicModel {
     type Primary = __BasicModel_id;
+    type Hooks = ();
+    type DefaultScope = ();
     type Fields<P: ::rorm::internal::relation_path::Path> = __BasicModel_Fields_Struct<
         P,
     >;
@@ -76,6 +78,26 @@ impl ::rorm::model::Model for BasicModel {
         ::rorm::internal::field::push_imr::<__BasicModel_id>(&mut *fields);
     }
 }
+const _: () = {
+    #[::rorm::linkme::distributed_slice(::rorm::MODELS)]
+    #[linkme(crate = ::rorm::linkme)]
+    static __get_imr: fn() -> ::rorm::imr::Model = <BasicModel as ::rorm::model::Model>::get_imr;
+    let mut count_auto_increment = 0;
+    let mut annos_slice = <__BasicModel_id as ::rorm::internal::field::Field>::EFFECTIVE_ANNOTATIONS
+        .as_slice();
+    while let [annos, tail @ ..] = annos_slice {
+        annos_slice = tail;
+        if annos.auto_increment.is_some() {
+            count_auto_increment += 1;
+        }
+    }
+    assert!(
+        count_auto_increment <= 1, "\"auto_increment\" can only be set once per model"
+    );
+};
+impl ::rorm::model::FieldByIndex<{ 0usize }> for BasicModel {
+    type Field = __BasicModel_id;
+}
 #[doc(hidden)]
 #[allow(non_camel_case_types)]
 pub enum __BasicModel_ValueSpaceImpl {
@@ -85,6 +107,12 @@ pub enum __BasicModel_ValueSpaceImpl {
     __BasicModel_ValueSpaceImplMarker(::std::marker::PhantomData<BasicModel>),
 }
 pub use __BasicModel_ValueSpaceImpl::*;
+impl ::std::clone::Clone for __BasicModel_ValueSpaceImpl {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl ::std::marker::Copy for __BasicModel_ValueSpaceImpl {}
 pub struct __BasicModel_Decoder {
     id: <i64 as ::rorm::fields::traits::FieldType>::Decoder,
 }
@@ -140,7 +168,7 @@ impl ::rorm::model::Patch for BasicModel {
             ),
         }
     }
-    fn push_columns(columns: &mut Vec<&'static str>) {
+    fn push_columns(columns: &mut impl Extend<&'static str>) {
         columns
             .extend(
                 ::rorm::internal::field::FieldProxy::columns(
@@ -149,13 +177,28 @@ impl ::rorm::model::Patch for BasicModel {
                 ),
             );
     }
-    fn push_references<'a>(&'a self, values: &mut Vec<::rorm::conditions::Value<'a>>) {
+    fn push_references<'a>(
+        &'a self,
+        values: &mut impl Extend<::rorm::conditions::Value<'a>>,
+    ) {
         values.extend(::rorm::fields::traits::FieldType::as_values(&self.id));
     }
-    fn push_values(self, values: &mut Vec<::rorm::conditions::Value>) {
+    fn push_values<'a>(self, values: &mut impl Extend<::rorm::conditions::Value<'a>>) {
         values.extend(::rorm::fields::traits::FieldType::into_values(self.id));
     }
 }
+impl ::rorm::validate::Validate for BasicModel {
+    fn validate(&self) -> Result<(), ::rorm::validate::ValidationErrors> {
+        let mut errors = Vec::new();
+        if let Err(error) = ::rorm::internal::field::FieldProxy::validate(
+            <<Self as ::rorm::model::Patch>::Model as ::rorm::model::Model>::FIELDS.id,
+            &self.id,
+        ) {
+            errors.push(error);
+        }
+        ::rorm::validate::ValidationErrors::from_vec(errors)
+    }
+}
 impl<'a> ::rorm::internal::patch::IntoPatchCow<'a> for BasicModel {
     type Patch = BasicModel;
     fn into_patch_cow(self) -> ::rorm::internal::patch::PatchCow<'a, BasicModel> {
@@ -168,26 +211,6 @@ impl<'a> ::rorm::internal::patch::IntoPatchCow<'a> for &'a BasicModel {
         ::rorm::internal::patch::PatchCow::Borrowed(self)
     }
 }
-const _: () = {
-    #[::rorm::linkme::distributed_slice(::rorm::MODELS)]
-    #[linkme(crate = ::rorm::linkme)]
-    static __get_imr: fn() -> ::rorm::imr::Model = <BasicModel as ::rorm::model::Model>::get_imr;
-    let mut count_auto_increment = 0;
-    let mut annos_slice = <__BasicModel_id as ::rorm::internal::field::Field>::EFFECTIVE_ANNOTATIONS
-        .as_slice();
-    while let [annos, tail @ ..] = annos_slice {
-        annos_slice = tail;
-        if annos.auto_increment.is_some() {
-            count_auto_increment += 1;
-        }
-    }
-    assert!(
-        count_auto_increment <= 1, "\"auto_increment\" can only be set once per model"
-    );
-};
-impl ::rorm::model::FieldByIndex<{ 0usize }> for BasicModel {
-    type Field = __BasicModel_id;
-}
 impl ::rorm::model::GetField<__BasicModel_id> for BasicModel {
     fn get_field(self) -> i64 {
         self.id
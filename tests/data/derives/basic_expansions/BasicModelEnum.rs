@@ -0,0 +1,588 @@
+///rorm's representation of [`BasicModelEnum`]'s `kind` field
+#[allow(non_camel_case_types)]
+pub struct __BasicModelEnum_kind(::std::marker::PhantomData<()>);
+impl ::std::clone::Clone for __BasicModelEnum_kind {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl ::std::marker::Copy for __BasicModelEnum_kind {}
+impl ::rorm::internal::field::Field for __BasicModelEnum_kind {
+    type Type = ::rorm::model_enum::Discriminator<BasicModelEnum>;
+    type Model = BasicModelEnum;
+    const INDEX: usize = 0usize;
+    const NAME: &'static str = "kind";
+    const EXPLICIT_ANNOTATIONS: ::rorm::internal::hmr::annotations::Annotations = ::rorm::internal::hmr::annotations::Annotations {
+        auto_create_time: None,
+        auto_update_time: None,
+        auto_increment: None,
+        choices: None,
+        default: None,
+        index: None,
+        max_length: None,
+        on_delete: None,
+        on_update: None,
+        primary_key: None,
+        unique: None,
+        nullable: false,
+        foreign: None,
+    };
+    const SOURCE: ::rorm::internal::hmr::Source = ::rorm::internal::hmr::Source {
+        file: ::std::file!(),
+        line: ::std::line!() as usize,
+        column: ::std::column!() as usize,
+    };
+    fn new() -> Self {
+        Self(::std::marker::PhantomData)
+    }
+}
+const _: () = {
+    if let Err(err) = ::rorm::internal::field::check::<__BasicModelEnum_kind>() {
+        panic!("{}", err.as_str());
+    }
+};
+///rorm's representation of [`BasicModelEnum`]'s `id` field
+#[allow(non_camel_case_types)]
+pub struct __BasicModelEnum_id(::std::marker::PhantomData<()>);
+impl ::std::clone::Clone for __BasicModelEnum_id {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl ::std::marker::Copy for __BasicModelEnum_id {}
+impl ::rorm::internal::field::Field for __BasicModelEnum_id {
+    type Type = i64;
+    type Model = BasicModelEnum;
+    const INDEX: usize = 1usize;
+    const NAME: &'static str = "id";
+    const EXPLICIT_ANNOTATIONS: ::rorm::internal::hmr::annotations::Annotations = ::rorm::internal::hmr::annotations::Annotations {
+        auto_create_time: None,
+        auto_update_time: None,
+        auto_increment: Some(::rorm::internal::hmr::annotations::AutoIncrement),
+        choices: None,
+        default: None,
+        index: None,
+        max_length: None,
+        on_delete: None,
+        on_update: None,
+        primary_key: Some(::rorm::internal::hmr::annotations::PrimaryKey),
+        unique: None,
+        nullable: false,
+        foreign: None,
+    };
+    const SOURCE: ::rorm::internal::hmr::Source = ::rorm::internal::hmr::Source {
+        file: ::std::file!(),
+        line: ::std::line!() as usize,
+        column: ::std::column!() as usize,
+    };
+    fn new() -> Self {
+        Self(::std::marker::PhantomData)
+    }
+}
+const _: () = {
+    if let Err(err) = ::rorm::internal::field::check::<__BasicModelEnum_id>() {
+        panic!("{}", err.as_str());
+    }
+};
+///rorm's representation of [`BasicModelEnum`]'s `foo` field
+#[allow(non_camel_case_types)]
+pub struct __BasicModelEnum_foo(::std::marker::PhantomData<()>);
+impl ::std::clone::Clone for __BasicModelEnum_foo {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl ::std::marker::Copy for __BasicModelEnum_foo {}
+impl ::rorm::internal::field::Field for __BasicModelEnum_foo {
+    type Type = Option<i32>;
+    type Model = BasicModelEnum;
+    const INDEX: usize = 2usize;
+    const NAME: &'static str = "foo";
+    const EXPLICIT_ANNOTATIONS: ::rorm::internal::hmr::annotations::Annotations = ::rorm::internal::hmr::annotations::Annotations {
+        auto_create_time: None,
+        auto_update_time: None,
+        auto_increment: None,
+        choices: None,
+        default: None,
+        index: None,
+        max_length: None,
+        on_delete: None,
+        on_update: None,
+        primary_key: None,
+        unique: None,
+        nullable: false,
+        foreign: None,
+    };
+    const SOURCE: ::rorm::internal::hmr::Source = ::rorm::internal::hmr::Source {
+        file: ::std::file!(),
+        line: ::std::line!() as usize,
+        column: ::std::column!() as usize,
+    };
+    fn new() -> Self {
+        Self(::std::marker::PhantomData)
+    }
+}
+const _: () = {
+    if let Err(err) = ::rorm::internal::field::check::<__BasicModelEnum_foo>() {
+        panic!("{}", err.as_str());
+    }
+};
+///rorm's representation of [`BasicModelEnum`]'s `bar` field
+#[allow(non_camel_case_types)]
+pub struct __BasicModelEnum_bar(::std::marker::PhantomData<()>);
+impl ::std::clone::Clone for __BasicModelEnum_bar {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl ::std::marker::Copy for __BasicModelEnum_bar {}
+impl ::rorm::internal::field::Field for __BasicModelEnum_bar {
+    type Type = Option<i32>;
+    type Model = BasicModelEnum;
+    const INDEX: usize = 3usize;
+    const NAME: &'static str = "bar";
+    const EXPLICIT_ANNOTATIONS: ::rorm::internal::hmr::annotations::Annotations = ::rorm::internal::hmr::annotations::Annotations {
+        auto_create_time: None,
+        auto_update_time: None,
+        auto_increment: None,
+        choices: None,
+        default: None,
+        index: None,
+        max_length: None,
+        on_delete: None,
+        on_update: None,
+        primary_key: None,
+        unique: None,
+        nullable: false,
+        foreign: None,
+    };
+    const SOURCE: ::rorm::internal::hmr::Source = ::rorm::internal::hmr::Source {
+        file: ::std::file!(),
+        line: ::std::line!() as usize,
+        column: ::std::column!() as usize,
+    };
+    fn new() -> Self {
+        Self(::std::marker::PhantomData)
+    }
+}
+const _: () = {
+    if let Err(err) = ::rorm::internal::field::check::<__BasicModelEnum_bar>() {
+        panic!("{}", err.as_str());
+    }
+};
+///[`BasicModelEnum`]'s [`Fields`](::rorm::model::Model::Fields) struct.
+#[allow(non_camel_case_types)]
+pub struct __BasicModelEnum_Fields_Struct<Path: 'static> {
+    ///[`BasicModelEnum`]'s `kind` field
+    pub kind: ::rorm::internal::field::FieldProxy<__BasicModelEnum_kind, Path>,
+    ///[`BasicModelEnum`]'s `id` field
+    pub id: ::rorm::internal::field::FieldProxy<__BasicModelEnum_id, Path>,
+    ///[`BasicModelEnum`]'s `foo` field
+    pub foo: ::rorm::internal::field::FieldProxy<__BasicModelEnum_foo, Path>,
+    ///[`BasicModelEnum`]'s `bar` field
+    pub bar: ::rorm::internal::field::FieldProxy<__BasicModelEnum_bar, Path>,
+}
+impl<Path: 'static> ::rorm::model::ConstNew for __BasicModelEnum_Fields_Struct<Path> {
+    const NEW: Self = Self {
+        kind: ::rorm::internal::field::FieldProxy::new(),
+        id: ::rorm::internal::field::FieldProxy::new(),
+        foo: ::rorm::internal::field::FieldProxy::new(),
+        bar: ::rorm::internal::field::FieldProxy::new(),
+    };
+    const REF: &'static Self = &Self::NEW;
+}
+impl ::std::ops::Deref for __BasicModelEnum_ValueSpaceImpl {
+    type Target = <BasicModelEnum as ::rorm::Model>::Fields<BasicModelEnum>;
+    fn deref(&self) -> &Self::Target {
+        ::rorm::model::ConstNew::REF
+    }
+}
+impl ::rorm::model::Model for BasicModelEnum {
+    type Primary = __BasicModelEnum_id;
+    type Hooks = ();
+    type DefaultScope = ();
+    type Fields<P: ::rorm::internal::relation_path::Path> = __BasicModelEnum_Fields_Struct<
+        P,
+    >;
+    const F: __BasicModelEnum_Fields_Struct<Self> = ::rorm::model::ConstNew::NEW;
+    const FIELDS: __BasicModelEnum_Fields_Struct<Self> = ::rorm::model::ConstNew::NEW;
+    const TABLE: &'static str = "basicmodelenum";
+    const SOURCE: ::rorm::internal::hmr::Source = ::rorm::internal::hmr::Source {
+        file: ::std::file!(),
+        line: ::std::line!() as usize,
+        column: ::std::column!() as usize,
+    };
+    fn push_fields_imr(fields: &mut Vec<::rorm::imr::Field>) {
+        ::rorm::internal::field::push_imr::<__BasicModelEnum_kind>(&mut *fields);
+        ::rorm::internal::field::push_imr::<__BasicModelEnum_id>(&mut *fields);
+        ::rorm::internal::field::push_imr::<__BasicModelEnum_foo>(&mut *fields);
+        ::rorm::internal::field::push_imr::<__BasicModelEnum_bar>(&mut *fields);
+    }
+}
+const _: () = {
+    #[::rorm::linkme::distributed_slice(::rorm::MODELS)]
+    #[linkme(crate = ::rorm::linkme)]
+    static __get_imr: fn() -> ::rorm::imr::Model = <BasicModelEnum as ::rorm::model::Model>::get_imr;
+    let mut count_auto_increment = 0;
+    let mut annos_slice = <__BasicModelEnum_kind as ::rorm::internal::field::Field>::EFFECTIVE_ANNOTATIONS
+        .as_slice();
+    while let [annos, tail @ ..] = annos_slice {
+        annos_slice = tail;
+        if annos.auto_increment.is_some() {
+            count_auto_increment += 1;
+        }
+    }
+    let mut annos_slice = <__BasicModelEnum_id as ::rorm::internal::field::Field>::EFFECTIVE_ANNOTATIONS
+        .as_slice();
+    while let [annos, tail @ ..] = annos_slice {
+        annos_slice = tail;
+        if annos.auto_increment.is_some() {
+            count_auto_increment += 1;
+        }
+    }
+    let mut annos_slice = <__BasicModelEnum_foo as ::rorm::internal::field::Field>::EFFECTIVE_ANNOTATIONS
+        .as_slice();
+    while let [annos, tail @ ..] = annos_slice {
+        annos_slice = tail;
+        if annos.auto_increment.is_some() {
+            count_auto_increment += 1;
+        }
+    }
+    let mut annos_slice = <__BasicModelEnum_bar as ::rorm::internal::field::Field>::EFFECTIVE_ANNOTATIONS
+        .as_slice();
+    while let [annos, tail @ ..] = annos_slice {
+        annos_slice = tail;
+        if annos.auto_increment.is_some() {
+            count_auto_increment += 1;
+        }
+    }
+    assert!(
+        count_auto_increment <= 1, "\"auto_increment\" can only be set once per model"
+    );
+};
+impl ::rorm::model::FieldByIndex<{ 0usize }> for BasicModelEnum {
+    type Field = __BasicModelEnum_kind;
+}
+impl ::rorm::model::FieldByIndex<{ 1usize }> for BasicModelEnum {
+    type Field = __BasicModelEnum_id;
+}
+impl ::rorm::model::FieldByIndex<{ 2usize }> for BasicModelEnum {
+    type Field = __BasicModelEnum_foo;
+}
+impl ::rorm::model::FieldByIndex<{ 3usize }> for BasicModelEnum {
+    type Field = __BasicModelEnum_bar;
+}
+impl<Path: ::rorm::internal::relation_path::Path> __BasicModelEnum_Fields_Struct<Path> {
+    /// Check the row to be of the variant `V`
+    pub fn type_is<V: ::rorm::model_enum::ModelEnumVariant<Enum = BasicModelEnum>>(
+        &self,
+    ) -> ::rorm::conditions::Binary<
+        ::rorm::conditions::Column<
+            ::rorm::internal::field::FieldProxy<__BasicModelEnum_kind, Path>,
+        >,
+        ::rorm::conditions::Value<'static>,
+    > {
+        self.kind.is_variant::<V>()
+    }
+}
+impl ::rorm::model_enum::ModelEnum for BasicModelEnum {
+    const VARIANTS: &'static [&'static str] = &["Foo", "Bar"];
+}
+///Markers for [`BasicModelEnum`]'s variants
+pub mod basic_model_enum {
+    ///Marker for [`BasicModelEnum::Foo`](super::BasicModelEnum::Foo)
+    pub struct Foo;
+    impl ::rorm::model_enum::ModelEnumVariant for Foo {
+        type Enum = super::BasicModelEnum;
+        const NAME: &'static str = "Foo";
+    }
+    ///Marker for [`BasicModelEnum::Bar`](super::BasicModelEnum::Bar)
+    pub struct Bar;
+    impl ::rorm::model_enum::ModelEnumVariant for Bar {
+        type Enum = super::BasicModelEnum;
+        const NAME: &'static str = "Bar";
+    }
+}
+#[doc(hidden)]
+#[allow(non_camel_case_types)]
+pub enum __BasicModelEnum_ValueSpaceImpl {
+    BasicModelEnum,
+    #[allow(dead_code)]
+    #[doc(hidden)]
+    __BasicModelEnum_ValueSpaceImplMarker(::std::marker::PhantomData<BasicModelEnum>),
+}
+pub use __BasicModelEnum_ValueSpaceImpl::*;
+impl ::std::clone::Clone for __BasicModelEnum_ValueSpaceImpl {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl ::std::marker::Copy for __BasicModelEnum_ValueSpaceImpl {}
+#[doc(hidden)]
+pub struct __BasicModelEnum_Decoder {
+    kind: <::rorm::model_enum::Discriminator<
+        BasicModelEnum,
+    > as ::rorm::fields::traits::FieldType>::Decoder,
+    id: <i64 as ::rorm::fields::traits::FieldType>::Decoder,
+    foo: ::rorm::model_enum::VariantFieldDecoder<i32>,
+    bar: <Option<i32> as ::rorm::fields::traits::FieldType>::Decoder,
+}
+impl ::rorm::crud::selector::Selector for __BasicModelEnum_ValueSpaceImpl {
+    type Result = BasicModelEnum;
+    type Model = BasicModelEnum;
+    type Decoder = __BasicModelEnum_Decoder;
+    const INSERT_COMPATIBLE: bool = true;
+    fn select(
+        self,
+        ctx: &mut ::rorm::internal::query_context::QueryContext,
+    ) -> Self::Decoder {
+        <BasicModelEnum as ::rorm::model::Patch>::select::<BasicModelEnum>(ctx)
+    }
+}
+impl ::std::default::Default for __BasicModelEnum_ValueSpaceImpl {
+    fn default() -> Self {
+        Self::BasicModelEnum
+    }
+}
+impl ::rorm::crud::decoder::Decoder for __BasicModelEnum_Decoder {
+    type Result = BasicModelEnum;
+    fn by_name<'index>(
+        &'index self,
+        row: &'_ ::rorm::db::Row,
+    ) -> Result<Self::Result, ::rorm::db::row::RowError<'index>> {
+        Ok(
+            match self.kind.by_name(row)?.name() {
+                "Foo" => {
+                    BasicModelEnum::Foo {
+                        id: self.id.by_name(row)?,
+                        foo: self.foo.by_name(row)?,
+                    }
+                }
+                "Bar" => {
+                    BasicModelEnum::Bar {
+                        id: self.id.by_name(row)?,
+                        bar: self.bar.by_name(row)?,
+                    }
+                }
+                _ => {
+                    unreachable!(
+                        "The discriminator's decoder only accepts the enum's variants"
+                    )
+                }
+            },
+        )
+    }
+    fn by_index<'index>(
+        &'index self,
+        row: &'_ ::rorm::db::Row,
+    ) -> Result<Self::Result, ::rorm::db::row::RowError<'index>> {
+        Ok(
+            match self.kind.by_index(row)?.name() {
+                "Foo" => {
+                    BasicModelEnum::Foo {
+                        id: self.id.by_index(row)?,
+                        foo: self.foo.by_index(row)?,
+                    }
+                }
+                "Bar" => {
+                    BasicModelEnum::Bar {
+                        id: self.id.by_index(row)?,
+                        bar: self.bar.by_index(row)?,
+                    }
+                }
+                _ => {
+                    unreachable!(
+                        "The discriminator's decoder only accepts the enum's variants"
+                    )
+                }
+            },
+        )
+    }
+}
+impl ::rorm::model::Patch for BasicModelEnum {
+    type Model = BasicModelEnum;
+    type ValueSpaceImpl = __BasicModelEnum_ValueSpaceImpl;
+    type Decoder = __BasicModelEnum_Decoder;
+    fn select<P: ::rorm::internal::relation_path::Path>(
+        ctx: &mut ::rorm::internal::query_context::QueryContext,
+    ) -> Self::Decoder {
+        __BasicModelEnum_Decoder {
+            kind: ::rorm::internal::field::decoder::FieldDecoder::new(
+                ctx,
+                <BasicModelEnum as ::rorm::model::Model>::FIELDS.kind.through::<P>(),
+            ),
+            id: ::rorm::internal::field::decoder::FieldDecoder::new(
+                ctx,
+                <BasicModelEnum as ::rorm::model::Model>::FIELDS.id.through::<P>(),
+            ),
+            foo: ::rorm::model_enum::VariantFieldDecoder::new(
+                ctx,
+                <BasicModelEnum as ::rorm::model::Model>::FIELDS.foo.through::<P>(),
+            ),
+            bar: ::rorm::internal::field::decoder::FieldDecoder::new(
+                ctx,
+                <BasicModelEnum as ::rorm::model::Model>::FIELDS.bar.through::<P>(),
+            ),
+        }
+    }
+    fn push_columns(columns: &mut impl Extend<&'static str>) {
+        columns
+            .extend(
+                ::rorm::internal::field::FieldProxy::columns(
+                    <Self as ::rorm::model::Model>::FIELDS.kind,
+                ),
+            );
+        columns
+            .extend(
+                ::rorm::internal::field::FieldProxy::columns(
+                    <Self as ::rorm::model::Model>::FIELDS.id,
+                ),
+            );
+        columns
+            .extend(
+                ::rorm::internal::field::FieldProxy::columns(
+                    <Self as ::rorm::model::Model>::FIELDS.foo,
+                ),
+            );
+        columns
+            .extend(
+                ::rorm::internal::field::FieldProxy::columns(
+                    <Self as ::rorm::model::Model>::FIELDS.bar,
+                ),
+            );
+    }
+    fn push_references<'a>(
+        &'a self,
+        values: &mut impl Extend<::rorm::conditions::Value<'a>>,
+    ) {
+        match self {
+            Self::Foo { id: field_1, foo: field_2 } => {
+                values
+                    .extend(
+                        ::rorm::fields::traits::FieldType::into_values(
+                            ::rorm::model_enum::Discriminator::<
+                                Self,
+                            >::of::<basic_model_enum::Foo>(),
+                        ),
+                    );
+                values.extend(::rorm::fields::traits::FieldType::as_values(field_1));
+                values.extend(::rorm::fields::traits::FieldType::as_values(field_2));
+                values
+                    .extend(
+                        ::rorm::fields::traits::FieldType::into_values(
+                            <Option<i32>>::None,
+                        ),
+                    );
+            }
+            Self::Bar { id: field_1, bar: field_3 } => {
+                values
+                    .extend(
+                        ::rorm::fields::traits::FieldType::into_values(
+                            ::rorm::model_enum::Discriminator::<
+                                Self,
+                            >::of::<basic_model_enum::Bar>(),
+                        ),
+                    );
+                values.extend(::rorm::fields::traits::FieldType::as_values(field_1));
+                values
+                    .extend(
+                        ::rorm::fields::traits::FieldType::into_values(
+                            <Option<i32>>::None,
+                        ),
+                    );
+                values.extend(::rorm::fields::traits::FieldType::as_values(field_3));
+            }
+        }
+    }
+    fn push_values<'a>(self, values: &mut impl Extend<::rorm::conditions::Value<'a>>) {
+        match self {
+            Self::Foo { id: field_1, foo: field_2 } => {
+                values
+                    .extend(
+                        ::rorm::fields::traits::FieldType::into_values(
+                            ::rorm::model_enum::Discriminator::<
+                                Self,
+                            >::of::<basic_model_enum::Foo>(),
+                        ),
+                    );
+                values.extend(::rorm::fields::traits::FieldType::into_values(field_1));
+                values.extend(::rorm::fields::traits::FieldType::into_values(field_2));
+                values
+                    .extend(
+                        ::rorm::fields::traits::FieldType::into_values(
+                            <Option<i32>>::None,
+                        ),
+                    );
+            }
+            Self::Bar { id: field_1, bar: field_3 } => {
+                values
+                    .extend(
+                        ::rorm::fields::traits::FieldType::into_values(
+                            ::rorm::model_enum::Discriminator::<
+                                Self,
+                            >::of::<basic_model_enum::Bar>(),
+                        ),
+                    );
+                values.extend(::rorm::fields::traits::FieldType::into_values(field_1));
+                values
+                    .extend(
+                        ::rorm::fields::traits::FieldType::into_values(
+                            <Option<i32>>::None,
+                        ),
+                    );
+                values.extend(::rorm::fields::traits::FieldType::into_values(field_3));
+            }
+        }
+    }
+}
+impl ::rorm::validate::Validate for BasicModelEnum {
+    fn validate(&self) -> Result<(), ::rorm::validate::ValidationErrors> {
+        let mut errors = Vec::new();
+        match self {
+            Self::Foo { id: field_1, foo: field_2 } => {
+                if let Err(error) = ::rorm::internal::field::FieldProxy::validate(
+                    <Self as ::rorm::model::Model>::FIELDS.id,
+                    field_1,
+                ) {
+                    errors.push(error);
+                }
+                if let Err(kind) = ::rorm::fields::traits::FieldType::check_value(
+                    field_2,
+                ) {
+                    errors
+                        .push(::rorm::validate::ValidationError {
+                            field: <__BasicModelEnum_foo as ::rorm::internal::field::Field>::NAME,
+                            kind,
+                        });
+                }
+            }
+            Self::Bar { id: field_1, bar: field_3 } => {
+                if let Err(error) = ::rorm::internal::field::FieldProxy::validate(
+                    <Self as ::rorm::model::Model>::FIELDS.id,
+                    field_1,
+                ) {
+                    errors.push(error);
+                }
+                if let Err(error) = ::rorm::internal::field::FieldProxy::validate(
+                    <Self as ::rorm::model::Model>::FIELDS.bar,
+                    field_3,
+                ) {
+                    errors.push(error);
+                }
+            }
+        }
+        ::rorm::validate::ValidationErrors::from_vec(errors)
+    }
+}
+impl<'a> ::rorm::internal::patch::IntoPatchCow<'a> for BasicModelEnum {
+    type Patch = BasicModelEnum;
+    fn into_patch_cow(self) -> ::rorm::internal::patch::PatchCow<'a, BasicModelEnum> {
+        ::rorm::internal::patch::PatchCow::Owned(self)
+    }
+}
+impl<'a> ::rorm::internal::patch::IntoPatchCow<'a> for &'a BasicModelEnum {
+    type Patch = BasicModelEnum;
+    fn into_patch_cow(self) -> ::rorm::internal::patch::PatchCow<'a, BasicModelEnum> {
+        ::rorm::internal::patch::PatchCow::Borrowed(self)
+    }
+}
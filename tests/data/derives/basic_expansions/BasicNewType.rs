@@ -0,0 +1,12 @@
+const _: () = {
+    impl ::rorm::fields::custom::CustomFieldType for BasicNewType {
+        type Inner = i64;
+        fn to_inner(&self) -> Self::Inner {
+            ::std::clone::Clone::clone(&self.0)
+        }
+        fn from_inner(inner: Self::Inner) -> Result<Self, String> {
+            Ok(Self(inner))
+        }
+    }
+    ::rorm::impl_CustomFieldType!(BasicNewType);
+};
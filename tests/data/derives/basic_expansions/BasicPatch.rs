@@ -7,6 +7,12 @@ pub enum __BasicPatch_ValueSpaceImpl {
     __BasicPatch_ValueSpaceImplMarker(::std::marker::PhantomData<BasicPatch>),
 }
 pub use __BasicPatch_ValueSpaceImpl::*;
+impl ::std::clone::Clone for __BasicPatch_ValueSpaceImpl {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl ::std::marker::Copy for __BasicPatch_ValueSpaceImpl {}
 pub struct __BasicPatch_Decoder {}
 impl ::rorm::crud::selector::Selector for __BasicPatch_ValueSpaceImpl {
     type Result = BasicPatch;
@@ -49,9 +55,18 @@ impl ::rorm::model::Patch for BasicPatch {
     ) -> Self::Decoder {
         __BasicPatch_Decoder {}
     }
-    fn push_columns(columns: &mut Vec<&'static str>) {}
-    fn push_references<'a>(&'a self, values: &mut Vec<::rorm::conditions::Value<'a>>) {}
-    fn push_values(self, values: &mut Vec<::rorm::conditions::Value>) {}
+    fn push_columns(columns: &mut impl Extend<&'static str>) {}
+    fn push_references<'a>(
+        &'a self,
+        values: &mut impl Extend<::rorm::conditions::Value<'a>>,
+    ) {}
+    fn push_values<'a>(self, values: &mut impl Extend<::rorm::conditions::Value<'a>>) {}
+}
+impl ::rorm::validate::Validate for BasicPatch {
+    fn validate(&self) -> Result<(), ::rorm::validate::ValidationErrors> {
+        let mut errors = Vec::new();
+        ::rorm::validate::ValidationErrors::from_vec(errors)
+    }
 }
 impl<'a> ::rorm::internal::patch::IntoPatchCow<'a> for BasicPatch {
     type Patch = BasicPatch;
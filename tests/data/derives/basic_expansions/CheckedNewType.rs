@@ -0,0 +1,13 @@
+const _: () = {
+    impl ::rorm::fields::custom::CustomFieldType for CheckedNewType {
+        type Inner = i16;
+        fn to_inner(&self) -> Self::Inner {
+            ::std::clone::Clone::clone(&self.0)
+        }
+        fn from_inner(inner: Self::Inner) -> Result<Self, String> {
+            CheckedNewType::new(inner)
+                .map_err(|error| ::std::string::ToString::to_string(&error))
+        }
+    }
+    ::rorm::impl_CustomFieldType!(CheckedNewType, ord);
+};
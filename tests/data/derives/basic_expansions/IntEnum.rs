@@ -0,0 +1,48 @@
+const _: () = {
+    impl ::rorm::fields::traits::FieldType for IntEnum {
+        type Columns = ::rorm::fields::traits::Array<1>;
+        const NULL: ::rorm::fields::traits::FieldColumns<
+            Self,
+            ::rorm::db::sql::value::NullType,
+        > = [::rorm::db::sql::value::NullType::I16];
+        fn into_values<'a>(
+            self,
+        ) -> ::rorm::fields::traits::FieldColumns<Self, ::rorm::conditions::Value<'a>> {
+            let value = match self {
+                Self::Foo => 0,
+                Self::Bar => 5,
+                Self::Baz => 6,
+                Self::Negative => -1,
+            };
+            [::rorm::conditions::Value::I16(value)]
+        }
+        fn as_values(
+            &self,
+        ) -> ::rorm::fields::traits::FieldColumns<Self, ::rorm::conditions::Value<'_>> {
+            let value = match self {
+                Self::Foo => 0,
+                Self::Bar => 5,
+                Self::Baz => 6,
+                Self::Negative => -1,
+            };
+            [::rorm::conditions::Value::I16(value)]
+        }
+        type Decoder = __IntEnum_Decoder;
+        type GetAnnotations = ::rorm::fields::utils::get_annotations::forward_annotations<
+            1,
+        >;
+        type Check = ::rorm::fields::utils::check::shared_linter_check<1>;
+        type GetNames = ::rorm::fields::utils::get_names::single_column_name;
+    }
+    ::rorm::new_converting_decoder!(
+        #[doc(hidden)] __IntEnum_Decoder, | value : i16 | -> IntEnum { match value { 0 =>
+        Ok(IntEnum::Foo), 5 => Ok(IntEnum::Bar), 6 => Ok(IntEnum::Baz), - 1 =>
+        Ok(IntEnum::Negative), _ => Err(format!("Invalid value '{}' for enum '{}'",
+        value, stringify!(IntEnum))), } }
+    );
+    ::rorm::impl_FieldEq!(
+        impl < 'rhs > FieldEq < 'rhs, IntEnum > for IntEnum { | value : IntEnum | { let
+        [value] = < IntEnum as ::rorm::fields::traits::FieldType > ::into_values(value);
+        value } }
+    );
+};
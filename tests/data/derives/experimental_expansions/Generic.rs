@@ -105,6 +105,8 @@ for __Generic_ValueSpaceImpl<X> {
 }
 impl<X: rorm::fields::traits::FieldType> ::rorm::model::Model for Generic<X> {
     type Primary = __Generic_id<X>;
+    type Hooks = ();
+    type DefaultScope = ();
     type Fields<P: ::rorm::internal::relation_path::Path> = __Generic_Fields_Struct<
         X,
         P,
@@ -122,6 +124,14 @@ impl<X: rorm::fields::traits::FieldType> ::rorm::model::Model for Generic<X> {
         ::rorm::internal::field::push_imr::<__Generic_x<X>>(&mut *fields);
     }
 }
+impl<X: rorm::fields::traits::FieldType> ::rorm::model::FieldByIndex<{ 0usize }>
+for Generic<X> {
+    type Field = __Generic_id<X>;
+}
+impl<X: rorm::fields::traits::FieldType> ::rorm::model::FieldByIndex<{ 1usize }>
+for Generic<X> {
+    type Field = __Generic_x<X>;
+}
 #[doc(hidden)]
 #[allow(non_camel_case_types)]
 pub enum __Generic_ValueSpaceImpl<X: rorm::fields::traits::FieldType> {
@@ -131,6 +141,14 @@ pub enum __Generic_ValueSpaceImpl<X: rorm::fields::traits::FieldType> {
     __Generic_ValueSpaceImplMarker(::std::marker::PhantomData<Generic<X>>),
 }
 pub use __Generic_ValueSpaceImpl::*;
+impl<X: rorm::fields::traits::FieldType> ::std::clone::Clone
+for __Generic_ValueSpaceImpl<X> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<X: rorm::fields::traits::FieldType> ::std::marker::Copy
+for __Generic_ValueSpaceImpl<X> {}
 pub struct __Generic_Decoder<X: rorm::fields::traits::FieldType> {
     id: <i64 as ::rorm::fields::traits::FieldType>::Decoder,
     x: <X as ::rorm::fields::traits::FieldType>::Decoder,
@@ -198,7 +216,7 @@ impl<X: rorm::fields::traits::FieldType> ::rorm::model::Patch for Generic<X> {
             ),
         }
     }
-    fn push_columns(columns: &mut Vec<&'static str>) {
+    fn push_columns(columns: &mut impl Extend<&'static str>) {
         columns
             .extend(
                 ::rorm::internal::field::FieldProxy::columns(
@@ -214,15 +232,36 @@ impl<X: rorm::fields::traits::FieldType> ::rorm::model::Patch for Generic<X> {
                 ),
             );
     }
-    fn push_references<'a>(&'a self, values: &mut Vec<::rorm::conditions::Value<'a>>) {
+    fn push_references<'a>(
+        &'a self,
+        values: &mut impl Extend<::rorm::conditions::Value<'a>>,
+    ) {
         values.extend(::rorm::fields::traits::FieldType::as_values(&self.id));
         values.extend(::rorm::fields::traits::FieldType::as_values(&self.x));
     }
-    fn push_values(self, values: &mut Vec<::rorm::conditions::Value>) {
+    fn push_values<'a>(self, values: &mut impl Extend<::rorm::conditions::Value<'a>>) {
         values.extend(::rorm::fields::traits::FieldType::into_values(self.id));
         values.extend(::rorm::fields::traits::FieldType::into_values(self.x));
     }
 }
+impl<X: rorm::fields::traits::FieldType> ::rorm::validate::Validate for Generic<X> {
+    fn validate(&self) -> Result<(), ::rorm::validate::ValidationErrors> {
+        let mut errors = Vec::new();
+        if let Err(error) = ::rorm::internal::field::FieldProxy::validate(
+            <<Self as ::rorm::model::Patch>::Model as ::rorm::model::Model>::FIELDS.id,
+            &self.id,
+        ) {
+            errors.push(error);
+        }
+        if let Err(error) = ::rorm::internal::field::FieldProxy::validate(
+            <<Self as ::rorm::model::Patch>::Model as ::rorm::model::Model>::FIELDS.x,
+            &self.x,
+        ) {
+            errors.push(error);
+        }
+        ::rorm::validate::ValidationErrors::from_vec(errors)
+    }
+}
 impl<'a, X: rorm::fields::traits::FieldType> ::rorm::internal::patch::IntoPatchCow<'a>
 for Generic<X> {
     type Patch = Generic<X>;
@@ -237,10 +276,6 @@ for &'a Generic<X> {
         ::rorm::internal::patch::PatchCow::Borrowed(self)
     }
 }
-impl<X: rorm::fields::traits::FieldType> ::rorm::model::FieldByIndex<{ 0usize }>
-for Generic<X> {
-    type Field = __Generic_id<X>;
-}
 impl<X: rorm::fields::traits::FieldType> ::rorm::model::GetField<__Generic_id<X>>
 for Generic<X> {
     fn get_field(self) -> i64 {
@@ -253,10 +288,6 @@ for Generic<X> {
         &mut self.id
     }
 }
-impl<X: rorm::fields::traits::FieldType> ::rorm::model::FieldByIndex<{ 1usize }>
-for Generic<X> {
-    type Field = __Generic_x<X>;
-}
 impl<X: rorm::fields::traits::FieldType> ::rorm::model::GetField<__Generic_x<X>>
 for Generic<X> {
     fn get_field(self) -> X {
@@ -56,6 +56,8 @@ impl ::std::ops::Deref for __Unregistered_ValueSpaceImpl {
 }
 impl ::rorm::model::Model for Unregistered {
     type Primary = __Unregistered_id;
+    type Hooks = ();
+    type DefaultScope = ();
     type Fields<P: ::rorm::internal::relation_path::Path> = __Unregistered_Fields_Struct<
         P,
     >;
@@ -71,6 +73,9 @@ impl ::rorm::model::Model for Unregistered {
         ::rorm::internal::field::push_imr::<__Unregistered_id>(&mut *fields);
     }
 }
+impl ::rorm::model::FieldByIndex<{ 0usize }> for Unregistered {
+    type Field = __Unregistered_id;
+}
 #[doc(hidden)]
 #[allow(non_camel_case_types)]
 pub enum __Unregistered_ValueSpaceImpl {
@@ -80,6 +85,12 @@ pub enum __Unregistered_ValueSpaceImpl {
     __Unregistered_ValueSpaceImplMarker(::std::marker::PhantomData<Unregistered>),
 }
 pub use __Unregistered_ValueSpaceImpl::*;
+impl ::std::clone::Clone for __Unregistered_ValueSpaceImpl {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl ::std::marker::Copy for __Unregistered_ValueSpaceImpl {}
 pub struct __Unregistered_Decoder {
     id: <i64 as ::rorm::fields::traits::FieldType>::Decoder,
 }
@@ -135,7 +146,7 @@ impl ::rorm::model::Patch for Unregistered {
             ),
         }
     }
-    fn push_columns(columns: &mut Vec<&'static str>) {
+    fn push_columns(columns: &mut impl Extend<&'static str>) {
         columns
             .extend(
                 ::rorm::internal::field::FieldProxy::columns(
@@ -144,13 +155,28 @@ impl ::rorm::model::Patch for Unregistered {
                 ),
             );
     }
-    fn push_references<'a>(&'a self, values: &mut Vec<::rorm::conditions::Value<'a>>) {
+    fn push_references<'a>(
+        &'a self,
+        values: &mut impl Extend<::rorm::conditions::Value<'a>>,
+    ) {
         values.extend(::rorm::fields::traits::FieldType::as_values(&self.id));
     }
-    fn push_values(self, values: &mut Vec<::rorm::conditions::Value>) {
+    fn push_values<'a>(self, values: &mut impl Extend<::rorm::conditions::Value<'a>>) {
         values.extend(::rorm::fields::traits::FieldType::into_values(self.id));
     }
 }
+impl ::rorm::validate::Validate for Unregistered {
+    fn validate(&self) -> Result<(), ::rorm::validate::ValidationErrors> {
+        let mut errors = Vec::new();
+        if let Err(error) = ::rorm::internal::field::FieldProxy::validate(
+            <<Self as ::rorm::model::Patch>::Model as ::rorm::model::Model>::FIELDS.id,
+            &self.id,
+        ) {
+            errors.push(error);
+        }
+        ::rorm::validate::ValidationErrors::from_vec(errors)
+    }
+}
 impl<'a> ::rorm::internal::patch::IntoPatchCow<'a> for Unregistered {
     type Patch = Unregistered;
     fn into_patch_cow(self) -> ::rorm::internal::patch::PatchCow<'a, Unregistered> {
@@ -163,9 +189,6 @@ impl<'a> ::rorm::internal::patch::IntoPatchCow<'a> for &'a Unregistered {
         ::rorm::internal::patch::PatchCow::Borrowed(self)
     }
 }
-impl ::rorm::model::FieldByIndex<{ 0usize }> for Unregistered {
-    type Field = __Unregistered_id;
-}
 impl ::rorm::model::GetField<__Unregistered_id> for Unregistered {
     fn get_field(self) -> i64 {
         self.id
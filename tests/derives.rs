@@ -91,10 +91,14 @@ fn get_derive_fn(item: &syn::Item) -> Result<Option<(Ident, fn(TokenStream) -> T
             item_ident.clone(),
             if ident == "Model" {
                 rorm_macro_impl::derive_model
+            } else if ident == "ModelEnum" {
+                rorm_macro_impl::derive_model_enum
             } else if ident == "Patch" {
                 rorm_macro_impl::derive_patch
             } else if ident == "DbEnum" {
                 rorm_macro_impl::derive_db_enum
+            } else if ident == "NewType" {
+                rorm_macro_impl::derive_new_type
             } else {
                 continue;
             },
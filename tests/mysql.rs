@@ -0,0 +1,67 @@
+//! Tests of the code paths specific to MySQL
+//!
+//! They require a running MySQL database and are ignored by default.
+//! Run them using `cargo test --test mysql -- --ignored`
+//! with the database configured by the environment variables
+//! `MYSQL_HOST`, `MYSQL_PORT`, `MYSQL_USER`, `MYSQL_PASSWORD` and `MYSQL_DATABASE`.
+
+use std::env;
+
+use rorm::db::database::raw_sql;
+use rorm::prelude::*;
+use rorm::{insert, Database, DatabaseConfiguration, DatabaseDriver};
+use tokio::runtime::Runtime;
+
+#[derive(Model)]
+struct Counter {
+    #[rorm(id)]
+    id: i64,
+
+    value: i32,
+}
+
+#[derive(Patch)]
+#[rorm(model = "Counter")]
+struct NewCounter {
+    value: i32,
+}
+
+async fn connect() -> Database {
+    let var = |name: &str| env::var(name).unwrap_or_else(|_| panic!("{name} is not set"));
+    let db = Database::connect(DatabaseConfiguration::new(DatabaseDriver::MySQL {
+        name: var("MYSQL_DATABASE"),
+        host: var("MYSQL_HOST"),
+        port: var("MYSQL_PORT").parse().expect("MYSQL_PORT is not a port"),
+        user: var("MYSQL_USER"),
+        password: var("MYSQL_PASSWORD"),
+    }))
+    .await
+    .unwrap();
+    raw_sql(&db, "DROP TABLE IF EXISTS `counter`;", None)
+        .await
+        .unwrap();
+    raw_sql(
+        &db,
+        "CREATE TABLE `counter` (`id` BIGINT NOT NULL AUTO_INCREMENT PRIMARY KEY, `value` INT NOT NULL);",
+        None,
+    )
+    .await
+    .unwrap();
+    db
+}
+
+#[test]
+#[ignore = "requires a MySQL database"]
+fn bulk_returning_generated_keys() {
+    Runtime::new().unwrap().block_on(async {
+        let db = connect().await;
+        let counters = insert(&db, Counter)
+            .bulk(&[NewCounter { value: 1 }, NewCounter { value: 2 }])
+            .await
+            .unwrap();
+        assert_eq!(counters.len(), 2);
+        assert_eq!(counters[0].value, 1);
+        assert_eq!(counters[1].value, 2);
+        assert_eq!(counters[1].id, counters[0].id + 1);
+    });
+}